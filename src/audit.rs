@@ -0,0 +1,90 @@
+//! Sampled values of every data file, for review
+//!
+//! With `Config::value_samples` set, every data file gets a text sidecar,
+//! `<file>.sample.txt`, listing a uniform sample of its rows column by
+//! column, so reviewers can audit what went into a dataset without opening
+//! multi-gigabyte Parquet files. Rows are reservoir-sampled while the file
+//! is written, seeded from the run seed and the file index, so a rerun
+//! reproduces the same samples. Vectors show their first few values;
+//! strings and byte values are cut short.
+
+use anyhow::{Context, Result};
+use arrow::record_batch::RecordBatch;
+use arrow::util::display::{ArrayFormatter, FormatOptions};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+use crate::holdout::Reservoir;
+
+/// Stream identifier for value sampling
+const AUDIT_STREAM: u64 = u64::MAX - 14;
+
+/// Leading vector values shown for each sampled vector
+const VECTOR_PREVIEW: usize = 8;
+
+/// Characters shown of other values before they are cut short
+const MAX_VALUE_CHARS: usize = 80;
+
+/// Path of the sample sidecar of the data file at `file`
+pub fn sample_path(file: &Path) -> PathBuf {
+    file.with_extension("sample.txt")
+}
+
+/// Reservoir for the value sample of file `file_index`
+pub fn reservoir(rows: usize, seed: u64, file_index: u64) -> Reservoir {
+    Reservoir::for_stream(rows, seed, &[AUDIT_STREAM, file_index])
+}
+
+/// Write the rows sampled from a file of `rows` rows at `file` to its
+/// sidecar; returns the sidecar path, or `None` if the file is empty
+pub fn write_sample(file: &Path, rows: usize, reservoir: &Reservoir) -> Result<Option<PathBuf>> {
+    let Some(sample) = reservoir.sample()? else {
+        return Ok(None);
+    };
+    let path = sample_path(file);
+    std::fs::write(&path, render(file, rows, &sample)?)
+        .with_context(|| format!("Failed to write value sample: {:?}", path))?;
+    Ok(Some(path))
+}
+
+/// Render the sampled rows of a file column by column
+pub fn render(file: &Path, rows: usize, sample: &RecordBatch) -> Result<String> {
+    let mut out = String::new();
+    writeln!(out, "# {}", file.display())?;
+    writeln!(out, "# {} of {} rows sampled", sample.num_rows(), rows)?;
+    let options = FormatOptions::default().with_null("null");
+    let schema = sample.schema();
+    for (field, column) in schema.fields().iter().zip(sample.columns()) {
+        writeln!(out)?;
+        writeln!(out, "{} ({})", field.name(), field.data_type())?;
+        let formatter = ArrayFormatter::try_new(column.as_ref(), &options)?;
+        for row in 0..sample.num_rows() {
+            let value = match crate::columns::vector_values(field, column.as_ref(), row) {
+                Some(values) => format_vector(&values),
+                None => truncate(formatter.value(row).to_string()),
+            };
+            writeln!(out, "  {}", value)?;
+        }
+    }
+    Ok(out)
+}
+
+/// `[v0, v1, ..., vN]`, with the values past the preview counted
+fn format_vector(values: &[f64]) -> String {
+    let shown: Vec<String> = values
+        .iter()
+        .take(VECTOR_PREVIEW)
+        .map(|v| format!("{:.4}", v))
+        .collect();
+    match values.len().saturating_sub(VECTOR_PREVIEW) {
+        0 => format!("[{}]", shown.join(", ")),
+        more => format!("[{}, … {} more]", shown.join(", "), more),
+    }
+}
+
+fn truncate(value: String) -> String {
+    match value.char_indices().nth(MAX_VALUE_CHARS) {
+        Some((end, _)) => format!("{}…", &value[..end]),
+        None => value,
+    }
+}
@@ -0,0 +1,54 @@
+//! Bucketed datasets: rows routed to files by key hash
+//!
+//! As in bucketed Hive or Paimon tables, every partition is split into
+//! `buckets` files under `bucket-<n>` directories and each row goes to
+//! bucket `hash(key) % buckets`. The hash is the one behind the `hash()`
+//! derived-column expression, so `--derive "b = hash(id) % 16"` reproduces
+//! the bucket of every row. Null keys go to bucket 0.
+
+use anyhow::{bail, Result};
+use arrow::record_batch::RecordBatch;
+use serde::{Deserialize, Serialize};
+
+use crate::derived::hash_array;
+
+/// Bucketing of the rows of each partition
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BucketConfig {
+    /// Column whose hash selects the bucket
+    pub column: String,
+    /// Buckets (files) per partition
+    pub buckets: u32,
+}
+
+impl BucketConfig {
+    /// Check the parameters
+    pub fn validate(&self) -> Result<()> {
+        if self.buckets == 0 {
+            bail!("bucketing needs at least one bucket");
+        }
+        Ok(())
+    }
+
+    /// Bucket of a key hash, `None` for null keys
+    pub fn bucket_of(&self, hash: Option<i64>) -> u32 {
+        hash.map_or(0, |h| h.rem_euclid(self.buckets as i64) as u32)
+    }
+
+    /// Indices of the rows of `batch` falling into each bucket
+    pub fn split(&self, batch: &RecordBatch) -> Result<Vec<Vec<u32>>> {
+        let Some(keys) = batch.column_by_name(&self.column) else {
+            bail!("unknown bucket column '{}'", self.column);
+        };
+        let mut rows = vec![Vec::new(); self.buckets as usize];
+        for (row, hash) in hash_array(keys.as_ref())?.into_iter().enumerate() {
+            rows[self.bucket_of(hash) as usize].push(row as u32);
+        }
+        Ok(rows)
+    }
+}
+
+/// Directory of bucket `bucket` within a partition
+pub fn bucket_dir_name(bucket: u32) -> String {
+    format!("bucket-{}", bucket)
+}
@@ -0,0 +1,75 @@
+//! Cooperative cancellation for long-running generation
+//!
+//! Writers poll a [`CancellationToken`] between batches. When cancellation is
+//! requested, the file currently being written is either finalized with the
+//! rows produced so far or removed, according to the configured
+//! [`CancelPolicy`].
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Handle used to request cancellation of an in-progress generation run
+///
+/// Clones share the same underlying flag, so one clone can be handed to a
+/// signal handler or UI thread while the writer polls another.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a token that has not been cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation; writers stop at the next batch boundary
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+
+    /// Check whether cancellation has been requested
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// What a writer does with the file in progress when cancelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum CancelPolicy {
+    /// Close the file with the rows written so far, leaving a valid Parquet file
+    #[default]
+    Finalize,
+    /// Remove the partially written file
+    Discard,
+}
+
+/// Error returned when a write stops because cancellation was requested
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled {
+    /// Rows written to the current file before cancellation was observed
+    pub rows_written: usize,
+    /// Policy that was applied to the current file
+    pub policy: CancelPolicy,
+}
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.policy {
+            CancelPolicy::Finalize => write!(
+                f,
+                "generation cancelled; file finalized with {} rows",
+                self.rows_written
+            ),
+            CancelPolicy::Discard => write!(
+                f,
+                "generation cancelled; partial file with {} rows discarded",
+                self.rows_written
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Cancelled {}
@@ -0,0 +1,68 @@
+//! Low-cardinality categorical columns
+//!
+//! A `category(N)` column of a schema spec holds Utf8 values drawn uniformly
+//! from a vocabulary of `N` distinct lowercase words; with `category(N,s=S)`
+//! the value of rank `r` (in vocabulary order) has Zipf weight `1 / r^S`,
+//! so a few values dominate as in real categorical data. The vocabulary is
+//! generated once per column from the seed, so every batch and file of a
+//! dataset shares it. With few distinct values Parquet writers keep the
+//! column dictionary-encoded, as for real categories (country, status,
+//! product type), and a value filter on a uniform column keeps about `1 / N`
+//! of the rows.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashSet;
+
+use crate::core_gen::stream_seed;
+use crate::skew::{KeySampler, Skew};
+
+/// Stream identifier for category vocabularies
+const CATEGORY_STREAM: u64 = u64::MAX - 13;
+
+/// Distinct values of a categorical column
+#[derive(Debug, Clone)]
+pub struct Categories {
+    values: Vec<String>,
+    sampler: KeySampler,
+}
+
+impl Categories {
+    /// Vocabulary of `cardinality` words for the column of `stream`, drawn
+    /// with `skew`; the cardinality and Zipf exponent must be validated
+    pub fn generate(seed: u64, stream: u64, cardinality: usize, skew: Skew) -> Self {
+        let mut rng = StdRng::seed_from_u64(stream_seed(seed, &[CATEGORY_STREAM, stream]));
+        // Long enough that collisions, and so retries, stay rare
+        let mut len = 4;
+        while 26f64.powi(len - 2) < cardinality as f64 {
+            len += 1;
+        }
+        let mut seen = HashSet::with_capacity(cardinality);
+        let mut values = Vec::with_capacity(cardinality);
+        while values.len() < cardinality {
+            let word: String = (0..len)
+                .map(|_| char::from(b'a' + rng.gen_range(0..26u8)))
+                .collect();
+            if seen.insert(word.clone()) {
+                values.push(word);
+            }
+        }
+        let sampler = KeySampler::new(cardinality, skew).expect("validated category column");
+        Self { values, sampler }
+    }
+
+    /// The distinct values, in generation order
+    pub fn values(&self) -> &[String] {
+        &self.values
+    }
+
+    /// A random value, the first ones most frequent under Zipf skew
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> &str {
+        &self.values[self.sampler.sample(rng)]
+    }
+
+    /// Length in bytes of every value
+    pub fn value_len(&self) -> usize {
+        self.values.first().map_or(0, String::len)
+    }
+}
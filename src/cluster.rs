@@ -0,0 +1,242 @@
+//! Rows of each file clustered along a space-filling curve
+//!
+//! Before a file is written its rows are sorted by their position on a
+//! Z-order (Morton) or Hilbert curve over a few columns, as Delta `OPTIMIZE
+//! ZORDER BY` or Iceberg/Paimon sort-based compaction would leave them. Row
+//! groups and pages then cover small boxes of the clustered columns, so
+//! min/max pruning and clustering-sensitive readers behave as on optimized
+//! tables.
+//!
+//! Each column is mapped to the rank of its values within the file, which
+//! works for any orderable type and spreads skewed values over the whole
+//! curve. The ranks of `n` columns are reduced to `64 / n` bits each and
+//! interleaved into one 64-bit key.
+
+use anyhow::{bail, Context, Result};
+use arrow::array::UInt32Array;
+use arrow::compute::{concat_batches, rank, take_record_batch};
+use arrow::datatypes::Schema;
+use arrow::record_batch::RecordBatch;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::sink::{Readiness, Sink};
+
+/// Most columns a curve can interleave, keeping 8 bits per column
+pub const MAX_COLUMNS: usize = 8;
+
+/// Space-filling curve ordering the rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Curve {
+    /// Bit-interleaved Morton order
+    #[default]
+    ZOrder,
+    /// Hilbert order, which never jumps between distant cells
+    Hilbert,
+}
+
+impl fmt::Display for Curve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Curve::ZOrder => "z-order",
+            Curve::Hilbert => "hilbert",
+        })
+    }
+}
+
+impl FromStr for Curve {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "z-order" | "zorder" | "morton" => Ok(Curve::ZOrder),
+            "hilbert" => Ok(Curve::Hilbert),
+            other => bail!("unknown curve '{}', expected z-order or hilbert", other),
+        }
+    }
+}
+
+/// Clustering of the rows within each file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClusterConfig {
+    /// Columns spanning the curve, the first one most significant
+    pub columns: Vec<String>,
+    pub curve: Curve,
+}
+
+impl ClusterConfig {
+    /// Check the parameters
+    pub fn validate(&self) -> Result<()> {
+        if self.columns.is_empty() || self.columns.len() > MAX_COLUMNS {
+            bail!(
+                "clustering needs between 1 and {} columns, got {}",
+                MAX_COLUMNS,
+                self.columns.len()
+            );
+        }
+        for (i, column) in self.columns.iter().enumerate() {
+            if self.columns[..i].contains(column) {
+                bail!("clustering column '{}' is listed twice", column);
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that every clustering column is in `schema`
+    pub fn check_schema(&self, schema: &Schema) -> Result<()> {
+        for column in &self.columns {
+            if schema.column_with_name(column).is_none() {
+                bail!("unknown clustering column '{}'", column);
+            }
+        }
+        Ok(())
+    }
+
+    /// Curve position of every row of `batch`
+    pub fn keys(&self, batch: &RecordBatch) -> Result<Vec<u64>> {
+        let rows = batch.num_rows();
+        let mut coordinates = Vec::with_capacity(self.columns.len());
+        for column in &self.columns {
+            let Some(values) = batch.column_by_name(column) else {
+                bail!("unknown clustering column '{}'", column);
+            };
+            let ranks = rank(values.as_ref(), None)
+                .with_context(|| format!("cannot cluster by column '{}'", column))?;
+            coordinates.push(ranks);
+        }
+
+        // Ranks run from 1 to the row count; keep their top bits
+        let bits = (64 / self.columns.len() as u32).min(32);
+        let needed = 32 - (rows as u32).leading_zeros();
+        let shift = needed.saturating_sub(bits);
+        let mut point = vec![0u32; self.columns.len()];
+        Ok((0..rows)
+            .map(|row| {
+                for (coordinate, ranks) in point.iter_mut().zip(&coordinates) {
+                    *coordinate = ranks[row] >> shift;
+                }
+                match self.curve {
+                    Curve::ZOrder => interleave(&point, bits),
+                    Curve::Hilbert => hilbert(&mut point, bits),
+                }
+            })
+            .collect())
+    }
+
+    /// Rows of `batch` in curve order; rows sharing a key keep their order
+    pub fn sort(&self, batch: &RecordBatch) -> Result<RecordBatch> {
+        let keys = self.keys(batch)?;
+        let mut order: Vec<u32> = (0..batch.num_rows() as u32).collect();
+        order.sort_by_key(|&row| keys[row as usize]);
+        Ok(take_record_batch(batch, &UInt32Array::from(order))?)
+    }
+}
+
+/// Interleave the low `bits` bits of each coordinate, most significant first
+fn interleave(point: &[u32], bits: u32) -> u64 {
+    let mut key = 0u64;
+    for bit in (0..bits).rev() {
+        for &coordinate in point {
+            key = (key << 1) | ((coordinate >> bit) & 1) as u64;
+        }
+    }
+    key
+}
+
+/// Hilbert index of `point` with `bits` bits per coordinate
+///
+/// Skilling's transform ("Programming the Hilbert curve", 2004) turns the
+/// coordinates into the transposed Hilbert index, whose interleaved bits are
+/// the index itself. A single coordinate is its own index.
+fn hilbert(point: &mut [u32], bits: u32) -> u64 {
+    if point.len() > 1 {
+        let top = 1u32 << (bits - 1);
+        let mut q = top;
+        while q > 1 {
+            let p = q - 1;
+            for i in 0..point.len() {
+                if point[i] & q != 0 {
+                    point[0] ^= p;
+                } else {
+                    let t = (point[0] ^ point[i]) & p;
+                    point[0] ^= t;
+                    point[i] ^= t;
+                }
+            }
+            q >>= 1;
+        }
+        for i in 1..point.len() {
+            point[i] ^= point[i - 1];
+        }
+        let last = point[point.len() - 1];
+        let mut t = 0;
+        let mut q = top;
+        while q > 1 {
+            if last & q != 0 {
+                t ^= q - 1;
+            }
+            q >>= 1;
+        }
+        for coordinate in point.iter_mut() {
+            *coordinate ^= t;
+        }
+    }
+    interleave(point, bits)
+}
+
+/// Holds back every row of a file and writes them in curve order
+///
+/// Sorting needs the whole file, so the sink buffers all of its rows until
+/// it is finished; files should fit in memory. Rows are written in batches
+/// of the largest incoming batch size.
+pub struct ClusterSink<S: Sink> {
+    inner: S,
+    config: ClusterConfig,
+    buffer: Vec<RecordBatch>,
+    max_batch_rows: usize,
+}
+
+impl<S: Sink> ClusterSink<S> {
+    /// Cluster the rows written to `inner` as `config` describes
+    pub fn new(inner: S, config: ClusterConfig) -> Self {
+        Self {
+            inner,
+            config,
+            buffer: Vec::new(),
+            max_batch_rows: 1,
+        }
+    }
+}
+
+impl<S: Sink> Sink for ClusterSink<S> {
+    fn readiness(&mut self) -> Result<Readiness> {
+        self.inner.readiness()
+    }
+
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        self.max_batch_rows = self.max_batch_rows.max(batch.num_rows());
+        self.buffer.push(batch.clone());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if let Some(first) = self.buffer.first() {
+            let all = concat_batches(&first.schema(), &self.buffer)?;
+            self.buffer.clear();
+            let sorted = self.config.sort(&all)?;
+            let mut offset = 0;
+            while offset < sorted.num_rows() {
+                let len = self.max_batch_rows.min(sorted.num_rows() - offset);
+                self.inner.write(&sorted.slice(offset, len))?;
+                offset += len;
+            }
+        }
+        self.inner.finish()
+    }
+
+    fn abort(&mut self) -> Result<()> {
+        self.buffer.clear();
+        self.inner.abort()
+    }
+}
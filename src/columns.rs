@@ -0,0 +1,1419 @@
+//! Column definitions and per-column value generation
+//!
+//! Each column of a batch is generated independently from its own RNG stream,
+//! seeded from the run seed, the file index, the batch index and the column
+//! position. This keeps output deterministic while allowing columns to be
+//! generated on separate threads.
+
+use anyhow::{anyhow, bail, Context, Result};
+use arrow::array::{
+    make_array, new_null_array, Array, ArrayRef, AsArray, BinaryBuilder, FixedSizeBinaryBuilder,
+    FixedSizeListArray, Float16Array, Float32Array, Float32Builder, Float64Array,
+    GenericBinaryBuilder, Int32Array, Int32Builder, Int64Array, Int8Array, ListBuilder,
+    OffsetSizeTrait, StringBuilder, StructArray, TimestampMillisecondArray, UInt32Array,
+    UInt8Array,
+};
+use arrow::buffer::{BooleanBuffer, NullBuffer};
+use arrow::compute::take;
+use arrow::datatypes::{
+    DataType, Field, FieldRef, Fields, Float16Type, Float32Type, Float64Type, Int8Type, TimeUnit,
+    UInt8Type,
+};
+use half::f16;
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, OnceLock};
+
+use crate::category::Categories;
+use crate::core_gen::{
+    decode_vector_with, dequantize_int8, encode_vector_with, int8_scale, normalize,
+    quantize_int8, sample_scalar, sample_scalar_into, stream_seed, ByteOrder, Quantization,
+    VectorEncoding, VectorLayout,
+};
+use crate::correlation::CorrelationPlan;
+use crate::duplicates::DuplicateBlock;
+use crate::id_column::{uuid, IdColumn, ID_COLUMN_NAME};
+use crate::mixture::{Mixture, VectorSampler};
+use crate::numeric::{self, NumericBounds, NumericDistribution};
+use crate::payload::{sample_payload, PayloadConfig};
+use crate::pq::Codebook;
+use crate::pruning::PruningConfig;
+use crate::schema_spec::{ColumnType, SchemaSpec};
+use crate::session::SessionConfig;
+use crate::skew::KeySampler;
+use crate::sparse::{sample_sparse, SparseConfig};
+use crate::text::{tokenize, Vocabulary};
+use crate::timestamp::TimestampValues;
+use crate::trajectory::TrajectoryBlock;
+use crate::wide;
+use crate::{Config, VectorElementType};
+
+/// Kind of values a column holds
+#[derive(Debug, Clone)]
+pub(crate) enum ColumnKind {
+    /// Vector of `dim` values stored as bytes in `layout`
+    Vector {
+        dim: usize,
+        element: VectorElementType,
+        layout: VectorLayout,
+        quantization: Option<Quantization>,
+        /// Divide each vector by its largest magnitude before encoding, so
+        /// int8 codes span [-127, 127] (SQ8 with a `vector_scale` column)
+        scaled: bool,
+        /// Use 64-bit offsets (`LargeBinary`) for batches beyond 2GB
+        large: bool,
+        encoding: VectorEncoding,
+        sampler: VectorSampler,
+    },
+    /// Random alphanumeric string of `len` bytes after a fixed `prefix`
+    Scalar { len: usize, prefix: String },
+    /// One of a fixed vocabulary of strings, with skewed frequencies
+    Category { categories: Arc<Categories> },
+    /// Int32, Int64, Float32 or Float64 values, uniform over the type range
+    /// or `[0, 1)` without a distribution, within `bounds`; correlated columns
+    /// map their score of the batch's correlated scores at `index`
+    Numeric {
+        data_type: DataType,
+        distribution: Option<NumericDistribution>,
+        bounds: NumericBounds,
+        correlated: Option<(Arc<CorrelationPlan>, usize)>,
+    },
+    /// Packed PQ codes of the vectors drawn from the same stream
+    PqCode {
+        dim: usize,
+        codebook: Arc<Codebook>,
+        sampler: VectorSampler,
+    },
+    /// SQ8 scale factor of the vectors drawn from the same stream
+    VectorScale { dim: usize, sampler: VectorSampler },
+    /// Tenant id drawn from a skewed distribution, or fixed to one tenant
+    Tenant {
+        sampler: WeightedIndex<f64>,
+        only: Option<u32>,
+    },
+    /// Foreign key into a parent table
+    ForeignKey { sampler: KeySampler },
+    /// User owning the session of each row
+    UserId { sessions: SessionConfig },
+    /// Session each row belongs to
+    SessionId { sessions: SessionConfig },
+    /// Pool index of rows whose vector repeats across files, null otherwise
+    DuplicateId,
+    /// Entity id of trajectory rows
+    EntityId,
+    /// Timestamp of trajectory rows
+    EventTime,
+    /// Global row index of pruning fixtures and monotonic ids
+    RowId,
+    /// UUID hashed from the seed and the global row index
+    Uuid { seed: u64 },
+    /// Timestamp derived from the global row index of pruning fixtures
+    RowTime { pruning: PruningConfig },
+    /// Random or monotonic timestamp of a timestamp column
+    Timestamp { values: TimestampValues },
+    /// Opaque bytes of controlled compressibility
+    Payload { payload: PayloadConfig },
+    /// Non-zero entries of a sparse vector, as lists of indices and values
+    Sparse { sparse: SparseConfig },
+    /// Column `index` of a wide logical schema
+    Wide { index: usize },
+    /// Words drawn from a Zipf-distributed vocabulary
+    Text { vocabulary: Arc<Vocabulary> },
+    /// Token ids of the text drawn from the same stream
+    TextTokens {
+        vocabulary: Arc<Vocabulary>,
+        seed: u64,
+    },
+    /// Locale of the text drawn from the same stream
+    Lang { vocabulary: Arc<Vocabulary> },
+}
+
+/// RNG stream of the first vector column
+///
+/// Mixed-precision copies and PQ codes draw from this stream too, and
+/// trajectory mode replaces its sampled values with the entity walks.
+pub(crate) const PRIMARY_VECTOR_STREAM: usize = 0;
+
+/// Values shared by the columns of one batch, computed before the
+/// per-column tasks run
+#[derive(Debug, Default)]
+pub(crate) struct BatchContext {
+    /// Global index of the batch's first row
+    pub(crate) first_row: u64,
+    /// Trajectory rows replacing the primary vector stream
+    pub(crate) trajectory: Option<TrajectoryBlock>,
+    /// Rows of the primary vector stream repeating a pool vector
+    pub(crate) duplicates: Option<DuplicateBlock>,
+    /// Vectors replacing the primary vector stream, laid out as `[row][dim]`,
+    /// e.g. perturbed base vectors of a query set
+    pub(crate) vectors: Option<Vec<f64>>,
+    /// Correlated normal scores of the correlated numeric columns, laid out
+    /// as `[row][column]`
+    pub(crate) correlated: Option<Vec<f64>>,
+}
+
+/// A named column and the recipe for generating its values
+#[derive(Debug, Clone)]
+pub(crate) struct ColumnGenerator {
+    pub(crate) field: FieldRef,
+    pub(crate) kind: ColumnKind,
+    /// Index of the RNG stream this column draws from
+    ///
+    /// Columns sharing a stream see the same random values, which is how
+    /// mixed-precision copies and PQ codes stay consistent with a vector column.
+    pub(crate) stream: usize,
+    /// Degenerate contents replacing the generated values
+    pub(crate) fill: Option<Fill>,
+    /// Array of the first batch of `fill` values, shared with clones: filled
+    /// values never change, so batches up to its size slice it
+    filled: Arc<OnceLock<ArrayRef>>,
+    /// Fraction of values replaced by nulls
+    pub(crate) null_ratio: Option<f64>,
+}
+
+/// Degenerate column contents for reader edge cases
+#[derive(Debug, Clone)]
+pub(crate) enum Fill {
+    /// Every value is null
+    Null,
+    /// Every value repeats this single-row array
+    Constant(ArrayRef),
+}
+
+impl ColumnGenerator {
+    fn new(name: &str, kind: ColumnKind, stream: usize) -> Self {
+        let field = match &kind {
+            ColumnKind::Vector {
+                dim,
+                element,
+                layout,
+                large,
+                encoding,
+                ..
+            } => {
+                let data_type = match (encoding, large) {
+                    (VectorEncoding::FixedSizeList, _) => DataType::FixedSizeList(
+                        Arc::new(Field::new("item", element_data_type(*element), true)),
+                        list_len(*element, *dim) as i32,
+                    ),
+                    (VectorEncoding::FixedSizeBinary, _) => {
+                        DataType::FixedSizeBinary(layout.encoded_len(*dim, *element) as i32)
+                    }
+                    (VectorEncoding::Binary, true) => DataType::LargeBinary,
+                    (VectorEncoding::Binary, false) => DataType::Binary,
+                };
+                let mut metadata = HashMap::from([
+                    (VECTOR_DIM_KEY.to_string(), dim.to_string()),
+                    (ELEMENT_TYPE_KEY.to_string(), element.name().to_string()),
+                ]);
+                // Only non-default layouts are recorded, keeping default
+                // schemas unchanged
+                if layout.byte_order != ByteOrder::Little {
+                    metadata.insert(
+                        BYTE_ORDER_KEY.to_string(),
+                        layout.byte_order.name().to_string(),
+                    );
+                }
+                if layout.dim_prefix {
+                    metadata.insert(DIM_PREFIX_KEY.to_string(), "true".to_string());
+                }
+                Field::new(name, data_type, false).with_metadata(metadata)
+            }
+            ColumnKind::Scalar { .. }
+            | ColumnKind::Category { .. }
+            | ColumnKind::Text { .. }
+            | ColumnKind::Lang { .. }
+            | ColumnKind::Uuid { .. } => {
+                Field::new(name, DataType::Utf8, false)
+            }
+            ColumnKind::TextTokens { seed, .. } => Field::new(
+                name,
+                DataType::List(Arc::new(Field::new("item", DataType::Int32, true))),
+                false,
+            )
+            .with_metadata(HashMap::from([(
+                TOKENIZER_SEED_KEY.to_string(),
+                seed.to_string(),
+            )])),
+            ColumnKind::Payload { .. } => Field::new(name, DataType::Binary, false),
+            ColumnKind::Sparse { sparse } => {
+                Field::new(name, DataType::Struct(sparse_fields()), false).with_metadata(
+                    HashMap::from([(SPARSE_DIM_KEY.to_string(), sparse.dim.to_string())]),
+                )
+            }
+            // Nullable like the merged schema, where files lacking the column
+            // contribute nulls
+            ColumnKind::Wide { index } => Field::new(name, wide::column_type(*index), true),
+            ColumnKind::PqCode { codebook, .. } => {
+                let pq = codebook.config();
+                Field::new(
+                    name,
+                    DataType::FixedSizeBinary(pq.code_bytes() as i32),
+                    false,
+                )
+                .with_metadata(HashMap::from([
+                    (
+                        PQ_SUBQUANTIZERS_KEY.to_string(),
+                        pq.subquantizers.to_string(),
+                    ),
+                    (PQ_NBITS_KEY.to_string(), pq.nbits.to_string()),
+                ]))
+            }
+            ColumnKind::Numeric { data_type, bounds, .. } => {
+                let mut metadata = HashMap::new();
+                metadata.extend(bounds.min.map(|min| (MIN_KEY.to_string(), min.to_string())));
+                metadata.extend(bounds.max.map(|max| (MAX_KEY.to_string(), max.to_string())));
+                metadata.extend(
+                    bounds
+                        .precision
+                        .map(|p| (PRECISION_KEY.to_string(), p.to_string())),
+                );
+                Field::new(name, data_type.clone(), false).with_metadata(metadata)
+            }
+            ColumnKind::VectorScale { .. } => Field::new(name, DataType::Float32, false),
+            ColumnKind::Tenant { .. } => Field::new(name, DataType::Int32, false),
+            ColumnKind::ForeignKey { .. }
+            | ColumnKind::UserId { .. }
+            | ColumnKind::SessionId { .. }
+            | ColumnKind::EntityId
+            | ColumnKind::RowId => Field::new(name, DataType::Int64, false),
+            ColumnKind::DuplicateId => Field::new(name, DataType::Int64, true),
+            ColumnKind::EventTime | ColumnKind::RowTime { .. } | ColumnKind::Timestamp { .. } => {
+                Field::new(
+                name,
+                    DataType::Timestamp(TimeUnit::Millisecond, None),
+                    false,
+                )
+            }
+        };
+        Self {
+            field: Arc::new(field),
+            kind,
+            stream,
+            fill: None,
+            filled: Arc::default(),
+            null_ratio: None,
+        }
+    }
+
+    /// Generate `rows` values for this column
+    pub(crate) fn generate(
+        &self,
+        rng: &mut StdRng,
+        rows: usize,
+        ctx: &BatchContext,
+    ) -> Result<ArrayRef> {
+        let values = self.generate_values(rng, rows, ctx)?;
+        match self.null_ratio {
+            Some(ratio) if ratio > 0.0 => with_nulls(values, rng, ratio),
+            _ => Ok(values),
+        }
+    }
+
+    fn generate_values(
+        &self,
+        rng: &mut StdRng,
+        rows: usize,
+        ctx: &BatchContext,
+    ) -> Result<ArrayRef> {
+        if let Some(fill) = &self.fill {
+            return self.filled_values(fill, rows);
+        }
+        match &self.kind {
+            &ColumnKind::Vector {
+                encoding: VectorEncoding::FixedSizeList,
+                ..
+            } => self.vector_list_array(rng, rows, ctx),
+            &ColumnKind::Vector {
+                encoding: VectorEncoding::FixedSizeBinary,
+                ..
+            } => self.vector_fixed_array(rng, rows, ctx),
+            &ColumnKind::Vector { large: true, .. } => Ok(self.vector_array::<i64>(rng, rows, ctx)),
+            &ColumnKind::Vector { large: false, .. } => {
+                Ok(self.vector_array::<i32>(rng, rows, ctx))
+            }
+            ColumnKind::Scalar { len, prefix } => {
+                let len = *len;
+                let mut builder = StringBuilder::with_capacity(rows, rows * (prefix.len() + len));
+                let mut value = String::with_capacity(prefix.len() + len);
+                for _ in 0..rows {
+                    value.clear();
+                    value.push_str(prefix);
+                    sample_scalar_into(rng, len, &mut value);
+                    builder.append_value(&value);
+                }
+                Ok(Arc::new(builder.finish()))
+            }
+            ColumnKind::Numeric {
+                data_type,
+                distribution,
+                bounds,
+                correlated: Some((plan, index)),
+            } => {
+                // Scores shared by the batch, or drawn here for single values
+                let drawn;
+                let scores = match &ctx.correlated {
+                    Some(scores) => scores,
+                    None => {
+                        drawn = plan.draw(rng, rows);
+                        &drawn
+                    }
+                };
+                let distribution = distribution
+                    .clone()
+                    .unwrap_or_else(|| NumericDistribution::default_for(data_type));
+                let values = scores
+                    .iter()
+                    .skip(*index)
+                    .step_by(plan.dims())
+                    .take(rows)
+                    .map(|&z| {
+                        let p = numeric::normal_cdf(z)
+                            .clamp(f64::MIN_POSITIVE, 1.0 - f64::EPSILON);
+                        bounds.apply(distribution.value_at(p, data_type))
+                    });
+                Ok(numeric_array(data_type, values))
+            }
+            ColumnKind::Numeric {
+                data_type,
+                distribution,
+                bounds,
+                ..
+            } if distribution.is_some() || !bounds.is_none() => {
+                let values = (0..rows).map(|_| {
+                    let value = match distribution {
+                        Some(distribution) => distribution.sample(rng, data_type),
+                        None => numeric::default_sample(rng, data_type),
+                    };
+                    bounds.apply(value)
+                });
+                Ok(numeric_array(data_type, values))
+            }
+            ColumnKind::Numeric { data_type, .. } => Ok(match data_type {
+                DataType::Int32 => Arc::new(Int32Array::from_iter_values(
+                    (0..rows).map(|_| rng.gen::<i32>()),
+                )),
+                DataType::Int64 => Arc::new(Int64Array::from_iter_values(
+                    (0..rows).map(|_| rng.gen::<i64>()),
+                )),
+                DataType::Float32 => Arc::new(Float32Array::from_iter_values(
+                    (0..rows).map(|_| rng.gen::<f32>()),
+                )),
+                _ => Arc::new(Float64Array::from_iter_values(
+                    (0..rows).map(|_| rng.gen::<f64>()),
+                )),
+            }),
+            &ColumnKind::Payload { payload } => {
+                let mut builder = BinaryBuilder::with_capacity(rows, rows * payload.len);
+                let mut bytes = Vec::with_capacity(payload.len);
+                for _ in 0..rows {
+                    sample_payload(rng, payload.len, payload.entropy, &mut bytes);
+                    builder.append_value(&bytes);
+                }
+                Ok(Arc::new(builder.finish()))
+            }
+            ColumnKind::Sparse { sparse } => {
+                let mut indices = ListBuilder::new(Int32Builder::new());
+                let mut values = ListBuilder::new(Float32Builder::new());
+                let (mut row_indices, mut row_values) = (Vec::new(), Vec::new());
+                for _ in 0..rows {
+                    sample_sparse(rng, sparse, &mut row_indices, &mut row_values);
+                    indices.append_value(row_indices.iter().map(|&i| Some(i)));
+                    values.append_value(row_values.iter().map(|&v| Some(v)));
+                }
+                Ok(Arc::new(StructArray::try_new(
+                    sparse_fields(),
+                    vec![Arc::new(indices.finish()) as ArrayRef, Arc::new(values.finish())],
+                    None,
+                )?))
+            }
+            &ColumnKind::Wide { index } => Ok(match wide::column_type(index) {
+                DataType::Int64 => Arc::new(Int64Array::from_iter_values(
+                    (0..rows).map(|_| rng.gen::<i64>()),
+                )),
+                DataType::Float64 => Arc::new(Float64Array::from_iter_values(
+                    (0..rows).map(|_| rng.gen::<f64>()),
+                )),
+                _ => {
+                    let mut builder = StringBuilder::with_capacity(rows, rows * wide::STRING_LEN);
+                    for _ in 0..rows {
+                        builder.append_value(sample_scalar(rng, wide::STRING_LEN));
+                    }
+                    Arc::new(builder.finish())
+                }
+            }),
+            ColumnKind::Text { vocabulary } => {
+                let mut builder =
+                    StringBuilder::with_capacity(rows, rows * vocabulary.mean_value_len());
+                let mut value = String::new();
+                for _ in 0..rows {
+                    value.clear();
+                    vocabulary.sample(rng, &mut value);
+                    builder.append_value(&value);
+                }
+                Ok(Arc::new(builder.finish()))
+            }
+            ColumnKind::TextTokens { vocabulary, seed } => {
+                let mut builder = ListBuilder::new(Int32Builder::new());
+                let mut value = String::new();
+                for _ in 0..rows {
+                    value.clear();
+                    vocabulary.sample(rng, &mut value);
+                    builder.append_value(tokenize(&value, *seed).into_iter().map(Some));
+                }
+                Ok(Arc::new(builder.finish()))
+            }
+            ColumnKind::Category { categories } => {
+                let mut builder =
+                    StringBuilder::with_capacity(rows, rows * categories.value_len());
+                for _ in 0..rows {
+                    builder.append_value(categories.sample(rng));
+                }
+                Ok(Arc::new(builder.finish()))
+            }
+            ColumnKind::Lang { vocabulary } => {
+                let mut builder = StringBuilder::with_capacity(rows, rows * 2);
+                let mut value = String::new();
+                for _ in 0..rows {
+                    value.clear();
+                    builder.append_value(vocabulary.sample(rng, &mut value).code());
+                }
+                Ok(Arc::new(builder.finish()))
+            }
+            ColumnKind::PqCode {
+                dim,
+                codebook,
+                sampler,
+            } => {
+                let code_bytes = codebook.config().code_bytes();
+                let mut builder = FixedSizeBinaryBuilder::with_capacity(rows, code_bytes as i32);
+                let mut code = Vec::with_capacity(code_bytes);
+                let mut result = Ok(());
+                self.for_each_vector(rng, *dim, sampler, rows, ctx, |vector| {
+                    code.clear();
+                    codebook.encode(vector, &mut code);
+                    if result.is_ok() {
+                        result = builder.append_value(&code);
+                    }
+                });
+                result?;
+                Ok(Arc::new(builder.finish()))
+            }
+            ColumnKind::VectorScale { dim, sampler } => {
+                let mut scales = Vec::with_capacity(rows);
+                self.for_each_vector(rng, *dim, sampler, rows, ctx, |vector| {
+                    scales.push(int8_scale(vector) as f32);
+                });
+                Ok(Arc::new(Float32Array::from(scales)))
+            }
+            ColumnKind::Tenant { sampler, only } => {
+                let tenants: Vec<i32> = match only {
+                    Some(tenant) => vec![*tenant as i32; rows],
+                    None => (0..rows).map(|_| sampler.sample(rng) as i32).collect(),
+                };
+                Ok(Arc::new(Int32Array::from(tenants)))
+            }
+            ColumnKind::ForeignKey { sampler } => {
+                let keys: Vec<i64> = (0..rows).map(|_| sampler.sample(rng) as i64).collect();
+                Ok(Arc::new(Int64Array::from(keys)))
+            }
+            ColumnKind::UserId { sessions } => Ok(Arc::new(Int64Array::from_iter_values(
+                (ctx.first_row..ctx.first_row + rows as u64).map(|r| sessions.user_id(r) as i64),
+            ))),
+            ColumnKind::SessionId { sessions } => Ok(Arc::new(Int64Array::from_iter_values(
+                (ctx.first_row..ctx.first_row + rows as u64).map(|r| sessions.session_id(r) as i64),
+            ))),
+            ColumnKind::DuplicateId => {
+                let block = ctx.duplicates.as_ref().ok_or_else(|| {
+                    anyhow::anyhow!("duplicate column generated without duplicates")
+                })?;
+                Ok(Arc::new(Int64Array::from_iter(
+                    block.picks.iter().map(|pick| pick.map(|i| i as i64)),
+                )))
+            }
+            ColumnKind::EntityId => Ok(Arc::new(Int64Array::from(
+                trajectory_block(ctx)?.entity_ids.clone(),
+            ))),
+            ColumnKind::EventTime => Ok(Arc::new(TimestampMillisecondArray::from(
+                trajectory_block(ctx)?.timestamps_ms.clone(),
+            ))),
+            ColumnKind::RowId => Ok(Arc::new(Int64Array::from_iter_values(
+                (ctx.first_row..ctx.first_row + rows as u64).map(|r| r as i64),
+            ))),
+            ColumnKind::Uuid { seed } => {
+                let mut builder = StringBuilder::with_capacity(rows, rows * IdColumn::UUID_LEN);
+                for row in ctx.first_row..ctx.first_row + rows as u64 {
+                    builder.append_value(uuid(*seed, row));
+                }
+                Ok(Arc::new(builder.finish()))
+            }
+            ColumnKind::RowTime { pruning } => {
+                Ok(Arc::new(TimestampMillisecondArray::from_iter_values(
+                    (ctx.first_row..ctx.first_row + rows as u64).map(|r| pruning.timestamp_ms(r)),
+                )))
+            }
+            ColumnKind::Timestamp { values } => {
+                let timestamps: Vec<i64> = match *values {
+                    TimestampValues::Uniform { start_ms, end_ms } => {
+                        (0..rows).map(|_| rng.gen_range(start_ms..end_ms)).collect()
+                    }
+                    TimestampValues::Monotonic { start_ms, step_ms } => (ctx.first_row
+                        ..ctx.first_row + rows as u64)
+                        .map(|row| TimestampValues::monotonic_ms(start_ms, step_ms, row))
+                        .collect(),
+                };
+                Ok(Arc::new(TimestampMillisecondArray::from(timestamps)))
+            }
+        }
+    }
+
+    /// Encode `rows` vectors into a binary array with `O` offsets
+    fn vector_array<O: OffsetSizeTrait>(
+        &self,
+        rng: &mut StdRng,
+        rows: usize,
+        ctx: &BatchContext,
+    ) -> ArrayRef {
+        let &ColumnKind::Vector {
+            dim,
+            element,
+            layout,
+            ..
+        } = &self.kind
+        else {
+            unreachable!("vector_array called on a non-vector column");
+        };
+        let value_bytes = layout.encoded_len(dim, element);
+        let mut builder = GenericBinaryBuilder::<O>::with_capacity(rows, rows * value_bytes);
+        self.for_each_encoded_vector(rng, rows, ctx, |bytes| builder.append_value(bytes));
+        Arc::new(builder.finish())
+    }
+
+    /// Build `rows` encoded vectors as a `FixedSizeBinary` array
+    fn vector_fixed_array(
+        &self,
+        rng: &mut StdRng,
+        rows: usize,
+        ctx: &BatchContext,
+    ) -> Result<ArrayRef> {
+        let DataType::FixedSizeBinary(value_bytes) = *self.field.data_type() else {
+            unreachable!("fixed-size vector column without a fixed-size type");
+        };
+        let mut builder = FixedSizeBinaryBuilder::with_capacity(rows, value_bytes);
+        let mut result = Ok(());
+        self.for_each_encoded_vector(rng, rows, ctx, |bytes| {
+            if result.is_ok() {
+                result = builder.append_value(bytes);
+            }
+        });
+        result?;
+        Ok(Arc::new(builder.finish()))
+    }
+
+    /// Call `f` with the encoded bytes of each of the `rows` vectors
+    fn for_each_encoded_vector(
+        &self,
+        rng: &mut StdRng,
+        rows: usize,
+        ctx: &BatchContext,
+        mut f: impl FnMut(&[u8]),
+    ) {
+        let &ColumnKind::Vector {
+            dim,
+            element,
+            layout,
+            quantization,
+            scaled,
+            ref sampler,
+            ..
+        } = &self.kind
+        else {
+            unreachable!("for_each_encoded_vector called on a non-vector column");
+        };
+        let mut bytes = Vec::with_capacity(layout.encoded_len(dim, element));
+        let mut quantized = Vec::with_capacity(dim);
+        self.for_each_vector(rng, dim, sampler, rows, ctx, |vector| {
+            bytes.clear();
+            match (quantization, scaled) {
+                (None, false) => encode_vector_with(vector, element, layout, &mut bytes),
+                _ => {
+                    quantized.clear();
+                    quantized.extend_from_slice(vector);
+                    transform_vector(&mut quantized, scaled, quantization);
+                    encode_vector_with(&quantized, element, layout, &mut bytes);
+                }
+            }
+            f(&bytes);
+        });
+    }
+
+    /// Build `rows` vectors as a `FixedSizeList` of typed values
+    fn vector_list_array(
+        &self,
+        rng: &mut StdRng,
+        rows: usize,
+        ctx: &BatchContext,
+    ) -> Result<ArrayRef> {
+        let &ColumnKind::Vector {
+            dim,
+            element,
+            quantization,
+            scaled,
+            ref sampler,
+            ..
+        } = &self.kind
+        else {
+            unreachable!("vector_list_array called on a non-vector column");
+        };
+        let mut values = Vec::with_capacity(rows * dim);
+        self.for_each_vector(rng, dim, sampler, rows, ctx, |vector| {
+            let start = values.len();
+            values.extend_from_slice(vector);
+            transform_vector(&mut values[start..], scaled, quantization);
+        });
+        let values: ArrayRef = match element {
+            VectorElementType::Float16 => Arc::new(Float16Array::from_iter_values(
+                values.into_iter().map(f16::from_f64),
+            )),
+            VectorElementType::Float32 => Arc::new(Float32Array::from_iter_values(
+                values.into_iter().map(|v| v as f32),
+            )),
+            VectorElementType::Float64 => Arc::new(Float64Array::from(values)),
+            VectorElementType::Int8 => Arc::new(Int8Array::from_iter_values(
+                values.into_iter().map(quantize_int8),
+            )),
+            VectorElementType::Binary => {
+                let mut bytes = Vec::with_capacity(rows * element.vector_bytes(dim));
+                for vector in values.chunks(dim) {
+                    encode_vector_with(vector, element, VectorLayout::default(), &mut bytes);
+                }
+                Arc::new(UInt8Array::from(bytes))
+            }
+        };
+        let DataType::FixedSizeList(item, _) = self.field.data_type() else {
+            unreachable!("list vector column without a list type");
+        };
+        Ok(Arc::new(FixedSizeListArray::try_new(
+            item.clone(),
+            list_len(element, dim) as i32,
+            values,
+            None,
+        )?))
+    }
+
+    /// Most rows one array of this column can hold
+    ///
+    /// Binary and string arrays with 32-bit offsets are limited to 2GB of
+    /// values; other columns have no practical limit.
+    pub(crate) fn max_rows(&self) -> usize {
+        let value_bytes = match &self.kind {
+            ColumnKind::Vector {
+                dim,
+                element,
+                layout,
+                large: false,
+                encoding: VectorEncoding::Binary,
+                ..
+            } => layout.encoded_len(*dim, *element),
+            ColumnKind::Scalar { len, prefix } => prefix.len() + len,
+            ColumnKind::Category { categories } => categories.value_len(),
+            ColumnKind::Payload { payload } => payload.len,
+            ColumnKind::Text { vocabulary } => vocabulary.max_value_len(),
+            ColumnKind::Uuid { .. } => IdColumn::UUID_LEN,
+            _ => return usize::MAX,
+        };
+        (i32::MAX as usize / value_bytes.max(1)).max(1)
+    }
+
+    /// `rows` values of a filled column, a slice of the cached array when it
+    /// is large enough
+    ///
+    /// Wide schemas of mostly constant columns then only pay for the columns
+    /// that change; writers never modify arrays, so the copies share buffers.
+    /// The first batch is cached without locking, so worker threads never
+    /// wait on each other; a larger batch later builds its own array.
+    fn filled_values(&self, fill: &Fill, rows: usize) -> Result<ArrayRef> {
+        if let Some(array) = self.filled.get().filter(|array| array.len() >= rows) {
+            return Ok(array.slice(0, rows));
+        }
+        let array = match fill {
+            Fill::Null => new_null_array(self.field.data_type(), rows),
+            Fill::Constant(value) => take(value.as_ref(), &UInt32Array::from(vec![0; rows]), None)?,
+        };
+        // Another thread may have cached its first batch meanwhile
+        let _ = self.filled.set(array.clone());
+        Ok(array)
+    }
+
+    /// Call `f` with each of the `rows` vectors of this column's stream
+    fn for_each_vector(
+        &self,
+        rng: &mut StdRng,
+        dim: usize,
+        sampler: &VectorSampler,
+        rows: usize,
+        ctx: &BatchContext,
+        mut f: impl FnMut(&[f64]),
+    ) {
+        let mut normalized = Vec::with_capacity(if sampler.normalize { dim } else { 0 });
+        let mut f = |vector: &[f64]| {
+            if sampler.normalize {
+                normalized.clear();
+                normalized.extend_from_slice(vector);
+                normalize(&mut normalized);
+                f(&normalized)
+            } else {
+                f(vector)
+            }
+        };
+        let primary = self.stream == PRIMARY_VECTOR_STREAM;
+        if let (Some(vectors), true) = (&ctx.vectors, primary) {
+            return vectors.chunks(dim).take(rows).for_each(f);
+        }
+        match (&ctx.trajectory, &ctx.duplicates) {
+            (Some(block), _) if primary => block.vectors.chunks(dim).take(rows).for_each(f),
+            (None, Some(block)) if primary => {
+                for pick in block.picks.iter().take(rows) {
+                    match pick {
+                        Some(index) => f(&block.pool_vector(*index, dim, sampler)),
+                        None => f(&sampler.sample(rng, dim)),
+                    }
+                }
+            }
+            _ => (0..rows).for_each(|_| f(&sampler.sample(rng, dim))),
+        }
+    }
+
+    /// Approximate encoded size of one value, including length overhead
+    pub(crate) fn estimated_value_bytes(&self) -> usize {
+        match &self.kind {
+            ColumnKind::Vector {
+                dim,
+                element,
+                encoding: VectorEncoding::FixedSizeList,
+                ..
+            } => element.vector_bytes(*dim),
+            ColumnKind::Vector {
+                dim,
+                element,
+                layout,
+                encoding: VectorEncoding::FixedSizeBinary,
+                ..
+            } => layout.encoded_len(*dim, *element),
+            ColumnKind::Vector {
+                dim,
+                element,
+                layout,
+                ..
+            } => layout.encoded_len(*dim, *element) + 8,
+            ColumnKind::Scalar { len, prefix } => prefix.len() + len + 8,
+            ColumnKind::Category { categories } => categories.value_len() + 8,
+            ColumnKind::Payload { payload } => payload.len + 8,
+            ColumnKind::Sparse { sparse } => (sparse.mean_nnz() * 8.0) as usize + 16,
+            ColumnKind::Wide { index } => match wide::column_type(*index) {
+                DataType::Utf8 => wide::STRING_LEN + 8,
+                _ => 8,
+            },
+            ColumnKind::PqCode { codebook, .. } => codebook.config().code_bytes(),
+            ColumnKind::VectorScale { .. } => 4,
+            ColumnKind::Text { vocabulary } => vocabulary.mean_value_len() + 8,
+            ColumnKind::TextTokens { vocabulary, .. } => vocabulary.mean_words() * 4 + 8,
+            ColumnKind::Lang { .. } => 2 + 8,
+            ColumnKind::Uuid { .. } => IdColumn::UUID_LEN + 8,
+            ColumnKind::Numeric { data_type, .. } => data_type.primitive_width().unwrap_or(8),
+            ColumnKind::Tenant { .. } => 4,
+            ColumnKind::ForeignKey { .. }
+            | ColumnKind::UserId { .. }
+            | ColumnKind::SessionId { .. }
+            | ColumnKind::DuplicateId
+            | ColumnKind::EntityId
+            | ColumnKind::EventTime
+            | ColumnKind::RowId
+            | ColumnKind::RowTime { .. }
+            | ColumnKind::Timestamp { .. } => 8,
+        }
+    }
+}
+
+/// Field metadata key holding a vector column's dimension
+pub const VECTOR_DIM_KEY: &str = "vdg.vector_dim";
+/// Field metadata key holding a vector column's element type name
+pub const ELEMENT_TYPE_KEY: &str = "vdg.element_type";
+/// Field metadata key holding a vector column's byte order, if not little-endian
+pub const BYTE_ORDER_KEY: &str = "vdg.byte_order";
+/// Field metadata key set to `true` if vector values start with their
+/// dimension as a u32
+pub const DIM_PREFIX_KEY: &str = "vdg.dim_prefix";
+/// Field metadata key holding the seed of a token column's tokenizer
+pub const TOKENIZER_SEED_KEY: &str = "vdg.tokenizer_seed";
+/// Field metadata key holding the dimension of a sparse vector column
+pub const SPARSE_DIM_KEY: &str = "vdg.sparse_dim";
+/// Field metadata key holding the number of PQ sub-quantizers
+pub const PQ_SUBQUANTIZERS_KEY: &str = "vdg.pq_subquantizers";
+/// Field metadata key holding the bits per PQ sub-quantizer code
+pub const PQ_NBITS_KEY: &str = "vdg.pq_nbits";
+/// Field metadata key holding the smallest value of a bounded numeric column
+pub const MIN_KEY: &str = "vdg.min";
+/// Field metadata key holding the largest value of a bounded numeric column
+pub const MAX_KEY: &str = "vdg.max";
+/// Field metadata key holding the decimal places of a numeric column
+pub const PRECISION_KEY: &str = "vdg.precision";
+
+/// Binary layout of a vector column, recorded in its field metadata
+pub(crate) fn vector_layout(field: &Field) -> VectorLayout {
+    let metadata = field.metadata();
+    VectorLayout {
+        byte_order: metadata
+            .get(BYTE_ORDER_KEY)
+            .and_then(|name| ByteOrder::from_name(name))
+            .unwrap_or_default(),
+        dim_prefix: metadata.get(DIM_PREFIX_KEY).is_some_and(|v| v == "true"),
+    }
+}
+
+/// Arrow type of the values of `element` vectors in list encodings
+fn element_data_type(element: VectorElementType) -> DataType {
+    match element {
+        VectorElementType::Float16 => DataType::Float16,
+        VectorElementType::Float32 => DataType::Float32,
+        VectorElementType::Float64 => DataType::Float64,
+        VectorElementType::Int8 => DataType::Int8,
+        VectorElementType::Binary => DataType::UInt8,
+    }
+}
+
+/// Items of each list of `element` vectors: the packed bytes of binary
+/// vectors, one per dimension otherwise
+fn list_len(element: VectorElementType, dim: usize) -> usize {
+    match element {
+        VectorElementType::Binary => element.vector_bytes(dim),
+        _ => dim,
+    }
+}
+
+/// Scale `values` onto [-1, 1] by their largest magnitude if `scaled`, then
+/// apply `quantization`
+fn transform_vector(values: &mut [f64], scaled: bool, quantization: Option<Quantization>) {
+    if scaled {
+        let max = int8_scale(values) * 127.0;
+        if max > 0.0 {
+            values.iter_mut().for_each(|v| *v /= max);
+        }
+    }
+    if let Some(quantization) = quantization {
+        quantization.apply(values);
+    }
+}
+
+/// Values of the vector at `row` of `array`, a vector column described by
+/// `field`, whatever its encoding; `None` if null or not a vector
+pub(crate) fn vector_values(field: &Field, array: &dyn Array, row: usize) -> Option<Vec<f64>> {
+    if array.is_null(row) {
+        return None;
+    }
+    let (element, layout) = vector_encoding(field)?;
+    let bytes = match array.data_type() {
+        DataType::FixedSizeList(_, _) => {
+            let vector = array.as_fixed_size_list().value(row);
+            return match vector.data_type() {
+                DataType::Float16 => Some(
+                    vector
+                        .as_primitive::<Float16Type>()
+                        .values()
+                        .iter()
+                        .map(|v| v.to_f64())
+                        .collect(),
+                ),
+                DataType::Float32 => Some(
+                    vector
+                        .as_primitive::<Float32Type>()
+                        .values()
+                        .iter()
+                        .map(|&v| v as f64)
+                        .collect(),
+                ),
+                DataType::Float64 => Some(vector.as_primitive::<Float64Type>().values().to_vec()),
+                DataType::Int8 => Some(
+                    vector
+                        .as_primitive::<Int8Type>()
+                        .values()
+                        .iter()
+                        .map(|&v| dequantize_int8(v))
+                        .collect(),
+                ),
+                DataType::UInt8 => Some(decode_vector_with(
+                    vector.as_primitive::<UInt8Type>().values(),
+                    element,
+                    VectorLayout::default(),
+                )),
+                _ => None,
+            };
+        }
+        DataType::Binary => array.as_binary::<i32>().value(row),
+        DataType::LargeBinary => array.as_binary::<i64>().value(row),
+        DataType::FixedSizeBinary(_) => array.as_fixed_size_binary().value(row),
+        _ => return None,
+    };
+    Some(decode_vector_with(bytes, element, layout))
+}
+
+/// Element type and layout of a vector column, `None` for other columns
+pub(crate) fn vector_encoding(field: &Field) -> Option<(VectorElementType, VectorLayout)> {
+    let element = field.metadata().get(ELEMENT_TYPE_KEY)?;
+    let element = VectorElementType::from_name(element)?;
+    Some((element, vector_layout(field)))
+}
+
+/// Stream identifier for the dataset-wide PQ codebook
+const CODEBOOK_STREAM: u64 = u64::MAX;
+
+/// Seed of the PQ codebook shared by every file generated from `config`
+pub(crate) fn codebook_seed(config: &Config) -> u64 {
+    stream_seed(config.seed, &[CODEBOOK_STREAM])
+}
+
+/// The default `vector` columns, their mixed-precision copy and `scalar`
+fn default_columns(
+    config: &Config,
+    vector_sampler: &VectorSampler,
+    columns: &mut Vec<ColumnGenerator>,
+) {
+    for i in 0..config.vector_columns.max(1) {
+        let name = match i {
+            0 => "vector".to_string(),
+            i => format!("vector_{}", i),
+        };
+        let kind = ColumnKind::Vector {
+            dim: config.vector_dim,
+            element: config.vector_type,
+            layout: config.vector_layout,
+            quantization: config.quantization,
+            scaled: config.int8_scale && i == 0,
+            large: config.large_binary,
+            encoding: config.vector_encoding,
+            sampler: vector_sampler.clone(),
+        };
+        columns.push(ColumnGenerator::new(&name, kind, columns.len()));
+    }
+    if let Some(element) = config.mixed_precision {
+        // Same values as the first vector column, stored at another precision
+        let name = format!("vector_{}", element.name());
+        let kind = ColumnKind::Vector {
+            dim: config.vector_dim,
+            element,
+            layout: config.vector_layout,
+            quantization: config.quantization,
+            scaled: false,
+            large: config.large_binary,
+            encoding: config.vector_encoding,
+            sampler: vector_sampler.clone(),
+        };
+        columns.push(ColumnGenerator::new(&name, kind, 0));
+    }
+    let kind = ColumnKind::Scalar {
+        len: config.scalar_len,
+        prefix: config.scalar_prefix.clone().unwrap_or_default(),
+    };
+    columns.push(ColumnGenerator::new("scalar", kind, columns.len()));
+}
+
+/// Columns of a schema spec, in spec order
+///
+/// The first vector column draws from the primary vector stream; the
+/// columns before it shift up by one stream.
+fn spec_columns(
+    config: &Config,
+    schema: &SchemaSpec,
+    sampler: &VectorSampler,
+    columns: &mut Vec<ColumnGenerator>,
+) {
+    let primary = schema.primary_vector().map(|(position, _)| position);
+    for (position, spec) in schema.columns.iter().enumerate() {
+        let stream = match primary {
+            Some(primary) if primary == position => PRIMARY_VECTOR_STREAM,
+            Some(primary) if position < primary => position + 1,
+            _ => position,
+        };
+        let kind = match spec.column_type {
+            ColumnType::Int32 | ColumnType::Int64 | ColumnType::Float32 | ColumnType::Float64 => {
+                // Uniform between both bounds unless a distribution is given
+                let bounds = spec.bounds;
+                let distribution = match (spec.distribution.clone(), bounds.min, bounds.max) {
+                    (None, Some(min), Some(max)) if min < max => {
+                        Some(NumericDistribution::Uniform { min, max })
+                    }
+                    (distribution, ..) => distribution,
+                };
+                ColumnKind::Numeric {
+                    data_type: spec
+                        .column_type
+                        .numeric_data_type()
+                        .expect("numeric column type"),
+                    distribution,
+                    bounds,
+                    correlated: None,
+                }
+            }
+            ColumnType::String { len } => ColumnKind::Scalar {
+                len: len.unwrap_or(config.scalar_len),
+                prefix: config.scalar_prefix.clone().unwrap_or_default(),
+            },
+            ColumnType::Category { cardinality, skew } => ColumnKind::Category {
+                categories: Arc::new(Categories::generate(
+                    config.seed,
+                    stream as u64,
+                    cardinality,
+                    skew,
+                )),
+            },
+            ColumnType::Sparse(sparse) => ColumnKind::Sparse { sparse },
+            ColumnType::Vector { element, dim } => {
+                // Cluster centroids have the primary dimension
+                let mut sampler = sampler.clone();
+                if dim != config.vector_dim {
+                    sampler.mixture = None;
+                }
+                ColumnKind::Vector {
+                    dim,
+                    element,
+                    layout: config.vector_layout,
+                    quantization: config.quantization,
+                    scaled: config.int8_scale && stream == PRIMARY_VECTOR_STREAM,
+                    large: config.large_binary,
+                    encoding: config.vector_encoding,
+                    sampler,
+                }
+            }
+        };
+        columns.push(ColumnGenerator::new(&spec.name, kind, stream));
+    }
+}
+
+/// Build the column layout described by `config`
+///
+/// Fails if the configuration cannot be realized, e.g. invalid PQ parameters.
+pub(crate) fn columns_for(config: &Config) -> Result<Vec<ColumnGenerator>> {
+    let mut columns = Vec::with_capacity(config.vector_columns + 2);
+    let mixture = match &config.mixture {
+        Some(mixture) => Some(Arc::new(Mixture::new(
+            mixture,
+            config.vector_dim,
+            config.vector_distribution,
+            config.seed,
+        )?)),
+        None => None,
+    };
+    let vector_sampler = VectorSampler {
+        distribution: config.vector_distribution,
+        mixture,
+        normalize: config.normalize,
+    };
+    match &config.schema {
+        Some(schema) => spec_columns(config, schema, &vector_sampler, &mut columns),
+        None => default_columns(config, &vector_sampler, &mut columns),
+    }
+    if let Some(pq) = config.pq {
+        // Codes of the first vector column, encoded against a dataset-wide codebook
+        let codebook = Codebook::generate(config.vector_dim, pq, codebook_seed(config))?;
+        let kind = ColumnKind::PqCode {
+            dim: config.vector_dim,
+            codebook: Arc::new(codebook),
+            sampler: vector_sampler.clone(),
+        };
+        columns.push(ColumnGenerator::new("pq_code", kind, 0));
+    }
+    if config.int8_scale {
+        // Restores the first vector column's values from its SQ8 codes
+        let kind = ColumnKind::VectorScale {
+            dim: config.vector_dim,
+            sampler: vector_sampler.clone(),
+        };
+        columns.push(ColumnGenerator::new("vector_scale", kind, 0));
+    }
+    if let Some(tenants) = &config.tenants {
+        let kind = ColumnKind::Tenant {
+            sampler: tenants.skew.sampler(tenants.tenants as usize)?,
+            only: tenants.only,
+        };
+        columns.push(ColumnGenerator::new("tenant_id", kind, columns.len()));
+    }
+    if let Some(fk) = &config.foreign_key {
+        let kind = ColumnKind::ForeignKey {
+            sampler: KeySampler::new(fk.parents, fk.skew)?,
+        };
+        columns.push(ColumnGenerator::new(&fk.column, kind, columns.len()));
+    }
+    if let Some(sessions) = config.sessions {
+        let kind = ColumnKind::UserId { sessions };
+        columns.push(ColumnGenerator::new("user_id", kind, columns.len()));
+        let kind = ColumnKind::SessionId { sessions };
+        columns.push(ColumnGenerator::new("session_id", kind, columns.len()));
+    }
+    if config.duplicates.is_some() {
+        columns.push(ColumnGenerator::new(
+            "duplicate_id",
+            ColumnKind::DuplicateId,
+            columns.len(),
+        ));
+    }
+    if config.trajectory.is_some() {
+        columns.push(ColumnGenerator::new(
+            "entity_id",
+            ColumnKind::EntityId,
+            columns.len(),
+        ));
+        columns.push(ColumnGenerator::new(
+            "ts",
+            ColumnKind::EventTime,
+            columns.len(),
+        ));
+    }
+    if let Some(pruning) = config.pruning {
+        columns.push(ColumnGenerator::new("id", ColumnKind::RowId, columns.len()));
+        let kind = ColumnKind::RowTime { pruning };
+        columns.push(ColumnGenerator::new("ts", kind, columns.len()));
+    }
+    if let Some(payload) = config.payload {
+        let kind = ColumnKind::Payload { payload };
+        columns.push(ColumnGenerator::new("payload", kind, columns.len()));
+    }
+    if let Some(sparse) = config.sparse {
+        let kind = ColumnKind::Sparse { sparse };
+        columns.push(ColumnGenerator::new("sparse_vector", kind, columns.len()));
+    }
+    if let Some(wide) = config.wide {
+        for index in 0..wide.columns {
+            let kind = ColumnKind::Wide { index };
+            columns.push(ColumnGenerator::new(
+                &wide::column_name(index),
+                kind,
+                columns.len(),
+            ));
+        }
+    }
+    if let Some(text) = &config.text {
+        let vocabulary = Arc::new(Vocabulary::new(text)?);
+        let stream = columns.len();
+        let kind = ColumnKind::Text {
+            vocabulary: vocabulary.clone(),
+        };
+        columns.push(ColumnGenerator::new("text", kind, stream));
+        // Tokens and locales of the same values, drawn again from the text stream
+        if text.tokens {
+            let kind = ColumnKind::TextTokens {
+                vocabulary: vocabulary.clone(),
+                seed: config.seed,
+            };
+            columns.push(ColumnGenerator::new("text_tokens", kind, stream));
+        }
+        if !text.locales.is_empty() {
+            let kind = ColumnKind::Lang { vocabulary };
+            columns.push(ColumnGenerator::new("lang", kind, stream));
+        }
+    }
+    if let Some(timestamp) = &config.timestamp {
+        if columns.iter().any(|c| c.field.name() == &timestamp.column) {
+            bail!("timestamp column '{}' clashes with another column", timestamp.column);
+        }
+        let kind = ColumnKind::Timestamp {
+            values: timestamp.values,
+        };
+        columns.push(ColumnGenerator::new(&timestamp.column, kind, columns.len()));
+    }
+    if let Some(id) = config.id_column {
+        // First in the schema, like a primary key; its stream follows the others
+        let kind = match id {
+            IdColumn::Monotonic => ColumnKind::RowId,
+            IdColumn::Uuid => ColumnKind::Uuid { seed: config.seed },
+        };
+        let column = ColumnGenerator::new(ID_COLUMN_NAME, kind, columns.len());
+        columns.insert(0, column);
+    }
+    apply_correlation(config, &mut columns)?;
+    apply_fills(config, &mut columns)?;
+    for null_ratio in &config.null_ratios {
+        let Some(column) = columns.iter_mut().find(|c| c.field.name() == &null_ratio.column) else {
+            bail!("unknown column '{}'", null_ratio.column);
+        };
+        column.field = Arc::new(column.field.as_ref().clone().with_nullable(true));
+        column.null_ratio = Some(null_ratio.ratio);
+    }
+    Ok(columns)
+}
+
+/// Drop the wide columns that file `file_index` does not write
+///
+/// Kept columns retain their streams, so their values do not depend on which
+/// other columns a file holds.
+pub(crate) fn select_file_columns(
+    config: &Config,
+    file_index: u64,
+    columns: &mut Vec<ColumnGenerator>,
+) {
+    let Some(wide) = config.wide else {
+        return;
+    };
+    let keep = wide.file_columns(config.seed, file_index);
+    columns.retain(|c| match c.kind {
+        ColumnKind::Wide { index } => keep.binary_search(&index).is_ok(),
+        _ => true,
+    });
+}
+
+/// Replace the columns named in `null_columns` and `constant_columns`
+///
+/// Constant columns repeat the first value their stream would produce in
+/// file 0, batch 0.
+fn apply_fills(config: &Config, columns: &mut [ColumnGenerator]) -> Result<()> {
+    for name in &config.null_columns {
+        let column = fill_target(columns, name)?;
+        column.field = Arc::new(column.field.as_ref().clone().with_nullable(true));
+        column.fill = Some(Fill::Null);
+    }
+    for name in &config.constant_columns {
+        let column = fill_target(columns, name)?;
+        let mut rng = column_rng(config.seed, 0, 0, column.stream);
+        let value = column
+            .generate(&mut rng, 1, &BatchContext::default())
+            .with_context(|| format!("column '{}' cannot be constant", name))?;
+        column.fill = Some(Fill::Constant(value));
+    }
+    Ok(())
+}
+
+/// Children of a sparse vector struct: ascending `indices` and their `values`
+fn sparse_fields() -> Fields {
+    let list = |item: DataType| DataType::List(Arc::new(Field::new("item", item, true)));
+    Fields::from(vec![
+        Field::new("indices", list(DataType::Int32), false),
+        Field::new("values", list(DataType::Float32), false),
+    ])
+}
+
+/// `array` with each value made null with probability `ratio`
+///
+/// The nulls are drawn from `rng` after the values, so the values that stay
+/// are the ones generated without nulls.
+fn with_nulls(array: ArrayRef, rng: &mut StdRng, ratio: f64) -> Result<ArrayRef> {
+    let valid = NullBuffer::new(BooleanBuffer::from_iter(
+        (0..array.len()).map(|_| !rng.gen_bool(ratio)),
+    ));
+    let nulls = NullBuffer::union(array.nulls(), Some(&valid));
+    let data = array.to_data().into_builder().nulls(nulls).build()?;
+    Ok(make_array(data))
+}
+
+/// Fraction of a column's values that are null, written `column=ratio`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NullRatio {
+    pub column: String,
+    /// Probability of each value being null, in [0, 1]
+    pub ratio: f64,
+}
+
+impl NullRatio {
+    pub fn validate(&self) -> Result<()> {
+        if !(0.0..=1.0).contains(&self.ratio) {
+            bail!(
+                "null ratio of column '{}' must be between 0 and 1, got {}",
+                self.column,
+                self.ratio
+            );
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for NullRatio {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.column, self.ratio)
+    }
+}
+
+impl FromStr for NullRatio {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (column, ratio) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow!("invalid null ratio '{}', expected COLUMN=RATIO", s))?;
+        let ratio = ratio
+            .trim()
+            .parse()
+            .with_context(|| format!("invalid null ratio '{}'", ratio.trim()))?;
+        let null_ratio = Self {
+            column: column.trim().to_string(),
+            ratio,
+        };
+        null_ratio.validate()?;
+        Ok(null_ratio)
+    }
+}
+
+fn fill_target<'a>(
+    columns: &'a mut [ColumnGenerator],
+    name: &str,
+) -> Result<&'a mut ColumnGenerator> {
+    match columns.iter_mut().find(|c| c.field.name() == name) {
+        Some(column) if column.fill.is_some() => {
+            bail!("column '{}' cannot be both null and constant", name)
+        }
+        Some(column) => Ok(column),
+        None => bail!("unknown column '{}'", name),
+    }
+}
+
+/// Array of `data_type` holding `values`, cast from `f64`
+fn numeric_array(data_type: &DataType, values: impl Iterator<Item = f64>) -> ArrayRef {
+    match data_type {
+        DataType::Int32 => Arc::new(Int32Array::from_iter_values(values.map(|v| v as i32))),
+        DataType::Int64 => Arc::new(Int64Array::from_iter_values(values.map(|v| v as i64))),
+        DataType::Float32 => Arc::new(Float32Array::from_iter_values(values.map(|v| v as f32))),
+        _ => Arc::new(Float64Array::from_iter_values(values)),
+    }
+}
+
+/// Link the correlated columns of `config` to one shared plan
+fn apply_correlation(config: &Config, columns: &mut [ColumnGenerator]) -> Result<()> {
+    let Some(correlation) = &config.correlation else {
+        return Ok(());
+    };
+    let plan = Arc::new(CorrelationPlan::new(correlation)?);
+    for (index, name) in correlation.columns.iter().enumerate() {
+        let Some(column) = columns.iter_mut().find(|c| c.field.name() == name) else {
+            bail!("unknown correlated column '{}'", name);
+        };
+        let ColumnKind::Numeric { correlated, .. } = &mut column.kind else {
+            bail!("correlated column '{}' is not a numeric column", name);
+        };
+        *correlated = Some((plan.clone(), index));
+    }
+    Ok(())
+}
+
+/// Correlation plan shared by the correlated columns, if any
+pub(crate) fn correlation_plan(columns: &[ColumnGenerator]) -> Option<Arc<CorrelationPlan>> {
+    columns.iter().find_map(|c| match &c.kind {
+        ColumnKind::Numeric {
+            correlated: Some((plan, _)),
+            ..
+        } => Some(plan.clone()),
+        _ => None,
+    })
+}
+
+fn trajectory_block(ctx: &BatchContext) -> Result<&TrajectoryBlock> {
+    ctx.trajectory
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("trajectory column generated outside trajectory mode"))
+}
+
+/// Derive an independent RNG for one column stream of one batch of one file
+pub(crate) fn column_rng(seed: u64, file_index: u64, batch_index: u64, stream: usize) -> StdRng {
+    StdRng::seed_from_u64(stream_seed(seed, &[file_index, batch_index, stream as u64]))
+}
@@ -0,0 +1,98 @@
+//! Companion files describing dataset-wide ground-truth structures
+//!
+//! Generators that draw rows from hidden structures (such as the PQ codebook)
+//! can write those structures to separate Parquet files, so index-quality
+//! evaluation can compare learned structures against the true ones.
+
+use anyhow::Result;
+use arrow::array::{ArrayRef, BinaryBuilder, Int32Array};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+#[cfg(feature = "parquet")]
+use parquet::file::properties::WriterProperties;
+use std::collections::HashMap;
+#[cfg(feature = "parquet")]
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::pq::Codebook;
+#[cfg(feature = "parquet")]
+use crate::sink::{ParquetFileSink, Sink};
+use crate::{ELEMENT_TYPE_KEY, PQ_NBITS_KEY, PQ_SUBQUANTIZERS_KEY, VECTOR_DIM_KEY};
+
+/// Write a PQ codebook to a Parquet file, returning the number of centroids
+#[cfg(feature = "parquet")]
+pub fn write_codebook(
+    path: impl AsRef<Path>,
+    codebook: &Codebook,
+    props: WriterProperties,
+) -> Result<usize> {
+    let batch = codebook_batch(codebook)?;
+    write_single_batch(path, &batch, props)?;
+    Ok(batch.num_rows())
+}
+
+/// A PQ codebook as one row per centroid
+///
+/// Columns are `subquantizer` (Int32), `centroid_id` (Int32) and `centroid`
+/// (little-endian f32 bytes of length `sub_dim`).
+pub fn codebook_batch(codebook: &Codebook) -> Result<RecordBatch> {
+    let pq = codebook.config();
+    let k = pq.centroids_per_subquantizer();
+    let rows = pq.subquantizers * k;
+
+    let mut subquantizers = Vec::with_capacity(rows);
+    let mut centroid_ids = Vec::with_capacity(rows);
+    let mut centroids = BinaryBuilder::with_capacity(rows, rows * codebook.sub_dim() * 4);
+    for sub in 0..pq.subquantizers {
+        for code in 0..k {
+            subquantizers.push(sub as i32);
+            centroid_ids.push(code as i32);
+            let bytes: Vec<u8> = codebook
+                .centroid(sub, code)
+                .iter()
+                .flat_map(|v| v.to_le_bytes())
+                .collect();
+            centroids.append_value(bytes);
+        }
+    }
+
+    let centroid_field =
+        Field::new("centroid", DataType::Binary, false).with_metadata(HashMap::from([
+            (VECTOR_DIM_KEY.to_string(), codebook.sub_dim().to_string()),
+            (ELEMENT_TYPE_KEY.to_string(), "f32".to_string()),
+        ]));
+    let schema = Schema::new(vec![
+        Field::new("subquantizer", DataType::Int32, false),
+        Field::new("centroid_id", DataType::Int32, false),
+        centroid_field,
+    ])
+    .with_metadata(HashMap::from([
+        (
+            PQ_SUBQUANTIZERS_KEY.to_string(),
+            pq.subquantizers.to_string(),
+        ),
+        (PQ_NBITS_KEY.to_string(), pq.nbits.to_string()),
+    ]));
+
+    Ok(RecordBatch::try_new(
+        Arc::new(schema),
+        vec![
+            Arc::new(Int32Array::from(subquantizers)) as ArrayRef,
+            Arc::new(Int32Array::from(centroid_ids)) as ArrayRef,
+            Arc::new(centroids.finish()) as ArrayRef,
+        ],
+    )?)
+}
+
+/// Write one batch to a new Parquet file
+#[cfg(feature = "parquet")]
+fn write_single_batch(
+    path: impl AsRef<Path>,
+    batch: &RecordBatch,
+    props: WriterProperties,
+) -> Result<()> {
+    let mut sink = ParquetFileSink::create(path, batch.schema(), props)?;
+    sink.write(batch)?;
+    sink.finish()
+}
@@ -0,0 +1,142 @@
+//! Versioned JSON form of [`Config`]
+//!
+//! Configurations are saved in dataset and golden manifests and passed as
+//! JSON to the WebAssembly, C and JVM bindings. Their JSON objects carry a
+//! `format_version` field; objects from releases before the field existed
+//! are format 1. Loading migrates older formats step by step to the current
+//! one, so saved configurations keep working as options are added, renamed
+//! or restructured. Newer formats and fields this release does not know
+//! (typos included) are rejected with an error naming them, instead of being
+//! silently ignored.
+//!
+//! Use with `#[serde(with = "crate::config_format")]` on `Config` fields of
+//! saved documents.
+
+use anyhow::{bail, Context, Result};
+use serde::de::Error as _;
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use crate::Config;
+
+/// Format version written by this release
+pub const CONFIG_FORMAT_VERSION: u32 = 1;
+
+/// Field holding the format version
+pub const FORMAT_VERSION_FIELD: &str = "format_version";
+
+/// Rewrite of a configuration object from one format to the next
+type Migration = fn(&mut Map<String, Value>) -> Result<()>;
+
+/// Migration of a configuration object from format `n` to `n + 1`, by `n - 1`
+///
+/// Empty until a release changes the meaning of a saved field; each such
+/// change bumps [`CONFIG_FORMAT_VERSION`] and appends its rewrite here.
+const MIGRATIONS: [Migration; CONFIG_FORMAT_VERSION as usize - 1] = [];
+
+/// JSON object of `config` in the current format
+pub fn to_value(config: &Config) -> Result<Value> {
+    let mut value = serde_json::to_value(config)?;
+    let Value::Object(object) = &mut value else {
+        bail!("configuration did not serialize to a JSON object");
+    };
+    object.insert(
+        FORMAT_VERSION_FIELD.to_string(),
+        Value::from(CONFIG_FORMAT_VERSION),
+    );
+    Ok(value)
+}
+
+/// Configuration from a JSON object of any known format
+///
+/// Omitted fields take their defaults.
+pub fn from_value(value: Value) -> Result<Config> {
+    from_value_over(value, &Config::default())
+}
+
+/// Configuration from a JSON object of any known format, with the fields it
+/// omits taken from `base`
+pub fn from_value_over(value: Value, base: &Config) -> Result<Config> {
+    let Value::Object(mut object) = value else {
+        bail!("configuration must be a JSON object");
+    };
+    let version = match object.remove(FORMAT_VERSION_FIELD) {
+        None => 1,
+        Some(version) => match version.as_u64() {
+            Some(version @ 1..) => version,
+            _ => bail!("invalid configuration format version {}", version),
+        },
+    };
+    if version > CONFIG_FORMAT_VERSION as u64 {
+        bail!(
+            "configuration format {} is newer than this release supports ({}); \
+             upgrade vector_data_gen",
+            version,
+            CONFIG_FORMAT_VERSION
+        );
+    }
+    for (from, migrate) in MIGRATIONS.iter().enumerate().skip(version as usize - 1) {
+        migrate(&mut object).with_context(|| {
+            format!(
+                "Failed to migrate configuration from format {} to {}",
+                from + 1,
+                from + 2
+            )
+        })?;
+    }
+
+    let Value::Object(mut fields) = serde_json::to_value(base)? else {
+        bail!("configuration did not serialize to a JSON object");
+    };
+    fields.extend(object.clone());
+    let config: Config = serde_json::from_value(Value::Object(fields)).context("Invalid configuration")?;
+    let mut unknown = Vec::new();
+    unknown_fields(&Value::Object(object), &serde_json::to_value(&config)?, "", &mut unknown);
+    if !unknown.is_empty() {
+        bail!("unknown configuration fields: {}", unknown.join(", "));
+    }
+    Ok(config)
+}
+
+/// Configuration from JSON text of any known format
+pub fn from_json(text: &str) -> Result<Config> {
+    from_value(serde_json::from_str(text).context("Invalid configuration JSON")?)
+}
+
+/// Dotted paths of the fields of `input` missing from `known`, the same
+/// configuration as serialized by this release
+fn unknown_fields(input: &Value, known: &Value, path: &str, unknown: &mut Vec<String>) {
+    match (input, known) {
+        (Value::Object(input), Value::Object(known)) => {
+            for (key, value) in input {
+                let field = match path {
+                    "" => key.clone(),
+                    path => format!("{}.{}", path, key),
+                };
+                match known.get(key) {
+                    Some(known) => unknown_fields(value, known, &field, unknown),
+                    None => unknown.push(field),
+                }
+            }
+        }
+        (Value::Array(input), Value::Array(known)) => {
+            for (i, (value, known)) in input.iter().zip(known).enumerate() {
+                unknown_fields(value, known, &format!("{}[{}]", path, i), unknown);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Serialize a configuration in the current format
+pub fn serialize<S: Serializer>(config: &Config, serializer: S) -> Result<S::Ok, S::Error> {
+    to_value(config)
+        .map_err(S::Error::custom)?
+        .serialize(serializer)
+}
+
+/// Deserialize a configuration of any known format
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Config, D::Error> {
+    from_value(Value::deserialize(deserializer)?).map_err(|e| D::Error::custom(format!("{:#}", e)))
+}
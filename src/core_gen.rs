@@ -0,0 +1,432 @@
+//! Pure value generation shared by every output format
+//!
+//! Vector and string sampling, vector encoding and seed derivation, with no
+//! dependency on Arrow or Parquet. Batch and file writers are built on top of
+//! these functions, and they can be reused on their own wherever only raw
+//! values are needed (e.g. a WASM widget). Key distributions live in
+//! [`crate::skew`] and are re-exported here.
+
+use half::f16;
+use rand::distributions::{Alphanumeric, Distribution, Uniform};
+use rand::Rng;
+use rand_distr::Normal;
+use serde::{Deserialize, Serialize};
+
+pub use crate::skew::{KeySampler, Skew};
+
+/// Element types for vector values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum VectorElementType {
+    /// IEEE half precision, as many vector stores ingest
+    Float16,
+    #[default]
+    Float32,
+    Float64,
+    /// Signed 8-bit codes in [-128, 127] of values scaled by 127, as SQ8
+    /// (scalar-quantized) indexes ingest
+    Int8,
+    /// One bit per dimension, set for positive values, packed eight to a
+    /// byte with the first dimension in the most significant bit; for
+    /// binary-embedding (Hamming distance) indexes
+    Binary,
+}
+
+impl VectorElementType {
+    /// Size of one element in bits
+    pub fn size_bits(self) -> usize {
+        match self {
+            VectorElementType::Float16 => 16,
+            VectorElementType::Float32 => 32,
+            VectorElementType::Float64 => 64,
+            VectorElementType::Int8 => 8,
+            VectorElementType::Binary => 1,
+        }
+    }
+
+    /// Size in bytes of the elements of a `dim`-dimensional vector
+    pub fn vector_bytes(self, dim: usize) -> usize {
+        (dim * self.size_bits()).div_ceil(8)
+    }
+
+    /// Short name used in column names and field metadata
+    pub fn name(self) -> &'static str {
+        match self {
+            VectorElementType::Float16 => "f16",
+            VectorElementType::Float32 => "f32",
+            VectorElementType::Float64 => "f64",
+            VectorElementType::Int8 => "i8",
+            VectorElementType::Binary => "binary",
+        }
+    }
+
+    /// Parse a name returned by [`VectorElementType::name`]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "f16" => Some(VectorElementType::Float16),
+            "f32" => Some(VectorElementType::Float32),
+            "f64" => Some(VectorElementType::Float64),
+            "i8" => Some(VectorElementType::Int8),
+            "binary" => Some(VectorElementType::Binary),
+            _ => None,
+        }
+    }
+}
+
+/// Sample a vector of `dim` values uniformly from [-1, 1)
+///
+/// Values are drawn at double precision and narrowed when encoded, so columns
+/// of different element types built from one stream hold the same vectors.
+pub fn sample_vector<R: Rng + ?Sized>(rng: &mut R, dim: usize) -> Vec<f64> {
+    let uniform = Uniform::new(-1.0, 1.0);
+    (0..dim).map(|_| uniform.sample(rng)).collect()
+}
+
+/// Scale `values` to unit L2 norm; the zero vector is left unchanged
+pub fn normalize(values: &mut [f64]) {
+    let norm = values.iter().map(|v| v * v).sum::<f64>().sqrt();
+    if norm > 0.0 {
+        values.iter_mut().for_each(|v| *v /= norm);
+    }
+}
+
+/// Distribution of vector components
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum VectorDistribution {
+    /// Uniform over [-1, 1), as [`sample_vector`] draws
+    #[default]
+    Uniform,
+    /// Independent Gaussian components, closer to real embeddings
+    Normal { mean: f64, std: f64 },
+}
+
+impl VectorDistribution {
+    /// Check the parameters
+    pub fn validate(self) -> anyhow::Result<()> {
+        match self {
+            VectorDistribution::Normal { mean, std }
+                if !mean.is_finite() || !std.is_finite() || std <= 0.0 =>
+            {
+                anyhow::bail!("normal vector distribution needs a finite mean and a positive std")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Short name used in messages
+    pub fn name(self) -> &'static str {
+        match self {
+            VectorDistribution::Uniform => "uniform",
+            VectorDistribution::Normal { .. } => "normal",
+        }
+    }
+
+    /// Sample a vector of `dim` values
+    ///
+    /// The uniform distribution draws exactly what [`sample_vector`] draws.
+    pub fn sample<R: Rng + ?Sized>(self, rng: &mut R, dim: usize) -> Vec<f64> {
+        match self {
+            VectorDistribution::Uniform => sample_vector(rng, dim),
+            VectorDistribution::Normal { mean, std } => {
+                let normal = Normal::new(mean, std).expect("validated normal parameters");
+                (0..dim).map(|_| normal.sample(rng)).collect()
+            }
+        }
+    }
+}
+
+/// Byte order of encoded vector elements
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ByteOrder {
+    #[default]
+    Little,
+    Big,
+}
+
+impl ByteOrder {
+    /// Name used in field metadata
+    pub fn name(self) -> &'static str {
+        match self {
+            ByteOrder::Little => "little",
+            ByteOrder::Big => "big",
+        }
+    }
+
+    /// Parse a name returned by [`ByteOrder::name`]
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "little" => Some(ByteOrder::Little),
+            "big" => Some(ByteOrder::Big),
+            _ => None,
+        }
+    }
+}
+
+/// Binary layout of one encoded vector value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VectorLayout {
+    /// Byte order of the elements and the dimension prefix (default:
+    /// little-endian)
+    pub byte_order: ByteOrder,
+    /// Start each value with its dimension as a u32, like a record of an
+    /// `.fvecs` file
+    pub dim_prefix: bool,
+}
+
+/// Size of the optional dimension prefix
+pub const DIM_PREFIX_BYTES: usize = 4;
+
+/// Arrow type holding the vectors of a column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum VectorEncoding {
+    /// Opaque `Binary` values of encoded bytes, in the configured layout
+    #[default]
+    Binary,
+    /// Typed `FixedSizeList<Float16 | Float32 | Float64 | Int8, dim>` values
+    /// (`FixedSizeList<UInt8, dim / 8>` of packed binary vectors), as
+    /// vector-aware engines (Lance, LanceDB, DuckDB arrays) expect
+    FixedSizeList,
+    /// `FixedSizeBinary` values in the configured layout; every vector has
+    /// the same length, so no offsets are stored
+    FixedSizeBinary,
+}
+
+impl VectorEncoding {
+    /// Short name used in messages
+    pub fn name(self) -> &'static str {
+        match self {
+            VectorEncoding::Binary => "binary",
+            VectorEncoding::FixedSizeList => "fixed_size_list",
+            VectorEncoding::FixedSizeBinary => "fixed_size_binary",
+        }
+    }
+}
+
+impl VectorLayout {
+    /// Encoded size of a `dim`-dimensional vector
+    pub fn encoded_len(self, dim: usize, element: VectorElementType) -> usize {
+        let prefix = if self.dim_prefix { DIM_PREFIX_BYTES } else { 0 };
+        prefix + element.vector_bytes(dim)
+    }
+}
+
+/// Append `values` to `out` as little-endian `element` values
+pub fn encode_vector(values: &[f64], element: VectorElementType, out: &mut Vec<u8>) {
+    encode_vector_with(values, element, VectorLayout::default(), out);
+}
+
+/// Append `values` to `out` as `element` values in `layout`
+pub fn encode_vector_with(
+    values: &[f64],
+    element: VectorElementType,
+    layout: VectorLayout,
+    out: &mut Vec<u8>,
+) {
+    let big = layout.byte_order == ByteOrder::Big;
+    if layout.dim_prefix {
+        let dim = values.len() as u32;
+        out.extend_from_slice(&if big {
+            dim.to_be_bytes()
+        } else {
+            dim.to_le_bytes()
+        });
+    }
+    match element {
+        VectorElementType::Float16 => {
+            for &v in values {
+                let v = f16::from_f64(v);
+                out.extend_from_slice(&if big {
+                    v.to_be_bytes()
+                } else {
+                    v.to_le_bytes()
+                });
+            }
+        }
+        VectorElementType::Float32 => {
+            for &v in values {
+                let v = v as f32;
+                out.extend_from_slice(&if big {
+                    v.to_be_bytes()
+                } else {
+                    v.to_le_bytes()
+                });
+            }
+        }
+        VectorElementType::Float64 => {
+            for &v in values {
+                out.extend_from_slice(&if big {
+                    v.to_be_bytes()
+                } else {
+                    v.to_le_bytes()
+                });
+            }
+        }
+        VectorElementType::Int8 => out.extend(values.iter().map(|&v| quantize_int8(v) as u8)),
+        VectorElementType::Binary => out.extend(values.chunks(8).map(|bits| {
+            bits.iter()
+                .enumerate()
+                .filter(|(_, &v)| v > 0.0)
+                .fold(0u8, |byte, (i, _)| byte | 0x80 >> i)
+        })),
+    }
+}
+
+/// Int8 code of `value`: scaled by 127, rounded and clamped to [-128, 127]
+pub fn quantize_int8(value: f64) -> i8 {
+    (value * 127.0).round().clamp(-128.0, 127.0) as i8
+}
+
+/// Value of an int8 code written by [`quantize_int8`]
+pub fn dequantize_int8(code: i8) -> f64 {
+    code as f64 / 127.0
+}
+
+/// Scale factor of the SQ8 codes of `values`: their largest magnitude over
+/// 127, so that dividing by it maps the vector onto [-127, 127]
+///
+/// Multiplying the codes by the scale restores the values; an all-zero
+/// vector has a scale of zero.
+pub fn int8_scale(values: &[f64]) -> f64 {
+    values.iter().fold(0.0f64, |max, v| max.max(v.abs())) / 127.0
+}
+
+/// Reduction of vector values to few distinct bit patterns
+///
+/// Quantized vectors compress far better than uniform floats, giving a
+/// best case for the compression of vector columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Quantization {
+    /// Snap values to this many evenly spaced levels over [-1, 1]
+    Levels(u32),
+    /// Round values to this many mantissa bits (f16 has 10, f32 23, f64 52)
+    MantissaBits(u32),
+}
+
+impl Quantization {
+    /// Check the parameters
+    pub fn validate(self) -> anyhow::Result<()> {
+        match self {
+            Quantization::Levels(levels) if levels < 2 => {
+                anyhow::bail!("quantization needs at least 2 levels")
+            }
+            Quantization::MantissaBits(bits) if bits > 52 => {
+                anyhow::bail!("mantissa bits must be at most 52")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Quantize `values` in place
+    pub fn apply(self, values: &mut [f64]) {
+        match self {
+            Quantization::Levels(levels) => {
+                let step = 2.0 / (levels - 1) as f64;
+                for v in values {
+                    *v = -1.0 + ((*v + 1.0) / step).round() * step;
+                }
+            }
+            Quantization::MantissaBits(bits) => {
+                let dropped = 52 - bits.min(52);
+                if dropped == 0 {
+                    return;
+                }
+                // Round to nearest by adding half of the last kept bit; a
+                // carry into the exponent is still the correctly rounded value
+                let half = 1u64 << (dropped - 1);
+                let mask = !((1u64 << dropped) - 1);
+                for v in values {
+                    *v = f64::from_bits((v.to_bits() + half) & mask);
+                }
+            }
+        }
+    }
+}
+
+/// Decode little-endian `element` values written by [`encode_vector`]
+pub fn decode_vector(bytes: &[u8], element: VectorElementType) -> Vec<f64> {
+    decode_vector_with(bytes, element, VectorLayout::default())
+}
+
+/// Decode `element` values written by [`encode_vector_with`] in `layout`
+pub fn decode_vector_with(
+    bytes: &[u8],
+    element: VectorElementType,
+    layout: VectorLayout,
+) -> Vec<f64> {
+    let big = layout.byte_order == ByteOrder::Big;
+    let bytes = match layout.dim_prefix {
+        true => bytes.get(DIM_PREFIX_BYTES..).unwrap_or_default(),
+        false => bytes,
+    };
+    match element {
+        VectorElementType::Float16 => bytes
+            .chunks_exact(2)
+            .map(|b| {
+                let b = b.try_into().unwrap();
+                (if big {
+                    f16::from_be_bytes(b)
+                } else {
+                    f16::from_le_bytes(b)
+                })
+                .to_f64()
+            })
+            .collect(),
+        VectorElementType::Float32 => bytes
+            .chunks_exact(4)
+            .map(|b| {
+                let b = b.try_into().unwrap();
+                (if big {
+                    f32::from_be_bytes(b)
+                } else {
+                    f32::from_le_bytes(b)
+                }) as f64
+            })
+            .collect(),
+        VectorElementType::Float64 => bytes
+            .chunks_exact(8)
+            .map(|b| {
+                let b = b.try_into().unwrap();
+                if big {
+                    f64::from_be_bytes(b)
+                } else {
+                    f64::from_le_bytes(b)
+                }
+            })
+            .collect(),
+        VectorElementType::Int8 => bytes.iter().map(|&b| dequantize_int8(b as i8)).collect(),
+        // Values of 0 and 1, whose squared L2 distance is the Hamming distance
+        VectorElementType::Binary => bytes
+            .iter()
+            .flat_map(|&b| (0..8).map(move |i| f64::from(b >> (7 - i) & 1)))
+            .collect(),
+    }
+}
+
+/// Sample an alphanumeric string of `len` bytes
+pub fn sample_scalar<R: Rng + ?Sized>(rng: &mut R, len: usize) -> String {
+    let mut value = String::with_capacity(len);
+    sample_scalar_into(rng, len, &mut value);
+    value
+}
+
+/// Append an alphanumeric string of `len` bytes to `out`, drawing the same
+/// values as [`sample_scalar`]
+pub fn sample_scalar_into<R: Rng + ?Sized>(rng: &mut R, len: usize, out: &mut String) {
+    out.extend(Alphanumeric.sample_iter(rng).take(len).map(char::from));
+}
+
+/// Mix a base seed with stream identifiers into a new seed (SplitMix64 finalizer)
+pub fn stream_seed(seed: u64, ids: &[u64]) -> u64 {
+    let mut state = seed;
+    for &id in ids {
+        state = splitmix64(state ^ splitmix64(id));
+    }
+    state
+}
+
+fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
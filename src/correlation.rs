@@ -0,0 +1,208 @@
+//! Correlated numeric columns
+//!
+//! Optimizers estimating the selectivity of filters on several columns tend
+//! to assume independence; real columns (price and discount, latency and
+//! payload size) are correlated. A [`Correlation`] names numeric columns of a
+//! schema spec and their target pairwise correlations, e.g.
+//! `price:qty=0.8,price:rating=-0.3` (pairs left out are uncorrelated).
+//!
+//! Each batch draws a vector of independent standard normal scores per row
+//! from a stream of its own and multiplies it by the Cholesky factor of the
+//! correlation matrix, giving scores with exactly the target covariance. Every
+//! correlated column maps its score through the normal CDF and its own
+//! quantile function (a Gaussian copula), so each column keeps its
+//! distribution and bounds. The correlation holds exactly between normal
+//! columns and approximately, with the same ordering of rows, for others.
+
+use anyhow::{bail, Context, Result};
+use rand::Rng;
+use rand_distr::StandardNormal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// Target correlations among numeric columns
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Correlation {
+    /// Correlated columns, in matrix order
+    pub columns: Vec<String>,
+    /// Correlation of each pair of columns: symmetric, positive definite,
+    /// with a unit diagonal
+    pub matrix: Vec<Vec<f64>>,
+}
+
+impl Correlation {
+    /// Check the column names and that the matrix is a correlation matrix
+    pub fn validate(&self) -> Result<()> {
+        let n = self.columns.len();
+        if n < 2 {
+            bail!("correlation needs at least two columns");
+        }
+        let mut names = HashSet::new();
+        if let Some(name) = self
+            .columns
+            .iter()
+            .find(|name| !names.insert(name.as_str()))
+        {
+            bail!("column '{}' appears twice in the correlation", name);
+        }
+        if self.matrix.len() != n || self.matrix.iter().any(|row| row.len() != n) {
+            bail!("correlation matrix must be {}x{}", n, n);
+        }
+        for i in 0..n {
+            if self.matrix[i][i] != 1.0 {
+                bail!(
+                    "correlation of column '{}' with itself must be 1",
+                    self.columns[i]
+                );
+            }
+            for j in 0..i {
+                let r = self.matrix[i][j];
+                if !(-1.0..=1.0).contains(&r) || r != self.matrix[j][i] {
+                    bail!(
+                        "correlation of '{}' and '{}' must be symmetric and in [-1, 1]",
+                        self.columns[j],
+                        self.columns[i]
+                    );
+                }
+            }
+        }
+        self.cholesky().map(|_| ())
+    }
+
+    /// Lower-triangular `L` with `L * L^T` equal to the matrix
+    pub fn cholesky(&self) -> Result<Vec<Vec<f64>>> {
+        let n = self.matrix.len();
+        let mut factor = vec![vec![0.0; n]; n];
+        for i in 0..n {
+            for j in 0..=i {
+                let dot: f64 = (0..j).map(|k| factor[i][k] * factor[j][k]).sum();
+                if i == j {
+                    let diagonal = self.matrix[i][i] - dot;
+                    if diagonal <= 1e-12 {
+                        bail!(
+                            "correlation matrix of {} is not positive definite",
+                            self.columns.join(", ")
+                        );
+                    }
+                    factor[i][i] = diagonal.sqrt();
+                } else {
+                    factor[i][j] = (self.matrix[i][j] - dot) / factor[j][j];
+                }
+            }
+        }
+        Ok(factor)
+    }
+}
+
+impl fmt::Display for Correlation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for i in 0..self.columns.len() {
+            for j in i + 1..self.columns.len() {
+                if self.matrix[i][j] == 0.0 {
+                    continue;
+                }
+                if !first {
+                    f.write_str(",")?;
+                }
+                first = false;
+                write!(
+                    f,
+                    "{}:{}={}",
+                    self.columns[i], self.columns[j], self.matrix[i][j]
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Correlation {
+    type Err = anyhow::Error;
+
+    /// Parse `A:B=R,...` pairs; columns take the order they first appear in
+    fn from_str(s: &str) -> Result<Self> {
+        let mut pairs = Vec::new();
+        let mut columns: Vec<String> = Vec::new();
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let Some(((a, b), r)) = part
+                .split_once('=')
+                .and_then(|(pair, r)| Some((pair.split_once(':')?, r)))
+            else {
+                bail!("invalid correlation '{}', expected COLUMN:COLUMN=R", part);
+            };
+            let r: f64 = r
+                .trim()
+                .parse()
+                .with_context(|| format!("invalid correlation '{}'", r.trim()))?;
+            let mut index = |name: &str| match columns.iter().position(|c| c == name) {
+                Some(i) => i,
+                None => {
+                    columns.push(name.to_string());
+                    columns.len() - 1
+                }
+            };
+            let (i, j) = (index(a.trim()), index(b.trim()));
+            if i == j {
+                bail!("column '{}' cannot be correlated with itself", a.trim());
+            }
+            pairs.push((i, j, r));
+        }
+        let n = columns.len();
+        let mut matrix = vec![vec![0.0; n]; n];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            row[i] = 1.0;
+        }
+        for (i, j, r) in pairs {
+            matrix[i][j] = r;
+            matrix[j][i] = r;
+        }
+        let correlation = Self { columns, matrix };
+        correlation.validate()?;
+        Ok(correlation)
+    }
+}
+
+/// Cholesky factor of a validated [`Correlation`], turning independent
+/// normal scores into correlated ones
+#[derive(Debug, Clone)]
+pub(crate) struct CorrelationPlan {
+    factor: Vec<Vec<f64>>,
+}
+
+impl CorrelationPlan {
+    pub(crate) fn new(correlation: &Correlation) -> Result<Self> {
+        Ok(Self {
+            factor: correlation.cholesky()?,
+        })
+    }
+
+    /// Number of correlated columns
+    pub(crate) fn dims(&self) -> usize {
+        self.factor.len()
+    }
+
+    /// Correlated standard normal scores of `rows` rows, laid out
+    /// `[row][column]`
+    pub(crate) fn draw<R: Rng + ?Sized>(&self, rng: &mut R, rows: usize) -> Vec<f64> {
+        let n = self.dims();
+        let mut independent = vec![0.0; n];
+        let mut scores = Vec::with_capacity(rows * n);
+        for _ in 0..rows {
+            for z in independent.iter_mut() {
+                *z = rng.sample(StandardNormal);
+            }
+            for row in &self.factor {
+                scores.push(
+                    row.iter()
+                        .zip(&independent)
+                        .map(|(l, z)| l * z)
+                        .sum::<f64>(),
+                );
+            }
+        }
+        scores
+    }
+}
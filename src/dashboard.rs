@@ -0,0 +1,303 @@
+//! Live terminal dashboard for long local runs
+//!
+//! A single progress bar says little when several files are written at once
+//! (NUMA generation runs one file per node). The dashboard redraws a few
+//! times a second and shows overall progress with an ETA, throughput, the
+//! resident memory of the process, and per worker the file it is writing and
+//! its own throughput. Workers are the threads reporting file events, in the
+//! order they first report. Progress advances as files finish.
+//!
+//! The terminal is in raw mode while the dashboard runs, so Ctrl-C and `q`
+//! are read as keys and cancel the run through its [`CancellationToken`].
+
+use anyhow::{anyhow, Result};
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Gauge, Paragraph, Row, Table};
+use ratatui::Frame;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{JoinHandle, ThreadId};
+use std::time::{Duration, Instant};
+
+use crate::cancel::CancellationToken;
+use crate::dataset::DatasetEvent;
+
+/// Time between redraws
+const REFRESH: Duration = Duration::from_millis(250);
+
+/// Progress of one worker thread
+#[derive(Debug, Clone, Default)]
+pub struct WorkerProgress {
+    /// File being written and when it was started
+    pub current: Option<(PathBuf, Instant)>,
+    pub files: usize,
+    pub rows: usize,
+    /// Time spent on finished files
+    pub busy: Duration,
+}
+
+impl WorkerProgress {
+    /// Rows per second over the files this worker finished
+    pub fn rows_per_sec(&self) -> f64 {
+        match self.busy.as_secs_f64() {
+            secs if secs > 0.0 => self.rows as f64 / secs,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Progress of a run, fed by dataset events
+#[derive(Debug, Clone)]
+pub struct DashboardState {
+    pub started: Instant,
+    pub total_rows: usize,
+    pub total_files: usize,
+    pub rows: usize,
+    pub files: usize,
+    pub bytes: u64,
+    /// Workers in the order they first reported
+    pub workers: Vec<(ThreadId, WorkerProgress)>,
+}
+
+impl DashboardState {
+    /// Empty progress of a run of `total_rows` rows in `total_files` files
+    pub fn new(total_rows: usize, total_files: usize) -> Self {
+        Self {
+            started: Instant::now(),
+            total_rows,
+            total_files,
+            rows: 0,
+            files: 0,
+            bytes: 0,
+            workers: Vec::new(),
+        }
+    }
+
+    /// Record `event`, reported by the thread `worker` at `now`
+    pub fn record(&mut self, event: &DatasetEvent<'_>, worker: ThreadId, now: Instant) {
+        let slot = match self.workers.iter().position(|(id, _)| *id == worker) {
+            Some(slot) => slot,
+            None => {
+                self.workers.push((worker, WorkerProgress::default()));
+                self.workers.len() - 1
+            }
+        };
+        let progress = &mut self.workers[slot].1;
+        match event {
+            DatasetEvent::FileStarted(plan) => {
+                progress.current = Some((plan.path.clone(), now));
+            }
+            DatasetEvent::FileFinished(file) => {
+                progress.current = None;
+                progress.files += 1;
+                progress.rows += file.rows;
+                progress.busy += file.elapsed;
+                self.files += 1;
+                self.rows += file.rows;
+                self.bytes += file.bytes;
+            }
+        }
+    }
+
+    /// Fraction of the rows written, between 0 and 1
+    pub fn ratio(&self) -> f64 {
+        match self.total_rows {
+            0 => 1.0,
+            total => (self.rows as f64 / total as f64).min(1.0),
+        }
+    }
+
+    /// Rows per second since the run started
+    pub fn rows_per_sec(&self, now: Instant) -> f64 {
+        match now.duration_since(self.started).as_secs_f64() {
+            secs if secs > 0.0 => self.rows as f64 / secs,
+            _ => 0.0,
+        }
+    }
+
+    /// Time left at the current throughput; `None` before the first file
+    pub fn eta(&self, now: Instant) -> Option<Duration> {
+        let rate = self.rows_per_sec(now);
+        if rate <= 0.0 {
+            return None;
+        }
+        let left = self.total_rows.saturating_sub(self.rows) as f64;
+        Some(Duration::from_secs_f64(left / rate))
+    }
+}
+
+/// Resident set size of this process in bytes, where the OS reports it
+pub fn resident_bytes() -> Option<u64> {
+    // Second field of statm: resident pages, almost always 4 KiB
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: u64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    Some(pages * 4096)
+}
+
+/// Live dashboard drawn on the terminal by a background thread
+pub struct Dashboard {
+    state: Arc<Mutex<DashboardState>>,
+    stop: Arc<AtomicBool>,
+    render: Option<JoinHandle<Result<()>>>,
+}
+
+impl Dashboard {
+    /// Take over the terminal and start drawing progress of a run
+    ///
+    /// Ctrl-C and `q` cancel `cancel`.
+    pub fn start(total_rows: usize, total_files: usize, cancel: CancellationToken) -> Result<Self> {
+        let state = Arc::new(Mutex::new(DashboardState::new(total_rows, total_files)));
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut terminal = ratatui::try_init()?;
+        let render = {
+            let (state, stop) = (state.clone(), stop.clone());
+            std::thread::spawn(move || -> Result<()> {
+                let result = (|| -> Result<()> {
+                    while !stop.load(Ordering::Relaxed) {
+                        let snapshot = state.lock().unwrap().clone();
+                        terminal.draw(|frame| draw(frame, &snapshot, cancel.is_cancelled()))?;
+                        if event::poll(REFRESH)? {
+                            if let Event::Key(key) = event::read()? {
+                                let ctrl_c = key.code == KeyCode::Char('c')
+                                    && key.modifiers.contains(KeyModifiers::CONTROL);
+                                if key.kind == KeyEventKind::Press
+                                    && (ctrl_c || key.code == KeyCode::Char('q'))
+                                {
+                                    cancel.cancel();
+                                }
+                            }
+                        }
+                    }
+                    Ok(())
+                })();
+                ratatui::try_restore()?;
+                result
+            })
+        };
+        Ok(Self {
+            state,
+            stop,
+            render: Some(render),
+        })
+    }
+
+    /// Record an event of the run; callable from any worker thread
+    pub fn on_event(&self, event: &DatasetEvent<'_>) {
+        let worker = std::thread::current().id();
+        self.state
+            .lock()
+            .unwrap()
+            .record(event, worker, Instant::now());
+    }
+
+    /// Stop drawing and give the terminal back
+    pub fn finish(mut self) -> Result<()> {
+        self.stop_render()
+    }
+
+    fn stop_render(&mut self) -> Result<()> {
+        self.stop.store(true, Ordering::Relaxed);
+        match self.render.take() {
+            Some(render) => render
+                .join()
+                .map_err(|_| anyhow!("dashboard thread panicked"))?,
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for Dashboard {
+    fn drop(&mut self) {
+        // Restore the terminal even if the run failed
+        let _ = self.stop_render();
+    }
+}
+
+fn draw(frame: &mut Frame, state: &DashboardState, cancelling: bool) {
+    let now = Instant::now();
+    let [gauge_area, summary_area, workers_area] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(4),
+        Constraint::Min(3),
+    ])
+    .areas(frame.area());
+
+    let eta = match state.eta(now) {
+        Some(eta) => format!("ETA {:.0?}", eta),
+        None => "ETA --".to_string(),
+    };
+    let gauge = Gauge::default()
+        .block(Block::bordered().title(" vector_data_gen "))
+        .gauge_style(Style::default().fg(Color::Cyan))
+        .ratio(state.ratio())
+        .label(format!(
+            "{}/{} rows ({:.1}%)  {}",
+            state.rows,
+            state.total_rows,
+            state.ratio() * 100.0,
+            eta
+        ));
+    frame.render_widget(gauge, gauge_area);
+
+    let memory = match resident_bytes() {
+        Some(bytes) => format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0)),
+        None => "n/a".to_string(),
+    };
+    let status = match cancelling {
+        true => "cancelling, finishing files in progress",
+        false => "q or Ctrl-C to cancel",
+    };
+    let summary = Paragraph::new(vec![
+        Line::from(format!(
+            "Files {}/{}   Elapsed {:.0?}   {:.0} rows/s   {:.1} MiB written",
+            state.files,
+            state.total_files,
+            now.duration_since(state.started),
+            state.rows_per_sec(now),
+            state.bytes as f64 / (1024.0 * 1024.0)
+        )),
+        Line::from(format!("Memory {}   {}", memory, status)),
+    ])
+    .block(Block::bordered().title(" Run "));
+    frame.render_widget(summary, summary_area);
+
+    let rows = state.workers.iter().enumerate().map(|(i, (_, worker))| {
+        let (file, elapsed) = match &worker.current {
+            Some((path, started)) => (
+                path.file_name()
+                    .map_or_else(String::new, |name| name.to_string_lossy().into_owned()),
+                format!("{:.0?}", now.duration_since(*started)),
+            ),
+            None => ("idle".to_string(), String::new()),
+        };
+        Row::new(vec![
+            format!("{}", i + 1),
+            file,
+            elapsed,
+            worker.files.to_string(),
+            worker.rows.to_string(),
+            format!("{:.0}", worker.rows_per_sec()),
+        ])
+    });
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(6),
+            Constraint::Min(20),
+            Constraint::Length(10),
+            Constraint::Length(7),
+            Constraint::Length(12),
+            Constraint::Length(10),
+        ],
+    )
+    .header(
+        Row::new(["Worker", "File", "Elapsed", "Files", "Rows", "Rows/s"])
+            .style(Style::default().fg(Color::Yellow)),
+    )
+    .block(Block::bordered().title(" Workers "));
+    frame.render_widget(table, workers_area);
+}
@@ -0,0 +1,661 @@
+//! Multi-file dataset generation
+//!
+//! Splits a run of `total_rows` rows into files of roughly
+//! `Config::target_file_size` bytes, writes them through a [`ParquetWriter`]
+//! and reports progress through a callback. Co-generated tables such as the
+//! edge list, the parent table, the holdout sample, the index sidecar and the
+//! query set are written once all rows are done.
+
+use anyhow::{bail, Context, Result};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::bucket::BucketConfig;
+use crate::holdout::{HoldoutSink, Reservoir};
+use crate::layout::{FileSlot, HiveLayout, LayoutStrategy};
+use crate::sink::{write_rows, Sink};
+use crate::{CancellationToken, Cancelled, Config, DataGenerator, ParquetWriter, StageTimings};
+
+/// File name of the co-generated edge list
+pub const EDGES_FILE_NAME: &str = "edges.parquet";
+/// File name of the holdout sample
+pub const HOLDOUT_FILE_NAME: &str = "holdout.parquet";
+/// File name of the index sidecar
+pub const INDEX_FILE_NAME: &str = "index.parquet";
+/// File name of the query set
+pub const QUERIES_FILE_NAME: &str = "queries.parquet";
+/// File name of the deletion workload
+pub const DELETES_FILE_NAME: &str = "deletes.parquet";
+
+/// Where and how much to generate
+#[derive(Debug, Clone)]
+pub struct DatasetOptions {
+    /// Directory receiving the generated files
+    pub output_dir: PathBuf,
+    /// Prefix of generated file names
+    pub prefix: String,
+    /// Total number of rows across all files
+    pub total_rows: usize,
+    /// Rows per generated batch
+    pub batch_size: usize,
+    /// Directory structure and file names of data files; `None` lays them
+    /// out as [`HiveLayout`]
+    pub layout: Option<Arc<dyn LayoutStrategy>>,
+}
+
+impl DatasetOptions {
+    /// Output path of the data file at `slot`
+    pub fn file_path(&self, slot: &FileSlot<'_>) -> PathBuf {
+        let relative = match &self.layout {
+            Some(layout) => layout.file_path(slot),
+            None => HiveLayout.file_path(slot),
+        };
+        self.output_dir.join(relative)
+    }
+}
+
+/// A file to be generated
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePlan {
+    /// Index of the file within the dataset; selects its row streams
+    pub index: u64,
+    /// Output path
+    pub path: PathBuf,
+    /// Rows to write; 0 for bucket files, whose rows depend on the keys
+    pub rows: usize,
+    /// Tenant whose partition this file belongs to
+    pub tenant: Option<u32>,
+}
+
+/// Outcome of writing one file
+#[derive(Debug, Clone)]
+pub struct FileReport {
+    pub index: u64,
+    pub path: PathBuf,
+    pub rows: usize,
+    pub bytes: u64,
+    pub elapsed: Duration,
+    /// Where `elapsed` went: generation, Arrow assembly, encoding and IO
+    pub timings: StageTimings,
+    pub tenant: Option<u32>,
+}
+
+/// Outcome of a dataset run
+#[derive(Debug, Clone, Default)]
+pub struct DatasetReport {
+    /// Files written, in generation order
+    pub files: Vec<FileReport>,
+    /// Rows written across all files
+    pub total_rows: usize,
+    /// Edge list written alongside the rows, with its edge count
+    pub edges: Option<(PathBuf, usize)>,
+    /// Parent table written alongside the rows, with its row count
+    pub parent_table: Option<(PathBuf, usize)>,
+    /// Holdout sample written alongside the rows, with its row count
+    pub holdout: Option<(PathBuf, usize)>,
+    /// Index sidecar written alongside the rows, with its row count
+    pub index: Option<(PathBuf, usize)>,
+    /// Query set written alongside the rows, with its query count
+    pub queries: Option<(PathBuf, usize)>,
+    /// Deletion workload written alongside the rows, with its deleted row count
+    pub deletes: Option<(PathBuf, usize)>,
+    /// Set if the run stopped because cancellation was requested
+    pub cancelled: Option<Cancelled>,
+}
+
+/// Progress notifications emitted during generation
+#[derive(Debug)]
+pub enum DatasetEvent<'a> {
+    /// A file is about to be written
+    FileStarted(&'a FilePlan),
+    /// A file was written completely
+    FileFinished(&'a FileReport),
+}
+
+/// Lay out the files of a dataset
+///
+/// Without tenant partitioning files hold up to `rows_per_file` rows each;
+/// with partitioning every tenant's files hold exactly its share of the rows.
+/// Paths follow `options.layout`, by default `<prefix>-<part>.parquet` in the
+/// output directory or a `tenant_id=<id>` directory per tenant. The planned
+/// rows always add up to `options.total_rows` exactly; the last file of each
+/// partition takes the remainder.
+pub fn plan_files(
+    config: &Config,
+    options: &DatasetOptions,
+    rows_per_file: usize,
+) -> Vec<FilePlan> {
+    let mut plans = Vec::new();
+    for partition in partitions(config, options) {
+        plan_partition(
+            &mut plans,
+            options,
+            partition.rows,
+            rows_per_file,
+            partition.tenant,
+        );
+    }
+    debug_assert_eq!(
+        plans.iter().map(|plan| plan.rows).sum::<usize>(),
+        options.total_rows
+    );
+    plans
+}
+
+/// Share of the rows of a dataset, one per tenant if partitioned
+struct Partition {
+    rows: usize,
+    tenant: Option<u32>,
+}
+
+/// The whole dataset, or one partition per tenant
+fn partitions(config: &Config, options: &DatasetOptions) -> Vec<Partition> {
+    match &config.tenants {
+        Some(tenants) if tenants.partitioned => tenants
+            .row_counts(options.total_rows)
+            .into_iter()
+            .enumerate()
+            .map(|(tenant, rows)| Partition {
+                rows,
+                tenant: Some(tenant as u32),
+            })
+            .collect(),
+        _ => vec![Partition {
+            rows: options.total_rows,
+            tenant: None,
+        }],
+    }
+}
+
+/// Append files holding the `rows` rows of a partition to `plans`
+fn plan_partition(
+    plans: &mut Vec<FilePlan>,
+    options: &DatasetOptions,
+    rows: usize,
+    rows_per_file: usize,
+    tenant: Option<u32>,
+) {
+    let rows_per_file = rows_per_file.max(1);
+    let mut remaining = rows;
+    let mut part = 0;
+    while remaining > 0 {
+        let file_rows = remaining.min(rows_per_file);
+        let index = plans.len() as u64;
+        plans.push(FilePlan {
+            index,
+            path: options.file_path(&FileSlot {
+                prefix: &options.prefix,
+                index,
+                part,
+                tenant,
+                bucket: None,
+            }),
+            rows: file_rows,
+            tenant,
+        });
+        remaining -= file_rows;
+        part += 1;
+    }
+}
+
+/// Generate every file of a dataset
+///
+/// Stops early, recording [`DatasetReport::cancelled`], if `cancel` is
+/// triggered; the file in progress is handled according to
+/// `Config::cancel_policy`.
+pub fn generate(
+    config: &Config,
+    options: &DatasetOptions,
+    cancel: &CancellationToken,
+    mut on_event: impl FnMut(DatasetEvent<'_>),
+) -> Result<DatasetReport> {
+    check_output(config, options)?;
+    if let Some(bucketing) = &config.bucketing {
+        return generate_bucketed(config, options, cancel, bucketing, on_event);
+    }
+    let rows_per_file = DataGenerator::try_new(config.clone())?.estimate_rows_per_file();
+    let writer = ParquetWriter::new(config.clone());
+    let mut report = DatasetReport::default();
+    let mut trajectories = None;
+    let mut monotonic = None;
+    let mut reservoir = config
+        .holdout_rows
+        .map(|rows| Reservoir::new(rows, config.seed));
+    let mut next_row = 0u64;
+
+    for plan in plan_files(config, options, rows_per_file) {
+        on_event(DatasetEvent::FileStarted(&plan));
+        let mut generator = file_generator(config, &plan, next_row)?;
+        next_row += plan.rows as u64;
+        if let Some(state) = trajectories.take() {
+            // Entities keep walking from where the previous file left off
+            generator.resume_trajectories(state);
+        }
+        if let Some(state) = monotonic.take() {
+            generator.resume_monotonic(state);
+        }
+
+        match write_file(
+            &writer,
+            config,
+            &plan,
+            &mut generator,
+            options,
+            cancel,
+            reservoir.as_mut(),
+        )? {
+            FileOutcome::Written(file) => {
+                trajectories = generator.take_trajectory_state();
+                monotonic = generator.take_monotonic_state();
+                report.total_rows += file.rows;
+                on_event(DatasetEvent::FileFinished(&file));
+                report.files.push(file);
+            }
+            FileOutcome::Cancelled(cancelled, partial) => {
+                if let Some(file) = partial {
+                    report.total_rows += file.rows;
+                    report.files.push(file);
+                }
+                report.cancelled = Some(cancelled);
+                return Ok(report);
+            }
+        }
+    }
+
+    check_row_total(&report, options)?;
+    write_co_tables(config, options, &writer, &mut report, reservoir.as_ref())?;
+    Ok(report)
+}
+
+/// Generate a bucketed dataset: one file per bucket in every partition
+///
+/// Each partition is generated by one generator, using the row streams of its
+/// first bucket file, and every batch is split across the open bucket files.
+/// Bucket files report the whole partition's elapsed time; generation time
+/// is shared out in proportion to their rows.
+fn generate_bucketed(
+    config: &Config,
+    options: &DatasetOptions,
+    cancel: &CancellationToken,
+    bucketing: &BucketConfig,
+    mut on_event: impl FnMut(DatasetEvent<'_>),
+) -> Result<DatasetReport> {
+    let writer = ParquetWriter::new(config.clone());
+    let mut report = DatasetReport::default();
+    let mut trajectories = None;
+    let mut monotonic = None;
+    let mut reservoir = config
+        .holdout_rows
+        .map(|rows| Reservoir::new(rows, config.seed));
+    let mut next_row = 0u64;
+    let buckets = bucketing.buckets;
+
+    for (p, partition) in partitions(config, options).into_iter().enumerate() {
+        let plans: Vec<FilePlan> = (0..buckets)
+            .map(|bucket| {
+                let index = p as u64 * buckets as u64 + bucket as u64;
+                FilePlan {
+                    index,
+                    path: options.file_path(&FileSlot {
+                        prefix: &options.prefix,
+                        index,
+                        part: 0,
+                        tenant: partition.tenant,
+                        bucket: Some(bucket),
+                    }),
+                    rows: 0,
+                    tenant: partition.tenant,
+                }
+            })
+            .collect();
+        let start_time = Instant::now();
+        let mut sinks = Vec::with_capacity(plans.len());
+        let mut generator = file_generator(config, &plans[0], next_row)?;
+        next_row += partition.rows as u64;
+        if let Some(state) = trajectories.take() {
+            generator.resume_trajectories(state);
+        }
+        if let Some(state) = monotonic.take() {
+            generator.resume_monotonic(state);
+        }
+        for plan in &plans {
+            on_event(DatasetEvent::FileStarted(plan));
+            create_parent_dir(&plan.path)?;
+            sinks.push(writer.create_sink(&plan.path, generator.schema())?);
+        }
+
+        let mut audits: Vec<Reservoir> = match config.value_samples {
+            Some(samples) => plans
+                .iter()
+                .map(|plan| crate::audit::reservoir(samples, config.seed, plan.index))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let batch_size = options.batch_size.max(1).min(generator.max_batch_rows());
+        let mut rows = vec![0usize; plans.len()];
+        let mut remaining = partition.rows;
+        while remaining > 0 && !cancel.is_cancelled() {
+            let batch = generator.generate_batch(batch_size.min(remaining))?;
+            if let Some(reservoir) = reservoir.as_mut() {
+                reservoir.offer(&batch)?;
+            }
+            for (bucket, indices) in bucketing.split(&batch)?.into_iter().enumerate() {
+                if indices.is_empty() {
+                    continue;
+                }
+                rows[bucket] += indices.len();
+                let indices = arrow::array::UInt32Array::from(indices);
+                let part = arrow::compute::take_record_batch(&batch, &indices)?;
+                if let Some(audit) = audits.get_mut(bucket) {
+                    audit.offer(&part)?;
+                }
+                sinks[bucket].write(&part)?;
+            }
+            remaining -= batch.num_rows();
+        }
+
+        let policy = config.cancel_policy;
+        let cancelled = remaining > 0;
+        for sink in &mut sinks {
+            match (cancelled, policy) {
+                (true, crate::CancelPolicy::Discard) => sink.abort()?,
+                _ => sink.finish()?,
+            }
+        }
+        if cancelled && policy == crate::CancelPolicy::Discard {
+            report.cancelled = Some(Cancelled {
+                rows_written: 0,
+                policy,
+            });
+            return Ok(report);
+        }
+
+        let generated = generator.take_timings();
+        let partition_rows = rows.iter().sum::<usize>().max(1) as f64;
+        for (bucket, ((plan, sink), rows)) in plans.iter().zip(&sinks).zip(rows).enumerate() {
+            write_value_sample(plan, rows, audits.get(bucket))?;
+            let share = rows as f64 / partition_rows;
+            let timings = StageTimings {
+                generate: generated.generate.mul_f64(share),
+                arrow: generated.arrow.mul_f64(share),
+                ..StageTimings::default()
+            } + sink.timings();
+            let file = file_report(plan, rows, sink.bytes_written(), start_time, timings);
+            report.total_rows += file.rows;
+            if !cancelled {
+                on_event(DatasetEvent::FileFinished(&file));
+            }
+            report.files.push(file);
+        }
+        if cancelled {
+            report.cancelled = Some(Cancelled {
+                rows_written: partition.rows - remaining,
+                policy,
+            });
+            return Ok(report);
+        }
+        trajectories = generator.take_trajectory_state();
+        monotonic = generator.take_monotonic_state();
+    }
+
+    check_row_total(&report, options)?;
+    write_co_tables(config, options, &writer, &mut report, reservoir.as_ref())?;
+    Ok(report)
+}
+
+/// Fail unless a completed run wrote exactly `options.total_rows` rows
+pub(crate) fn check_row_total(report: &DatasetReport, options: &DatasetOptions) -> Result<()> {
+    let written: usize = report.files.iter().map(|file| file.rows).sum();
+    if written != options.total_rows || report.total_rows != written {
+        bail!(
+            "wrote {} rows ({} reported) but {} were requested",
+            written,
+            report.total_rows,
+            options.total_rows
+        );
+    }
+    Ok(())
+}
+
+/// Result of writing one planned file
+pub(crate) enum FileOutcome {
+    /// The file holds all planned rows
+    Written(FileReport),
+    /// Cancellation stopped the file; it is reported if it was kept
+    Cancelled(Cancelled, Option<FileReport>),
+}
+
+/// Create the local directory of the file at `path`; object stores have no
+/// directories to create
+fn create_parent_dir(path: &Path) -> Result<()> {
+    if crate::sink::is_object_url(path) {
+        return Ok(());
+    }
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create output directory: {:?}", dir))?;
+    }
+    Ok(())
+}
+
+/// Fail if the dataset writes to an object store but needs to read its own
+/// files back, which is only supported for local output
+pub(crate) fn check_output(config: &Config, options: &DatasetOptions) -> Result<()> {
+    if !crate::sink::is_object_url(&options.output_dir) {
+        return Ok(());
+    }
+    if config.index_column.is_some() {
+        bail!("index sidecars cannot be written to an object store");
+    }
+    if config.value_samples.is_some() {
+        bail!("value samples cannot be written to an object store");
+    }
+    if let Some(crate::QueryConfig {
+        source: crate::QuerySource::Perturb { .. },
+        ..
+    }) = config.queries
+    {
+        bail!("perturbed queries cannot be written to an object store");
+    }
+    Ok(())
+}
+
+/// Create the directory of `plan` and a generator for its rows, which start
+/// at global row `first_row`
+pub(crate) fn file_generator(
+    config: &Config,
+    plan: &FilePlan,
+    first_row: u64,
+) -> Result<DataGenerator> {
+    create_parent_dir(&plan.path)?;
+    let mut file_config = config.clone();
+    if let (Some(tenants), Some(tenant)) = (file_config.tenants.as_mut(), plan.tenant) {
+        tenants.only = Some(tenant);
+    }
+    let mut generator = DataGenerator::for_file(file_config, plan.index)?;
+    generator.start_at_row(first_row);
+    Ok(generator)
+}
+
+/// Write the rows of `plan` with `generator`, offering them to `reservoir`
+/// and, with `Config::value_samples`, to the file's own value sample
+pub(crate) fn write_file(
+    writer: &ParquetWriter,
+    config: &Config,
+    plan: &FilePlan,
+    generator: &mut DataGenerator,
+    options: &DatasetOptions,
+    cancel: &CancellationToken,
+    reservoir: Option<&mut Reservoir>,
+) -> Result<FileOutcome> {
+    let start_time = Instant::now();
+    let mut sink = writer.create_sink(&plan.path, generator.schema())?;
+    let mut audit = config
+        .value_samples
+        .map(|rows| crate::audit::reservoir(rows, config.seed, plan.index));
+    let mut target: Box<dyn Sink + '_> = Box::new(&mut sink);
+    if let Some(reservoir) = reservoir {
+        target = Box::new(HoldoutSink::new(target, reservoir));
+    }
+    if let Some(audit) = audit.as_mut() {
+        target = Box::new(HoldoutSink::new(target, audit));
+    }
+    let result = write_rows(
+        &mut target,
+        generator,
+        plan.rows,
+        options.batch_size,
+        cancel,
+        config.cancel_policy,
+    );
+    drop(target);
+    let timings = generator.take_timings() + sink.timings();
+    match result {
+        Ok(rows) => {
+            write_value_sample(plan, rows, audit.as_ref())?;
+            Ok(FileOutcome::Written(file_report(
+                plan,
+                rows,
+                sink.bytes_written(),
+                start_time,
+                timings,
+            )))
+        }
+        Err(e) => match e.downcast_ref::<Cancelled>() {
+            Some(cancelled) => {
+                let partial = match cancelled.policy {
+                    crate::CancelPolicy::Finalize => {
+                        write_value_sample(plan, cancelled.rows_written, audit.as_ref())?;
+                        Some(file_report(
+                            plan,
+                            cancelled.rows_written,
+                            sink.bytes_written(),
+                            start_time,
+                            timings,
+                        ))
+                    }
+                    crate::CancelPolicy::Discard => None,
+                };
+                Ok(FileOutcome::Cancelled(*cancelled, partial))
+            }
+            None => Err(e),
+        },
+    }
+}
+
+/// Write the value sample of the file at `plan`, if one is taken
+fn write_value_sample(plan: &FilePlan, rows: usize, audit: Option<&Reservoir>) -> Result<()> {
+    if let Some(audit) = audit {
+        crate::audit::write_sample(&plan.path, rows, audit)?;
+    }
+    Ok(())
+}
+
+/// Write the tables co-generated with the rows (edge list, parent table,
+/// holdout sample, index sidecar)
+pub(crate) fn write_co_tables(
+    config: &Config,
+    options: &DatasetOptions,
+    writer: &ParquetWriter,
+    report: &mut DatasetReport,
+    reservoir: Option<&Reservoir>,
+) -> Result<()> {
+    if let Some(edges) = &config.edges {
+        let path = options.output_dir.join(EDGES_FILE_NAME);
+        let mut sink = writer.create_sink(&path, &crate::graph::edge_schema())?;
+        let count = crate::graph::write_edges(
+            &mut sink,
+            report.total_rows,
+            edges,
+            config.seed,
+            options.batch_size,
+        )?;
+        report.edges = Some((path, count));
+    }
+
+    if let Some(fk) = &config.foreign_key {
+        if let Some(name) = &fk.parent_table {
+            let path = options.output_dir.join(format!("{}.parquet", name));
+            let mut sink = writer.create_sink(&path, &crate::foreign_key::parent_schema(fk))?;
+            let count = crate::foreign_key::write_parent_table(
+                &mut sink,
+                fk,
+                config.scalar_len,
+                config.seed,
+                options.batch_size,
+            )?;
+            report.parent_table = Some((path, count));
+        }
+    }
+
+    if let Some(sample) = reservoir.map(Reservoir::sample).transpose()?.flatten() {
+        let path = options.output_dir.join(HOLDOUT_FILE_NAME);
+        let mut sink = writer.create_sink(&path, &sample.schema())?;
+        sink.write(&sample)?;
+        sink.finish()?;
+        report.holdout = Some((path, sample.num_rows()));
+    }
+
+    if let Some(column) = &config.index_column {
+        if !report.files.is_empty() {
+            let path = options.output_dir.join(INDEX_FILE_NAME);
+            let count = crate::sidecar::write_index(
+                writer,
+                &path,
+                column,
+                &report.files,
+                &options.output_dir,
+                options.batch_size,
+            )?;
+            report.index = Some((path, count));
+        }
+    }
+
+    if let Some(queries) = &config.queries {
+        let path = options.output_dir.join(QUERIES_FILE_NAME);
+        let count = crate::queries::write_queries(
+            writer,
+            &path,
+            config,
+            queries,
+            &report.files,
+            options.batch_size,
+        )?;
+        report.queries = Some((path, count));
+    }
+
+    if let Some(deletes) = &config.deletes {
+        let path = options.output_dir.join(DELETES_FILE_NAME);
+        let count = crate::deletes::write_deletes(
+            writer,
+            &path,
+            deletes,
+            config.seed,
+            report.total_rows as u64,
+            options.batch_size,
+        )?;
+        report.deletes = Some((path, count));
+    }
+
+    Ok(())
+}
+
+fn file_report(
+    plan: &FilePlan,
+    rows: usize,
+    bytes: u64,
+    start_time: Instant,
+    timings: StageTimings,
+) -> FileReport {
+    FileReport {
+        index: plan.index,
+        path: plan.path.clone(),
+        rows,
+        bytes,
+        elapsed: start_time.elapsed(),
+        timings,
+        tenant: plan.tenant,
+    }
+}
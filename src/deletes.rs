@@ -0,0 +1,185 @@
+//! Deletion workloads
+//!
+//! Recall after deletes is measured by deleting rows from an index and
+//! checking that searches no longer return them while still finding the
+//! next-closest rows. A deletion workload picks `fraction` of the base rows
+//! in each of `epochs` epochs, uniformly at random without repetition, from a
+//! stream of its own. It is written to `deletes.parquet` next to the dataset
+//! with one row per deleted row: the `epoch` (from 1) and the `id` (position
+//! in the dataset, as in ground truth and the monotonic `id` column), sorted
+//! by epoch and id.
+//!
+//! Ground truth reads the workload back and writes one result per epoch,
+//! excluding every row deleted up to that epoch.
+
+#[cfg(feature = "parquet")]
+use anyhow::Context;
+use anyhow::{bail, Result};
+#[cfg(feature = "parquet")]
+use arrow::array::{ArrayRef, AsArray, Int32Array, Int64Array};
+#[cfg(feature = "parquet")]
+use arrow::datatypes::{DataType, Field, Int32Type, Int64Type, Schema};
+#[cfg(feature = "parquet")]
+use arrow::record_batch::RecordBatch;
+#[cfg(feature = "parquet")]
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+#[cfg(feature = "parquet")]
+use std::{fs::File, path::Path, sync::Arc};
+
+use crate::core_gen::stream_seed;
+#[cfg(feature = "parquet")]
+use crate::sink::Sink;
+#[cfg(feature = "parquet")]
+use crate::ParquetWriter;
+
+/// Stream identifier for deleted row picks
+const DELETE_STREAM: u64 = u64::MAX - 12;
+
+/// Rows deleted from a dataset over several epochs
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DeletionConfig {
+    /// Number of deletion epochs
+    pub epochs: usize,
+    /// Fraction of the base rows deleted in each epoch
+    pub fraction: f64,
+}
+
+impl DeletionConfig {
+    /// Check the parameters
+    pub fn validate(&self) -> Result<()> {
+        if self.epochs == 0 {
+            bail!("deletion workload needs at least one epoch");
+        }
+        if !(self.fraction > 0.0 && self.fraction * self.epochs as f64 <= 1.0) {
+            bail!(
+                "delete fraction {} over {} epochs must be above 0 and delete at most every row",
+                self.fraction,
+                self.epochs
+            );
+        }
+        Ok(())
+    }
+
+    /// Rows deleted per epoch out of `total` base rows
+    pub fn rows_per_epoch(&self, total: u64) -> u64 {
+        (self.fraction * total as f64).round() as u64
+    }
+
+    /// Ids deleted in each epoch out of `total` base rows, sorted within
+    /// each epoch
+    pub fn deleted_ids(&self, seed: u64, total: u64) -> Vec<Vec<u64>> {
+        let per_epoch = self.rows_per_epoch(total);
+        let count = (per_epoch * self.epochs as u64).min(total);
+        let mut rng = StdRng::seed_from_u64(stream_seed(seed, &[DELETE_STREAM]));
+        // Floyd's sampling: `count` distinct ids without materializing `total`
+        let mut picked = HashSet::with_capacity(count as usize);
+        let mut order = Vec::with_capacity(count as usize);
+        for j in total - count..total {
+            let t = rng.gen_range(0..=j);
+            let id = if picked.insert(t) {
+                t
+            } else {
+                picked.insert(j);
+                j
+            };
+            order.push(id);
+        }
+        // The set is uniform but its order is not: shuffle before splitting
+        // it into epochs
+        for i in (1..order.len()).rev() {
+            order.swap(i, rng.gen_range(0..=i));
+        }
+        let per_epoch = per_epoch as usize;
+        (0..self.epochs)
+            .map(|epoch| {
+                let start = (epoch * per_epoch).min(order.len());
+                let end = (start + per_epoch).min(order.len());
+                let mut ids = order[start..end].to_vec();
+                ids.sort_unstable();
+                ids
+            })
+            .collect()
+    }
+}
+
+/// Schema of the deletion workload file
+#[cfg(feature = "parquet")]
+pub fn schema() -> Schema {
+    Schema::new(vec![
+        Field::new("epoch", DataType::Int32, false),
+        Field::new("id", DataType::Int64, false),
+    ])
+}
+
+/// Write the deletion workload of `deletes` over `total` base rows to
+/// `path`, returning the number of deleted rows
+#[cfg(feature = "parquet")]
+pub fn write_deletes(
+    writer: &ParquetWriter,
+    path: &Path,
+    deletes: &DeletionConfig,
+    seed: u64,
+    total: u64,
+    batch_size: usize,
+) -> Result<usize> {
+    let schema = Arc::new(schema());
+    let mut sink = writer.create_sink(path, &schema)?;
+    let mut count = 0;
+    for (epoch, ids) in deletes.deleted_ids(seed, total).iter().enumerate() {
+        for chunk in ids.chunks(batch_size.max(1)) {
+            let columns: Vec<ArrayRef> = vec![
+                Arc::new(Int32Array::from(vec![epoch as i32 + 1; chunk.len()])),
+                Arc::new(Int64Array::from_iter_values(
+                    chunk.iter().map(|&id| id as i64),
+                )),
+            ];
+            sink.write(&RecordBatch::try_new(schema.clone(), columns)?)?;
+            count += chunk.len();
+        }
+    }
+    sink.finish()?;
+    Ok(count)
+}
+
+/// Ids deleted in each epoch of the deletion workload file at `path`; the
+/// first entry holds epoch 1
+#[cfg(feature = "parquet")]
+pub fn read_deletes(path: &Path) -> Result<Vec<Vec<u64>>> {
+    let reader = ParquetRecordBatchReaderBuilder::try_new(
+        File::open(path).with_context(|| format!("Failed to open file: {:?}", path))?,
+    )?
+    .build()?;
+    let mut epochs: Vec<Vec<u64>> = Vec::new();
+    for batch in reader {
+        let batch = batch?;
+        let (Some(epoch), Some(id)) = (batch.column_by_name("epoch"), batch.column_by_name("id"))
+        else {
+            bail!("{:?} is not a deletion workload file", path);
+        };
+        let epoch = epoch.as_primitive_opt::<Int32Type>();
+        let id = id.as_primitive_opt::<Int64Type>();
+        let (Some(epoch), Some(id)) = (epoch, id) else {
+            bail!("{:?} is not a deletion workload file", path);
+        };
+        for (epoch, id) in epoch.values().iter().zip(id.values()) {
+            if *epoch < 1 || *id < 0 {
+                bail!(
+                    "invalid deletion of row {} in epoch {} in {:?}",
+                    id,
+                    epoch,
+                    path
+                );
+            }
+            let epoch = *epoch as usize;
+            if epochs.len() < epoch {
+                epochs.resize(epoch, Vec::new());
+            }
+            epochs[epoch - 1].push(*id as u64);
+        }
+    }
+    Ok(epochs)
+}
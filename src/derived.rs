@@ -0,0 +1,429 @@
+//! Columns computed from other columns of the same row
+//!
+//! A derived column is declared as `name = expression`, e.g.
+//! `norm = l2(vector)` or `bucket = hash(id) % 16`. Expressions may refer to
+//! generated columns and to other derived columns; they are evaluated once
+//! per batch in dependency order, so values stay consistent with the columns
+//! they are computed from.
+//!
+//! Supported expressions:
+//!
+//! - `column`: a copy of another column
+//! - `l2(vector)`: Euclidean norm of a vector column (Float64)
+//! - `hash(column)`: stable 64-bit FNV-1a hash of each value (Int64)
+//! - `len(column)`: byte length of string or binary values (Int64)
+//! - `expr % n`: non-negative remainder of an integer expression (Int64)
+
+use anyhow::{anyhow, bail, Context, Result};
+use arrow::array::{Array, ArrayRef, AsArray, Float64Array, Int64Array};
+use arrow::datatypes::{
+    DataType, Field, FieldRef, Float64Type, Int32Type, Int64Type, TimeUnit,
+    TimestampMillisecondType,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::columns::{vector_encoding, vector_values};
+
+/// Expression computing a derived column
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Expr {
+    /// Another column
+    Column(String),
+    /// Euclidean norm of a vector
+    L2(Box<Expr>),
+    /// Stable hash of each value
+    Hash(Box<Expr>),
+    /// Byte length of each value
+    Len(Box<Expr>),
+    /// Non-negative remainder of an integer
+    Mod(Box<Expr>, i64),
+}
+
+/// A named derived column
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DerivedColumn {
+    pub name: String,
+    pub expr: Expr,
+}
+
+impl Expr {
+    /// Names of the columns this expression reads
+    pub fn dependencies(&self) -> Vec<&str> {
+        match self {
+            Expr::Column(name) => vec![name.as_str()],
+            Expr::L2(inner) | Expr::Hash(inner) | Expr::Len(inner) | Expr::Mod(inner, _) => {
+                inner.dependencies()
+            }
+        }
+    }
+
+    /// Output field named `name`, given the fields of the input columns
+    fn field(&self, name: &str, inputs: &HashMap<&str, FieldRef>) -> Result<Field> {
+        let (data_type, nullable) = match self {
+            Expr::Column(column) => {
+                let field = input(inputs, column)?;
+                return Ok(field.as_ref().clone().with_name(name));
+            }
+            Expr::L2(inner) => {
+                let Expr::Column(column) = inner.as_ref() else {
+                    bail!("l2() takes a vector column");
+                };
+                let field = input(inputs, column)?;
+                if vector_encoding(field).is_none() {
+                    bail!("l2() needs a vector column, '{}' is not one", column);
+                }
+                (DataType::Float64, field.is_nullable())
+            }
+            Expr::Hash(inner) | Expr::Len(inner) => {
+                let field = inner.field(name, inputs)?;
+                if matches!(self, Expr::Len(_)) && !has_bytes(field.data_type()) {
+                    bail!("len() needs a string or binary column");
+                }
+                (DataType::Int64, field.is_nullable())
+            }
+            Expr::Mod(inner, n) => {
+                if *n <= 0 {
+                    bail!("modulus must be positive, got {}", n);
+                }
+                let field = inner.field(name, inputs)?;
+                if !matches!(field.data_type(), DataType::Int32 | DataType::Int64) {
+                    bail!("% needs an integer operand, got {}", field.data_type());
+                }
+                (DataType::Int64, field.is_nullable())
+            }
+        };
+        Ok(Field::new(name, data_type, nullable))
+    }
+
+    /// Evaluate over the columns of one batch
+    fn evaluate(&self, inputs: &HashMap<&str, (FieldRef, ArrayRef)>) -> Result<ArrayRef> {
+        Ok(match self {
+            Expr::Column(column) => column_input(inputs, column)?.1.clone(),
+            Expr::L2(inner) => {
+                let Expr::Column(column) = inner.as_ref() else {
+                    bail!("l2() takes a vector column");
+                };
+                let (field, array) = column_input(inputs, column)?;
+                if vector_encoding(field).is_none() {
+                    bail!("'{}' is not a vector column", column);
+                }
+                let norms = (0..array.len()).map(|row| {
+                    vector_values(field, array.as_ref(), row)
+                        .map(|values| values.iter().map(|v| v * v).sum::<f64>().sqrt())
+                });
+                Arc::new(Float64Array::from_iter(norms))
+            }
+            Expr::Hash(inner) => {
+                let array = inner.evaluate(inputs)?;
+                Arc::new(Int64Array::from(hash_array(array.as_ref())?))
+            }
+            Expr::Len(inner) => {
+                let array = inner.evaluate(inputs)?;
+                let lengths = (0..array.len())
+                    .map(|row| bytes_at(array.as_ref(), row).map(|b| b.len() as i64));
+                Arc::new(Int64Array::from_iter(lengths))
+            }
+            Expr::Mod(inner, n) => {
+                let array = inner.evaluate(inputs)?;
+                let values: Vec<Option<i64>> = match array.data_type() {
+                    DataType::Int32 => array
+                        .as_primitive::<Int32Type>()
+                        .iter()
+                        .map(|v| v.map(i64::from))
+                        .collect(),
+                    DataType::Int64 => array.as_primitive::<Int64Type>().iter().collect(),
+                    other => bail!("% needs an integer operand, got {}", other),
+                };
+                Arc::new(Int64Array::from_iter(
+                    values.into_iter().map(|v| v.map(|v| v.rem_euclid(*n))),
+                ))
+            }
+        })
+    }
+}
+
+fn input<'a>(inputs: &'a HashMap<&str, FieldRef>, column: &str) -> Result<&'a FieldRef> {
+    inputs
+        .get(column)
+        .ok_or_else(|| anyhow!("unknown column '{}'", column))
+}
+
+fn column_input<'a>(
+    inputs: &'a HashMap<&str, (FieldRef, ArrayRef)>,
+    column: &str,
+) -> Result<&'a (FieldRef, ArrayRef)> {
+    inputs
+        .get(column)
+        .ok_or_else(|| anyhow!("unknown column '{}'", column))
+}
+
+fn has_bytes(data_type: &DataType) -> bool {
+    matches!(
+        data_type,
+        DataType::Utf8 | DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_)
+    )
+}
+
+/// Bytes of a string or binary value, `None` if null or not a byte column
+fn bytes_at(array: &dyn Array, row: usize) -> Option<&[u8]> {
+    if array.is_null(row) {
+        return None;
+    }
+    Some(match array.data_type() {
+        DataType::Utf8 => array.as_string::<i32>().value(row).as_bytes(),
+        DataType::Binary => array.as_binary::<i32>().value(row),
+        DataType::LargeBinary => array.as_binary::<i64>().value(row),
+        DataType::FixedSizeBinary(_) => array.as_fixed_size_binary().value(row),
+        _ => return None,
+    })
+}
+
+/// Stable hash of every value of `array`, as computed by `hash()`
+pub(crate) fn hash_array(array: &dyn Array) -> Result<Vec<Option<i64>>> {
+    (0..array.len()).map(|row| hash_value(array, row)).collect()
+}
+
+/// FNV-1a hash of the value at `row`, `None` if null
+fn hash_value(array: &dyn Array, row: usize) -> Result<Option<i64>> {
+    if array.is_null(row) {
+        return Ok(None);
+    }
+    let fixed;
+    let bytes: &[u8] = match array.data_type() {
+        DataType::Int32 => {
+            fixed = (array.as_primitive::<Int32Type>().value(row) as i64).to_le_bytes();
+            &fixed
+        }
+        DataType::Int64 => {
+            fixed = array.as_primitive::<Int64Type>().value(row).to_le_bytes();
+            &fixed
+        }
+        DataType::Timestamp(TimeUnit::Millisecond, _) => {
+            fixed = array
+                .as_primitive::<TimestampMillisecondType>()
+                .value(row)
+                .to_le_bytes();
+            &fixed
+        }
+        DataType::Float64 => {
+            fixed = array.as_primitive::<Float64Type>().value(row).to_le_bytes();
+            &fixed
+        }
+        other => match bytes_at(array, row) {
+            Some(bytes) => bytes,
+            None => bail!("hash() does not support {} columns", other),
+        },
+    };
+    Ok(Some(fnv1a(bytes) as i64))
+}
+
+/// 64-bit FNV-1a, stable across platforms and releases
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    fnv1a_extend(0xcbf2_9ce4_8422_2325, bytes)
+}
+
+/// Continue an FNV-1a hash over more bytes
+pub(crate) fn fnv1a_extend(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &b| {
+        (hash ^ b as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Derived columns in evaluation order, with their output fields
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DerivedPlan {
+    columns: Vec<DerivedColumn>,
+    fields: Vec<FieldRef>,
+}
+
+impl DerivedPlan {
+    /// Order `derived` so every column follows the columns it reads
+    ///
+    /// Fails on unknown columns, duplicate names and dependency cycles.
+    pub(crate) fn new(derived: &[DerivedColumn], base: &[FieldRef]) -> Result<Self> {
+        let mut known: HashMap<&str, FieldRef> = base
+            .iter()
+            .map(|f| (f.name().as_str(), f.clone()))
+            .collect();
+        for column in derived {
+            if known.contains_key(column.name.as_str())
+                || derived.iter().filter(|c| c.name == column.name).count() > 1
+            {
+                bail!("derived column '{}' is defined twice", column.name);
+            }
+        }
+
+        // Repeatedly take the columns whose inputs are all known
+        let mut pending: Vec<&DerivedColumn> = derived.iter().collect();
+        let mut plan = Self::default();
+        while !pending.is_empty() {
+            let (ready, waiting): (Vec<_>, Vec<_>) = pending.into_iter().partition(|c| {
+                c.expr
+                    .dependencies()
+                    .iter()
+                    .all(|d| known.contains_key(d) || !derived.iter().any(|c| c.name == *d))
+            });
+            if ready.is_empty() {
+                let names: Vec<&str> = waiting.iter().map(|c| c.name.as_str()).collect();
+                bail!(
+                    "derived columns depend on each other in a cycle: {}",
+                    names.join(", ")
+                );
+            }
+            for column in ready {
+                let field = column
+                    .expr
+                    .field(&column.name, &known)
+                    .with_context(|| format!("invalid derived column '{}'", column.name))?;
+                let field = Arc::new(field);
+                known.insert(column.name.as_str(), field.clone());
+                plan.fields.push(field);
+                plan.columns.push(column.clone());
+            }
+            pending = waiting;
+        }
+        Ok(plan)
+    }
+
+    /// Output fields, in evaluation order
+    pub(crate) fn fields(&self) -> &[FieldRef] {
+        &self.fields
+    }
+
+    /// Append the derived arrays to the `arrays` of the generated `fields`
+    pub(crate) fn evaluate(&self, fields: &[FieldRef], arrays: &mut Vec<ArrayRef>) -> Result<()> {
+        if self.columns.is_empty() {
+            return Ok(());
+        }
+        let mut inputs: HashMap<&str, (FieldRef, ArrayRef)> = fields
+            .iter()
+            .zip(arrays.iter())
+            .map(|(f, a)| (f.name().as_str(), (f.clone(), a.clone())))
+            .collect();
+        for (column, field) in self.columns.iter().zip(&self.fields) {
+            let array = column.expr.evaluate(&inputs)?;
+            inputs.insert(column.name.as_str(), (field.clone(), array.clone()));
+            arrays.push(array);
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Column(name) => f.write_str(name),
+            Expr::L2(inner) => write!(f, "l2({})", inner),
+            Expr::Hash(inner) => write!(f, "hash({})", inner),
+            Expr::Len(inner) => write!(f, "len({})", inner),
+            Expr::Mod(inner, n) => write!(f, "{} % {}", inner, n),
+        }
+    }
+}
+
+impl fmt::Display for DerivedColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.name, self.expr)
+    }
+}
+
+impl FromStr for DerivedColumn {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, expr) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow!("invalid derived column '{}', expected NAME = EXPR", s))?;
+        let name = name.trim();
+        if !is_identifier(name) {
+            bail!("invalid derived column name '{}'", name);
+        }
+        Ok(Self {
+            name: name.to_string(),
+            expr: expr.parse()?,
+        })
+    }
+}
+
+impl FromStr for Expr {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parser = Parser { rest: s };
+        let expr = parser.expr()?;
+        if !parser.rest.trim().is_empty() {
+            bail!("unexpected '{}' in expression '{}'", parser.rest.trim(), s);
+        }
+        Ok(expr)
+    }
+}
+
+fn is_identifier(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Recursive-descent parser: `expr := term ('%' integer)*`,
+/// `term := name '(' expr ')' | name`
+struct Parser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Parser<'a> {
+    fn expr(&mut self) -> Result<Expr> {
+        let mut expr = self.term()?;
+        while self.eat('%') {
+            let digits = self.token(|c| c.is_ascii_digit() || c == '-');
+            let n = digits
+                .parse()
+                .with_context(|| format!("invalid modulus '{}'", digits))?;
+            expr = Expr::Mod(Box::new(expr), n);
+        }
+        Ok(expr)
+    }
+
+    fn term(&mut self) -> Result<Expr> {
+        let name = self.token(|c| c.is_ascii_alphanumeric() || c == '_');
+        if name.is_empty() {
+            bail!("expected a column or function at '{}'", self.rest.trim());
+        }
+        if !self.eat('(') {
+            return Ok(Expr::Column(name.to_string()));
+        }
+        let inner = Box::new(self.expr()?);
+        if !self.eat(')') {
+            bail!("missing ')' after {}(", name);
+        }
+        Ok(match name {
+            "l2" => Expr::L2(inner),
+            "hash" => Expr::Hash(inner),
+            "len" => Expr::Len(inner),
+            other => bail!("unknown function '{}', expected l2, hash or len", other),
+        })
+    }
+
+    fn token(&mut self, accept: impl Fn(char) -> bool) -> &'a str {
+        self.rest = self.rest.trim_start();
+        let end = self
+            .rest
+            .find(|c: char| !accept(c))
+            .unwrap_or(self.rest.len());
+        let (token, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        token
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        self.rest = self.rest.trim_start();
+        match self.rest.strip_prefix(c) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+}
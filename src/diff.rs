@@ -0,0 +1,257 @@
+//! Comparison of two generated datasets
+//!
+//! Walks both directories for Parquet files and compares them pairwise by
+//! relative path: schema, row count and a SHA-256 checksum of the file bytes.
+//! Files whose checksums differ can additionally be compared row by row at
+//! evenly spaced sample positions, to tell value changes apart from layout or
+//! encoding changes. Used to confirm that generator changes keep the output
+//! for a fixed seed.
+
+use anyhow::{Context, Result};
+use arrow::array::Array;
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// How thoroughly to compare files
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DiffOptions {
+    /// Compare this many evenly spaced rows of files whose checksums differ
+    pub sample_rows: Option<usize>,
+}
+
+/// Which side of a comparison a file was found on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// A difference between two datasets
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Difference {
+    /// File present on one side only
+    MissingFile { path: PathBuf, only_in: Side },
+    /// Arrow schemas differ, including field metadata
+    Schema { path: PathBuf },
+    /// Row counts differ
+    RowCount {
+        path: PathBuf,
+        left: usize,
+        right: usize,
+    },
+    /// File bytes differ
+    Checksum {
+        path: PathBuf,
+        left: String,
+        right: String,
+    },
+    /// A sampled row differs in the named column
+    Row {
+        path: PathBuf,
+        row: usize,
+        column: String,
+    },
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Difference::MissingFile { path, only_in } => {
+                let side = match only_in {
+                    Side::Left => "left",
+                    Side::Right => "right",
+                };
+                write!(f, "{}: only in {}", path.display(), side)
+            }
+            Difference::Schema { path } => write!(f, "{}: schemas differ", path.display()),
+            Difference::RowCount { path, left, right } => {
+                write!(f, "{}: {} rows vs {} rows", path.display(), left, right)
+            }
+            Difference::Checksum { path, left, right } => {
+                write!(f, "{}: sha256 {} vs {}", path.display(), left, right)
+            }
+            Difference::Row { path, row, column } => {
+                write!(
+                    f,
+                    "{}: row {} differs in column {}",
+                    path.display(),
+                    row,
+                    column
+                )
+            }
+        }
+    }
+}
+
+/// Outcome of comparing two datasets
+#[derive(Debug, Clone, Default)]
+pub struct DiffReport {
+    /// Files present on both sides
+    pub files_compared: usize,
+    /// Differences, ordered by file path
+    pub differences: Vec<Difference>,
+}
+
+impl DiffReport {
+    /// True if no differences were found
+    pub fn is_identical(&self) -> bool {
+        self.differences.is_empty()
+    }
+}
+
+/// Compare the Parquet files under `left` and `right`
+pub fn compare(left: &Path, right: &Path, options: &DiffOptions) -> Result<DiffReport> {
+    let left_files = parquet_files(left)?;
+    let right_files = parquet_files(right)?;
+    let mut report = DiffReport::default();
+
+    for path in left_files.union(&right_files) {
+        if !right_files.contains(path) || !left_files.contains(path) {
+            let only_in = if left_files.contains(path) {
+                Side::Left
+            } else {
+                Side::Right
+            };
+            report.differences.push(Difference::MissingFile {
+                path: path.clone(),
+                only_in,
+            });
+            continue;
+        }
+        report.files_compared += 1;
+        compare_file(
+            path,
+            &left.join(path),
+            &right.join(path),
+            options,
+            &mut report.differences,
+        )?;
+    }
+    Ok(report)
+}
+
+fn compare_file(
+    path: &Path,
+    left: &Path,
+    right: &Path,
+    options: &DiffOptions,
+    differences: &mut Vec<Difference>,
+) -> Result<()> {
+    let left_sum = sha256_file(left)?;
+    let right_sum = sha256_file(right)?;
+    if left_sum == right_sum {
+        return Ok(());
+    }
+
+    let (left_schema, left_rows) = file_summary(left)?;
+    let (right_schema, right_rows) = file_summary(right)?;
+    let same_schema = left_schema == right_schema;
+    if !same_schema {
+        differences.push(Difference::Schema {
+            path: path.to_path_buf(),
+        });
+    }
+    if left_rows != right_rows {
+        differences.push(Difference::RowCount {
+            path: path.to_path_buf(),
+            left: left_rows,
+            right: right_rows,
+        });
+    }
+    differences.push(Difference::Checksum {
+        path: path.to_path_buf(),
+        left: left_sum,
+        right: right_sum,
+    });
+
+    if let Some(samples) = options.sample_rows {
+        if same_schema && samples > 0 {
+            let rows = left_rows.min(right_rows);
+            let positions: BTreeSet<usize> = (0..samples.min(rows))
+                .map(|i| i * rows / samples.min(rows))
+                .collect();
+            let left_batch = read_rows(left, &positions)?;
+            let right_batch = read_rows(right, &positions)?;
+            for (i, row) in positions.iter().enumerate() {
+                for (c, field) in left_schema.fields().iter().enumerate() {
+                    let a = left_batch.column(c).slice(i, 1);
+                    let b = right_batch.column(c).slice(i, 1);
+                    if a.to_data() != b.to_data() {
+                        differences.push(Difference::Row {
+                            path: path.to_path_buf(),
+                            row: *row,
+                            column: field.name().clone(),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Relative paths of every `.parquet` file under `root`
+pub(crate) fn parquet_files(root: &Path) -> Result<BTreeSet<PathBuf>> {
+    let mut files = BTreeSet::new();
+    let mut dirs = vec![root.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read directory: {:?}", dir))?
+        {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "parquet") {
+                files.insert(path.strip_prefix(root)?.to_path_buf());
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 1 << 20];
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+fn file_summary(path: &Path) -> Result<(SchemaRef, usize)> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?;
+    let rows = builder.metadata().file_metadata().num_rows() as usize;
+    Ok((builder.schema().clone(), rows))
+}
+
+/// Read the rows at `positions`, in order, into one batch
+fn read_rows(path: &Path, positions: &BTreeSet<usize>) -> Result<RecordBatch> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(File::open(path)?)?;
+    let schema = builder.schema().clone();
+    let mut selected = Vec::with_capacity(positions.len());
+    let mut offset = 0;
+    for batch in builder.build()? {
+        let batch = batch?;
+        for &row in positions.range(offset..offset + batch.num_rows()) {
+            selected.push(batch.slice(row - offset, 1));
+        }
+        offset += batch.num_rows();
+    }
+    Ok(arrow::compute::concat_batches(&schema, &selected)?)
+}
@@ -0,0 +1,70 @@
+//! Vectors repeated across output files
+//!
+//! A fraction of rows take their primary vector from a dataset-wide pool
+//! instead of sampling a fresh one. Pool vectors depend only on the seed and
+//! their pool index, so the same vector shows up in different files, giving
+//! cross-file dedup and index-merge tests a known overlap. The pool index of
+//! every repeated row is recorded in a `duplicate_id` column.
+
+use anyhow::{bail, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::core_gen::stream_seed;
+use crate::mixture::VectorSampler;
+
+/// Stream identifier for pool vectors
+const POOL_STREAM: u64 = u64::MAX - 4;
+
+/// Cross-file duplicate parameters
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateConfig {
+    /// Fraction of rows whose vector is drawn from the pool
+    pub fraction: f64,
+    /// Number of distinct pool vectors shared by all files
+    pub pool_size: u64,
+}
+
+impl DuplicateConfig {
+    /// Check the parameters
+    pub fn validate(&self) -> Result<()> {
+        if !(0.0..=1.0).contains(&self.fraction) {
+            bail!("duplicate fraction must be between 0 and 1");
+        }
+        if self.pool_size == 0 {
+            bail!("duplicate pool size must be greater than zero");
+        }
+        Ok(())
+    }
+}
+
+/// Pool picks for the rows of one batch
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DuplicateBlock {
+    pool_seed: u64,
+    /// Pool index per row, `None` for freshly sampled rows
+    pub(crate) picks: Vec<Option<u64>>,
+}
+
+impl DuplicateBlock {
+    /// Decide which of `rows` rows repeat a pool vector
+    pub(crate) fn draw(config: &DuplicateConfig, seed: u64, rng: &mut StdRng, rows: usize) -> Self {
+        let picks = (0..rows)
+            .map(|_| {
+                rng.gen_bool(config.fraction)
+                    .then(|| rng.gen_range(0..config.pool_size))
+            })
+            .collect();
+        Self {
+            pool_seed: stream_seed(seed, &[POOL_STREAM]),
+            picks,
+        }
+    }
+
+    /// The `index`-th pool vector
+    pub(crate) fn pool_vector(&self, index: u64, dim: usize, sampler: &VectorSampler) -> Vec<f64> {
+        let mut rng = StdRng::seed_from_u64(stream_seed(self.pool_seed, &[index]));
+        sampler.sample(&mut rng, dim)
+    }
+}
@@ -0,0 +1,129 @@
+//! Edge-case Parquet files for reader testing
+//!
+//! Writes the file shapes reader implementations most often get wrong, all
+//! with the full schema of the configured columns:
+//!
+//! - a file with zero rows (schema and footer only)
+//! - a file with a single row
+//! - a file whose rows all sit in one huge row group
+//! - a file split into thousands of one-row row groups
+
+use anyhow::Result;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::sink::{write_rows, ParquetFileSink, Sink};
+use crate::{CancellationToken, Config, DataGenerator, ParquetWriter};
+
+/// Shape of an edge-case file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeCase {
+    /// No rows, full schema
+    Empty,
+    /// Exactly one row
+    SingleRow,
+    /// All rows in a single row group
+    HugeRowGroup,
+    /// One row per row group
+    TinyRowGroups,
+}
+
+impl EdgeCase {
+    /// Every edge case, in the order files are written
+    pub const ALL: [EdgeCase; 4] = [
+        EdgeCase::Empty,
+        EdgeCase::SingleRow,
+        EdgeCase::HugeRowGroup,
+        EdgeCase::TinyRowGroups,
+    ];
+
+    /// Name used in file names
+    pub fn name(self) -> &'static str {
+        match self {
+            EdgeCase::Empty => "empty",
+            EdgeCase::SingleRow => "single-row",
+            EdgeCase::HugeRowGroup => "huge-row-group",
+            EdgeCase::TinyRowGroups => "tiny-row-groups",
+        }
+    }
+}
+
+impl fmt::Display for EdgeCase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Sizes of the edge-case files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EdgeCaseOptions {
+    /// Rows of the single-row-group file
+    pub huge_rows: usize,
+    /// Row groups (of one row each) of the tiny-row-group file
+    pub tiny_row_groups: usize,
+    /// Rows per generated batch
+    pub batch_size: usize,
+}
+
+/// An edge-case file that was written
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EdgeCaseFile {
+    pub case: EdgeCase,
+    pub path: PathBuf,
+    pub rows: usize,
+    pub row_groups: usize,
+}
+
+/// Write every [`EdgeCase`] to `<prefix>-<case>.parquet` in `output_dir`
+///
+/// Each file draws from its own file index, so the files hold different rows.
+pub fn write_edge_cases(
+    output_dir: impl AsRef<Path>,
+    prefix: &str,
+    config: &Config,
+    options: &EdgeCaseOptions,
+) -> Result<Vec<EdgeCaseFile>> {
+    let writer = ParquetWriter::new(config.clone());
+    let mut files = Vec::with_capacity(EdgeCase::ALL.len());
+    for (index, case) in EdgeCase::ALL.into_iter().enumerate() {
+        let (rows, row_group_rows) = match case {
+            EdgeCase::Empty => (0, 1),
+            EdgeCase::SingleRow => (1, 1),
+            EdgeCase::HugeRowGroup => (options.huge_rows, options.huge_rows.max(1)),
+            EdgeCase::TinyRowGroups => (options.tiny_row_groups, 1),
+        };
+        let props = writer
+            .writer_properties()
+            .clone()
+            .into_builder()
+            .set_max_row_group_size(row_group_rows)
+            .build();
+
+        let mut generator = DataGenerator::for_file(config.clone(), index as u64)?;
+        let path = output_dir
+            .as_ref()
+            .join(format!("{}-{}.parquet", prefix, case.name()));
+        let mut sink = ParquetFileSink::create(&path, Arc::new(generator.schema().clone()), props)?;
+        let rows = if rows == 0 {
+            sink.finish()?;
+            0
+        } else {
+            write_rows(
+                &mut sink,
+                &mut generator,
+                rows,
+                options.batch_size,
+                &CancellationToken::new(),
+                config.cancel_policy,
+            )?
+        };
+        files.push(EdgeCaseFile {
+            case,
+            path,
+            rows,
+            row_groups: rows.div_ceil(row_group_rows),
+        });
+    }
+    Ok(files)
+}
@@ -0,0 +1,136 @@
+//! Arrow C Stream export of the generator
+//!
+//! Non-Rust processes (Python, R, Java) can pull generated batches in-process
+//! through the Arrow C Stream interface (`ArrowArrayStream`): batches are
+//! handed over as Arrow C Data arrays without copying or serializing them.
+//! Rust callers use [`export_stream`]; the library built as a `cdylib`
+//! exports [`vdg_export_stream`], which takes the configuration as JSON in
+//! the same shape as [`Config`], with omitted fields taking their defaults.
+
+use anyhow::{bail, Result};
+use arrow::datatypes::SchemaRef;
+use arrow::error::ArrowError;
+use arrow::ffi_stream::FFI_ArrowArrayStream;
+use arrow::record_batch::{RecordBatch, RecordBatchReader};
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::sync::Arc;
+
+use crate::{config_format, Config, DataGenerator};
+
+/// Batches of a generator as a [`RecordBatchReader`]
+pub struct GeneratorReader {
+    generator: DataGenerator,
+    schema: SchemaRef,
+    remaining: usize,
+    batch_size: usize,
+}
+
+impl GeneratorReader {
+    /// Read `total_rows` rows from `generator` in batches of `batch_size`
+    ///
+    /// Batches are capped at [`DataGenerator::max_batch_rows`].
+    pub fn new(generator: DataGenerator, total_rows: usize, batch_size: usize) -> Self {
+        let batch_size = batch_size.clamp(1, generator.max_batch_rows());
+        Self {
+            schema: Arc::new(generator.schema().clone()),
+            generator,
+            remaining: total_rows,
+            batch_size,
+        }
+    }
+}
+
+impl Iterator for GeneratorReader {
+    type Item = Result<RecordBatch, ArrowError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let rows = self.batch_size.min(self.remaining);
+        self.remaining -= rows;
+        Some(
+            self.generator
+                .generate_batch(rows)
+                .map_err(|e| ArrowError::ExternalError(e.into())),
+        )
+    }
+}
+
+impl RecordBatchReader for GeneratorReader {
+    fn schema(&self) -> SchemaRef {
+        self.schema.clone()
+    }
+}
+
+/// C stream of `total_rows` rows generated with `config`
+pub fn export_stream(
+    config: Config,
+    total_rows: usize,
+    batch_size: usize,
+) -> Result<FFI_ArrowArrayStream> {
+    if batch_size == 0 {
+        bail!("batch size must be greater than zero");
+    }
+    let generator = DataGenerator::try_new(config)?;
+    let reader = GeneratorReader::new(generator, total_rows, batch_size);
+    Ok(FFI_ArrowArrayStream::new(Box::new(reader)))
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Export a generator stream into `out`
+///
+/// `config_json` is a NUL-terminated JSON object such as
+/// `{"vector_dim": 128, "seed": 7}`. Returns 0 on success; on failure returns
+/// `EINVAL` and leaves `out` untouched, and [`vdg_last_error`] describes the
+/// failure. The caller owns the stream and must call its `release` callback.
+///
+/// # Safety
+///
+/// `config_json` must point to a NUL-terminated string and `out` to writable
+/// memory for one `ArrowArrayStream`.
+#[no_mangle]
+pub unsafe extern "C" fn vdg_export_stream(
+    config_json: *const c_char,
+    total_rows: u64,
+    batch_size: u64,
+    out: *mut FFI_ArrowArrayStream,
+) -> c_int {
+    const EINVAL: c_int = 22;
+    let result = (|| -> Result<FFI_ArrowArrayStream> {
+        if config_json.is_null() || out.is_null() {
+            bail!("configuration and output stream must not be null");
+        }
+        let config = config_format::from_json(CStr::from_ptr(config_json).to_str()?)?;
+        export_stream(config, total_rows as usize, batch_size as usize)
+    })();
+    match result {
+        Ok(stream) => {
+            std::ptr::write(out, stream);
+            0
+        }
+        Err(e) => {
+            let message =
+                CString::new(format!("{:#}", e).replace('\0', " ")).expect("NUL bytes replaced");
+            LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+            EINVAL
+        }
+    }
+}
+
+/// Message of the last failed [`vdg_export_stream`] call on this thread
+///
+/// Null if no call has failed. The string stays valid until the next failed
+/// call on the same thread.
+#[no_mangle]
+pub extern "C" fn vdg_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(std::ptr::null(), |message| message.as_ptr())
+    })
+}
@@ -0,0 +1,177 @@
+//! Footer size and metadata overhead of Parquet files
+//!
+//! With kilobyte-sized binary cells, the footer is not free: column chunk
+//! statistics store the min and max value of every chunk verbatim, and the
+//! column and offset indexes add per-page entries. This module reads the
+//! footer of each file and reports how many bytes go to metadata, per column,
+//! next to the data and dictionary pages they describe, so row group and
+//! statistics settings can be tuned against the metadata-to-data ratio.
+
+use anyhow::{bail, Context, Result};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use std::fmt;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::diff::parquet_files;
+
+/// Trailing bytes of a Parquet file: footer length and the `PAR1` magic
+const TRAILER_BYTES: u64 = 8;
+
+/// Sizes of one column, summed over all row groups of a file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnOverhead {
+    /// Dotted column path
+    pub column: String,
+    /// Compressed bytes of the column chunks, dictionary pages included
+    pub data_bytes: u64,
+    /// Compressed bytes of dictionary pages
+    pub dictionary_bytes: u64,
+    /// Bytes of min/max values stored in the footer statistics
+    pub statistics_bytes: u64,
+    /// Bytes of the column and offset indexes
+    pub index_bytes: u64,
+    /// Bytes of bloom filters
+    pub bloom_filter_bytes: u64,
+}
+
+impl ColumnOverhead {
+    /// Metadata bytes attributable to this column
+    pub fn metadata_bytes(&self) -> u64 {
+        self.statistics_bytes + self.index_bytes + self.bloom_filter_bytes
+    }
+}
+
+/// Footer and metadata overhead of one file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FooterReport {
+    pub path: PathBuf,
+    pub file_bytes: u64,
+    /// Thrift-encoded footer, excluding the 8 trailing bytes
+    pub footer_bytes: u64,
+    pub row_groups: usize,
+    pub rows: i64,
+    pub columns: Vec<ColumnOverhead>,
+}
+
+impl FooterReport {
+    /// Compressed bytes of all column chunks
+    pub fn data_bytes(&self) -> u64 {
+        self.columns.iter().map(|c| c.data_bytes).sum()
+    }
+
+    /// Footer plus page indexes and bloom filters stored outside it
+    pub fn metadata_bytes(&self) -> u64 {
+        self.footer_bytes
+            + self
+                .columns
+                .iter()
+                .map(|c| c.index_bytes + c.bloom_filter_bytes)
+                .sum::<u64>()
+    }
+
+    /// Metadata bytes per data byte
+    pub fn metadata_ratio(&self) -> f64 {
+        self.metadata_bytes() as f64 / self.data_bytes().max(1) as f64
+    }
+}
+
+impl fmt::Display for FooterReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{}: {} bytes, {} rows in {} row groups",
+            self.path.display(),
+            self.file_bytes,
+            self.rows,
+            self.row_groups
+        )?;
+        writeln!(
+            f,
+            "  footer {} bytes, metadata {} bytes ({:.3}% of data)",
+            self.footer_bytes,
+            self.metadata_bytes(),
+            self.metadata_ratio() * 100.0
+        )?;
+        for column in &self.columns {
+            writeln!(
+                f,
+                "  {}: data {}, dictionary {}, statistics {}, indexes {}, bloom filters {}",
+                column.column,
+                column.data_bytes,
+                column.dictionary_bytes,
+                column.statistics_bytes,
+                column.index_bytes,
+                column.bloom_filter_bytes
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Read the footer of the Parquet file at `path`
+pub fn inspect(path: &Path) -> Result<FooterReport> {
+    let mut file = File::open(path).with_context(|| format!("Failed to open {:?}", path))?;
+    let file_bytes = file.metadata()?.len();
+    if file_bytes < TRAILER_BYTES {
+        bail!("{:?} is too short to be a Parquet file", path);
+    }
+    let mut trailer = [0u8; TRAILER_BYTES as usize];
+    file.seek(SeekFrom::End(-(TRAILER_BYTES as i64)))?;
+    file.read_exact(&mut trailer)?;
+    if &trailer[4..] != b"PAR1" {
+        bail!("{:?} has no Parquet footer", path);
+    }
+    let footer_bytes = u32::from_le_bytes(trailer[..4].try_into().expect("4 bytes")) as u64;
+
+    let reader = SerializedFileReader::new(file)
+        .with_context(|| format!("Failed to read footer of {:?}", path))?;
+    let metadata = reader.metadata();
+    let mut columns: Vec<ColumnOverhead> = metadata
+        .file_metadata()
+        .schema_descr()
+        .columns()
+        .iter()
+        .map(|column| ColumnOverhead {
+            column: column.path().string(),
+            ..ColumnOverhead::default()
+        })
+        .collect();
+    for group in metadata.row_groups() {
+        for (overhead, chunk) in columns.iter_mut().zip(group.columns()) {
+            overhead.data_bytes += chunk.compressed_size() as u64;
+            if let Some(offset) = chunk.dictionary_page_offset() {
+                overhead.dictionary_bytes += (chunk.data_page_offset() - offset) as u64;
+            }
+            if let Some(statistics) = chunk.statistics() {
+                overhead.statistics_bytes += statistics.min_bytes_opt().map_or(0, <[u8]>::len)
+                    as u64
+                    + statistics.max_bytes_opt().map_or(0, <[u8]>::len) as u64;
+            }
+            overhead.index_bytes += chunk.column_index_length().unwrap_or(0) as u64
+                + chunk.offset_index_length().unwrap_or(0) as u64;
+            overhead.bloom_filter_bytes += chunk.bloom_filter_length().unwrap_or(0) as u64;
+        }
+    }
+
+    Ok(FooterReport {
+        path: path.to_path_buf(),
+        file_bytes,
+        footer_bytes,
+        row_groups: metadata.num_row_groups(),
+        rows: metadata.file_metadata().num_rows(),
+        columns,
+    })
+}
+
+/// Inspect every Parquet file under `root`, or `root` itself if it is a file
+pub fn inspect_all(root: &Path) -> Result<Vec<FooterReport>> {
+    if root.is_file() {
+        return Ok(vec![inspect(root)?]);
+    }
+    parquet_files(root)?
+        .into_iter()
+        .map(|path| inspect(&root.join(path)))
+        .collect()
+}
@@ -0,0 +1,93 @@
+//! Parent/child tables linked by a skewed foreign key
+//!
+//! Rows get a foreign-key column referencing `0..parents` of a parent table.
+//! Under Zipf skew a few parents own most rows (e.g. documents with thousands
+//! of chunks), giving join benchmarks realistic hot keys. The parent table can
+//! be co-generated so both sides of the join exist.
+
+use anyhow::{bail, Result};
+use arrow::array::{ArrayRef, Int64Array, StringBuilder};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::core_gen::{sample_scalar, stream_seed};
+use crate::sink::Sink;
+use crate::skew::Skew;
+
+/// Stream identifier for parent table rows
+const PARENT_STREAM: u64 = u64::MAX - 3;
+
+/// Foreign-key column and its parent table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ForeignKeyConfig {
+    /// Name of the foreign-key column (and the parent table's key column)
+    pub column: String,
+    /// Number of parent rows; keys are `0..parents`
+    pub parents: usize,
+    /// Distribution of child rows over parents; key 0 is the hottest under Zipf
+    pub skew: Skew,
+    /// Write the parent table to `<name>.parquet` next to the child files
+    pub parent_table: Option<String>,
+}
+
+impl ForeignKeyConfig {
+    /// Check the parameters
+    pub fn validate(&self) -> Result<()> {
+        if self.column.is_empty() {
+            bail!("foreign key column name must not be empty");
+        }
+        if self.parents == 0 {
+            bail!("foreign key parent count must be greater than zero");
+        }
+        Ok(())
+    }
+}
+
+/// Schema of the parent table: the key column and a `title` string
+pub fn parent_schema(config: &ForeignKeyConfig) -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new(&config.column, DataType::Int64, false),
+        Field::new("title", DataType::Utf8, false),
+    ]))
+}
+
+/// Write the parent table with one row per key to `sink`, which must accept
+/// [`parent_schema`] batches; the sink is finished on success
+pub fn write_parent_table(
+    sink: &mut dyn Sink,
+    config: &ForeignKeyConfig,
+    title_len: usize,
+    seed: u64,
+    batch_size: usize,
+) -> Result<usize> {
+    config.validate()?;
+    let schema = parent_schema(config);
+    let mut rng = StdRng::seed_from_u64(stream_seed(seed, &[PARENT_STREAM]));
+
+    let batch_size = batch_size.max(1);
+    let mut start = 0;
+    while start < config.parents {
+        let rows = batch_size.min(config.parents - start);
+        let keys: Vec<i64> = (start..start + rows).map(|k| k as i64).collect();
+        let mut titles = StringBuilder::with_capacity(rows, rows * title_len);
+        for _ in 0..rows {
+            titles.append_value(sample_scalar(&mut rng, title_len));
+        }
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int64Array::from(keys)) as ArrayRef,
+                Arc::new(titles.finish()) as ArrayRef,
+            ],
+        )?;
+        sink.write(&batch)?;
+        start += rows;
+    }
+
+    sink.finish()?;
+    Ok(config.parents)
+}
@@ -0,0 +1,241 @@
+//! ann-benchmarks HDF5 output
+//!
+//! The ann-benchmarks harness reads each dataset from one HDF5 file holding
+//! the base vectors as `train`, the query vectors as `test`, and the exact
+//! nearest neighbors of every query as `neighbors` (row ids) and `distances`,
+//! nearest first, with the metric in the `distance` attribute (`euclidean`
+//! or `angular`). This module produces such a file in one run: base rows are
+//! streamed into `train` as they are generated, the query set of the config
+//! is generated next (perturbed queries read their rows back from `train`),
+//! and the ground truth is computed by scanning `train` in batches.
+
+use anyhow::{bail, Context, Result};
+use arrow::record_batch::RecordBatch;
+use hdf5::types::VarLenUnicode;
+use hdf5::{Dataset, File};
+use ndarray::{s, Array2};
+use std::path::{Path, PathBuf};
+
+use crate::columns::{vector_encoding, vector_values};
+use crate::ground_truth::{GroundTruth, Metric};
+use crate::queries::generate_queries;
+use crate::sink::{write_rows, Sink};
+use crate::{CancellationToken, Config, DataGenerator};
+
+/// Contents of a written ann-benchmarks file
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnBenchmarksReport {
+    pub path: PathBuf,
+    /// Rows of `train`
+    pub train_rows: usize,
+    /// Rows of `test`, `neighbors` and `distances`
+    pub test_rows: usize,
+    /// Neighbors per query
+    pub k: usize,
+}
+
+/// Name of `metric` in the `distance` attribute
+fn distance_name(metric: Metric) -> &'static str {
+    match metric {
+        Metric::L2 => "euclidean",
+        Metric::Cosine => "angular",
+    }
+}
+
+/// Write `total_rows` base rows of `config`, its query set and their `k`
+/// nearest neighbors under `metric` to an HDF5 file at `path`
+///
+/// The config must have a query set. On failure or cancellation the file is
+/// removed, since the harness cannot use it without its ground truth.
+pub fn write_ann_benchmarks(
+    path: &Path,
+    config: &Config,
+    total_rows: usize,
+    batch_size: usize,
+    k: usize,
+    metric: Metric,
+    cancel: &CancellationToken,
+) -> Result<AnnBenchmarksReport> {
+    let result = write_file(path, config, total_rows, batch_size, k, metric, cancel);
+    if result.is_err() {
+        let _ = std::fs::remove_file(path);
+    }
+    result
+}
+
+fn write_file(
+    path: &Path,
+    config: &Config,
+    total_rows: usize,
+    batch_size: usize,
+    k: usize,
+    metric: Metric,
+    cancel: &CancellationToken,
+) -> Result<AnnBenchmarksReport> {
+    let Some(queries) = &config.queries else {
+        bail!("ann-benchmarks files need a query set");
+    };
+    if total_rows < k {
+        bail!(
+            "{} base rows cannot give {} neighbors per query",
+            total_rows,
+            k
+        );
+    }
+    let batch_size = batch_size.max(1);
+    let dim = config.vector_dim;
+    let file =
+        File::create(path).with_context(|| format!("Failed to create file: {}", path.display()))?;
+    file.new_attr::<VarLenUnicode>()
+        .create("distance")?
+        .write_scalar(&distance_name(metric).parse::<VarLenUnicode>()?)?;
+    file.new_attr::<VarLenUnicode>()
+        .create("point_type")?
+        .write_scalar(&"float".parse::<VarLenUnicode>()?)?;
+    file.new_attr::<u64>()
+        .create("dimension")?
+        .write_scalar(&(dim as u64))?;
+
+    let mut generator = DataGenerator::try_new(config.clone())?;
+    let Some(column) = generator
+        .schema()
+        .fields()
+        .iter()
+        .find(|field| vector_encoding(field).is_some())
+        .map(|field| field.name().clone())
+    else {
+        bail!("ann-benchmarks files need a vector column");
+    };
+    let mut train = TrainSink {
+        dataset: file
+            .new_dataset::<f32>()
+            .shape((total_rows, dim))
+            .create("train")?,
+        column,
+        dim,
+        written: 0,
+    };
+    write_rows(
+        &mut train,
+        &mut generator,
+        total_rows,
+        batch_size,
+        cancel,
+        config.cancel_policy,
+    )?;
+
+    let mut test = Vec::with_capacity(queries.count * dim);
+    generate_queries(
+        config,
+        queries,
+        total_rows,
+        batch_size,
+        |_, ids| {
+            let mut values = Vec::with_capacity(ids.len() * dim);
+            for &id in ids {
+                let id = id as usize;
+                let row = train.dataset.read_slice_2d::<f32, _>(s![id..id + 1, ..])?;
+                values.extend(row.iter().map(|&v| v as f64));
+            }
+            Ok(values)
+        },
+        |batch| {
+            test.extend(batch_vectors(&batch, 1, dim)?);
+            Ok(())
+        },
+    )?;
+    let test_rows = queries.count;
+    let mut search = GroundTruth::new(
+        test.chunks(dim.max(1)).map(<[f32]>::to_vec).collect(),
+        k,
+        metric,
+    )?;
+    file.new_dataset::<f32>()
+        .shape((test_rows, dim))
+        .create("test")?
+        .write(&Array2::from_shape_vec((test_rows, dim), test)?)?;
+
+    let mut start = 0;
+    while start < total_rows {
+        let end = (start + batch_size).min(total_rows);
+        let rows = train.dataset.read_slice_2d::<f32, _>(s![start..end, ..])?;
+        let rows: Vec<Option<Vec<f32>>> = rows.outer_iter().map(|row| Some(row.to_vec())).collect();
+        search.add(&rows)?;
+        start = end;
+    }
+    let neighbors = search.finish();
+    let mut ids = Vec::with_capacity(test_rows * k);
+    let mut distances = Vec::with_capacity(test_rows * k);
+    for neighbor in neighbors.iter().flatten() {
+        let Ok(id) = i32::try_from(neighbor.id) else {
+            bail!(
+                "row id {} does not fit an ann-benchmarks neighbor id",
+                neighbor.id
+            );
+        };
+        ids.push(id);
+        distances.push(neighbor.distance);
+    }
+    file.new_dataset::<i32>()
+        .shape((test_rows, k))
+        .create("neighbors")?
+        .write(&Array2::from_shape_vec((test_rows, k), ids)?)?;
+    file.new_dataset::<f32>()
+        .shape((test_rows, k))
+        .create("distances")?
+        .write(&Array2::from_shape_vec((test_rows, k), distances)?)?;
+    file.close()?;
+
+    Ok(AnnBenchmarksReport {
+        path: path.to_path_buf(),
+        train_rows: total_rows,
+        test_rows,
+        k,
+    })
+}
+
+/// Values of the vector column at `position` of `batch` as `f32`, row after
+/// row; HDF5 datasets have no nulls, so a null vector is an error
+fn batch_vectors(batch: &RecordBatch, position: usize, dim: usize) -> Result<Vec<f32>> {
+    let schema = batch.schema();
+    let field = schema.field(position);
+    let array = batch.column(position);
+    let mut values = Vec::with_capacity(batch.num_rows() * dim);
+    for row in 0..batch.num_rows() {
+        let Some(vector) = vector_values(field, array.as_ref(), row) else {
+            bail!("ann-benchmarks files cannot hold null vectors");
+        };
+        values.extend(vector.into_iter().map(|v| v as f32));
+    }
+    Ok(values)
+}
+
+/// Sink writing the vector column of each batch to the next rows of `train`
+struct TrainSink {
+    dataset: Dataset,
+    column: String,
+    dim: usize,
+    written: usize,
+}
+
+impl Sink for TrainSink {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        let schema = batch.schema();
+        let Some((position, _)) = schema.column_with_name(&self.column) else {
+            bail!("column '{}' is missing from the batch", self.column);
+        };
+        let rows = batch.num_rows();
+        let values = batch_vectors(batch, position, self.dim)?;
+        let end = self.written + rows;
+        self.dataset.write_slice(
+            &Array2::from_shape_vec((rows, self.dim), values)?,
+            s![self.written..end, ..],
+        )?;
+        self.written = end;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
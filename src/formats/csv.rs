@@ -0,0 +1,161 @@
+//! CSV output
+//!
+//! Rows are written with the Arrow CSV encoder under a header line. Vector
+//! columns have no CSV form of their own and are written as text, either as
+//! base64 of their bytes or as a bracketed float list such as
+//! `[0.25,-1.5,3]`, which Postgres `COPY` into a pgvector column and most
+//! spreadsheets read directly. Base64 holds the bytes stored in the Parquet
+//! column (byte order, dimension prefix and quantization included); list
+//! encoded vectors are written as their little-endian elements. Null
+//! vectors are empty fields.
+
+use anyhow::{anyhow, Context, Result};
+use arrow::array::{Array, ArrayRef, AsArray, StringBuilder};
+use arrow::csv::{Writer, WriterBuilder};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use half::f16;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::columns::{vector_encoding, vector_values};
+use crate::core_gen::{encode_vector_with, quantize_int8, VectorLayout};
+use crate::sink::Sink;
+use crate::VectorElementType;
+
+/// Text form of vector columns in CSV output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum VectorText {
+    /// Base64 of the vector bytes
+    #[default]
+    Base64,
+    /// Bracketed, comma-separated element values
+    List,
+}
+
+/// Sink writing rows as CSV with a header line
+pub struct CsvSink {
+    writer: Option<Writer<BufWriter<File>>>,
+    vectors: VectorText,
+}
+
+impl CsvSink {
+    /// Create the output file, writing vectors as `vectors`
+    pub fn create(path: impl AsRef<Path>, vectors: VectorText) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create file: {}", path.display()))?;
+        Ok(Self {
+            writer: Some(
+                WriterBuilder::new()
+                    .with_header(true)
+                    .build(BufWriter::new(file)),
+            ),
+            vectors,
+        })
+    }
+
+    /// The batch with its vector columns replaced by their text form
+    fn text_batch(&self, batch: &RecordBatch) -> Result<RecordBatch> {
+        let schema = batch.schema();
+        let mut fields = Vec::with_capacity(schema.fields().len());
+        let mut columns = Vec::with_capacity(batch.num_columns());
+        for (field, column) in schema.fields().iter().zip(batch.columns()) {
+            let Some((element, _)) = vector_encoding(field) else {
+                fields.push(field.as_ref().clone());
+                columns.push(column.clone());
+                continue;
+            };
+            fields.push(Field::new(field.name(), DataType::Utf8, true));
+            columns.push(self.vector_text(field, element, column.as_ref())?);
+        }
+        Ok(RecordBatch::try_new(
+            Arc::new(Schema::new(fields)),
+            columns,
+        )?)
+    }
+
+    fn vector_text(
+        &self,
+        field: &Field,
+        element: VectorElementType,
+        array: &dyn Array,
+    ) -> Result<ArrayRef> {
+        let mut builder = StringBuilder::with_capacity(array.len(), array.len() * 16);
+        let mut bytes = Vec::new();
+        for row in 0..array.len() {
+            if array.is_null(row) {
+                builder.append_null();
+                continue;
+            }
+            match self.vectors {
+                VectorText::Base64 => {
+                    let stored = match array.data_type() {
+                        DataType::Binary => array.as_binary::<i32>().value(row),
+                        DataType::LargeBinary => array.as_binary::<i64>().value(row),
+                        DataType::FixedSizeBinary(_) => array.as_fixed_size_binary().value(row),
+                        _ => {
+                            let values = vector_values(field, array, row).unwrap_or_default();
+                            bytes.clear();
+                            encode_vector_with(
+                                &values,
+                                element,
+                                VectorLayout::default(),
+                                &mut bytes,
+                            );
+                            &bytes
+                        }
+                    };
+                    builder.append_value(STANDARD.encode(stored));
+                }
+                VectorText::List => {
+                    let values = vector_values(field, array, row).unwrap_or_default();
+                    builder.append_value(float_list(&values, element));
+                }
+            }
+        }
+        Ok(Arc::new(builder.finish()))
+    }
+}
+
+/// `values` as `[v0,v1,...]`, in the shortest form that reads back as the
+/// same `element` value
+fn float_list(values: &[f64], element: VectorElementType) -> String {
+    let items: Vec<String> = match element {
+        VectorElementType::Float16 => values
+            .iter()
+            .map(|&v| f16::from_f64(v).to_string())
+            .collect(),
+        VectorElementType::Float32 => values.iter().map(|&v| (v as f32).to_string()).collect(),
+        VectorElementType::Float64 => values.iter().map(f64::to_string).collect(),
+        VectorElementType::Int8 => values
+            .iter()
+            .map(|&v| quantize_int8(v).to_string())
+            .collect(),
+        VectorElementType::Binary => values.iter().map(|&v| (v as u8).to_string()).collect(),
+    };
+    format!("[{}]", items.join(","))
+}
+
+impl Sink for CsvSink {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        let batch = self.text_batch(batch)?;
+        self.writer
+            .as_mut()
+            .ok_or_else(|| anyhow!("CSV sink already closed"))?
+            .write(&batch)?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if let Some(writer) = self.writer.take() {
+            writer.into_inner().flush()?;
+        }
+        Ok(())
+    }
+}
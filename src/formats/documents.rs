@@ -0,0 +1,161 @@
+//! Search-engine batch import documents
+//!
+//! Writes rows as the documents search engines ingest directly, with the
+//! first vector column embedded as the object's vector:
+//!
+//! - Weaviate: a `/v1/batch/objects` request body,
+//!   `{"objects": [{"class", "id", "properties", "vector"}, ...]}`
+//! - Vespa: a JSON Lines feed of `{"put": "id:<ns>:<type>::<row>", "fields"}`
+//!   operations, the vector under `fields.<name>.values`
+//!
+//! Other vector columns become number arrays; binary columns that are not
+//! vectors (e.g. PQ codes) are omitted.
+
+use anyhow::{anyhow, Context, Result};
+use arrow::record_batch::RecordBatch;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::sink::value::{is_vector, json_value, JsonValue};
+use crate::sink::Sink;
+
+/// Document flavor to write
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DocumentFormat {
+    /// Weaviate batch objects of class `class`
+    Weaviate { class: String },
+    /// Vespa feed operations for `document_type` in `namespace`
+    Vespa {
+        namespace: String,
+        document_type: String,
+    },
+}
+
+/// One row's document, serialized with its keys in this order
+#[derive(Serialize)]
+#[serde(untagged)]
+enum Document<'a> {
+    Weaviate {
+        class: &'a str,
+        id: String,
+        properties: JsonValue<'a>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        vector: Option<JsonValue<'a>>,
+    },
+    Vespa {
+        put: String,
+        fields: JsonValue<'a>,
+    },
+}
+
+/// Sink writing rows as search-engine import documents
+pub struct DocumentSink {
+    writer: Option<BufWriter<File>>,
+    format: DocumentFormat,
+    next_row: u64,
+}
+
+impl DocumentSink {
+    /// Create the output file
+    pub fn create(path: impl AsRef<Path>, format: DocumentFormat) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create file: {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        if let DocumentFormat::Weaviate { .. } = format {
+            writer.write_all(b"{\"objects\":[")?;
+        }
+        Ok(Self {
+            writer: Some(writer),
+            format,
+            next_row: 0,
+        })
+    }
+
+    /// Build the document of `row` in `batch`, whose first row is `first_row`
+    fn document<'a>(
+        format: &'a DocumentFormat,
+        first_row: u64,
+        batch: &'a RecordBatch,
+        row: usize,
+    ) -> Result<Document<'a>> {
+        let mut vector = None;
+        let mut fields = Vec::new();
+        for (field, column) in batch.schema_ref().fields().iter().zip(batch.columns()) {
+            let Some(json) = json_value(field, column.as_ref(), row)? else {
+                continue;
+            };
+            if vector.is_none() && is_vector(field) {
+                vector = Some((field.name().as_str(), json));
+                continue;
+            }
+            fields.push((field.name().as_str(), json));
+        }
+
+        let id = first_row + row as u64;
+        Ok(match format {
+            DocumentFormat::Weaviate { class } => Document::Weaviate {
+                class,
+                id: uuid_for_row(id),
+                properties: JsonValue::Object(fields),
+                vector: vector.map(|(_, values)| values),
+            },
+            DocumentFormat::Vespa {
+                namespace,
+                document_type,
+            } => {
+                if let Some((name, values)) = vector {
+                    fields.push((name, JsonValue::Object(vec![("values", values)])));
+                }
+                Document::Vespa {
+                    put: format!("id:{}:{}::{}", namespace, document_type, id),
+                    fields: JsonValue::Object(fields),
+                }
+            }
+        })
+    }
+}
+
+impl Sink for DocumentSink {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        let first_row = self.next_row;
+        let documents = (0..batch.num_rows())
+            .map(|row| Self::document(&self.format, first_row, batch, row))
+            .collect::<Result<Vec<_>>>()?;
+        let weaviate = matches!(self.format, DocumentFormat::Weaviate { .. });
+        let writer = self
+            .writer
+            .as_mut()
+            .ok_or_else(|| anyhow!("document sink already closed"))?;
+        for (i, document) in documents.iter().enumerate() {
+            if weaviate {
+                if first_row + i as u64 > 0 {
+                    writer.write_all(b",")?;
+                }
+                serde_json::to_writer(&mut *writer, document)?;
+            } else {
+                serde_json::to_writer(&mut *writer, document)?;
+                writer.write_all(b"\n")?;
+            }
+        }
+        self.next_row += batch.num_rows() as u64;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            if let DocumentFormat::Weaviate { .. } = self.format {
+                writer.write_all(b"]}")?;
+            }
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+/// Deterministic UUID-formatted id of a row, as Weaviate requires UUIDs
+fn uuid_for_row(row: u64) -> String {
+    format!("00000000-0000-4000-8000-{:012x}", row)
+}
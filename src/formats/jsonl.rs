@@ -0,0 +1,55 @@
+//! JSON Lines (NDJSON) output
+//!
+//! Writes every row as one JSON object on its own line, keyed by column
+//! name: vectors as number arrays, strings as strings and numbers as
+//! numbers. Null cells are written as `null`; binary columns that are not
+//! vectors (e.g. PQ codes) are omitted. The file can be fed to line-oriented
+//! ingestion pipelines or checked with `jq`. Keys follow the column order.
+
+use anyhow::{anyhow, Context, Result};
+use arrow::record_batch::RecordBatch;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::sink::value::{json_row, JsonValue};
+use crate::sink::Sink;
+
+/// Sink writing rows as JSON Lines
+pub struct JsonLinesSink {
+    writer: Option<BufWriter<File>>,
+}
+
+impl JsonLinesSink {
+    /// Create the output file
+    pub fn create(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create file: {}", path.display()))?;
+        Ok(Self {
+            writer: Some(BufWriter::new(file)),
+        })
+    }
+}
+
+impl Sink for JsonLinesSink {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        let writer = self
+            .writer
+            .as_mut()
+            .ok_or_else(|| anyhow!("JSON Lines sink already closed"))?;
+        for row in 0..batch.num_rows() {
+            let object = JsonValue::Object(json_row(batch, row)?);
+            serde_json::to_writer(&mut *writer, &object)?;
+            writer.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
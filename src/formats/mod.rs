@@ -0,0 +1,30 @@
+//! Output formats other than Parquet
+//!
+//! Each format is a [`Sink`](crate::Sink) writing one local file, so it can
+//! be driven by [`sink::write_rows`](crate::sink::write_rows) like any other
+//! destination. ann-benchmarks HDF5 files, which also hold queries and their
+//! ground truth, are written by a function of their own.
+
+#[cfg(feature = "hdf5")]
+mod ann_benchmarks;
+#[cfg(feature = "csv")]
+mod csv;
+#[cfg(feature = "json")]
+mod documents;
+#[cfg(feature = "json")]
+mod jsonl;
+#[cfg(feature = "numpy")]
+mod npy;
+mod vecs;
+
+#[cfg(feature = "hdf5")]
+pub use ann_benchmarks::{write_ann_benchmarks, AnnBenchmarksReport};
+#[cfg(feature = "csv")]
+pub use csv::{CsvSink, VectorText};
+#[cfg(feature = "json")]
+pub use documents::{DocumentFormat, DocumentSink};
+#[cfg(feature = "json")]
+pub use jsonl::JsonLinesSink;
+#[cfg(feature = "numpy")]
+pub use npy::{NpySink, NpzSink};
+pub use vecs::{VecsFormat, VecsSink};
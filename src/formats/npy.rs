@@ -0,0 +1,296 @@
+//! NumPy `.npy` and `.npz` output
+//!
+//! An `.npy` file is a short text header describing the array (element
+//! type, shape, C order) followed by the raw little-endian elements, so
+//! `np.load` reads it without Arrow or Parquet. [`NpySink`] writes one vector
+//! column as a 2-D `float32` array of `rows × dim`. [`NpzSink`] writes an
+//! `.npz` archive as `np.savez` does (uncompressed zip entries) holding that
+//! array as `vectors` and a string column as the 1-D unicode array
+//! `scalars`.
+//!
+//! The row count is only known at the end, so the header is written with
+//! room for any count and rewritten in place when the sink finishes. Each
+//! array of an archive is first written to a temporary `.npy` file next to
+//! the output, since zip entries are stored one after the other.
+
+use anyhow::{anyhow, bail, Context, Result};
+use arrow::array::{Array, AsArray};
+use arrow::datatypes::DataType;
+use arrow::record_batch::RecordBatch;
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::columns::vector_values;
+use crate::sink::Sink;
+
+/// Magic string and format version 1.0 of `.npy` files
+const MAGIC: &[u8] = b"\x93NUMPY\x01\x00";
+
+/// Header and data are aligned to this many bytes
+const ALIGNMENT: usize = 64;
+
+/// An `.npy` file being written row by row
+struct NpyWriter {
+    path: PathBuf,
+    out: BufWriter<File>,
+    /// NumPy type string of the elements, e.g. `<f4`
+    descr: String,
+    /// Trailing dimensions of the shape, after the row count
+    row_shape: Vec<usize>,
+    rows: u64,
+}
+
+impl NpyWriter {
+    fn create(path: &Path, descr: &str, row_shape: Vec<usize>) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create file: {}", path.display()))?;
+        let mut writer = Self {
+            path: path.to_path_buf(),
+            out: BufWriter::new(file),
+            descr: descr.to_string(),
+            row_shape,
+            rows: 0,
+        };
+        // Placeholder with the widest row count, replaced by `finish`
+        let header = writer.header(u64::MAX);
+        writer.out.write_all(&header)?;
+        Ok(writer)
+    }
+
+    /// Magic, header length and header dict, padded to the alignment; the
+    /// length does not depend on `rows`
+    fn header(&self, rows: u64) -> Vec<u8> {
+        let mut shape = format!("{}", rows);
+        for dim in &self.row_shape {
+            shape.push_str(&format!(", {}", dim));
+        }
+        if self.row_shape.is_empty() {
+            shape.push(',');
+        }
+        let dict = format!(
+            "{{'descr': '{}', 'fortran_order': False, 'shape': ({}), }}",
+            self.descr, shape
+        );
+        let widest = dict.len() - rows.to_string().len() + u64::MAX.to_string().len();
+        let unpadded = MAGIC.len() + 2 + widest + 1;
+        let len = unpadded.div_ceil(ALIGNMENT) * ALIGNMENT;
+        let mut header = Vec::with_capacity(len);
+        header.extend_from_slice(MAGIC);
+        header.extend_from_slice(&((len - MAGIC.len() - 2) as u16).to_le_bytes());
+        header.extend_from_slice(dict.as_bytes());
+        header.resize(len - 1, b' ');
+        header.push(b'\n');
+        header
+    }
+
+    fn finish(mut self) -> Result<PathBuf> {
+        let header = self.header(self.rows);
+        self.out.seek(SeekFrom::Start(0))?;
+        self.out.write_all(&header)?;
+        self.out.flush()?;
+        Ok(self.path)
+    }
+}
+
+/// Append the vectors of `column` in `batch` as rows of `f32` elements;
+/// arrays have no nulls, so a null vector is an error
+fn write_vectors(writer: &mut NpyWriter, batch: &RecordBatch, column: &str) -> Result<()> {
+    let schema = batch.schema();
+    let Some((position, field)) = schema.column_with_name(column) else {
+        bail!("column '{}' is missing from the batch", column);
+    };
+    let dim = writer.row_shape[0];
+    let array = batch.column(position);
+    for row in 0..batch.num_rows() {
+        let Some(values) = vector_values(field, array.as_ref(), row) else {
+            match array.is_null(row) {
+                true => bail!("NumPy arrays cannot hold null vectors"),
+                false => bail!("column '{}' is not a vector column", column),
+            }
+        };
+        if values.len() != dim {
+            bail!(
+                "vector of dimension {} in a {}-column array",
+                values.len(),
+                dim
+            );
+        }
+        for value in values {
+            writer.out.write_all(&(value as f32).to_le_bytes())?;
+        }
+    }
+    writer.rows += batch.num_rows() as u64;
+    Ok(())
+}
+
+/// Sink writing one vector column as a 2-D `float32` `.npy` array
+pub struct NpySink {
+    writer: Option<NpyWriter>,
+    column: String,
+}
+
+impl NpySink {
+    /// Create the output file for the `dim`-dimensional vectors of `column`
+    pub fn create(path: impl AsRef<Path>, column: &str, dim: usize) -> Result<Self> {
+        Ok(Self {
+            writer: Some(NpyWriter::create(path.as_ref(), "<f4", vec![dim])?),
+            column: column.to_string(),
+        })
+    }
+}
+
+impl Sink for NpySink {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        let writer = self
+            .writer
+            .as_mut()
+            .ok_or_else(|| anyhow!("npy sink already closed"))?;
+        write_vectors(writer, batch, &self.column)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if let Some(writer) = self.writer.take() {
+            writer.finish()?;
+        }
+        Ok(())
+    }
+}
+
+/// Sink writing an `.npz` archive of a vector column (`vectors`) and a
+/// string column (`scalars`)
+pub struct NpzSink {
+    path: PathBuf,
+    vectors: Option<(NpyWriter, String)>,
+    scalars: Option<(NpyWriter, String)>,
+    /// Characters per string; longer values are an error
+    width: usize,
+}
+
+impl NpzSink {
+    /// Create the archive for the `dim`-dimensional vectors of `vectors` and
+    /// the strings of `scalars`, of at most `width` characters
+    pub fn create(
+        path: impl AsRef<Path>,
+        vectors: &str,
+        dim: usize,
+        scalars: &str,
+        width: usize,
+    ) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let vector_path = temp_path(&path, "vectors");
+        let scalar_path = temp_path(&path, "scalars");
+        // Unicode arrays need a width of at least one character
+        let width = width.max(1);
+        Ok(Self {
+            vectors: Some((
+                NpyWriter::create(&vector_path, "<f4", vec![dim])?,
+                vectors.to_string(),
+            )),
+            scalars: Some((
+                NpyWriter::create(&scalar_path, &format!("<U{}", width), Vec::new())?,
+                scalars.to_string(),
+            )),
+            width,
+            path,
+        })
+    }
+
+    fn write_scalars(&mut self, batch: &RecordBatch) -> Result<()> {
+        let (writer, column) = self
+            .scalars
+            .as_mut()
+            .ok_or_else(|| anyhow!("npz sink already closed"))?;
+        let Some(array) = batch.column_by_name(column) else {
+            bail!("column '{}' is missing from the batch", column);
+        };
+        if array.data_type() != &DataType::Utf8 {
+            bail!("column '{}' is not a string column", column);
+        }
+        // UTF-32 code points, zero padded; nulls are empty strings
+        for value in array.as_string::<i32>().iter() {
+            let value = value.unwrap_or_default();
+            let chars = value.chars().count();
+            if chars > self.width {
+                bail!(
+                    "string of {} characters in a {}-character array",
+                    chars,
+                    self.width
+                );
+            }
+            for c in value.chars() {
+                writer.out.write_all(&(c as u32).to_le_bytes())?;
+            }
+            for _ in chars..self.width {
+                writer.out.write_all(&0u32.to_le_bytes())?;
+            }
+        }
+        writer.rows += batch.num_rows() as u64;
+        Ok(())
+    }
+
+    /// Remove the temporary array files
+    fn remove_temporaries(&mut self) {
+        for (writer, _) in [self.vectors.take(), self.scalars.take()]
+            .into_iter()
+            .flatten()
+        {
+            let _ = std::fs::remove_file(&writer.path);
+        }
+    }
+}
+
+/// Temporary file for the array `name` of the archive at `path`
+fn temp_path(path: &Path, name: &str) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".{}.npy.tmp", name));
+    path.with_file_name(file_name)
+}
+
+impl Sink for NpzSink {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        let (writer, column) = self
+            .vectors
+            .as_mut()
+            .ok_or_else(|| anyhow!("npz sink already closed"))?;
+        write_vectors(writer, batch, column)?;
+        self.write_scalars(batch)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        let (Some((vectors, _)), Some((scalars, _))) = (self.vectors.take(), self.scalars.take())
+        else {
+            return Ok(());
+        };
+        let parts = [
+            ("vectors.npy", vectors.finish()?),
+            ("scalars.npy", scalars.finish()?),
+        ];
+        let file = File::create(&self.path)
+            .with_context(|| format!("Failed to create file: {}", self.path.display()))?;
+        let mut zip = ZipWriter::new(BufWriter::new(file));
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Stored)
+            .large_file(true);
+        for (name, path) in &parts {
+            zip.start_file(*name, options)?;
+            io::copy(&mut File::open(path)?, &mut zip)?;
+            std::fs::remove_file(path)?;
+        }
+        zip.finish()?.flush()?;
+        Ok(())
+    }
+
+    fn abort(&mut self) -> Result<()> {
+        self.remove_temporaries();
+        Ok(())
+    }
+}
+
+impl Drop for NpzSink {
+    fn drop(&mut self) {
+        self.remove_temporaries();
+    }
+}
@@ -0,0 +1,133 @@
+//! fvecs and bvecs output
+//!
+//! The base files of the SIFT1M/SIFT1B style ANN benchmarks hold one vector
+//! per record: a little-endian `i32` dimension followed by that many
+//! elements, `f32` in `.fvecs` files and `u8` in `.bvecs` files. Float vector
+//! columns are written as fvecs. PQ code columns with at most 8 bits per
+//! sub-quantizer are written as bvecs, one byte per sub-quantizer code. The
+//! records carry no row ids or nulls, so only one column is written and a
+//! null vector is an error. Matching `.ivecs` ground truth comes from the
+//! `ground-truth` subcommand run over a Parquet dataset of the same config,
+//! whose rows are the same.
+
+use anyhow::{anyhow, bail, Context, Result};
+use arrow::array::{Array, AsArray};
+use arrow::datatypes::Field;
+use arrow::record_batch::RecordBatch;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::columns::{vector_encoding, vector_values, PQ_NBITS_KEY, PQ_SUBQUANTIZERS_KEY};
+use crate::pq::unpack_code;
+use crate::sink::Sink;
+
+/// Record type of a vecs file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VecsFormat {
+    /// `f32` elements
+    Fvecs,
+    /// `u8` elements
+    Bvecs,
+}
+
+impl VecsFormat {
+    /// Format of `field`, `None` if it has no vecs form
+    pub fn for_field(field: &Field) -> Option<Self> {
+        if vector_encoding(field).is_some() {
+            return Some(VecsFormat::Fvecs);
+        }
+        match pq_shape(field) {
+            Some((_, nbits)) if nbits <= 8 => Some(VecsFormat::Bvecs),
+            _ => None,
+        }
+    }
+
+    /// File extension, without the dot
+    pub fn extension(self) -> &'static str {
+        match self {
+            VecsFormat::Fvecs => "fvecs",
+            VecsFormat::Bvecs => "bvecs",
+        }
+    }
+}
+
+/// Sub-quantizers and bits per code of a PQ code column
+fn pq_shape(field: &Field) -> Option<(usize, u32)> {
+    let metadata = field.metadata();
+    let subquantizers = metadata.get(PQ_SUBQUANTIZERS_KEY)?.parse().ok()?;
+    let nbits = metadata.get(PQ_NBITS_KEY)?.parse().ok()?;
+    Some((subquantizers, nbits))
+}
+
+/// Sink writing one column of every row as fvecs or bvecs records
+pub struct VecsSink {
+    writer: Option<BufWriter<File>>,
+    column: String,
+    format: VecsFormat,
+}
+
+impl VecsSink {
+    /// Create the output file for the values of `column`
+    pub fn create(path: impl AsRef<Path>, column: &str, format: VecsFormat) -> Result<Self> {
+        let path = path.as_ref();
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create file: {}", path.display()))?;
+        Ok(Self {
+            writer: Some(BufWriter::new(file)),
+            column: column.to_string(),
+            format,
+        })
+    }
+}
+
+impl Sink for VecsSink {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        let writer = self
+            .writer
+            .as_mut()
+            .ok_or_else(|| anyhow!("{} sink already closed", self.format.extension()))?;
+        let schema = batch.schema();
+        let Some((position, field)) = schema.column_with_name(&self.column) else {
+            bail!("column '{}' is missing from the batch", self.column);
+        };
+        if VecsFormat::for_field(field) != Some(self.format) {
+            bail!(
+                "column '{}' cannot be written as {}",
+                self.column,
+                self.format.extension()
+            );
+        }
+        let array = batch.column(position);
+        for row in 0..batch.num_rows() {
+            if array.is_null(row) {
+                bail!("{} files cannot hold null vectors", self.format.extension());
+            }
+            match self.format {
+                VecsFormat::Fvecs => {
+                    let values = vector_values(field, array.as_ref(), row).unwrap_or_default();
+                    writer.write_all(&(values.len() as i32).to_le_bytes())?;
+                    for value in values {
+                        writer.write_all(&(value as f32).to_le_bytes())?;
+                    }
+                }
+                VecsFormat::Bvecs => {
+                    let (subquantizers, nbits) = pq_shape(field).unwrap_or_default();
+                    let packed = array.as_fixed_size_binary().value(row);
+                    writer.write_all(&(subquantizers as i32).to_le_bytes())?;
+                    for subquantizer in 0..subquantizers {
+                        writer.write_all(&[unpack_code(packed, subquantizer, nbits) as u8])?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+//! Golden manifests pinning deterministic output
+//!
+//! A manifest stores a configuration together with SHA-256 hashes of the
+//! first rows of some files generated from it. Verifying regenerates those
+//! rows and compares the hashes, so an upgrade that changes the output for an
+//! old seed is detected instead of going unnoticed.
+//!
+//! Rows are regenerated with [`DataGenerator::for_file`], i.e. each sampled
+//! file starts from fresh state; hashes cover the Arrow IPC encoding of every
+//! batch, including the schema and its metadata.
+
+use anyhow::{Context, Result};
+use arrow::ipc::writer::StreamWriter;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::path::Path;
+
+use crate::{Config, DataGenerator};
+
+/// Hash of the first rows of one file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GoldenSample {
+    /// Index of the file within the dataset
+    pub file_index: u64,
+    /// Rows hashed from the start of the file
+    pub rows: usize,
+    /// Rows per generated batch; batch boundaries select the RNG streams
+    pub batch_size: usize,
+    /// Hex-encoded SHA-256 of the rows
+    pub sha256: String,
+}
+
+/// Configuration and recorded hashes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GoldenManifest {
+    /// Crate version that recorded the manifest
+    pub crate_version: String,
+    #[serde(with = "crate::config_format")]
+    pub config: Config,
+    pub samples: Vec<GoldenSample>,
+}
+
+/// A sample whose regenerated rows no longer match the manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoldenMismatch {
+    pub file_index: u64,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl fmt::Display for GoldenMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "file {}: expected sha256 {}, got {}",
+            self.file_index, self.expected, self.actual
+        )
+    }
+}
+
+impl GoldenManifest {
+    /// Record hashes of the first `rows` rows of files `0..files`
+    pub fn record(config: &Config, files: u64, rows: usize, batch_size: usize) -> Result<Self> {
+        let samples = (0..files)
+            .map(|file_index| {
+                Ok(GoldenSample {
+                    file_index,
+                    rows,
+                    batch_size,
+                    sha256: hash_rows(config, file_index, rows, batch_size)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            config: config.clone(),
+            samples,
+        })
+    }
+
+    /// Regenerate every sample and return those whose hash changed
+    pub fn verify(&self) -> Result<Vec<GoldenMismatch>> {
+        let mut mismatches = Vec::new();
+        for sample in &self.samples {
+            let actual = hash_rows(
+                &self.config,
+                sample.file_index,
+                sample.rows,
+                sample.batch_size,
+            )?;
+            if actual != sample.sha256 {
+                mismatches.push(GoldenMismatch {
+                    file_index: sample.file_index,
+                    expected: sample.sha256.clone(),
+                    actual,
+                });
+            }
+        }
+        Ok(mismatches)
+    }
+
+    /// Read a manifest from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("Failed to open golden manifest: {:?}", path))?;
+        serde_json::from_reader(std::io::BufReader::new(file))
+            .with_context(|| format!("Invalid golden manifest: {:?}", path))
+    }
+
+    /// Write the manifest as pretty-printed JSON
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let json = serde_json::to_string_pretty(self)?;
+        std::fs::write(path, json)
+            .with_context(|| format!("Failed to write golden manifest: {:?}", path))
+    }
+}
+
+/// Hex-encoded SHA-256 of the first `rows` rows of a file
+fn hash_rows(config: &Config, file_index: u64, rows: usize, batch_size: usize) -> Result<String> {
+    let mut generator = DataGenerator::for_file(config.clone(), file_index)?;
+    let mut writer = StreamWriter::try_new(Vec::new(), generator.schema())?;
+    let batch_size = batch_size.max(1);
+    let mut remaining = rows;
+    while remaining > 0 {
+        let batch = generator.generate_batch(remaining.min(batch_size))?;
+        writer.write(&batch)?;
+        remaining -= batch.num_rows();
+    }
+    let bytes = writer.into_inner()?;
+    Ok(Sha256::digest(&bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
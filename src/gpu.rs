@@ -0,0 +1,240 @@
+//! Query-to-base distances computed on a GPU
+//!
+//! Brute-force ground truth spends nearly all of its time computing the
+//! distance of every query to every base vector. With the `gpu` feature
+//! that matrix is computed by a compute shader through `wgpu` (Vulkan,
+//! Metal, DX12), one block of base rows at a time, while the top-k
+//! selection stays on the CPU. The queries are uploaded once; each block
+//! uploads its base rows and reads back a `rows × queries` distance matrix.
+//! Sums run in a different order than on the CPU, so distances may differ
+//! in the last bits.
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::sync::mpsc;
+use wgpu::util::DeviceExt;
+
+use crate::ground_truth::Metric;
+
+/// Invocations per workgroup along each axis
+const WORKGROUP: u32 = 16;
+
+const SHADER: &str = r#"
+struct Params {
+    dim: u32,
+    queries: u32,
+    rows: u32,
+    metric: u32,
+}
+
+@group(0) @binding(0) var<uniform> params: Params;
+@group(0) @binding(1) var<storage, read> queries: array<f32>;
+@group(0) @binding(2) var<storage, read> query_norms: array<f32>;
+@group(0) @binding(3) var<storage, read> base: array<f32>;
+@group(0) @binding(4) var<storage, read_write> distances: array<f32>;
+
+@compute @workgroup_size(16, 16)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let query = id.x;
+    let row = id.y;
+    if (query >= params.queries || row >= params.rows) {
+        return;
+    }
+    var squared = 0.0;
+    var dot = 0.0;
+    var norm = 0.0;
+    for (var i = 0u; i < params.dim; i++) {
+        let a = queries[query * params.dim + i];
+        let b = base[row * params.dim + i];
+        squared += (a - b) * (a - b);
+        dot += a * b;
+        norm += b * b;
+    }
+    var distance = sqrt(squared);
+    if (params.metric == 1u) {
+        let norms = query_norms[query] * sqrt(norm);
+        distance = select(1.0 - dot / norms, 1.0, norms == 0.0);
+    }
+    distances[row * params.queries + query] = distance;
+}
+"#;
+
+/// Distances of a fixed query set to blocks of base vectors
+pub(crate) struct GpuDistances {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    pipeline: wgpu::ComputePipeline,
+    queries: wgpu::Buffer,
+    query_norms: wgpu::Buffer,
+    dim: usize,
+    query_count: usize,
+    metric: Metric,
+    /// Base rows per dispatch, bounded by buffer and dispatch limits
+    max_rows: usize,
+}
+
+impl GpuDistances {
+    /// Upload `queries` to the first high-performance adapter
+    pub(crate) fn new(queries: &[Vec<f32>], metric: Metric) -> Result<Self> {
+        let dim = queries.first().map_or(0, Vec::len);
+        if queries.is_empty() || dim == 0 {
+            bail!("GPU ground truth needs at least one non-empty query");
+        }
+        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor::default());
+        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            power_preference: wgpu::PowerPreference::HighPerformance,
+            ..Default::default()
+        }))
+        .context("No GPU adapter found")?;
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor {
+            label: Some("ground truth"),
+            required_limits: adapter.limits(),
+            ..Default::default()
+        }))
+        .context("Failed to open the GPU")?;
+
+        let limits = device.limits();
+        let max_binding = limits.max_storage_buffer_binding_size as usize / 4;
+        let max_groups = limits.max_compute_workgroups_per_dimension as usize;
+        if queries.len() * dim > max_binding || queries.len() > max_groups * WORKGROUP as usize {
+            bail!(
+                "{} queries of dimension {} exceed the GPU buffer limits",
+                queries.len(),
+                dim
+            );
+        }
+        let max_rows = (max_binding / dim)
+            .min(max_binding / queries.len())
+            .min(max_groups * WORKGROUP as usize);
+
+        let values: Vec<f32> = queries.concat();
+        let norms: Vec<f32> = queries
+            .iter()
+            .map(|query| query.iter().map(|v| v * v).sum::<f32>().sqrt())
+            .collect();
+        let storage = |label, contents: &[f32]| {
+            device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(label),
+                contents: bytemuck::cast_slice(contents),
+                usage: wgpu::BufferUsages::STORAGE,
+            })
+        };
+        let queries_buffer = storage("queries", &values);
+        let query_norms = storage("query norms", &norms);
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("distances"),
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("distances"),
+            layout: None,
+            module: &module,
+            entry_point: Some("main"),
+            compilation_options: Default::default(),
+            cache: None,
+        });
+        Ok(Self {
+            device,
+            queue,
+            pipeline,
+            queries: queries_buffer,
+            query_norms,
+            dim,
+            query_count: queries.len(),
+            metric,
+            max_rows,
+        })
+    }
+
+    /// Distances of every query to every vector of `base`, as
+    /// `[row][query]`
+    pub(crate) fn distances(&self, base: &[&[f32]]) -> Result<Vec<f32>> {
+        let mut distances = Vec::with_capacity(base.len() * self.query_count);
+        for block in base.chunks(self.max_rows.max(1)) {
+            distances.extend(self.block_distances(block)?);
+        }
+        Ok(distances)
+    }
+
+    fn block_distances(&self, block: &[&[f32]]) -> Result<Vec<f32>> {
+        let rows = block.len();
+        let params = [
+            self.dim as u32,
+            self.query_count as u32,
+            rows as u32,
+            match self.metric {
+                Metric::L2 => 0,
+                Metric::Cosine => 1,
+            },
+        ];
+        let params = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("params"),
+                contents: bytemuck::cast_slice(&params),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+        let base = self
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("base"),
+                contents: bytemuck::cast_slice(&block.concat()),
+                usage: wgpu::BufferUsages::STORAGE,
+            });
+        let size = (rows * self.query_count * 4) as u64;
+        let output = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("distances"),
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let staging = self.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("staging"),
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bindings = [&params, &self.queries, &self.query_norms, &base, &output];
+        let entries: Vec<wgpu::BindGroupEntry> = bindings
+            .iter()
+            .enumerate()
+            .map(|(binding, buffer)| wgpu::BindGroupEntry {
+                binding: binding as u32,
+                resource: buffer.as_entire_binding(),
+            })
+            .collect();
+        let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("distances"),
+            layout: &self.pipeline.get_bind_group_layout(0),
+            entries: &entries,
+        });
+
+        let mut encoder = self.device.create_command_encoder(&Default::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&Default::default());
+            pass.set_pipeline(&self.pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(
+                (self.query_count as u32).div_ceil(WORKGROUP),
+                (rows as u32).div_ceil(WORKGROUP),
+                1,
+            );
+        }
+        encoder.copy_buffer_to_buffer(&output, 0, &staging, 0, size);
+        self.queue.submit([encoder.finish()]);
+
+        let slice = staging.slice(..);
+        let (sender, receiver) = mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        self.device
+            .poll(wgpu::PollType::Wait)
+            .map_err(|e| anyhow!("GPU failed: {}", e))?;
+        receiver
+            .recv()?
+            .map_err(|e| anyhow!("Failed to read GPU distances: {}", e))?;
+        let distances = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+        staging.unmap();
+        Ok(distances)
+    }
+}
@@ -0,0 +1,150 @@
+//! Graph edge lists co-generated with the node table
+//!
+//! Node ids are global row positions in the generated dataset (file order,
+//! then row order). Each node's out-degree follows a [`DegreeDistribution`]
+//! and destinations are drawn with a [`Skew`] over node ids, so low ids act as
+//! hubs under Zipf skew.
+
+use anyhow::{bail, Result};
+use arrow::array::{ArrayRef, Float32Array, Int64Array};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+use rand::distributions::Uniform;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Distribution, Poisson};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::core_gen::stream_seed;
+use crate::sink::Sink;
+use crate::skew::{KeySampler, Skew};
+
+/// Stream identifier for edge generation
+const EDGE_STREAM: u64 = u64::MAX - 2;
+
+/// Distribution of per-node out-degrees
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DegreeDistribution {
+    /// Every node has exactly this many out-edges
+    Constant(usize),
+    /// Out-degrees are Poisson distributed with this mean
+    Poisson(f64),
+}
+
+/// Edge-list generation parameters
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EdgeConfig {
+    /// Out-degree of each node
+    pub degree: DegreeDistribution,
+    /// Popularity of destination nodes
+    pub destination_skew: Skew,
+}
+
+impl EdgeConfig {
+    /// Check the parameters
+    pub fn validate(&self) -> Result<()> {
+        if let DegreeDistribution::Poisson(mean) = self.degree {
+            if !(mean.is_finite() && mean > 0.0) {
+                bail!("mean edge degree must be a positive number");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Schema of the edge table: `src_id`, `dst_id`, `weight`
+pub fn edge_schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("src_id", DataType::Int64, false),
+        Field::new("dst_id", DataType::Int64, false),
+        Field::new("weight", DataType::Float32, false),
+    ]))
+}
+
+/// Write edges between `num_nodes` nodes to `sink`, which must accept
+/// [`edge_schema`] batches
+///
+/// Edges are seeded from `seed` only, so the same dataset configuration always
+/// yields the same graph. Self-loops are never generated. The sink is finished
+/// on success. Returns the number of edges written.
+pub fn write_edges(
+    sink: &mut dyn Sink,
+    num_nodes: usize,
+    config: &EdgeConfig,
+    seed: u64,
+    batch_size: usize,
+) -> Result<usize> {
+    config.validate()?;
+    let schema = edge_schema();
+
+    let batch_size = batch_size.max(1);
+    let mut rng = StdRng::seed_from_u64(stream_seed(seed, &[EDGE_STREAM]));
+    let destinations = KeySampler::new(num_nodes.max(1), config.destination_skew)?;
+    let degrees = match config.degree {
+        DegreeDistribution::Constant(k) => DegreeSampler::Constant(k),
+        DegreeDistribution::Poisson(mean) => DegreeSampler::Poisson(Poisson::new(mean)?),
+    };
+    let weights = Uniform::new_inclusive(0.0f32, 1.0);
+
+    let mut src_ids = Vec::with_capacity(batch_size);
+    let mut dst_ids = Vec::with_capacity(batch_size);
+    let mut edge_weights = Vec::with_capacity(batch_size);
+    let mut total = 0;
+
+    // Self-loops are impossible in a single-node graph
+    let eligible_nodes = if num_nodes > 1 { num_nodes } else { 0 };
+    for src in 0..eligible_nodes {
+        let degree = match &degrees {
+            DegreeSampler::Constant(k) => *k,
+            DegreeSampler::Poisson(poisson) => poisson.sample(&mut rng) as usize,
+        };
+        for _ in 0..degree {
+            let dst = loop {
+                let dst = destinations.sample(&mut rng);
+                if dst != src {
+                    break dst;
+                }
+            };
+            src_ids.push(src as i64);
+            dst_ids.push(dst as i64);
+            edge_weights.push(weights.sample(&mut rng));
+
+            if src_ids.len() == batch_size {
+                total += flush(sink, &schema, &mut src_ids, &mut dst_ids, &mut edge_weights)?;
+            }
+        }
+    }
+    total += flush(sink, &schema, &mut src_ids, &mut dst_ids, &mut edge_weights)?;
+
+    sink.finish()?;
+    Ok(total)
+}
+
+fn flush(
+    sink: &mut dyn Sink,
+    schema: &SchemaRef,
+    src_ids: &mut Vec<i64>,
+    dst_ids: &mut Vec<i64>,
+    weights: &mut Vec<f32>,
+) -> Result<usize> {
+    let rows = src_ids.len();
+    if rows == 0 {
+        return Ok(0);
+    }
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(Int64Array::from(std::mem::take(src_ids))) as ArrayRef,
+            Arc::new(Int64Array::from(std::mem::take(dst_ids))) as ArrayRef,
+            Arc::new(Float32Array::from(std::mem::take(weights))) as ArrayRef,
+        ],
+    )?;
+    sink.write(&batch)?;
+    Ok(rows)
+}
+
+enum DegreeSampler {
+    Constant(usize),
+    Poisson(Poisson<f64>),
+}
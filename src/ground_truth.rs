@@ -0,0 +1,885 @@
+//! Exact nearest neighbors of a query set over a generated dataset
+//!
+//! Recall of an ANN index is measured against the exact top-k neighbors of
+//! each query. They are computed here by brute force: the vector column of
+//! every base file is streamed in file order, and each query keeps the `k`
+//! closest rows seen so far. Rows are identified by their position in the
+//! dataset, counting from 0 across files in order; null vectors keep their
+//! position but are never neighbors. Ties are broken by the smaller id, so
+//! the result does not depend on the number of threads.
+//!
+//! A query may carry a filter [`Predicate`], as written by a query
+//! [workload](crate::workload): its neighbors are then the `k` closest rows
+//! passing the filter, which is what a filtered ANN search should return.
+//! Queries whose filter matches fewer than `k` rows have fewer neighbors.
+//!
+//! [`compute_after_deletes`] answers the same queries after each epoch of a
+//! [deletion workload](crate::deletes), leaving out every row deleted so far,
+//! in a single pass over the base files.
+//!
+//! On the CPU, blocks of queries are searched in parallel, each walking the
+//! base rows in cache-sized blocks with vectorized distance kernels and a
+//! bounded heap per query.
+//!
+//! Results are written as Parquet (`query_id`, `neighbors`, `distances`) or
+//! as `.ivecs`, the format of the classic SIFT/GIST ground-truth files: per
+//! query, a little-endian `i32` count followed by that many `i32` ids.
+//!
+//! [`compute_cached`] keeps the top-k of each query within each base file on
+//! disk, so adding queries to a set only searches for the new ones.
+
+use anyhow::{bail, Context, Result};
+use arrow::array::{ArrayRef, Float32Builder, Int64Array, Int64Builder, ListBuilder};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::array::{Array, StringArray};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ProjectionMask;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::UNIX_EPOCH;
+
+use crate::columns::vector_values;
+use crate::derived::{fnv1a, fnv1a_extend};
+use crate::sink::Sink;
+use crate::workload::Predicate;
+use crate::ParquetWriter;
+
+/// Distance between vectors
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Metric {
+    /// Euclidean distance
+    #[default]
+    L2,
+    /// One minus the cosine similarity; 1 if either vector is zero
+    Cosine,
+}
+
+impl fmt::Display for Metric {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Metric::L2 => "l2",
+            Metric::Cosine => "cosine",
+        })
+    }
+}
+
+impl FromStr for Metric {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "l2" | "euclidean" => Ok(Metric::L2),
+            "cosine" => Ok(Metric::Cosine),
+            other => bail!("unknown metric '{}', expected l2 or cosine", other),
+        }
+    }
+}
+
+/// Hardware computing the distances
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Device {
+    /// All CPU cores
+    #[default]
+    Cpu,
+    /// The first high-performance GPU, through `wgpu` (`gpu` feature)
+    Gpu,
+}
+
+impl fmt::Display for Device {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Device::Cpu => "cpu",
+            Device::Gpu => "gpu",
+        })
+    }
+}
+
+impl FromStr for Device {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "cpu" => Ok(Device::Cpu),
+            "gpu" => Ok(Device::Gpu),
+            other => bail!("unknown device '{}', expected cpu or gpu", other),
+        }
+    }
+}
+
+/// Accumulators of the distance kernels, enough for 256-bit registers
+const LANES: usize = 8;
+
+/// Queries searched by one task
+const QUERY_BLOCK: usize = 16;
+
+/// Base rows compared with a block of queries before moving on, sized to
+/// stay in cache at typical dimensions
+const BASE_BLOCK: usize = 256;
+
+/// Parameters of a search over base files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchOptions {
+    /// Neighbors per query
+    pub k: usize,
+    pub metric: Metric,
+    /// Base rows read per batch
+    pub batch_size: usize,
+    pub device: Device,
+}
+
+/// A base row and its distance to a query
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Neighbor {
+    /// Position of the row in the dataset
+    pub id: u64,
+    pub distance: f32,
+}
+
+impl Eq for Neighbor {}
+
+impl Ord for Neighbor {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then(self.id.cmp(&other.id))
+    }
+}
+
+impl PartialOrd for Neighbor {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A query vector and its closest rows so far
+struct Query {
+    vector: Vec<f32>,
+    norm: f32,
+    /// Rows must pass this filter to be neighbors
+    filter: Option<Predicate>,
+    /// Max-heap of the `k` closest rows, farthest on top
+    closest: BinaryHeap<Neighbor>,
+}
+
+impl Query {
+    /// Keep `neighbor` if it is among the `k` closest rows so far
+    fn offer(&mut self, neighbor: Neighbor, k: usize) {
+        if self.closest.len() < k {
+            self.closest.push(neighbor);
+        } else if neighbor < *self.closest.peek().expect("heap holds k rows") {
+            self.closest.pop();
+            self.closest.push(neighbor);
+        }
+    }
+}
+
+/// Running top-k search of a query set over base vectors added in order
+pub struct GroundTruth {
+    k: usize,
+    metric: Metric,
+    queries: Vec<Query>,
+    next_id: u64,
+    /// Epoch deleting each deleted row, and the epoch searched after
+    deleted: Option<(Arc<HashMap<u64, usize>>, usize)>,
+    #[cfg(feature = "gpu")]
+    gpu: Option<crate::gpu::GpuDistances>,
+}
+
+impl GroundTruth {
+    /// Search for the `k` closest base vectors of every query
+    pub fn new(queries: Vec<Vec<f32>>, k: usize, metric: Metric) -> Result<Self> {
+        if k == 0 {
+            bail!("ground truth needs k of at least 1");
+        }
+        if let Some(dim) = queries.first().map(Vec::len) {
+            if queries.iter().any(|query| query.len() != dim) {
+                bail!("query vectors must all have the same dimension");
+            }
+        }
+        let queries = queries
+            .into_iter()
+            .map(|vector| Query {
+                norm: norm(&vector),
+                vector,
+                filter: None,
+                closest: BinaryHeap::with_capacity(k + 1),
+            })
+            .collect();
+        Ok(Self {
+            k,
+            metric,
+            queries,
+            next_id: 0,
+            deleted: None,
+            #[cfg(feature = "gpu")]
+            gpu: None,
+        })
+    }
+
+    /// [`GroundTruth::new`] computing distances on `device`
+    pub fn on(queries: Vec<Vec<f32>>, k: usize, metric: Metric, device: Device) -> Result<Self> {
+        #[cfg(feature = "gpu")]
+        if device == Device::Gpu && !queries.is_empty() {
+            let gpu = crate::gpu::GpuDistances::new(&queries, metric)?;
+            let mut search = Self::new(queries, k, metric)?;
+            search.gpu = Some(gpu);
+            return Ok(search);
+        }
+        #[cfg(not(feature = "gpu"))]
+        if device == Device::Gpu {
+            bail!("GPU ground truth needs the gpu feature");
+        }
+        Self::new(queries, k, metric)
+    }
+
+    /// Only count the rows passing `filters[q]` as neighbors of query `q`
+    ///
+    /// Filtered queries need their filter columns: base rows are then added
+    /// with [`GroundTruth::add_batch`].
+    pub fn with_filters(mut self, filters: Vec<Option<Predicate>>) -> Result<Self> {
+        if filters.len() != self.queries.len() {
+            bail!(
+                "{} filters given for {} queries",
+                filters.len(),
+                self.queries.len()
+            );
+        }
+        for (query, filter) in self.queries.iter_mut().zip(filters) {
+            query.filter = filter;
+        }
+        Ok(self)
+    }
+
+    /// Leave out the rows that `deleted`, mapping row ids to the epoch
+    /// deleting them, deletes in epochs up to `epoch`
+    pub fn without_deleted(mut self, deleted: Arc<HashMap<u64, usize>>, epoch: usize) -> Self {
+        self.deleted = Some((deleted, epoch));
+        self
+    }
+
+    /// Columns read by the filters of the queries
+    pub fn filter_columns(&self) -> Vec<String> {
+        let mut columns: Vec<String> = self
+            .queries
+            .iter()
+            .filter_map(|query| Some(query.filter.as_ref()?.column.clone()))
+            .collect();
+        columns.sort_unstable();
+        columns.dedup();
+        columns
+    }
+
+    /// Add the next base vectors, in dataset order; `None` for null rows
+    pub fn add(&mut self, vectors: &[Option<Vec<f32>>]) -> Result<()> {
+        if self.queries.iter().any(|query| query.filter.is_some()) {
+            bail!("filtered queries need the filter columns of the base rows");
+        }
+        self.add_masked(vectors, &[])
+    }
+
+    /// [`GroundTruth::add`] with `batch` holding the filter columns of the
+    /// same rows
+    pub fn add_batch(&mut self, vectors: &[Option<Vec<f32>>], batch: &RecordBatch) -> Result<()> {
+        if batch.num_rows() != vectors.len() {
+            bail!(
+                "{} filter rows given for {} base vectors",
+                batch.num_rows(),
+                vectors.len()
+            );
+        }
+        let masks = self
+            .queries
+            .par_iter()
+            .map(|query| query.filter.as_ref().map(|filter| filter.evaluate(batch)).transpose())
+            .collect::<Result<Vec<_>>>()?;
+        self.add_masked(vectors, &masks)
+    }
+
+    /// Add base vectors, rows `r` with `masks[q][r]` false excluded from
+    /// query `q`; no masks for unfiltered queries
+    fn add_masked(&mut self, vectors: &[Option<Vec<f32>>], masks: &[Option<Vec<bool>>]) -> Result<()> {
+        if let (Some(query), Some(vector)) = (self.queries.first(), vectors.iter().flatten().next())
+        {
+            if vector.len() != query.vector.len() {
+                bail!(
+                    "base vectors have dimension {}, queries {}",
+                    vector.len(),
+                    query.vector.len()
+                );
+            }
+        }
+        let base: Vec<(u64, &[f32], f32)> = vectors
+            .iter()
+            .enumerate()
+            .filter_map(|(i, vector)| {
+                let vector = vector.as_deref()?;
+                let id = self.next_id + i as u64;
+                if let Some((deleted, epoch)) = &self.deleted {
+                    if deleted.get(&id).is_some_and(|deleted_in| deleted_in <= epoch) {
+                        return None;
+                    }
+                }
+                Some((id, vector, norm(vector)))
+            })
+            .collect();
+        let (k, metric) = (self.k, self.metric);
+        #[cfg(feature = "gpu")]
+        if let Some(gpu) = &self.gpu {
+            let rows: Vec<&[f32]> = base.iter().map(|&(_, vector, _)| vector).collect();
+            let distances = gpu.distances(&rows)?;
+            let count = self.queries.len();
+            let first = self.next_id;
+            self.queries.par_iter_mut().enumerate().for_each(|(q, query)| {
+                let mask = masks.get(q).and_then(Option::as_ref);
+                for (row, &(id, _, _)) in base.iter().enumerate() {
+                    if mask.is_some_and(|mask| !mask[(id - first) as usize]) {
+                        continue;
+                    }
+                    let distance = distances[row * count + q];
+                    query.offer(Neighbor { id, distance }, k);
+                }
+            });
+            self.next_id += vectors.len() as u64;
+            return Ok(());
+        }
+        // Each task takes a block of queries past the base rows one cache-sized
+        // block at a time, so every block is read from memory once per task
+        let first = self.next_id;
+        self.queries
+            .par_chunks_mut(QUERY_BLOCK)
+            .enumerate()
+            .for_each(|(chunk, queries)| {
+                for block in base.chunks(BASE_BLOCK) {
+                    for (i, query) in queries.iter_mut().enumerate() {
+                        let mask = masks.get(chunk * QUERY_BLOCK + i).and_then(Option::as_ref);
+                        for &(id, vector, base_norm) in block {
+                            if mask.is_some_and(|mask| !mask[(id - first) as usize]) {
+                                continue;
+                            }
+                            let distance = match metric {
+                                Metric::L2 => l2(&query.vector, vector),
+                                Metric::Cosine => {
+                                    cosine(&query.vector, query.norm, vector, base_norm)
+                                }
+                            };
+                            query.offer(Neighbor { id, distance }, k);
+                        }
+                    }
+                }
+            });
+        self.next_id += vectors.len() as u64;
+        Ok(())
+    }
+
+    /// Base rows added so far
+    pub fn base_rows(&self) -> u64 {
+        self.next_id
+    }
+
+    /// Closest rows of every query, nearest first
+    ///
+    /// Queries have fewer than `k` neighbors if fewer base vectors were added.
+    pub fn finish(self) -> Vec<Vec<Neighbor>> {
+        self.queries
+            .into_iter()
+            .map(|query| query.closest.into_sorted_vec())
+            .collect()
+    }
+}
+
+fn norm(vector: &[f32]) -> f32 {
+    lane_sum(vector, vector, |a, b| a * b).sqrt()
+}
+
+fn l2(a: &[f32], b: &[f32]) -> f32 {
+    lane_sum(a, b, |a, b| (a - b) * (a - b)).sqrt()
+}
+
+fn cosine(a: &[f32], a_norm: f32, b: &[f32], b_norm: f32) -> f32 {
+    if a_norm == 0.0 || b_norm == 0.0 {
+        return 1.0;
+    }
+    1.0 - lane_sum(a, b, |a, b| a * b) / (a_norm * b_norm)
+}
+
+/// Sum of `f` over the element pairs of `a` and `b`
+///
+/// Elements are summed in [`LANES`] independent accumulators, which the
+/// compiler turns into SIMD registers; a single running sum would force one
+/// addition after the other.
+#[inline]
+fn lane_sum(a: &[f32], b: &[f32], f: impl Fn(f32, f32) -> f32) -> f32 {
+    let mut lanes = [0.0f32; LANES];
+    let (a_chunks, b_chunks) = (a.chunks_exact(LANES), b.chunks_exact(LANES));
+    let tail: f32 = a_chunks
+        .remainder()
+        .iter()
+        .zip(b_chunks.remainder())
+        .map(|(&a, &b)| f(a, b))
+        .sum();
+    for (a, b) in a_chunks.zip(b_chunks) {
+        for ((sum, &a), &b) in lanes.iter_mut().zip(a).zip(b) {
+            *sum += f(a, b);
+        }
+    }
+    lanes.iter().sum::<f32>() + tail
+}
+
+/// Call `f` with the vectors of `column` in each batch of `path`, and the
+/// batch read, which also holds the columns `extra`
+fn for_each_vector_batch(
+    path: &Path,
+    column: &str,
+    extra: &[String],
+    batch_size: usize,
+    mut f: impl FnMut(Vec<Option<Vec<f32>>>, &RecordBatch) -> Result<()>,
+) -> Result<()> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(
+        File::open(path).with_context(|| format!("Failed to open file: {:?}", path))?,
+    )?;
+    let schema = builder.schema().clone();
+    let Some((position, field)) = schema.column_with_name(column) else {
+        bail!("vector column '{}' is missing from {:?}", column, path);
+    };
+    let mut positions = vec![position];
+    for name in extra {
+        let Some((position, _)) = schema.column_with_name(name) else {
+            bail!("filter column '{}' is missing from {:?}", name, path);
+        };
+        positions.push(position);
+    }
+    let mask = ProjectionMask::roots(builder.parquet_schema(), positions);
+    let reader = builder
+        .with_projection(mask)
+        .with_batch_size(batch_size.max(1))
+        .build()?;
+    for batch in reader {
+        let batch = batch?;
+        let array = batch
+            .column_by_name(column)
+            .context("projected vector column")?;
+        let mut vectors = Vec::with_capacity(batch.num_rows());
+        for row in 0..batch.num_rows() {
+            let vector = match vector_values(field, array.as_ref(), row) {
+                Some(values) => Some(values.into_iter().map(|v| v as f32).collect()),
+                None if array.is_null(row) => None,
+                None => bail!("column '{}' of {:?} is not a vector column", column, path),
+            };
+            vectors.push(vector);
+        }
+        f(vectors, &batch)?;
+    }
+    Ok(())
+}
+
+/// Non-null vectors of `column` in `path`, in row order
+pub fn read_vectors(path: &Path, column: &str, batch_size: usize) -> Result<Vec<Vec<f32>>> {
+    let mut all = Vec::new();
+    for_each_vector_batch(path, column, &[], batch_size, |vectors, _| {
+        all.extend(vectors.into_iter().flatten());
+        Ok(())
+    })?;
+    Ok(all)
+}
+
+/// Filters of the queries in a query file at `path`, from its `filter`
+/// column; empty if it has none
+pub fn read_filters(path: &Path, batch_size: usize) -> Result<Vec<Option<Predicate>>> {
+    let builder = ParquetRecordBatchReaderBuilder::try_new(
+        File::open(path).with_context(|| format!("Failed to open file: {:?}", path))?,
+    )?;
+    let Some((position, _)) = builder.schema().column_with_name("filter") else {
+        return Ok(Vec::new());
+    };
+    let mask = ProjectionMask::roots(builder.parquet_schema(), [position]);
+    let reader = builder
+        .with_projection(mask)
+        .with_batch_size(batch_size.max(1))
+        .build()?;
+    let mut filters = Vec::new();
+    for batch in reader {
+        let batch = batch?;
+        let Some(column) = batch.column(0).as_any().downcast_ref::<StringArray>() else {
+            bail!("filter column of {:?} is not a string column", path);
+        };
+        for row in 0..column.len() {
+            let filter = column.is_valid(row).then(|| column.value(row).parse());
+            filters.push(filter.transpose()?);
+        }
+    }
+    Ok(filters)
+}
+
+/// Search of `queries` with their `filters` (none if empty)
+fn start_search(
+    queries: Vec<Vec<f32>>,
+    filters: &[Option<Predicate>],
+    options: &SearchOptions,
+) -> Result<GroundTruth> {
+    let SearchOptions { k, metric, device, .. } = *options;
+    let search = GroundTruth::on(queries, k, metric, device)?;
+    if filters.is_empty() {
+        return Ok(search);
+    }
+    search.with_filters(filters.to_vec())
+}
+
+/// Add the rows of `column` in `path` to `search`
+fn add_file(search: &mut GroundTruth, path: &Path, column: &str, batch_size: usize) -> Result<()> {
+    let columns = search.filter_columns();
+    for_each_vector_batch(path, column, &columns, batch_size, |vectors, batch| {
+        if columns.is_empty() {
+            search.add(&vectors)
+        } else {
+            search.add_batch(&vectors, batch)
+        }
+    })
+}
+
+/// Exact `options.k` nearest rows of `column` in `base` for every query,
+/// among the rows passing its filter if `filters` (one per query) is not
+/// empty
+///
+/// Base files are read in the order given.
+pub fn compute(
+    base: &[PathBuf],
+    column: &str,
+    queries: Vec<Vec<f32>>,
+    filters: &[Option<Predicate>],
+    options: &SearchOptions,
+) -> Result<Vec<Vec<Neighbor>>> {
+    let mut search = start_search(queries, filters, options)?;
+    for path in base {
+        add_file(&mut search, path, column, options.batch_size)?;
+    }
+    Ok(search.finish())
+}
+
+/// Exact neighbors as [`compute`] after each epoch of `deletes`, which
+/// holds the row ids deleted in epochs 1, 2, ...; the result of epoch `e`
+/// leaves out the rows deleted in epochs up to `e`
+///
+/// Base files are read once for all epochs.
+pub fn compute_after_deletes(
+    base: &[PathBuf],
+    column: &str,
+    queries: Vec<Vec<f32>>,
+    filters: &[Option<Predicate>],
+    deletes: &[Vec<u64>],
+    options: &SearchOptions,
+) -> Result<Vec<Vec<Vec<Neighbor>>>> {
+    let mut deleted = HashMap::new();
+    for (epoch, ids) in deletes.iter().enumerate() {
+        for &id in ids {
+            deleted.entry(id).or_insert(epoch + 1);
+        }
+    }
+    let deleted = Arc::new(deleted);
+    let mut searches = (1..=deletes.len())
+        .map(|epoch| {
+            let search = start_search(queries.clone(), filters, options)?;
+            Ok(search.without_deleted(deleted.clone(), epoch))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let Some(first) = searches.first() else {
+        return Ok(Vec::new());
+    };
+    let columns = first.filter_columns();
+    for path in base {
+        for_each_vector_batch(path, column, &columns, options.batch_size, |vectors, batch| {
+            for search in &mut searches {
+                if columns.is_empty() {
+                    search.add(&vectors)?;
+                } else {
+                    search.add_batch(&vectors, batch)?;
+                }
+            }
+            Ok(())
+        })?;
+    }
+    Ok(searches.into_iter().map(GroundTruth::finish).collect())
+}
+
+/// Path of the ground truth after deletion epoch `epoch`, next to `output`:
+/// `ground_truth.parquet` becomes `ground_truth.epoch-1.parquet`
+pub fn epoch_path(output: &Path, epoch: usize) -> PathBuf {
+    let stem = output.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match output.extension() {
+        Some(extension) => format!("{}.epoch-{}.{}", stem, epoch, extension.to_string_lossy()),
+        None => format!("{}.epoch-{}", stem, epoch),
+    };
+    output.with_file_name(name)
+}
+
+/// Schema of Parquet ground-truth files
+pub fn schema() -> Schema {
+    Schema::new(vec![
+        Field::new("query_id", DataType::Int64, false),
+        Field::new(
+            "neighbors",
+            DataType::List(Arc::new(Field::new("item", DataType::Int64, true))),
+            false,
+        ),
+        Field::new(
+            "distances",
+            DataType::List(Arc::new(Field::new("item", DataType::Float32, true))),
+            false,
+        ),
+    ])
+}
+
+/// Write one row per query to a Parquet file at `path`
+pub fn write_parquet(
+    writer: &ParquetWriter,
+    path: &Path,
+    neighbors: &[Vec<Neighbor>],
+) -> Result<()> {
+    let schema = Arc::new(schema());
+    let mut ids = ListBuilder::new(Int64Builder::new());
+    let mut distances = ListBuilder::new(Float32Builder::new());
+    for closest in neighbors {
+        for neighbor in closest {
+            ids.values().append_value(neighbor.id as i64);
+            distances.values().append_value(neighbor.distance);
+        }
+        ids.append(true);
+        distances.append(true);
+    }
+    let columns: Vec<ArrayRef> = vec![
+        Arc::new(Int64Array::from_iter_values(0..neighbors.len() as i64)),
+        Arc::new(ids.finish()),
+        Arc::new(distances.finish()),
+    ];
+    let mut sink = writer.create_sink(path, &schema)?;
+    sink.write(&RecordBatch::try_new(schema, columns)?)?;
+    sink.finish()?;
+    Ok(())
+}
+
+/// Write the neighbor ids of every query to an `.ivecs` file at `path`
+pub fn write_ivecs(path: &Path, neighbors: &[Vec<Neighbor>]) -> Result<()> {
+    let file = File::create(path).with_context(|| format!("Failed to create file: {:?}", path))?;
+    let mut out = BufWriter::new(file);
+    for closest in neighbors {
+        out.write_all(&(closest.len() as i32).to_le_bytes())?;
+        for neighbor in closest {
+            let Ok(id) = i32::try_from(neighbor.id) else {
+                bail!("row id {} does not fit an ivecs entry", neighbor.id);
+            };
+            out.write_all(&id.to_le_bytes())?;
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// Directory of a dataset holding cached per-file results by default
+pub const CACHE_DIR_NAME: &str = "_ground_truth_cache";
+
+/// Magic and version of cache files
+const CACHE_MAGIC: &[u8; 8] = b"VDGGTC01";
+
+/// Top-k of one query within one base file, by row position in that file
+struct CachedQuery {
+    /// Neighbors searched for; fewer are kept if the file has fewer rows
+    k: usize,
+    closest: Vec<Neighbor>,
+}
+
+/// Cached results of one base file, valid while its size and modification
+/// time are unchanged
+struct ShardCache {
+    path: PathBuf,
+    bytes: u64,
+    modified_ms: u64,
+    /// Rows of the base file, null vectors included
+    rows: u64,
+    queries: HashMap<u64, CachedQuery>,
+}
+
+impl ShardCache {
+    /// Results for `base` cached at `path`; empty if missing, unreadable or
+    /// written for another version of the file
+    fn load(path: PathBuf, base: &Path) -> Result<Self> {
+        let metadata = std::fs::metadata(base)
+            .with_context(|| format!("Failed to read metadata of {:?}", base))?;
+        let modified_ms = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        let mut cache = Self {
+            path,
+            bytes: metadata.len(),
+            modified_ms,
+            rows: 0,
+            queries: HashMap::new(),
+        };
+        if let Ok(file) = File::open(&cache.path) {
+            // A stale or damaged cache is recomputed, not an error
+            let _ = cache.read(&mut BufReader::new(file));
+        }
+        Ok(cache)
+    }
+
+    fn read(&mut self, input: &mut impl Read) -> io::Result<()> {
+        let mut magic = [0u8; 8];
+        input.read_exact(&mut magic)?;
+        if &magic != CACHE_MAGIC
+            || read_u64(input)? != self.bytes
+            || read_u64(input)? != self.modified_ms
+        {
+            return Ok(());
+        }
+        let rows = read_u64(input)?;
+        let mut queries = HashMap::new();
+        for _ in 0..read_u64(input)? {
+            let key = read_u64(input)?;
+            let k = read_u64(input)? as usize;
+            let count = read_u64(input)? as usize;
+            let mut closest = Vec::with_capacity(count.min(k));
+            for _ in 0..count {
+                let id = read_u64(input)?;
+                let mut distance = [0u8; 4];
+                input.read_exact(&mut distance)?;
+                closest.push(Neighbor {
+                    id,
+                    distance: f32::from_le_bytes(distance),
+                });
+            }
+            queries.insert(key, CachedQuery { k, closest });
+        }
+        self.rows = rows;
+        self.queries = queries;
+        Ok(())
+    }
+
+    /// Atomically replace the cache file
+    fn save(&self) -> Result<()> {
+        let temp = self.path.with_extension("tmp");
+        let mut out = BufWriter::new(
+            File::create(&temp).with_context(|| format!("Failed to create file: {:?}", temp))?,
+        );
+        out.write_all(CACHE_MAGIC)?;
+        for value in [self.bytes, self.modified_ms, self.rows, self.queries.len() as u64] {
+            out.write_all(&value.to_le_bytes())?;
+        }
+        for (key, query) in &self.queries {
+            for value in [*key, query.k as u64, query.closest.len() as u64] {
+                out.write_all(&value.to_le_bytes())?;
+            }
+            for neighbor in &query.closest {
+                out.write_all(&neighbor.id.to_le_bytes())?;
+                out.write_all(&neighbor.distance.to_le_bytes())?;
+            }
+        }
+        out.flush()?;
+        drop(out);
+        std::fs::rename(&temp, &self.path)
+            .with_context(|| format!("Failed to write cache: {:?}", self.path))
+    }
+
+    /// Cached `k` closest rows of the query with `key`, if known
+    fn get(&self, key: u64, k: usize) -> Option<&[Neighbor]> {
+        let query = self.queries.get(&key)?;
+        // A query that found fewer rows than it searched for has them all
+        let complete = query.k >= k || query.closest.len() < query.k;
+        complete.then(|| &query.closest[..k.min(query.closest.len())])
+    }
+}
+
+fn read_u64(input: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Cache key of a query vector and its filter
+fn query_key(vector: &[f32], filter: Option<&Predicate>) -> u64 {
+    let hash = vector
+        .iter()
+        .fold(fnv1a(&[]), |hash, v| fnv1a_extend(hash, &v.to_le_bytes()));
+    match filter {
+        Some(filter) => fnv1a_extend(hash, filter.to_string().as_bytes()),
+        None => hash,
+    }
+}
+
+/// [`compute`] keeping the top-k of every query within every base file in
+/// `cache_dir`
+///
+/// A later run over the same files only searches the files for queries
+/// without a cached result (new queries, or a larger `k`), and merges the
+/// per-file results, which gives the same neighbors as a full search. Cache
+/// entries of a base file are dropped when its size or modification time
+/// changes.
+pub fn compute_cached(
+    base: &[PathBuf],
+    column: &str,
+    queries: Vec<Vec<f32>>,
+    filters: &[Option<Predicate>],
+    options: &SearchOptions,
+    cache_dir: &Path,
+) -> Result<Vec<Vec<Neighbor>>> {
+    let SearchOptions { k, metric, batch_size, .. } = *options;
+    if k == 0 {
+        bail!("ground truth needs k of at least 1");
+    }
+    std::fs::create_dir_all(cache_dir)
+        .with_context(|| format!("Failed to create cache directory: {:?}", cache_dir))?;
+    if !filters.is_empty() && filters.len() != queries.len() {
+        bail!("{} filters given for {} queries", filters.len(), queries.len());
+    }
+    let filter = |query: usize| filters.get(query).and_then(Option::as_ref);
+    let keys: Vec<u64> = (0..queries.len())
+        .map(|query| query_key(&queries[query], filter(query)))
+        .collect();
+    let mut merged: Vec<Vec<Neighbor>> = vec![Vec::new(); queries.len()];
+    let mut offset = 0;
+    for path in base {
+        // Files of different directories may share a name
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let hash = fnv1a(path.to_string_lossy().as_bytes());
+        let cache_path = cache_dir.join(format!("{}.{:016x}.{}.{}.gtc", name, hash, column, metric));
+        let mut cache = ShardCache::load(cache_path, path)?;
+        let missing: Vec<usize> = (0..queries.len())
+            .filter(|&query| cache.get(keys[query], k).is_none())
+            .collect();
+        if !missing.is_empty() {
+            let vectors = missing.iter().map(|&query| queries[query].clone()).collect();
+            let missing_filters: Vec<Option<Predicate>> = if filters.is_empty() {
+                Vec::new()
+            } else {
+                missing.iter().map(|&query| filter(query).cloned()).collect()
+            };
+            let mut search = start_search(vectors, &missing_filters, options)?;
+            add_file(&mut search, path, column, batch_size)?;
+            cache.rows = search.base_rows();
+            for (&query, closest) in missing.iter().zip(search.finish()) {
+                cache.queries.insert(keys[query], CachedQuery { k, closest });
+            }
+            cache.save()?;
+        }
+        for (key, merged) in keys.iter().zip(&mut merged) {
+            let closest = cache.get(*key, k).unwrap_or_default();
+            merged.extend(closest.iter().map(|neighbor| Neighbor {
+                id: neighbor.id + offset,
+                distance: neighbor.distance,
+            }));
+        }
+        offset += cache.rows;
+    }
+    for closest in &mut merged {
+        closest.sort_unstable();
+        closest.truncate(k);
+    }
+    Ok(merged)
+}
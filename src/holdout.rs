@@ -0,0 +1,141 @@
+//! Holdout rows sampled while the dataset is generated
+//!
+//! A [`Reservoir`] keeps a uniform random sample of `capacity` rows out of
+//! every row offered to it (Algorithm R), so a validation subset is
+//! available after a single pass without re-reading the written files.
+//! Sampled rows are copied out of their batches, so the reservoir never pins
+//! more than about twice its capacity in memory.
+
+use anyhow::Result;
+use arrow::array::UInt32Array;
+use arrow::compute::{interleave_record_batch, take_record_batch};
+use arrow::record_batch::RecordBatch;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::core_gen::stream_seed;
+use crate::sink::{Readiness, Sink};
+
+/// Stream identifier for holdout sampling
+const HOLDOUT_STREAM: u64 = u64::MAX - 7;
+
+/// Uniform sample of a fixed number of rows from a stream of batches
+pub struct Reservoir {
+    capacity: usize,
+    seen: u64,
+    rng: StdRng,
+    /// Copies of sampled rows, some of them since replaced
+    chunks: Vec<RecordBatch>,
+    /// Chunk and row holding each slot of the sample
+    slots: Vec<(usize, usize)>,
+}
+
+impl Reservoir {
+    /// Sample `capacity` rows, seeded from the run seed
+    pub fn new(capacity: usize, seed: u64) -> Self {
+        Self::for_stream(capacity, seed, &[HOLDOUT_STREAM])
+    }
+
+    /// Sample `capacity` rows, seeded from the run seed and `stream`
+    pub fn for_stream(capacity: usize, seed: u64, stream: &[u64]) -> Self {
+        Self {
+            capacity,
+            seen: 0,
+            rng: StdRng::seed_from_u64(stream_seed(seed, stream)),
+            chunks: Vec::new(),
+            slots: Vec::new(),
+        }
+    }
+
+    /// Rows offered so far
+    pub fn seen(&self) -> u64 {
+        self.seen
+    }
+
+    /// Consider every row of `batch` for the sample
+    pub fn offer(&mut self, batch: &RecordBatch) -> Result<()> {
+        // Slot each picked row replaces, or `None` while the sample fills up
+        let mut picks: Vec<(Option<usize>, u32)> = Vec::new();
+        let mut filled = self.slots.len();
+        for row in 0..batch.num_rows() {
+            if filled < self.capacity {
+                picks.push((None, row as u32));
+                filled += 1;
+            } else {
+                let j = self.rng.gen_range(0..=self.seen);
+                if j < self.capacity as u64 {
+                    picks.push((Some(j as usize), row as u32));
+                }
+            }
+            self.seen += 1;
+        }
+        if picks.is_empty() {
+            return Ok(());
+        }
+
+        let rows = UInt32Array::from_iter_values(picks.iter().map(|(_, row)| *row));
+        let chunk = self.chunks.len();
+        self.chunks.push(take_record_batch(batch, &rows)?);
+        for (i, (slot, _)) in picks.into_iter().enumerate() {
+            match slot {
+                Some(slot) => self.slots[slot] = (chunk, i),
+                None => self.slots.push((chunk, i)),
+            }
+        }
+
+        let held: usize = self.chunks.iter().map(|c| c.num_rows()).sum();
+        if held > 2 * self.capacity.max(1) {
+            self.compact()?;
+        }
+        Ok(())
+    }
+
+    /// Drop replaced rows, keeping the sample in one chunk
+    fn compact(&mut self) -> Result<()> {
+        let sample = self.sample()?;
+        self.slots = (0..self.slots.len()).map(|row| (0, row)).collect();
+        self.chunks = sample.into_iter().collect();
+        Ok(())
+    }
+
+    /// The sampled rows, in slot order; `None` if no row was offered
+    pub fn sample(&self) -> Result<Option<RecordBatch>> {
+        if self.slots.is_empty() {
+            return Ok(None);
+        }
+        let chunks: Vec<&RecordBatch> = self.chunks.iter().collect();
+        Ok(Some(interleave_record_batch(&chunks, &self.slots)?))
+    }
+}
+
+/// Sink offering every written batch to a [`Reservoir`] before passing it on
+pub struct HoldoutSink<'a, S: Sink> {
+    inner: S,
+    reservoir: &'a mut Reservoir,
+}
+
+impl<'a, S: Sink> HoldoutSink<'a, S> {
+    /// Sample the rows written to `inner` into `reservoir`
+    pub fn new(inner: S, reservoir: &'a mut Reservoir) -> Self {
+        Self { inner, reservoir }
+    }
+}
+
+impl<S: Sink> Sink for HoldoutSink<'_, S> {
+    fn readiness(&mut self) -> Result<Readiness> {
+        self.inner.readiness()
+    }
+
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        self.reservoir.offer(batch)?;
+        self.inner.write(batch)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.inner.finish()
+    }
+
+    fn abort(&mut self) -> Result<()> {
+        self.inner.abort()
+    }
+}
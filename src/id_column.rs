@@ -0,0 +1,74 @@
+//! Primary-key `id` column
+//!
+//! Upserts and recall checks need a unique key per row. The `id` column is
+//! either the row's position in the dataset (`Int64`, counting from 0 across
+//! files, so the same number as a ground-truth neighbor id) or a version-4
+//! UUID string. UUIDs are hashed from the seed and the row position rather
+//! than drawn from a batch stream, so a row keeps its UUID whatever the batch
+//! size or file layout.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::core_gen::stream_seed;
+
+/// Name of the generated column
+pub const ID_COLUMN_NAME: &str = "id";
+
+/// Stream identifier of UUID hashes
+const UUID_STREAM: u64 = u64::MAX - 2;
+
+/// Values of the `id` column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum IdColumn {
+    /// Position of the row in the dataset, as `Int64`
+    Monotonic,
+    /// Random version-4 UUID, as a 36-character string
+    Uuid,
+}
+
+impl IdColumn {
+    /// Characters of a UUID string
+    pub const UUID_LEN: usize = 36;
+}
+
+/// Version-4 UUID of the row at `row` of a dataset generated with `seed`
+pub fn uuid(seed: u64, row: u64) -> String {
+    let mut bytes = [0u8; 16];
+    bytes[..8].copy_from_slice(&stream_seed(seed, &[UUID_STREAM, row, 0]).to_be_bytes());
+    bytes[8..].copy_from_slice(&stream_seed(seed, &[UUID_STREAM, row, 1]).to_be_bytes());
+    // Version 4, RFC 4122 variant
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let mut out = String::with_capacity(IdColumn::UUID_LEN);
+    for (i, byte) in bytes.iter().enumerate() {
+        if matches!(i, 4 | 6 | 8 | 10) {
+            out.push('-');
+        }
+        out.push_str(&format!("{:02x}", byte));
+    }
+    out
+}
+
+impl fmt::Display for IdColumn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            IdColumn::Monotonic => "monotonic",
+            IdColumn::Uuid => "uuid",
+        })
+    }
+}
+
+impl FromStr for IdColumn {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "monotonic" => Ok(IdColumn::Monotonic),
+            "uuid" => Ok(IdColumn::Uuid),
+            other => bail!("unknown id column '{}', expected monotonic or uuid", other),
+        }
+    }
+}
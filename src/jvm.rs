@@ -0,0 +1,133 @@
+//! JNI bindings for JVM benchmark harnesses
+//!
+//! A JVM process loads the `cdylib` and pulls batches in-process instead of
+//! running the CLI and reading the files back. The bindings are a thin layer
+//! over [`crate::ffi::GeneratorReader`]: `start` creates a generator from a
+//! JSON configuration, `nextBatch` exports one batch into an `ArrowArray` and
+//! an `ArrowSchema` allocated by the caller (Arrow Java's C Data Interface),
+//! and `close` frees the generator. They bind to this Java class:
+//!
+//! ```java
+//! package vdg;
+//!
+//! public final class VectorDataGen {
+//!     public static native long start(String configJson, long totalRows, long batchSize);
+//!     public static native boolean nextBatch(long handle, long arrayAddress, long schemaAddress);
+//!     public static native void close(long handle);
+//! }
+//! ```
+//!
+//! Failures are thrown as `RuntimeException`s.
+
+use anyhow::{bail, Result};
+use arrow::array::{Array, StructArray};
+use arrow::ffi::{to_ffi, FFI_ArrowArray, FFI_ArrowSchema};
+use jni::objects::{JClass, JString};
+use jni::sys::{jboolean, jlong, JNI_FALSE, JNI_TRUE};
+use jni::JNIEnv;
+
+use crate::ffi::GeneratorReader;
+use crate::{config_format, DataGenerator};
+
+/// Create a generator of `total_rows` rows and return its handle
+#[no_mangle]
+pub extern "system" fn Java_vdg_VectorDataGen_start<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    config_json: JString<'local>,
+    total_rows: jlong,
+    batch_size: jlong,
+) -> jlong {
+    let result = (|| -> Result<jlong> {
+        if total_rows < 0 || batch_size <= 0 {
+            bail!("row count must not be negative and batch size must be positive");
+        }
+        let json: String = env.get_string(&config_json)?.into();
+        let config = config_format::from_json(&json)?;
+        let generator = DataGenerator::try_new(config)?;
+        let reader = GeneratorReader::new(generator, total_rows as usize, batch_size as usize);
+        Ok(Box::into_raw(Box::new(reader)) as jlong)
+    })();
+    result.unwrap_or_else(|e| {
+        throw(&mut env, e);
+        0
+    })
+}
+
+/// Export the next batch to the given `ArrowArray` and `ArrowSchema`
+///
+/// Returns false, leaving both untouched, once every row has been exported.
+#[no_mangle]
+pub extern "system" fn Java_vdg_VectorDataGen_nextBatch<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+    array_address: jlong,
+    schema_address: jlong,
+) -> jboolean {
+    let result = (|| -> Result<bool> {
+        if handle == 0 || array_address == 0 || schema_address == 0 {
+            bail!("handle and C Data Interface addresses must not be null");
+        }
+        // SAFETY: the handle comes from `start` and has not been closed; the
+        // addresses point to structs allocated by the caller
+        unsafe {
+            export_next(
+                &mut *(handle as *mut GeneratorReader),
+                array_address as *mut FFI_ArrowArray,
+                schema_address as *mut FFI_ArrowSchema,
+            )
+        }
+    })();
+    match result {
+        Ok(true) => JNI_TRUE,
+        Ok(false) => JNI_FALSE,
+        Err(e) => {
+            throw(&mut env, e);
+            JNI_FALSE
+        }
+    }
+}
+
+/// Free the generator behind `handle`
+#[no_mangle]
+pub extern "system" fn Java_vdg_VectorDataGen_close<'local>(
+    _env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    handle: jlong,
+) {
+    if handle != 0 {
+        // SAFETY: the handle comes from `start` and is closed only once
+        drop(unsafe { Box::from_raw(handle as *mut GeneratorReader) });
+    }
+}
+
+/// Write the next batch of `reader` to `array` and `schema`
+///
+/// Returns false once the reader is exhausted. The receiver owns the exported
+/// structs and releases them through their `release` callbacks.
+///
+/// # Safety
+///
+/// `array` and `schema` must point to writable memory for one struct each.
+pub(crate) unsafe fn export_next(
+    reader: &mut GeneratorReader,
+    array: *mut FFI_ArrowArray,
+    schema: *mut FFI_ArrowSchema,
+) -> Result<bool> {
+    let Some(batch) = reader.next() else {
+        return Ok(false);
+    };
+    let data = StructArray::from(batch?).into_data();
+    let (ffi_array, ffi_schema) = to_ffi(&data)?;
+    std::ptr::write(array, ffi_array);
+    std::ptr::write(schema, ffi_schema);
+    Ok(true)
+}
+
+fn throw(env: &mut JNIEnv, e: anyhow::Error) {
+    // A pending exception from a failed JNI call takes precedence
+    if !env.exception_check().unwrap_or(true) {
+        let _ = env.throw_new("java/lang/RuntimeException", format!("{:#}", e));
+    }
+}
@@ -0,0 +1,217 @@
+//! Directory structure and file names of generated datasets
+//!
+//! A [`LayoutStrategy`] maps every planned data file to a path below the
+//! output directory. Files are identified by a [`FileSlot`]: their tenant
+//! partition and hash bucket, if any, and their position within it. Built-in
+//! strategies cover the common conventions:
+//!
+//! - [`HiveLayout`] (default):
+//!   `tenant_id=<id>/bucket-<n>/<prefix>-<part>.parquet`, leaving out the
+//!   directories a run does not partition by
+//! - [`FlatLayout`]: every file directly in the output directory, the
+//!   partition and bucket spelled out in its name
+//! - [`BucketedLayout`]: partition directories holding bucket files named
+//!   `<prefix>-<part>_<bucket>.parquet`, the bucket id suffix Spark and Hive
+//!   read back for bucketed tables
+//! - [`DateLayout`]: `dt=<YYYY-MM-DD>` directories of daily partitions, a
+//!   fixed number of files per day from a start date
+//!
+//! Library users implement the trait for any other convention and pass it in
+//! [`crate::DatasetOptions::layout`]. Co-generated tables (edge list, query
+//! set, holdout sample, ...) keep their fixed names in the output directory.
+
+use anyhow::{anyhow, bail, Result};
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::bucket::bucket_dir_name;
+
+/// Position of a data file within the dataset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileSlot<'a> {
+    /// Prefix of generated file names
+    pub prefix: &'a str,
+    /// Index of the file within the dataset, unique across partitions
+    pub index: u64,
+    /// Position of the file within its partition and bucket
+    pub part: usize,
+    /// Tenant whose partition the file belongs to
+    pub tenant: Option<u32>,
+    /// Hash bucket the file holds
+    pub bucket: Option<u32>,
+}
+
+/// Maps data files to paths relative to the output directory
+///
+/// Paths must be distinct for distinct slots; the directories are created
+/// before a file is written.
+pub trait LayoutStrategy: fmt::Debug + Send + Sync {
+    /// Path of the file at `slot`, relative to the output directory
+    fn file_path(&self, slot: &FileSlot<'_>) -> PathBuf;
+}
+
+/// Hive-style `key=value` partition directories, bucket directories within
+/// them; the layout of earlier releases
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HiveLayout;
+
+impl LayoutStrategy for HiveLayout {
+    fn file_path(&self, slot: &FileSlot<'_>) -> PathBuf {
+        let mut path = PathBuf::new();
+        if let Some(tenant) = slot.tenant {
+            path.push(format!("tenant_id={}", tenant));
+        }
+        if let Some(bucket) = slot.bucket {
+            path.push(bucket_dir_name(bucket));
+        }
+        path.push(format!("{}-{:08}.parquet", slot.prefix, slot.part));
+        path
+    }
+}
+
+/// Every file directly in the output directory, named
+/// `<prefix>[-tenant<id>][-bucket<n>]-<part>.parquet`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FlatLayout;
+
+impl LayoutStrategy for FlatLayout {
+    fn file_path(&self, slot: &FileSlot<'_>) -> PathBuf {
+        let mut name = slot.prefix.to_string();
+        if let Some(tenant) = slot.tenant {
+            name.push_str(&format!("-tenant{}", tenant));
+        }
+        if let Some(bucket) = slot.bucket {
+            name.push_str(&format!("-bucket{}", bucket));
+        }
+        PathBuf::from(format!("{}-{:08}.parquet", name, slot.part))
+    }
+}
+
+/// Partition directories holding bucket files whose names end in the bucket
+/// id, `<prefix>-<part>_<bucket>.parquet`, as Spark and Hive name the files
+/// of bucketed tables; files without a bucket are named as in [`HiveLayout`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BucketedLayout;
+
+impl LayoutStrategy for BucketedLayout {
+    fn file_path(&self, slot: &FileSlot<'_>) -> PathBuf {
+        let mut path = PathBuf::new();
+        if let Some(tenant) = slot.tenant {
+            path.push(format!("tenant_id={}", tenant));
+        }
+        path.push(match slot.bucket {
+            Some(bucket) => format!("{}-{:08}_{:05}.parquet", slot.prefix, slot.part, bucket),
+            None => format!("{}-{:08}.parquet", slot.prefix, slot.part),
+        });
+        path
+    }
+}
+
+/// Daily `dt=<YYYY-MM-DD>` partitions within each tenant partition: the
+/// file at `part` goes to the day `part / files_per_day` after `start`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateLayout {
+    /// First day, in days since 1970-01-01
+    pub start: i64,
+    /// Files in each daily partition
+    pub files_per_day: usize,
+}
+
+impl DateLayout {
+    /// Check the parameters
+    pub fn validate(&self) -> Result<()> {
+        if self.files_per_day == 0 {
+            bail!("date layout needs at least one file per day");
+        }
+        Ok(())
+    }
+}
+
+impl LayoutStrategy for DateLayout {
+    fn file_path(&self, slot: &FileSlot<'_>) -> PathBuf {
+        let mut path = PathBuf::new();
+        if let Some(tenant) = slot.tenant {
+            path.push(format!("tenant_id={}", tenant));
+        }
+        let day = self.start + (slot.part / self.files_per_day.max(1)) as i64;
+        path.push(format!("dt={}", Date::from_days(day)));
+        if let Some(bucket) = slot.bucket {
+            path.push(bucket_dir_name(bucket));
+        }
+        path.push(format!("{}-{:08}.parquet", slot.prefix, slot.part));
+        path
+    }
+}
+
+/// Calendar date of the proleptic Gregorian calendar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// Date `days` days after 1970-01-01
+    pub fn from_days(days: i64) -> Self {
+        // Civil-from-days over 400-year eras of 146097 days, with years
+        // starting in March so the leap day comes last
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+        let year = yoe + era * 400 + i64::from(month <= 2);
+        Self { year, month, day }
+    }
+
+    /// Days since 1970-01-01
+    pub fn to_days(self) -> i64 {
+        let year = self.year - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let yoe = year.rem_euclid(400);
+        let mp = (self.month as i64 + 9) % 12;
+        let doy = (153 * mp + 2) / 5 + self.day as i64 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146_097 + doe - 719_468
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = anyhow::Error;
+
+    /// Parse `YYYY-MM-DD`
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || anyhow!("invalid date '{}', expected YYYY-MM-DD", s);
+        let mut parts = s.trim().splitn(3, '-');
+        let mut next = || parts.next().and_then(|part| part.parse::<i64>().ok());
+        let (year, month, day) = (
+            next().ok_or_else(invalid)?,
+            next().ok_or_else(invalid)?,
+            next().ok_or_else(invalid)?,
+        );
+        if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+            return Err(invalid());
+        }
+        let date = Self {
+            year,
+            month: month as u32,
+            day: day as u32,
+        };
+        // Days past the end of the month roll over into the next one
+        if Date::from_days(date.to_days()) != date {
+            return Err(invalid());
+        }
+        Ok(date)
+    }
+}
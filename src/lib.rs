@@ -5,36 +5,283 @@
 //! - 32-byte scalar strings
 //! - Outputs to compressed Parquet files (512MB per file)
 
-use arrow::array::{ArrayRef, BinaryArray, StringArray};
-use arrow::datatypes::{DataType, Field, Schema};
+use arrow::datatypes::{Schema, SchemaRef};
 use arrow::record_batch::RecordBatch;
-use parquet::arrow::ArrowWriter;
+use anyhow::bail;
+#[cfg(feature = "parquet")]
 use parquet::basic::{Compression, GzipLevel, ZstdLevel};
-use parquet::file::properties::WriterProperties;
-use rand::distributions::{Distribution, Uniform, Alphanumeric};
+#[cfg(feature = "parquet")]
+use parquet::file::metadata::KeyValue;
+#[cfg(feature = "parquet")]
+use parquet::file::properties::{EnabledStatistics, WriterProperties};
 use rand::SeedableRng;
-use rand::rngs::StdRng;
-use std::fs::File;
+use rand_chacha::ChaCha12Rng;
+use rayon::prelude::*;
+#[cfg(feature = "parquet")]
+use std::path::Path;
 use std::sync::Arc;
-use anyhow::{Result, Context};
+use std::time::{Duration, Instant};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use columns::{BatchContext, ColumnGenerator};
+use derived::DerivedPlan;
+use duplicates::DuplicateBlock;
+
+pub use columns::{
+    NullRatio, BYTE_ORDER_KEY, DIM_PREFIX_KEY, ELEMENT_TYPE_KEY, MAX_KEY, MIN_KEY, PQ_NBITS_KEY, PQ_SUBQUANTIZERS_KEY,
+    PRECISION_KEY, SPARSE_DIM_KEY, TOKENIZER_SEED_KEY, VECTOR_DIM_KEY,
+};
+pub use bucket::BucketConfig;
+pub use cluster::{ClusterConfig, Curve};
+pub use core_gen::{
+    ByteOrder, Quantization, VectorDistribution, VectorElementType, VectorEncoding, VectorLayout,
+};
+pub use correlation::Correlation;
+pub use derived::DerivedColumn;
+pub use ordering::{Monotonic, MonotonicState};
+#[cfg(feature = "parquet")]
+pub use dataset::{DatasetOptions, DatasetReport};
+pub use deletes::DeletionConfig;
+pub use duplicates::DuplicateConfig;
+pub use foreign_key::ForeignKeyConfig;
+pub use id_column::IdColumn;
+pub use layout::{FileSlot, LayoutStrategy};
+pub use mixture::MixtureConfig;
+pub use numeric::{NumericBounds, NumericDistribution};
+pub use graph::{DegreeDistribution, EdgeConfig};
+pub use payload::{PayloadConfig, PayloadEntropy};
+pub use pq::PqConfig;
+pub use pruning::{PageIndexConfig, PageRange, PruningConfig, RowGroupRange};
+pub use queries::{QueryConfig, QuerySource};
+pub use schema_spec::{ColumnSpec, ColumnType, SchemaSpec};
+pub use session::SessionConfig;
+pub use skew::Skew;
+pub use sparse::SparseConfig;
+pub use tenant::TenantConfig;
+pub use text::{Locale, TextConfig};
+pub use timestamp::{TimeRange, TimestampConfig, TimestampValues};
+pub use timing::StageTimings;
+pub use trajectory::{TrajectoryConfig, TrajectoryState};
+pub use version::{GeneratorVersion, GENERATOR_VERSION_KEY};
+pub use wide::{ColumnSubset, WideConfig};
+pub use workload::{KDistribution, Predicate, WorkloadConfig};
+
+pub mod audit;
+pub mod bucket;
+pub mod cancel;
+pub mod category;
+pub mod cluster;
+mod columns;
+pub mod companion;
+#[cfg(any(feature = "json", feature = "tools", feature = "ffi", feature = "wasm"))]
+pub mod config_format;
+pub mod core_gen;
+pub mod correlation;
+#[cfg(feature = "tui")]
+pub mod dashboard;
+#[cfg(feature = "parquet")]
+pub mod dataset;
+pub mod deletes;
+pub mod derived;
+#[cfg(feature = "tools")]
+pub mod diff;
+pub mod duplicates;
+#[cfg(feature = "parquet")]
+pub mod edge_cases;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "tools")]
+pub mod footer;
+pub mod foreign_key;
+pub mod formats;
+#[cfg(feature = "tools")]
+pub mod golden;
+#[cfg(feature = "gpu")]
+mod gpu;
+pub mod graph;
+#[cfg(feature = "parquet")]
+pub mod ground_truth;
+pub mod holdout;
+pub mod id_column;
+#[cfg(feature = "jni")]
+pub mod jvm;
+pub mod layout;
+#[cfg(feature = "tools")]
+pub mod manifest;
+pub mod mixture;
+pub mod numeric;
+#[cfg(feature = "numa")]
+pub mod numa;
+pub mod ordering;
+pub mod payload;
+pub mod pq;
+pub mod preview;
+pub mod pruning;
+pub mod queries;
+pub mod schema_spec;
+pub mod session;
+#[cfg(feature = "parquet")]
+pub mod sidecar;
+pub mod skew;
+pub mod snapshot;
+pub mod sparse;
+pub mod tenant;
+pub mod text;
+pub mod timestamp;
+pub mod timing;
+pub mod trajectory;
+pub mod version;
+pub mod wide;
+pub mod workload;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod sink;
+
+pub use cancel::{CancelPolicy, CancellationToken, Cancelled};
+pub use sink::{BoundedSink, Readiness, Sink};
+#[cfg(feature = "parquet")]
+pub use sink::ParquetFileSink;
 
 /// Configuration for data generation
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
     /// Vector dimension (default: 1024)
     pub vector_dim: usize,
     /// Scalar string length in bytes (default: 32)
     pub scalar_len: usize,
+    /// Namespace prepended to every `scalar` value and schema-spec string
+    /// column, e.g. `tenantA:`, so keys of several datasets never collide in
+    /// one index; not counted in `scalar_len` (default: none)
+    pub scalar_prefix: Option<String>,
+    /// Add a unique `id` column first in the schema: the row's position in
+    /// the dataset or a UUID (default: none)
+    pub id_column: Option<IdColumn>,
+    /// Add a `Timestamp(Millisecond)` column of random or monotonic values
+    pub timestamp: Option<TimestampConfig>,
+    /// Columns generated instead of `vector` and `scalar`, e.g.
+    /// `id:int64,emb:vector<f32,768>,title:string(64)` (default: none)
+    pub schema: Option<SchemaSpec>,
+    /// Target correlations among numeric columns of the schema spec, e.g.
+    /// `price:qty=0.8` (default: none)
+    pub correlation: Option<Correlation>,
     /// Target file size in bytes (default: 512MB)
     pub target_file_size: u64,
     /// Compression type for Parquet files
     pub compression: CompressionType,
     /// Random seed for reproducible data
     pub seed: u64,
+    /// What to do with the file in progress when generation is cancelled
+    pub cancel_policy: CancelPolicy,
+    /// Number of vector columns per row (default: 1)
+    pub vector_columns: usize,
+    /// Element type of vector values (default: Float32)
+    pub vector_type: VectorElementType,
+    /// Also emit the first vector column at this precision, for comparing
+    /// distance computations across precisions on identical vectors
+    pub mixed_precision: Option<VectorElementType>,
+    /// Store vectors as `LargeBinary` (64-bit offsets) so a batch of vectors
+    /// can exceed 2GB; otherwise batches shrink to fit `Binary` arrays
+    pub large_binary: bool,
+    /// Byte order and optional dimension prefix of encoded vector values
+    /// (default: little-endian, no prefix)
+    pub vector_layout: VectorLayout,
+    /// Arrow type of vector columns (default: `Binary` in `vector_layout`)
+    pub vector_encoding: VectorEncoding,
+    /// Distribution of vector components (default: uniform over [-1, 1))
+    pub vector_distribution: VectorDistribution,
+    /// Draw vectors around seeded cluster centroids (mixture of Gaussians);
+    /// centroids follow `vector_distribution`
+    pub mixture: Option<MixtureConfig>,
+    /// Scale every vector to unit L2 norm, for cosine-similarity benchmarks
+    pub normalize: bool,
+    /// Quantize vector values to few distinct values for best-case compression
+    pub quantization: Option<Quantization>,
+    /// Store the first vector column as SQ8 codes of each vector divided by
+    /// its largest magnitude, with the per-vector factor restoring its values
+    /// in a Float32 `vector_scale` column; needs int8 vectors
+    pub int8_scale: bool,
+    /// Emit a `pq_code` column with product-quantization codes of the first
+    /// vector column
+    pub pq: Option<PqConfig>,
+    /// Add a skewed `tenant_id` column, optionally partitioning output by tenant
+    pub tenants: Option<TenantConfig>,
+    /// Generate the first vector column as per-entity random walks with
+    /// `entity_id` and `ts` columns
+    pub trajectory: Option<TrajectoryConfig>,
+    /// Co-generate an edge list between the generated rows
+    pub edges: Option<EdgeConfig>,
+    /// Add a skewed foreign-key column referencing a (co-generated) parent table
+    pub foreign_key: Option<ForeignKeyConfig>,
+    /// Add `user_id` and `session_id` columns grouping rows into sessions
+    pub sessions: Option<SessionConfig>,
+    /// Repeat a fraction of first-column vectors across files from a shared pool
+    pub duplicates: Option<DuplicateConfig>,
+    /// Add `id` and `ts` columns with known per-row-group ranges for
+    /// row-group pruning tests
+    pub pruning: Option<PruningConfig>,
+    /// Write column/offset indexes (page-level statistics) with fixed-size pages
+    pub page_index: Option<PageIndexConfig>,
+    /// Target uncompressed row group size in bytes; rows per group are
+    /// estimated from the schema width (default: 100k rows per group)
+    pub row_group_size: Option<u64>,
+    /// Add a binary `payload` column of incompressible or highly compressible bytes
+    pub payload: Option<PayloadConfig>,
+    /// Add a `sparse_vector` column of (indices, values) pairs, e.g. for
+    /// hybrid dense and sparse retrieval
+    pub sparse: Option<SparseConfig>,
+    /// Add a wide logical schema of extra columns, of which each file writes
+    /// a subset
+    pub wide: Option<WideConfig>,
+    /// Add a `text` column of words from a Zipf-distributed vocabulary,
+    /// optionally with its token ids
+    pub text: Option<TextConfig>,
+    /// Columns whose values are all null
+    pub null_columns: Vec<String>,
+    /// Columns with a fraction of null values, e.g. `scalar=0.1`
+    pub null_ratios: Vec<NullRatio>,
+    /// Columns repeating a single value in every row
+    pub constant_columns: Vec<String>,
+    /// Write rows through a shuffle buffer of this many rows, locally
+    /// scrambling id and timestamp order
+    pub shuffle_buffer: Option<usize>,
+    /// Reservoir-sample this many rows of a dataset into a holdout file
+    pub holdout_rows: Option<usize>,
+    /// Write this many sampled rows of every data file, value by value, to a
+    /// text sidecar next to it for review
+    pub value_samples: Option<usize>,
+    /// Write a sidecar mapping the values of this column to their file, row
+    /// group and row offset
+    pub index_column: Option<String>,
+    /// Write a set of query vectors matching the base vectors to a query file
+    pub queries: Option<QueryConfig>,
+    /// Give each query of the query set a `k` and optionally a filter
+    pub workload: Option<WorkloadConfig>,
+    /// Write a deletion workload of base row ids over several epochs
+    pub deletes: Option<DeletionConfig>,
+    /// Columns computed from other columns of the same row, e.g.
+    /// `norm = l2(vector)`
+    pub derived: Vec<DerivedColumn>,
+    /// Columns kept non-decreasing within each key group, e.g. a version
+    /// per id, in generation order
+    pub monotonic: Vec<Monotonic>,
+    /// Route each partition's rows to bucket files by key hash, like
+    /// bucketed Hive/Paimon tables
+    pub bucketing: Option<BucketConfig>,
+    /// Sort the rows of each file along a Z-order or Hilbert curve over
+    /// some of its columns
+    pub clustering: Option<ClusterConfig>,
+    /// Generate this many batches concurrently on the rayon pool before
+    /// writing them in order (default: one batch at a time, its columns in
+    /// parallel); output does not depend on it
+    pub parallel_batches: Option<usize>,
+    /// Algorithm version; older versions reproduce data from earlier releases
+    pub generator_version: GeneratorVersion,
 }
 
 /// Compression types supported by Parquet
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum CompressionType {
     Snappy,
     Gzip,
@@ -51,6 +298,53 @@ impl Default for Config {
             target_file_size: 512 * 1024 * 1024, // 512MB
             compression: CompressionType::Snappy,
             seed: 42,
+            cancel_policy: CancelPolicy::default(),
+            scalar_prefix: None,
+            id_column: None,
+            timestamp: None,
+            schema: None,
+            correlation: None,
+            monotonic: Vec::new(),
+            vector_columns: 1,
+            vector_type: VectorElementType::default(),
+            mixed_precision: None,
+            large_binary: false,
+            vector_layout: VectorLayout::default(),
+            vector_encoding: VectorEncoding::default(),
+            vector_distribution: VectorDistribution::default(),
+            mixture: None,
+            normalize: false,
+            quantization: None,
+            int8_scale: false,
+            pq: None,
+            tenants: None,
+            trajectory: None,
+            edges: None,
+            foreign_key: None,
+            sessions: None,
+            duplicates: None,
+            pruning: None,
+            page_index: None,
+            row_group_size: None,
+            payload: None,
+            sparse: None,
+            wide: None,
+            text: None,
+            null_columns: Vec::new(),
+            null_ratios: Vec::new(),
+            constant_columns: Vec::new(),
+            shuffle_buffer: None,
+            holdout_rows: None,
+            value_samples: None,
+            index_column: None,
+            queries: None,
+            workload: None,
+            deletes: None,
+            derived: Vec::new(),
+            bucketing: None,
+            clustering: None,
+            parallel_batches: None,
+            generator_version: GeneratorVersion::LATEST,
         }
     }
 }
@@ -70,88 +364,511 @@ impl Config {
             target_file_size,
             compression,
             seed,
+            ..Config::default()
+        }
+    }
+
+    /// Check that the options are consistent with each other
+    pub fn validate(&self) -> Result<()> {
+        if self.vector_dim == 0 {
+            bail!("vector dimension must be greater than zero");
+        }
+        let binary = [Some(self.vector_type), self.mixed_precision]
+            .contains(&Some(VectorElementType::Binary));
+        if binary && self.schema.is_none() && !self.vector_dim.is_multiple_of(8) {
+            bail!(
+                "binary vectors need a dimension that is a multiple of 8, not {}",
+                self.vector_dim
+            );
+        }
+        if self.mixed_precision == Some(self.vector_type) {
+            bail!(
+                "mixed precision type must differ from the vector type ({})",
+                self.vector_type.name()
+            );
+        }
+        if self.vector_encoding != VectorEncoding::Binary {
+            if self.large_binary {
+                bail!(
+                    "large_binary only applies to the binary vector encoding, not {}",
+                    self.vector_encoding.name()
+                );
+            }
+            if self.vector_encoding == VectorEncoding::FixedSizeList
+                && self.vector_layout != VectorLayout::default()
+            {
+                bail!(
+                    "byte order and dimension prefix only apply to the binary vector encoding, \
+                     not {}",
+                    self.vector_encoding.name()
+                );
+            }
+        }
+        self.vector_distribution.validate()?;
+        self.generator_version.validate(self)?;
+        if let Some(schema) = &self.schema {
+            schema.validate(self)?;
+        }
+        if let Some(correlation) = &self.correlation {
+            if self.schema.is_none() {
+                bail!("correlated columns must be numeric columns of a schema spec");
+            }
+            correlation.validate()?;
+        }
+        for null_ratio in &self.null_ratios {
+            null_ratio.validate()?;
+        }
+        if self.id_column.is_some() {
+            if self.pruning.is_some() {
+                bail!("an id column cannot be combined with pruning fixtures, which add their own `id`");
+            }
+            let named_id = |schema: &SchemaSpec| schema.columns.iter().any(|c| c.name == id_column::ID_COLUMN_NAME);
+            if self.schema.as_ref().is_some_and(named_id) {
+                bail!("an id column cannot be combined with a schema spec column named `id`");
+            }
+        }
+        if let Some(timestamp) = &self.timestamp {
+            timestamp.validate()?;
+        }
+        if let Some(quantization) = self.quantization {
+            quantization.validate()?;
+        }
+        if self.int8_scale && self.schema.is_none() && self.vector_type != VectorElementType::Int8 {
+            bail!("int8_scale needs int8 vectors, not {}", self.vector_type.name());
+        }
+        if let Some(pq) = &self.pq {
+            pq.validate(self.vector_dim)?;
+        }
+        if let Some(tenants) = &self.tenants {
+            tenants.validate()?;
+        }
+        if let Some(mixture) = &self.mixture {
+            mixture.validate()?;
+        }
+        if let Some(trajectory) = &self.trajectory {
+            trajectory.validate()?;
+            if self.mixture.is_some() {
+                bail!("clustered vectors cannot be combined with trajectory mode");
+            }
+        }
+        if let Some(edges) = &self.edges {
+            edges.validate()?;
+        }
+        if let Some(fk) = &self.foreign_key {
+            fk.validate()?;
+        }
+        if let Some(sessions) = &self.sessions {
+            sessions.validate()?;
+        }
+        if let Some(duplicates) = &self.duplicates {
+            duplicates.validate()?;
+            if self.trajectory.is_some() {
+                bail!("cross-file duplicates cannot be combined with trajectory mode");
+            }
+        }
+        if let Some(pruning) = &self.pruning {
+            pruning.validate()?;
+            if self.trajectory.is_some() {
+                bail!("pruning fixtures cannot be combined with trajectory mode (both add `ts`)");
+            }
+        }
+        if let Some(page_index) = &self.page_index {
+            page_index.validate()?;
+        }
+        if let Some(payload) = &self.payload {
+            payload.validate()?;
+        }
+        if let Some(sparse) = &self.sparse {
+            sparse.validate()?;
+        }
+        if let Some(wide) = &self.wide {
+            wide.validate()?;
+        }
+        if let Some(text) = &self.text {
+            text.validate()?;
+        }
+        if self.parallel_batches == Some(0) {
+            bail!("parallel batches must be at least one");
         }
+        if self.shuffle_buffer == Some(0) {
+            bail!("shuffle buffer must hold at least one row");
+        }
+        if let Some(rows) = self.holdout_rows {
+            if rows == 0 {
+                bail!("holdout must hold at least one row");
+            }
+            if self.wide.is_some_and(|wide| wide.per_file < wide.columns) {
+                bail!("holdout sampling needs every file to share one schema");
+            }
+        }
+        if self.value_samples == Some(0) {
+            bail!("value samples must hold at least one row");
+        }
+        if let Some(queries) = &self.queries {
+            queries.validate()?;
+        }
+        if let Some(workload) = &self.workload {
+            if self.queries.is_none() {
+                bail!("a query workload needs a query set");
+            }
+            workload.validate()?;
+        }
+        if let Some(deletes) = &self.deletes {
+            deletes.validate()?;
+        }
+        if let Some(bucketing) = &self.bucketing {
+            bucketing.validate()?;
+            if self.shuffle_buffer.is_some() {
+                bail!("bucketing cannot be combined with a shuffle buffer");
+            }
+        }
+        if !self.monotonic.is_empty() {
+            if self.shuffle_buffer.is_some() {
+                bail!("ordering constraints cannot be combined with a shuffle buffer");
+            }
+            if self.clustering.is_some() {
+                bail!("ordering constraints cannot be combined with clustering, which reorders rows");
+            }
+        }
+        if let Some(clustering) = &self.clustering {
+            clustering.validate()?;
+            if self.shuffle_buffer.is_some() {
+                bail!("clustering cannot be combined with a shuffle buffer");
+            }
+            if self.bucketing.is_some() {
+                bail!("clustering cannot be combined with bucketing");
+            }
+            if self.pruning.is_some() {
+                bail!("clustering cannot be combined with pruning fixtures, which fix the rows of each row group");
+            }
+        }
+        if let Some(bytes) = self.row_group_size {
+            if bytes == 0 {
+                bail!("row group size must be greater than zero");
+            }
+            if self.pruning.is_some() {
+                bail!("row group size cannot be combined with pruning fixtures, which fix rows per group");
+            }
+        }
+        Ok(())
     }
 }
 
 /// Data generator for creating test data
+///
+/// Columns of a batch are generated as parallel tasks, each with an RNG
+/// derived from the seed, the file index, the batch index and the column
+/// position, so the output for a given seed does not depend on thread
+/// scheduling.
 pub struct DataGenerator {
     config: Config,
-    rng: StdRng,
-    vector_field: Field,
-    scalar_field: Field,
-    schema: Schema,
+    /// Sequential RNG of v0 files and single values (the ChaCha12 behind `StdRng`)
+    rng: ChaCha12Rng,
+    columns: Vec<ColumnGenerator>,
+    schema: SchemaRef,
+    /// Columns computed from the generated ones, appended to each batch
+    derived: DerivedPlan,
+    file_index: u64,
+    batch_index: u64,
+    /// Global index of the next row, for row-derived columns such as sessions
+    next_row: u64,
+    /// Branch of the row streams, see [`DataGenerator::fork`]
+    branch: u64,
+    trajectory: Option<TrajectoryState>,
+    /// Largest values per key of the ordering constraints
+    monotonic: Option<MonotonicState>,
+    /// Generation and batch assembly time since the last `take_timings`
+    timings: StageTimings,
 }
 
 impl DataGenerator {
     /// Create a new data generator with the given configuration
+    ///
+    /// # Panics
+    ///
+    /// Panics if the configuration is invalid; use [`DataGenerator::try_new`]
+    /// to handle that case.
     pub fn new(config: Config) -> Self {
-        let rng = StdRng::seed_from_u64(config.seed);
+        Self::try_new(config).expect("invalid generator configuration")
+    }
+
+    /// Create a new data generator, failing on an invalid configuration
+    pub fn try_new(config: Config) -> Result<Self> {
+        Self::for_file(config, 0)
+    }
+
+    /// Create a generator for the `file_index`-th file of a dataset
+    ///
+    /// Every file of a dataset shares the seed; the file index selects
+    /// independent row streams, while dataset-wide structures such as the PQ
+    /// codebook stay identical across files.
+    pub fn for_file(config: Config, file_index: u64) -> Result<Self> {
+        config.validate()?;
+        let rng = match config.generator_version {
+            GeneratorVersion::V0 => {
+                ChaCha12Rng::seed_from_u64(version::v0_file_seed(config.seed, file_index))
+            }
+            GeneratorVersion::V1 => ChaCha12Rng::seed_from_u64(config.seed),
+        };
 
         // Define schema - using Binary for vector data (store as raw bytes)
-        let vector_field = Field::new("vector", DataType::Binary, false);
-        let scalar_field = Field::new("scalar", DataType::Utf8, false);
+        let mut columns = columns::columns_for(&config)?;
+        columns::select_file_columns(&config, file_index, &mut columns);
+        let mut fields = columns.iter().map(|c| match config.generator_version {
+            // v0 files carried no field metadata
+            GeneratorVersion::V0 => Arc::new(c.field.as_ref().clone().with_metadata(Default::default())),
+            GeneratorVersion::V1 => c.field.clone(),
+        }).collect::<Vec<_>>();
+        let derived = DerivedPlan::new(&config.derived, &fields)?;
+        fields.extend(derived.fields().iter().cloned());
+        let schema = Arc::new(Schema::new(fields));
+        let trajectory = config
+            .trajectory
+            .map(|t| {
+                TrajectoryState::new(&t, config.vector_dim, config.vector_distribution, config.seed)
+            });
 
-        let schema = Schema::new(vec![vector_field.clone(), scalar_field.clone()]);
+        let monotonic = match config.monotonic.is_empty() {
+            true => None,
+            false => Some(MonotonicState::new(&config.monotonic, &schema)?),
+        };
 
-        Self {
+        Ok(Self {
             config,
             rng,
-            vector_field,
-            scalar_field,
+            columns,
             schema,
-        }
+            derived,
+            file_index,
+            batch_index: 0,
+            next_row: 0,
+            branch: 0,
+            trajectory,
+            monotonic,
+            timings: StageTimings::default(),
+        })
     }
 
-    /// Generate a single vector (1024 f32 values) as bytes
-    pub fn generate_vector(&mut self) -> Vec<u8> {
-        let uniform = Uniform::new(-1.0, 1.0);
-        let floats: Vec<f32> = (0..self.config.vector_dim)
-            .map(|_| uniform.sample(&mut self.rng))
-            .collect();
+    /// Take the trajectory walk state, e.g. to continue it in the next file
+    pub fn take_trajectory_state(&mut self) -> Option<TrajectoryState> {
+        self.trajectory.take()
+    }
+
+    /// Continue trajectories from a state taken from a previous generator
+    pub fn resume_trajectories(&mut self, state: TrajectoryState) {
+        self.trajectory = Some(state);
+    }
+
+    /// Take the largest values per key of the ordering constraints, e.g. to
+    /// keep enforcing them in the next file
+    pub fn take_monotonic_state(&mut self) -> Option<MonotonicState> {
+        self.monotonic.take()
+    }
+
+    /// Enforce ordering constraints from a state taken from a previous
+    /// generator of the same configuration
+    pub fn resume_monotonic(&mut self, state: MonotonicState) {
+        self.monotonic = Some(state);
+    }
+
+    /// Number the next generated row `row` within the dataset
+    ///
+    /// Row-derived columns such as `session_id` continue from this index, so
+    /// a file generated after others should start where they ended.
+    pub fn start_at_row(&mut self, row: u64) {
+        self.next_row = row;
+    }
 
-        // Convert to bytes (little-endian)
-        let mut bytes = Vec::with_capacity(floats.len() * 4);
-        for &f in &floats {
-            bytes.extend_from_slice(&f.to_le_bytes());
+    /// Generate a single vector (1024 f32 values by default) as bytes, drawn
+    /// from the configured distribution and normalized if configured
+    pub fn generate_vector(&mut self) -> Vec<u8> {
+        let mut values = self
+            .config
+            .vector_distribution
+            .sample(&mut self.rng, self.config.vector_dim);
+        if self.config.normalize {
+            core_gen::normalize(&mut values);
         }
+
+        // Convert to bytes (little-endian unless configured otherwise)
+        let element = self.config.vector_type;
+        let layout = self.config.vector_layout;
+        let mut bytes = Vec::with_capacity(layout.encoded_len(values.len(), element));
+        core_gen::encode_vector_with(&values, element, layout, &mut bytes);
         bytes
     }
 
-    /// Generate a single scalar string (32 bytes)
+    /// Generate a single scalar string (32 bytes), after the scalar prefix
     pub fn generate_scalar(&mut self) -> String {
-        let chars: Vec<char> = Alphanumeric
-            .sample_iter(&mut self.rng)
-            .take(self.config.scalar_len)
-            .map(char::from)
-            .collect();
-        chars.into_iter().collect()
+        let mut value = self.config.scalar_prefix.clone().unwrap_or_default();
+        core_gen::sample_scalar_into(&mut self.rng, self.config.scalar_len, &mut value);
+        value
+    }
+
+    /// Most rows one batch can hold without overflowing 32-bit array offsets
+    ///
+    /// With 1024-dimensional f32 vectors this is about 524k rows; at
+    /// dimension 65536 only 8191. [`sink::write_rows`] splits larger requests
+    /// into batches of at most this size.
+    pub fn max_batch_rows(&self) -> usize {
+        self.columns
+            .iter()
+            .map(|c| c.max_rows())
+            .min()
+            .unwrap_or(usize::MAX)
     }
 
     /// Generate a batch of data with the specified number of rows
+    ///
+    /// Fails if `batch_size` exceeds [`DataGenerator::max_batch_rows`].
     pub fn generate_batch(&mut self, batch_size: usize) -> Result<RecordBatch> {
-        // Generate vectors as binary data
-        let mut vector_data: Vec<Vec<u8>> = Vec::with_capacity(batch_size);
-        let mut scalar_data = Vec::with_capacity(batch_size);
+        let mut batches = self.generate_batches(&[batch_size])?;
+        Ok(batches.remove(0))
+    }
+
+    /// Generate one batch per entry of `batch_sizes`, concurrently
+    ///
+    /// The batches are identical to those of calling
+    /// [`DataGenerator::generate_batch`] once per size, in order: stateful
+    /// values such as trajectories are advanced sequentially first, then the
+    /// batches are built as parallel tasks on the current rayon pool. Output
+    /// for a seed therefore does not depend on how many batches are generated
+    /// at once.
+    ///
+    /// Fails if any size exceeds [`DataGenerator::max_batch_rows`].
+    pub fn generate_batches(&mut self, batch_sizes: &[usize]) -> Result<Vec<RecordBatch>> {
+        let max_batch_rows = self.max_batch_rows();
+        if let Some(&batch_size) = batch_sizes.iter().find(|&&size| size > max_batch_rows) {
+            bail!(
+                "batch of {} rows exceeds the 2GB Arrow array limit (at most {} rows); \
+                 use smaller batches or enable large_binary",
+                batch_size,
+                max_batch_rows
+            );
+        }
+        let start = Instant::now();
+        if self.config.generator_version == GeneratorVersion::V0 {
+            let batches = batch_sizes
+                .iter()
+                .map(|&batch_size| {
+                    version::generate_v0_batch(
+                        &mut self.rng,
+                        self.schema.clone(),
+                        self.config.vector_dim,
+                        self.config.scalar_len,
+                        batch_size,
+                    )
+                })
+                .collect();
+            self.timings.generate += start.elapsed();
+            return batches;
+        }
+
+        // Stateful values are computed up front, then shared with the column tasks
+        let mut tasks = Vec::with_capacity(batch_sizes.len());
+        for &batch_size in batch_sizes {
+            tasks.push(self.next_batch_context(batch_size)?);
+        }
+        let results = tasks
+            .par_iter()
+            .map(|(batch_index, batch_size, ctx)| self.build_batch(*batch_index, *batch_size, ctx))
+            .collect::<Vec<_>>();
 
-        for _ in 0..batch_size {
-            vector_data.push(self.generate_vector());
-            scalar_data.push(self.generate_scalar());
+        let mut batches = Vec::with_capacity(results.len());
+        let (mut generate, mut arrow) = (Duration::ZERO, Duration::ZERO);
+        for result in results {
+            let (mut batch, batch_generate, batch_arrow) = result?;
+            generate += batch_generate;
+            arrow += batch_arrow;
+            // Constraints depend on every earlier row, so they apply in order
+            if let Some(state) = self.monotonic.as_mut() {
+                batch = state.apply(batch)?;
+            }
+            batches.push(batch);
+        }
+        // Batches overlap, so their summed times can exceed the wall time
+        let busy = (generate + arrow).as_secs_f64();
+        let scale = if busy > 0.0 {
+            (start.elapsed().as_secs_f64() / busy).min(1.0)
+        } else {
+            1.0
+        };
+        self.timings.generate += generate.mul_f64(scale);
+        self.timings.arrow += arrow.mul_f64(scale);
+        Ok(batches)
+    }
+
+    /// Claim the next batch index and rows, computing the batch's stateful values
+    fn next_batch_context(&mut self, batch_size: usize) -> Result<(u64, usize, BatchContext)> {
+        let batch_index = self.batch_index;
+        self.batch_index += 1;
+        let seed = snapshot::branch_seed(self.config.seed, self.branch);
+        let file_index = self.file_index;
+
+        let mut ctx = BatchContext {
+            first_row: self.next_row,
+            ..BatchContext::default()
+        };
+        self.next_row += batch_size as u64;
+        if let (Some(config), Some(state)) = (&self.config.trajectory, &mut self.trajectory) {
+            let mut rng = columns::column_rng(seed, file_index, batch_index, usize::MAX);
+            ctx.trajectory = Some(state.advance(config, &mut rng, batch_size)?);
+        }
+        if let Some(config) = &self.config.duplicates {
+            let mut rng = columns::column_rng(seed, file_index, batch_index, usize::MAX - 1);
+            // The pool is dataset-wide; only the picks follow the branch
+            ctx.duplicates = Some(DuplicateBlock::draw(
+                config,
+                self.config.seed,
+                &mut rng,
+                batch_size,
+            ));
+        }
+        if let Some(plan) = columns::correlation_plan(&self.columns) {
+            let mut rng = columns::column_rng(seed, file_index, batch_index, usize::MAX - 2);
+            ctx.correlated = Some(plan.draw(&mut rng, batch_size));
         }
+        Ok((batch_index, batch_size, ctx))
+    }
+
+    /// Generate the columns of one batch, returning generation and assembly time
+    fn build_batch(
+        &self,
+        batch_index: u64,
+        batch_size: usize,
+        ctx: &BatchContext,
+    ) -> Result<(RecordBatch, Duration, Duration)> {
+        let start = Instant::now();
+        let seed = snapshot::branch_seed(self.config.seed, self.branch);
+        let file_index = self.file_index;
 
-        // Create arrays
-        let vector_array = BinaryArray::from_iter_values(vector_data.iter().map(|v| v.as_slice()));
-        let scalar_array = StringArray::from(scalar_data);
+        // Vector columns dominate the cost, so each column is its own task
+        let mut arrays = self
+            .columns
+            .par_iter()
+            .map(|column| {
+                let mut rng = columns::column_rng(seed, file_index, batch_index, column.stream);
+                column.generate(&mut rng, batch_size, ctx)
+            })
+            .collect::<Result<Vec<_>>>()?;
+        self.derived.evaluate(self.schema.fields(), &mut arrays)?;
+        let generated = Instant::now();
 
-        let batch = RecordBatch::try_new(
-            Arc::new(self.schema.clone()),
-            vec![
-                Arc::new(vector_array) as ArrayRef,
-                Arc::new(scalar_array) as ArrayRef,
-            ],
-        )?;
+        let batch = RecordBatch::try_new(self.schema.clone(), arrays)?;
+        Ok((batch, generated - start, generated.elapsed()))
+    }
+
+    /// Generation and batch assembly time spent since the last call
+    pub fn take_timings(&mut self) -> StageTimings {
+        std::mem::take(&mut self.timings)
+    }
 
-        Ok(batch)
+    /// PQ codebook shared by every file of the dataset, if PQ codes are enabled
+    pub fn codebook(&self) -> Option<&pq::Codebook> {
+        self.columns.iter().find_map(|c| match &c.kind {
+            columns::ColumnKind::PqCode { codebook, .. } => Some(codebook.as_ref()),
+            _ => None,
+        })
     }
 
     /// Get the Arrow schema
@@ -161,19 +878,34 @@ impl DataGenerator {
 
     /// Estimate number of rows needed to reach target file size
     pub fn estimate_rows_per_file(&self) -> usize {
-        // Rough estimation: each row has vector (1024 * 4 bytes) + scalar (32 bytes + overhead)
-        // Binary data has some overhead for length encoding
-        let bytes_per_row = (self.config.vector_dim * 4 + 8) + (self.config.scalar_len + 8);
-        (self.config.target_file_size as usize / bytes_per_row).max(1)
+        (self.config.target_file_size as usize / self.estimated_row_bytes()).max(1)
+    }
+
+    /// Estimate number of rows filling a row group of `bytes` bytes
+    pub fn estimate_rows_per_row_group(&self, bytes: u64) -> usize {
+        (bytes as usize / self.estimated_row_bytes()).max(1)
+    }
+
+    /// Rough size of one row from the per-value size of every column
+    ///
+    /// Binary data has some overhead for length encoding.
+    fn estimated_row_bytes(&self) -> usize {
+        self.columns
+            .iter()
+            .map(|c| c.estimated_value_bytes())
+            .sum::<usize>()
+            .max(1)
     }
 }
 
 /// Writer for generating Parquet files
+#[cfg(feature = "parquet")]
 pub struct ParquetWriter {
     config: Config,
     writer_props: WriterProperties,
 }
 
+#[cfg(feature = "parquet")]
 impl ParquetWriter {
     /// Create a new Parquet writer with the given configuration
     pub fn new(config: Config) -> Self {
@@ -190,8 +922,36 @@ impl ParquetWriter {
         // Enable dictionary encoding for better compression
         let builder = builder.set_dictionary_enabled(true);
 
-        // Set row group size to optimize for large files
-        let builder = builder.set_max_row_group_size(100_000);
+        // Set row group size to optimize for large files, to the exact group
+        // size of pruning fixtures, or to the rows filling a byte budget at
+        // this schema's width
+        let row_group_rows = match (config.pruning, config.row_group_size) {
+            (Some(pruning), _) => pruning.row_group_rows,
+            (None, Some(bytes)) => DataGenerator::try_new(config.clone())
+                .map_or(100_000, |generator| generator.estimate_rows_per_row_group(bytes)),
+            (None, None) => 100_000,
+        };
+        let builder = builder.set_max_row_group_size(row_group_rows);
+
+        // Page-level statistics with pages of exactly `page_rows` rows: the
+        // row limit is only checked between write batches, which sinks slice
+        // at page boundaries, and the byte limit must not split vector pages
+        // early
+        let builder = match config.page_index {
+            Some(page_index) => builder
+                .set_statistics_enabled(EnabledStatistics::Page)
+                .set_data_page_row_count_limit(page_index.page_rows)
+                .set_write_batch_size(page_index.page_rows)
+                .set_data_page_size_limit(usize::MAX)
+                .set_dictionary_page_size_limit(usize::MAX),
+            None => builder,
+        };
+
+        // Record which algorithm produced the data
+        let builder = builder.set_key_value_metadata(Some(vec![KeyValue::new(
+            GENERATOR_VERSION_KEY.to_string(),
+            config.generator_version.to_string(),
+        )]));
 
         Self {
             config,
@@ -199,6 +959,11 @@ impl ParquetWriter {
         }
     }
 
+    /// Maximum rows per row group of written files
+    pub fn row_group_rows(&self) -> usize {
+        self.writer_props.max_row_group_size()
+    }
+
     /// Write data to a Parquet file
     pub fn write_to_file(
         &self,
@@ -207,39 +972,122 @@ impl ParquetWriter {
         num_rows: usize,
         batch_size: usize,
     ) -> Result<usize> {
-        let file = File::create(file_path)
-            .with_context(|| format!("Failed to create file: {}", file_path))?;
+        self.write_to_file_cancellable(
+            file_path,
+            data_generator,
+            num_rows,
+            batch_size,
+            &CancellationToken::new(),
+        )
+    }
 
-        let schema = data_generator.schema().clone();
-        let mut writer = ArrowWriter::try_new(
-            file,
-            Arc::new(schema),
-            Some(self.writer_props.clone()),
-        )?;
+    /// Write data to a Parquet file, stopping early if `cancel` is triggered
+    ///
+    /// The token is checked before each batch. On cancellation the file is
+    /// finalized or removed according to `Config::cancel_policy`, and a
+    /// [`Cancelled`] error is returned.
+    pub fn write_to_file_cancellable(
+        &self,
+        file_path: &str,
+        data_generator: &mut DataGenerator,
+        num_rows: usize,
+        batch_size: usize,
+        cancel: &CancellationToken,
+    ) -> Result<usize> {
+        let mut sink = self.create_sink(file_path, data_generator.schema())?;
+        sink::write_rows(
+            &mut sink,
+            data_generator,
+            num_rows,
+            batch_size,
+            cancel,
+            self.config.cancel_policy,
+        )
+    }
 
+    /// Write rows to a Parquet file until its encoded size reaches about
+    /// `max_bytes`, returning how many rows fit
+    ///
+    /// The first batch is sized from the schema's estimated row width, later
+    /// ones from the encoded size per row so far, shrinking to the rows that
+    /// are expected to fill the rest of the budget; writing stops when not
+    /// even one more row is expected to fit. The footer written when the file
+    /// is finished comes on top, so the file can end slightly past the budget.
+    pub fn write_to_file_until_size(
+        &self,
+        file_path: &str,
+        data_generator: &mut DataGenerator,
+        max_bytes: u64,
+        batch_size: usize,
+    ) -> Result<usize> {
+        let config = &data_generator.config;
+        if config.shuffle_buffer.is_some() || config.clustering.is_some() {
+            bail!("a byte budget cannot be combined with shuffling or clustering");
+        }
+        let mut sink = self.create_sink(file_path, data_generator.schema())?;
+        let batch_size = batch_size.max(1).min(data_generator.max_batch_rows());
         let mut total_rows = 0;
-        let mut remaining_rows = num_rows;
+        loop {
+            let size = sink.encoded_size();
+            let remaining = max_bytes.saturating_sub(size);
+            let fitting = match total_rows {
+                0 => data_generator.estimate_rows_per_row_group(remaining),
+                rows => (remaining as f64 * rows as f64 / size.max(1) as f64) as usize,
+            };
+            if remaining == 0 || fitting == 0 {
+                break;
+            }
+            let batch = data_generator.generate_batch(fitting.min(batch_size))?;
+            sink.write(&batch)?;
+            total_rows += batch.num_rows();
+        }
+        sink.finish()?;
+        Ok(total_rows)
+    }
 
+    /// Write data to an in-memory Parquet file and return its bytes
+    pub fn write_to_bytes(
+        &self,
+        data_generator: &mut DataGenerator,
+        num_rows: usize,
+        batch_size: usize,
+    ) -> Result<Vec<u8>> {
+        let schema = Arc::new(data_generator.schema().clone());
+        let mut writer =
+            parquet::arrow::ArrowWriter::try_new(Vec::new(), schema, Some(self.writer_props.clone()))?;
+        let batch_size = batch_size.max(1).min(data_generator.max_batch_rows());
+        let mut remaining_rows = num_rows;
         while remaining_rows > 0 {
-            let current_batch_size = batch_size.min(remaining_rows);
-            let batch = data_generator.generate_batch(current_batch_size)?;
-
-            let batch_rows = batch.num_rows();
+            let batch = data_generator.generate_batch(batch_size.min(remaining_rows))?;
             writer.write(&batch)?;
-
-            total_rows += batch_rows;
-            remaining_rows -= batch_rows;
+            remaining_rows -= batch.num_rows();
         }
+        Ok(writer.into_inner()?)
+    }
 
-        writer.close()?;
+    /// Create a Parquet file sink using this writer's properties
+    pub fn create_sink(
+        &self,
+        file_path: impl AsRef<Path>,
+        schema: &Schema,
+    ) -> Result<ParquetFileSink> {
+        Ok(
+            ParquetFileSink::create(file_path, Arc::new(schema.clone()), self.writer_props.clone())?
+                .with_page_rows(self.config.page_index.map(|page_index| page_index.page_rows)),
+        )
+    }
 
-        Ok(total_rows)
+    /// Writer properties derived from the configuration
+    pub fn writer_properties(&self) -> &WriterProperties {
+        &self.writer_props
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rand::rngs::StdRng;
+    #[cfg(feature = "parquet")]
     use tempfile::NamedTempFile;
 
     #[test]
@@ -262,34 +1110,4332 @@ mod tests {
     }
 
     #[test]
-    fn test_parquet_writing() {
-        let config = Config::default();
+    fn test_core_values() {
+        // Raw values without building a batch
+        let mut rng = StdRng::seed_from_u64(core_gen::stream_seed(42, &[1, 2]));
+        let vector = core_gen::sample_vector(&mut rng, 16);
+        assert!(vector.iter().all(|v| (-1.0..1.0).contains(v)));
+
+        let mut bytes = Vec::new();
+        core_gen::encode_vector(&vector, VectorElementType::Float64, &mut bytes);
+        assert_eq!(bytes.len(), 16 * 8);
+        assert_eq!(core_gen::sample_scalar(&mut rng, 12).len(), 12);
+        assert_ne!(core_gen::stream_seed(42, &[1]), core_gen::stream_seed(42, &[2]));
+    }
+
+    #[test]
+    fn test_parallel_columns_deterministic() {
+        let config = Config {
+            vector_columns: 4,
+            vector_dim: 16,
+            ..Config::default()
+        };
+        let mut a = DataGenerator::new(config.clone());
+        let mut b = DataGenerator::new(config);
+
+        let batch_a = a.generate_batch(50).unwrap();
+        let batch_b = b.generate_batch(50).unwrap();
+        assert_eq!(batch_a, batch_b);
+
+        let names: Vec<_> = a.schema().fields().iter().map(|f| f.name().clone()).collect();
+        assert_eq!(names, ["vector", "vector_1", "vector_2", "vector_3", "scalar"]);
+        assert_ne!(batch_a.column(0), batch_a.column(1));
+
+        // Later batches draw from fresh streams
+        assert_ne!(a.generate_batch(50).unwrap(), batch_a);
+    }
+
+    #[test]
+    fn test_mixed_precision() {
+        let config = Config {
+            vector_dim: 8,
+            vector_type: VectorElementType::Float64,
+            mixed_precision: Some(VectorElementType::Float32),
+            ..Config::default()
+        };
+        config.validate().unwrap();
+        let mut generator = DataGenerator::new(config);
+        let batch = generator.generate_batch(5).unwrap();
+
+        let wide = batch.column_by_name("vector").unwrap();
+        let wide = wide.as_any().downcast_ref::<arrow::array::BinaryArray>().unwrap();
+        let narrow = batch.column_by_name("vector_f32").unwrap();
+        let narrow = narrow.as_any().downcast_ref::<arrow::array::BinaryArray>().unwrap();
+
+        for row in 0..batch.num_rows() {
+            assert_eq!(wide.value(row).len(), 8 * 8);
+            assert_eq!(narrow.value(row).len(), 8 * 4);
+            for (w, n) in wide.value(row).chunks(8).zip(narrow.value(row).chunks(4)) {
+                let w = f64::from_le_bytes(w.try_into().unwrap());
+                let n = f32::from_le_bytes(n.try_into().unwrap());
+                assert_eq!(w as f32, n);
+            }
+        }
+
+        let invalid = Config {
+            mixed_precision: Some(VectorElementType::Float32),
+            ..Config::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_pq_codes() {
+        let pq = PqConfig {
+            subquantizers: 4,
+            nbits: 6,
+        };
+        let config = Config {
+            vector_dim: 16,
+            pq: Some(pq),
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::for_file(config.clone(), 3).unwrap();
+        let batch = generator.generate_batch(20).unwrap();
+
+        let codes = batch.column_by_name("pq_code").unwrap();
+        let codes = codes
+            .as_any()
+            .downcast_ref::<arrow::array::FixedSizeBinaryArray>()
+            .unwrap();
+        assert_eq!(codes.value_length(), 3);
+
+        // Every code must name the nearest centroid of its sub-vector
+        let codebook = pq::Codebook::generate(16, pq, columns::codebook_seed(&config)).unwrap();
+        let vectors = batch.column_by_name("vector").unwrap();
+        let vectors = vectors.as_any().downcast_ref::<arrow::array::BinaryArray>().unwrap();
+        for row in 0..batch.num_rows() {
+            let vector: Vec<f32> = vectors
+                .value(row)
+                .chunks(4)
+                .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+                .collect();
+            for sub in 0..4 {
+                let sub_vector = &vector[sub * 4..(sub + 1) * 4];
+                let dist = |code: usize| -> f32 {
+                    codebook
+                        .centroid(sub, code)
+                        .iter()
+                        .zip(sub_vector)
+                        .map(|(c, v)| (c - v) * (c - v))
+                        .sum()
+                };
+                let code = pq::unpack_code(codes.value(row), sub, 6);
+                assert!((0..64).all(|other| dist(code) <= dist(other) + 1e-5));
+            }
+        }
+
+        let centroids = companion::codebook_batch(generator.codebook().unwrap()).unwrap();
+        assert_eq!(centroids.num_rows(), 4 * 64);
+
+        let invalid = Config {
+            vector_dim: 10,
+            pq: Some(pq),
+            ..Config::default()
+        };
+        assert!(DataGenerator::try_new(invalid).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_tenant_partitioning() {
+        let skew: Skew = "zipf(1.1)".parse().unwrap();
+        let counts = skew.apportion(1000, 10);
+        assert_eq!(counts.iter().sum::<usize>(), 1000);
+        assert!(counts.windows(2).all(|w| w[0] >= w[1]));
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            vector_dim: 8,
+            tenants: Some(TenantConfig {
+                partitioned: true,
+                ..TenantConfig::new(10, skew)
+            }),
+            ..Config::default()
+        };
+        let options = DatasetOptions {
+            output_dir: temp_dir.path().to_path_buf(),
+            prefix: "part".to_string(),
+            total_rows: 1000,
+            batch_size: 64,
+            layout: None,
+        };
+        let report =
+            dataset::generate(&config, &options, &CancellationToken::new(), |_| {}).unwrap();
+
+        assert_eq!(report.total_rows, 1000);
+        for file in &report.files {
+            let tenant = file.tenant.unwrap();
+            let dir = temp_dir.path().join(format!("tenant_id={}", tenant));
+            assert!(file.path.starts_with(dir));
+            assert_eq!(file.rows, counts[tenant as usize]);
+        }
+
+        // Unpartitioned rows sample tenants with the same skew
+        let mut generator = DataGenerator::new(Config {
+            tenants: Some(TenantConfig::new(10, skew)),
+            ..config
+        });
+        let batch = generator.generate_batch(500).unwrap();
+        let tenants = batch.column_by_name("tenant_id").unwrap();
+        let tenants = tenants.as_any().downcast_ref::<arrow::array::Int32Array>().unwrap();
+        assert!(tenants.values().iter().all(|t| (0..10).contains(t)));
+    }
+
+    #[test]
+    fn test_trajectories() {
+        let config = Config {
+            vector_dim: 4,
+            trajectory: Some(TrajectoryConfig {
+                entities: 3,
+                step_std: 0.01,
+                start_ms: 1_000,
+                interval_ms: 500,
+            }),
+            ..Config::default()
+        };
         let mut generator = DataGenerator::new(config.clone());
-        let writer = ParquetWriter::new(config);
+        let first = generator.generate_batch(4).unwrap();
+        let second = generator.generate_batch(5).unwrap();
 
-        let temp_file = NamedTempFile::new().unwrap();
-        let file_path = temp_file.path().to_str().unwrap();
+        let ids = |b: &RecordBatch| -> Vec<i64> {
+            let ids = b.column_by_name("entity_id").unwrap();
+            ids.as_any().downcast_ref::<arrow::array::Int64Array>().unwrap().values().to_vec()
+        };
+        let times = |b: &RecordBatch| -> Vec<i64> {
+            let ts = b.column_by_name("ts").unwrap();
+            let ts = ts.as_any().downcast_ref::<arrow::array::TimestampMillisecondArray>();
+            ts.unwrap().values().to_vec()
+        };
+        assert_eq!(ids(&first), [0, 1, 2, 0]);
+        assert_eq!(ids(&second), [1, 2, 0, 1, 2]);
+        assert_eq!(times(&first), [1_000, 1_000, 1_000, 1_500]);
+        assert_eq!(times(&second), [1_500, 1_500, 2_000, 2_000, 2_000]);
 
-        let rows_written = writer.write_to_file(
-            file_path,
-            &mut generator,
-            100,
-            10,
-        ).unwrap();
+        // Entity 0 moves by a small step between visits
+        let vectors = first.column_by_name("vector").unwrap();
+        let vectors = vectors.as_any().downcast_ref::<arrow::array::BinaryArray>().unwrap();
+        let decode = |bytes: &[u8]| -> Vec<f32> {
+            bytes.chunks(4).map(|b| f32::from_le_bytes(b.try_into().unwrap())).collect()
+        };
+        let (a, b) = (decode(vectors.value(0)), decode(vectors.value(3)));
+        assert_ne!(a, b);
+        assert!(a.iter().zip(&b).all(|(x, y)| (x - y).abs() < 0.1));
 
-        assert_eq!(rows_written, 100);
+        // State carries over into the next file
+        let mut next = DataGenerator::for_file(config, 1).unwrap();
+        next.resume_trajectories(generator.take_trajectory_state().unwrap());
+        assert_eq!(ids(&next.generate_batch(2).unwrap()), [0, 1]);
+    }
 
-        // Verify file exists and has content
-        let metadata = std::fs::metadata(file_path).unwrap();
-        assert!(metadata.len() > 0);
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_edge_list() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            vector_dim: 4,
+            edges: Some(EdgeConfig {
+                degree: DegreeDistribution::Constant(3),
+                destination_skew: Skew::Zipf(1.0),
+            }),
+            ..Config::default()
+        };
+        let options = DatasetOptions {
+            output_dir: temp_dir.path().to_path_buf(),
+            prefix: "nodes".to_string(),
+            total_rows: 200,
+            batch_size: 50,
+            layout: None,
+        };
+        let report =
+            dataset::generate(&config, &options, &CancellationToken::new(), |_| {}).unwrap();
+
+        let (path, count) = report.edges.unwrap();
+        assert_eq!(count, 600);
+
+        let file = std::fs::File::open(path).unwrap();
+        let reader = parquet::arrow::arrow_reader::ParquetRecordBatchReader::try_new(file, 1024)
+            .unwrap();
+        let mut edges = 0;
+        for batch in reader {
+            let batch = batch.unwrap();
+            let src = batch.column(0).as_any().downcast_ref::<arrow::array::Int64Array>();
+            let dst = batch.column(1).as_any().downcast_ref::<arrow::array::Int64Array>();
+            for (s, d) in src.unwrap().values().iter().zip(dst.unwrap().values()) {
+                assert_ne!(s, d);
+                assert!((0..200).contains(d));
+            }
+            edges += batch.num_rows();
+        }
+        assert_eq!(edges, 600);
     }
 
     #[test]
-    fn test_estimate_rows() {
-        let config = Config::default();
-        let generator = DataGenerator::new(config);
+    #[cfg(feature = "parquet")]
+    fn test_foreign_key_skew() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            vector_dim: 4,
+            foreign_key: Some(ForeignKeyConfig {
+                column: "doc_id".to_string(),
+                parents: 100,
+                skew: Skew::Zipf(1.5),
+                parent_table: Some("documents".to_string()),
+            }),
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config.clone());
+        let batch = generator.generate_batch(2000).unwrap();
+        let keys = batch.column_by_name("doc_id").unwrap();
+        let keys = keys.as_any().downcast_ref::<arrow::array::Int64Array>().unwrap();
 
-        let estimated = generator.estimate_rows_per_file();
-        assert!(estimated > 0);
+        let mut counts = vec![0usize; 100];
+        for &key in keys.values() {
+            counts[key as usize] += 1;
+        }
+        // The hottest key owns a large share of rows; the tail is sparse
+        assert!(counts[0] > 500);
+        assert!(counts[0] > 10 * counts[50]);
+
+        let options = DatasetOptions {
+            output_dir: temp_dir.path().to_path_buf(),
+            prefix: "chunks".to_string(),
+            total_rows: 10,
+            batch_size: 10,
+            layout: None,
+        };
+        let report =
+            dataset::generate(&config, &options, &CancellationToken::new(), |_| {}).unwrap();
+        let (path, parents) = report.parent_table.unwrap();
+        assert_eq!(parents, 100);
+        assert_eq!(path, temp_dir.path().join("documents.parquet"));
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_exact_row_totals() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            vector_dim: 4,
+            // Neither the estimated rows per file nor the batch size divide the total
+            target_file_size: 20 * 1024,
+            pruning: Some(PruningConfig {
+                row_group_rows: 70,
+                start_ms: 0,
+                interval_ms: 1,
+            }),
+            ..Config::default()
+        };
+        let options = DatasetOptions {
+            output_dir: temp_dir.path().to_path_buf(),
+            prefix: "rows".to_string(),
+            total_rows: 1003,
+            batch_size: 64,
+            layout: None,
+        };
+        let rows_per_file = DataGenerator::new(config.clone()).estimate_rows_per_file();
+        assert_ne!(1003 % rows_per_file, 0);
+        let report = dataset::generate(&config, &options, &CancellationToken::new(), |_| {}).unwrap();
+        assert_eq!(report.total_rows, 1003);
+
+        let mut footer_rows = 0;
+        for file in &report.files {
+            let reader = SerializedFileReader::new(std::fs::File::open(&file.path).unwrap()).unwrap();
+            let groups: Vec<i64> = reader.metadata().row_groups().iter().map(|g| g.num_rows()).collect();
+            assert_eq!(groups.iter().sum::<i64>() as usize, file.rows);
+            // Full row groups, then a final short one with the remainder
+            let (last, full) = groups.split_last().unwrap();
+            assert!(full.iter().all(|&rows| rows == 70));
+            assert_eq!(*last as usize, file.rows - full.len() * 70);
+            footer_rows += file.rows;
+        }
+        assert_eq!(footer_rows, 1003);
+        assert_eq!(report.files.last().unwrap().rows, 1003 % rows_per_file);
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_holdout_sample() {
+        let config = Config {
+            vector_dim: 4,
+            pruning: Some(PruningConfig {
+                row_group_rows: 100,
+                start_ms: 0,
+                interval_ms: 1,
+            }),
+            holdout_rows: Some(30),
+            ..Config::default()
+        };
+        let holdout_ids = |dir: &std::path::Path| {
+            let options = DatasetOptions {
+                output_dir: dir.to_path_buf(),
+                prefix: "rows".to_string(),
+                total_rows: 500,
+                batch_size: 50,
+                layout: None,
+            };
+            let report =
+                dataset::generate(&config, &options, &CancellationToken::new(), |_| {}).unwrap();
+            let (path, rows) = report.holdout.unwrap();
+            assert_eq!(rows, 30);
+            let file = std::fs::File::open(path).unwrap();
+            let reader =
+                parquet::arrow::arrow_reader::ParquetRecordBatchReader::try_new(file, 1024).unwrap();
+            let mut ids: Vec<i64> = reader
+                .flat_map(|b| {
+                    let b = b.unwrap();
+                    let ids = b.column_by_name("id").unwrap();
+                    ids.as_any().downcast_ref::<arrow::array::Int64Array>().unwrap().values().to_vec()
+                })
+                .collect();
+            ids.sort();
+            ids
+        };
+        let (a, b) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let ids = holdout_ids(a.path());
+        assert_eq!(ids, holdout_ids(b.path()));
+        ids.windows(2).for_each(|w| assert!(w[0] < w[1]));
+        // Drawn from the whole run, not just the first rows
+        assert!(*ids.last().unwrap() >= 100 && *ids.last().unwrap() < 500);
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_index_sidecar() {
+        use arrow::array::{Int32Array, Int64Array, StringArray};
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            vector_dim: 4,
+            target_file_size: 16 * 1024,
+            pruning: Some(PruningConfig {
+                row_group_rows: 30,
+                start_ms: 0,
+                interval_ms: 1,
+            }),
+            index_column: Some("id".to_string()),
+            ..Config::default()
+        };
+        let options = DatasetOptions {
+            output_dir: temp_dir.path().to_path_buf(),
+            prefix: "rows".to_string(),
+            total_rows: 500,
+            batch_size: 64,
+            layout: None,
+        };
+        let report = dataset::generate(&config, &options, &CancellationToken::new(), |_| {}).unwrap();
+        let (path, rows) = report.index.unwrap();
+        assert_eq!(path, temp_dir.path().join(dataset::INDEX_FILE_NAME));
+        assert_eq!(rows, 500);
+
+        // Ids of every row group of a data file
+        let groups = |file: &str| {
+            let open = || ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(temp_dir.path().join(file)).unwrap()).unwrap();
+            (0..open().metadata().num_row_groups())
+                .map(|group| {
+                    open()
+                        .with_row_groups(vec![group])
+                        .build()
+                        .unwrap()
+                        .flat_map(|b| {
+                            let ids = b.unwrap().column_by_name("id").unwrap().clone();
+                            ids.as_any().downcast_ref::<Int64Array>().unwrap().values().to_vec()
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+        let reader = parquet::arrow::arrow_reader::ParquetRecordBatchReader::try_new(std::fs::File::open(path).unwrap(), 1024).unwrap();
+        let mut seen = Vec::new();
+        for batch in reader {
+            let batch = batch.unwrap();
+            let column = |name: &str| batch.column_by_name(name).unwrap().clone();
+            let (ids, files, row_groups, offsets) = (column("id"), column("file"), column("row_group"), column("row_offset"));
+            let ids = ids.as_any().downcast_ref::<Int64Array>().unwrap();
+            let files = files.as_any().downcast_ref::<StringArray>().unwrap();
+            let row_groups = row_groups.as_any().downcast_ref::<Int32Array>().unwrap();
+            let offsets = offsets.as_any().downcast_ref::<Int64Array>().unwrap();
+            for row in 0..batch.num_rows() {
+                let located = groups(files.value(row))[row_groups.value(row) as usize][offsets.value(row) as usize];
+                assert_eq!(located, ids.value(row));
+                seen.push(ids.value(row));
+            }
+        }
+        assert_eq!(seen, (0..500).collect::<Vec<_>>());
+        assert!(report.files.len() > 1);
+    }
+
+    #[test]
+    fn test_parallel_batches() {
+        struct Collect(Vec<RecordBatch>);
+        impl Sink for Collect {
+            fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+                self.0.push(batch.clone());
+                Ok(())
+            }
+            fn finish(&mut self) -> Result<()> {
+                Ok(())
+            }
+        }
+
+        let config = Config {
+            vector_dim: 8,
+            trajectory: Some(TrajectoryConfig {
+                entities: 3,
+                step_std: 0.01,
+                start_ms: 1_000,
+                interval_ms: 500,
+            }),
+            ..Config::default()
+        };
+        let batches = |parallel_batches| {
+            let mut generator = DataGenerator::new(Config { parallel_batches, ..config.clone() });
+            let mut sink = Collect(Vec::new());
+            let rows = sink::write_rows(&mut sink, &mut generator, 105, 10, &CancellationToken::new(), CancelPolicy::Finalize).unwrap();
+            assert_eq!(rows, 105);
+            sink.0
+        };
+        let sequential = batches(None);
+        assert_eq!(sequential.len(), 11);
+        // Stateful walks and every batch match regardless of batches in flight
+        assert_eq!(batches(Some(4)), sequential);
+        assert_eq!(batches(Some(16)), sequential);
+
+        let mut generator = DataGenerator::new(config.clone());
+        let together = generator.generate_batches(&[10, 3, 7]).unwrap();
+        let mut generator = DataGenerator::new(config);
+        let one_by_one: Vec<_> = [10, 3, 7].iter().map(|&n| generator.generate_batch(n).unwrap()).collect();
+        assert_eq!(together, one_by_one);
+    }
+
+    #[test]
+    fn test_clustered_rows() {
+        use arrow::array::Float64Array;
+
+        struct Collect(Vec<RecordBatch>);
+        impl Sink for Collect {
+            fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+                self.0.push(batch.clone());
+                Ok(())
+            }
+            fn finish(&mut self) -> Result<()> {
+                Ok(())
+            }
+        }
+
+        let base = Config {
+            vector_dim: 4,
+            tenants: Some(TenantConfig::new(10, Skew::Uniform)),
+            derived: vec!["norm = l2(vector)".parse().unwrap()],
+            ..Config::default()
+        };
+        let rows = |clustering: Option<ClusterConfig>| {
+            let mut generator = DataGenerator::new(Config { clustering, ..base.clone() });
+            let mut sink = Collect(Vec::new());
+            sink::write_rows(&mut sink, &mut generator, 100, 10, &CancellationToken::new(), CancelPolicy::Finalize).unwrap();
+            assert!(sink.0.iter().all(|b| b.num_rows() == 10));
+            arrow::compute::concat_batches(&sink.0[0].schema(), &sink.0).unwrap()
+        };
+        let norms = |batch: &RecordBatch| {
+            let norms = batch.column_by_name("norm").unwrap();
+            norms.as_any().downcast_ref::<Float64Array>().unwrap().values().to_vec()
+        };
+        let mut expected = norms(&rows(None));
+        expected.sort_by(f64::total_cmp);
+
+        // One column clusters to a plain sort
+        let by_norm = ClusterConfig {
+            columns: vec!["norm".to_string()],
+            curve: Curve::Hilbert,
+        };
+        assert_eq!(norms(&rows(Some(by_norm))), expected);
+
+        for curve in [Curve::ZOrder, Curve::Hilbert] {
+            let clustering = ClusterConfig {
+                columns: vec!["tenant_id".to_string(), "norm".to_string()],
+                curve,
+            };
+            let batch = rows(Some(clustering.clone()));
+            let mut sorted = norms(&batch);
+            sorted.sort_by(f64::total_cmp);
+            assert_eq!(sorted, expected);
+            let keys = clustering.keys(&batch).unwrap();
+            assert!(keys.windows(2).all(|w| w[0] <= w[1]), "{} keys out of order", curve);
+        }
+
+        let unknown = Config {
+            clustering: Some(ClusterConfig {
+                columns: vec!["category".to_string()],
+                curve: Curve::ZOrder,
+            }),
+            ..base.clone()
+        };
+        let mut generator = DataGenerator::new(unknown);
+        let mut sink = Collect(Vec::new());
+        assert!(sink::write_rows(&mut sink, &mut generator, 10, 10, &CancellationToken::new(), CancelPolicy::Finalize).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_bucketed_dataset() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            vector_dim: 4,
+            pruning: Some(PruningConfig {
+                row_group_rows: 100,
+                start_ms: 0,
+                interval_ms: 1,
+            }),
+            derived: vec!["b = hash(id) % 4".parse().unwrap()],
+            bucketing: Some(BucketConfig {
+                column: "id".to_string(),
+                buckets: 4,
+            }),
+            ..Config::default()
+        };
+        let options = DatasetOptions {
+            output_dir: temp_dir.path().to_path_buf(),
+            prefix: "rows".to_string(),
+            total_rows: 200,
+            batch_size: 64,
+            layout: None,
+        };
+        let report =
+            dataset::generate(&config, &options, &CancellationToken::new(), |_| {}).unwrap();
+        assert_eq!(report.files.len(), 4);
+        assert_eq!(report.total_rows, 200);
+
+        for (bucket, file) in report.files.iter().enumerate() {
+            assert_eq!(file.path, temp_dir.path().join(format!("bucket-{}", bucket)).join("rows-00000000.parquet"));
+            let reader = parquet::arrow::arrow_reader::ParquetRecordBatchReader::try_new(
+                std::fs::File::open(&file.path).unwrap(),
+                1024,
+            )
+            .unwrap();
+            let mut rows = 0;
+            for batch in reader {
+                let batch = batch.unwrap();
+                let b = batch.column_by_name("b").unwrap();
+                let b = b.as_any().downcast_ref::<arrow::array::Int64Array>().unwrap();
+                // The derived hash agrees with the routing
+                assert!(b.values().iter().all(|&v| v == bucket as i64));
+                rows += batch.num_rows();
+            }
+            assert_eq!(rows, file.rows);
+            assert!(rows > 20);
+        }
+    }
+
+    #[test]
+    fn test_session_columns() {
+        let config = Config {
+            vector_dim: 4,
+            sessions: Some(SessionConfig {
+                sessions_per_user: 2,
+                events_per_session: 3,
+            }),
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config);
+        generator.start_at_row(4);
+        let first = generator.generate_batch(5).unwrap();
+        let second = generator.generate_batch(5).unwrap();
+
+        let ids = |batch: &RecordBatch, name: &str| -> Vec<i64> {
+            let column = batch.column_by_name(name).unwrap();
+            column
+                .as_any()
+                .downcast_ref::<arrow::array::Int64Array>()
+                .unwrap()
+                .values()
+                .to_vec()
+        };
+        // Rows 4..14: sessions of 3 events, users of 2 sessions
+        assert_eq!(ids(&first, "session_id"), vec![1, 1, 2, 2, 2]);
+        assert_eq!(ids(&second, "session_id"), vec![3, 3, 3, 4, 4]);
+        assert_eq!(ids(&first, "user_id"), vec![0, 0, 1, 1, 1]);
+        assert_eq!(ids(&second, "user_id"), vec![1, 1, 1, 2, 2]);
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_pruning_row_group_ranges() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use parquet::file::statistics::Statistics;
+
+        let pruning = PruningConfig {
+            row_group_rows: 10,
+            start_ms: 1_000,
+            interval_ms: 5,
+        };
+        let config = Config {
+            vector_dim: 4,
+            pruning: Some(pruning),
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config.clone());
+        generator.start_at_row(100);
+        let temp_file = NamedTempFile::new().unwrap();
+        let file_path = temp_file.path().to_str().unwrap();
+        ParquetWriter::new(config)
+            .write_to_file(file_path, &mut generator, 35, 8)
+            .unwrap();
+
+        let expected = pruning.row_group_ranges(100, 35);
+        assert_eq!(expected.len(), 4);
+        assert_eq!((expected[3].min_id, expected[3].max_id), (130, 134));
+
+        let reader = SerializedFileReader::new(std::fs::File::open(file_path).unwrap()).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.num_row_groups(), expected.len());
+        let column = |name: &str| {
+            metadata
+                .file_metadata()
+                .schema_descr()
+                .columns()
+                .iter()
+                .position(|c| c.name() == name)
+                .unwrap()
+        };
+        let min_max = |group: usize, name: &str| match metadata
+            .row_group(group)
+            .column(column(name))
+            .statistics()
+        {
+            Some(Statistics::Int64(s)) => (*s.min_opt().unwrap(), *s.max_opt().unwrap()),
+            other => panic!("unexpected statistics {:?}", other),
+        };
+        for range in &expected {
+            assert_eq!(metadata.row_group(range.row_group).num_rows(), range.rows as i64);
+            assert_eq!(min_max(range.row_group, "id"), (range.min_id, range.max_id));
+            assert_eq!(min_max(range.row_group, "ts"), (range.min_ts_ms, range.max_ts_ms));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_page_index() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+        use parquet::file::serialized_reader::ReadOptionsBuilder;
+
+        let pruning = PruningConfig {
+            row_group_rows: 10,
+            start_ms: 0,
+            interval_ms: 1,
+        };
+        let config = Config {
+            vector_dim: 64,
+            pruning: Some(pruning),
+            page_index: Some(PageIndexConfig { page_rows: 4 }),
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config.clone());
+        let temp_file = NamedTempFile::new().unwrap();
+        let file_path = temp_file.path().to_str().unwrap();
+        ParquetWriter::new(config)
+            .write_to_file(file_path, &mut generator, 25, 7)
+            .unwrap();
+
+        let pages = pruning.page_ranges(0, 25, 4);
+        // Groups of 10, 10 and 5 rows split into pages of 4, 4, 2 / 4, 4, 2 / 4, 1
+        assert_eq!(pages.len(), 8);
+        assert_eq!((pages[2].min_id, pages[2].max_id), (8, 9));
+        assert_eq!((pages[7].row_group, pages[7].page, pages[7].min_id), (2, 1, 24));
+
+        let options = ReadOptionsBuilder::new().with_page_index().build();
+        let reader =
+            SerializedFileReader::new_with_options(std::fs::File::open(file_path).unwrap(), options)
+                .unwrap();
+        let metadata = reader.metadata();
+        assert!(metadata.column_index().is_some());
+        let offset_index = metadata.offset_index().unwrap();
+        for (group, columns) in offset_index.iter().enumerate() {
+            let expected: Vec<i64> = pages
+                .iter()
+                .filter(|p| p.row_group == group)
+                .map(|p| p.min_id - 10 * group as i64)
+                .collect();
+            // Every column, vectors included, has the same page boundaries
+            for column in columns {
+                let first_rows: Vec<i64> =
+                    column.page_locations().iter().map(|l| l.first_row_index).collect();
+                assert_eq!(first_rows, expected);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_edge_case_files() {
+        use edge_cases::{write_edge_cases, EdgeCase, EdgeCaseOptions};
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            vector_dim: 4,
+            ..Config::default()
+        };
+        let files = write_edge_cases(
+            temp_dir.path(),
+            "edge",
+            &config,
+            &EdgeCaseOptions {
+                huge_rows: 300,
+                tiny_row_groups: 1500,
+                batch_size: 128,
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            files.iter().map(|f| f.case).collect::<Vec<_>>(),
+            EdgeCase::ALL.to_vec()
+        );
+
+        let expected = [(0, 0), (1, 1), (300, 1), (1500, 1500)];
+        for (file, (rows, row_groups)) in files.iter().zip(expected) {
+            let reader = SerializedFileReader::new(std::fs::File::open(&file.path).unwrap()).unwrap();
+            let metadata = reader.metadata();
+            assert_eq!(metadata.file_metadata().num_rows(), rows, "{}", file.case);
+            assert_eq!(metadata.num_row_groups(), row_groups, "{}", file.case);
+            assert_eq!((file.rows, file.row_groups), (rows as usize, row_groups));
+            // The empty file still carries every column
+            assert_eq!(metadata.file_metadata().schema_descr().num_columns(), 2);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_row_group_size_in_bytes() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let rows_per_group = |vector_dim: usize| {
+            let config = Config {
+                vector_dim,
+                row_group_size: Some(64 * 1024),
+                ..Config::default()
+            };
+            let rows = ParquetWriter::new(config.clone()).row_group_rows();
+            let bytes_per_row = DataGenerator::new(config).estimated_row_bytes();
+            assert!(rows * bytes_per_row <= 64 * 1024);
+            assert!((rows + 1) * bytes_per_row > 64 * 1024);
+            rows
+        };
+        // The same byte budget holds far fewer wide rows
+        assert_eq!(rows_per_group(1024), 15);
+        assert!(rows_per_group(16) > 500);
+
+        let config = Config {
+            vector_dim: 1024,
+            row_group_size: Some(64 * 1024),
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config.clone());
+        let temp_file = NamedTempFile::new().unwrap();
+        let file_path = temp_file.path().to_str().unwrap();
+        ParquetWriter::new(config)
+            .write_to_file(file_path, &mut generator, 100, 40)
+            .unwrap();
+        let reader = SerializedFileReader::new(std::fs::File::open(file_path).unwrap()).unwrap();
+        assert_eq!(reader.metadata().num_row_groups(), 7);
+        assert_eq!(reader.metadata().row_group(0).num_rows(), 15);
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_payload_compressibility() {
+        use arrow::array::BinaryArray;
+
+        let file_size = |entropy: PayloadEntropy| {
+            let config = Config {
+                vector_dim: 1,
+                payload: Some(PayloadConfig { len: 1024, entropy }),
+                ..Config::default()
+            };
+            let mut generator = DataGenerator::new(config.clone());
+            let batch = generator.generate_batch(4).unwrap();
+            let payloads = batch.column_by_name("payload").unwrap();
+            let value = payloads.as_any().downcast_ref::<BinaryArray>().unwrap().value(0);
+            assert_eq!(value.len(), 1024);
+            if entropy == PayloadEntropy::Repeating {
+                assert!(value.chunks(payload::PATTERN_LEN).all(|c| c == &value[..payload::PATTERN_LEN]));
+            }
+
+            let temp_file = NamedTempFile::new().unwrap();
+            let file_path = temp_file.path().to_str().unwrap();
+            ParquetWriter::new(config)
+                .write_to_file(file_path, &mut generator, 200, 50)
+                .unwrap();
+            std::fs::metadata(file_path).unwrap().len()
+        };
+        let incompressible = file_size(PayloadEntropy::Incompressible);
+        let repeating = file_size(PayloadEntropy::Repeating);
+        assert!(incompressible > 200 * 1024);
+        assert!(incompressible > 10 * repeating);
+    }
+
+    #[test]
+    fn test_vector_quantization() {
+        use arrow::array::BinaryArray;
+        use std::collections::HashSet;
+
+        let distinct = |quantization: Quantization| {
+            let config = Config {
+                vector_dim: 16,
+                quantization: Some(quantization),
+                ..Config::default()
+            };
+            let batch = DataGenerator::new(config).generate_batch(100).unwrap();
+            let vectors = batch.column_by_name("vector").unwrap();
+            let vectors = vectors.as_any().downcast_ref::<BinaryArray>().unwrap();
+            vectors
+                .iter()
+                .flat_map(|v| core_gen::decode_vector(v.unwrap(), VectorElementType::Float32))
+                .map(|v| (v as f32).to_bits())
+                .collect::<HashSet<_>>()
+        };
+
+        let levels = distinct(Quantization::Levels(5));
+        let expected: HashSet<u32> = [-1.0f32, -0.5, 0.0, 0.5, 1.0].iter().map(|v| v.to_bits()).collect();
+        assert!(levels.is_subset(&expected));
+        // 2 mantissa bits leave 4 values per binade
+        let rounded = distinct(Quantization::MantissaBits(2));
+        assert!(rounded.iter().all(|bits| bits & 0x001F_FFFF == 0));
+        assert!(Quantization::Levels(1).validate().is_err());
+    }
+
+    #[test]
+    fn test_batch_size_limits() {
+        let config = Config {
+            vector_dim: 65536,
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config.clone());
+        // 256KB vectors: 8191 rows fit in 32-bit offsets
+        assert_eq!(generator.max_batch_rows(), 8191);
+        assert!(generator.generate_batch(10_000).is_err());
+
+        let large = Config {
+            large_binary: true,
+            ..config
+        };
+        let generator = DataGenerator::new(large);
+        // Only the 32-byte scalar column is still limited
+        assert_eq!(generator.max_batch_rows(), 2_147_483_647 / 32);
+        assert_eq!(
+            generator.schema().field_with_name("vector").unwrap().data_type(),
+            &arrow::datatypes::DataType::LargeBinary
+        );
+    }
+
+    #[test]
+    fn test_big_endian_vectors() {
+        use arrow::array::BinaryArray;
+
+        let batch_with = |byte_order: ByteOrder| {
+            let config = Config {
+                vector_dim: 8,
+                vector_layout: VectorLayout {
+                    byte_order,
+                    ..VectorLayout::default()
+                },
+                ..Config::default()
+            };
+            DataGenerator::new(config).generate_batch(5).unwrap()
+        };
+        let little = batch_with(ByteOrder::Little);
+        let big = batch_with(ByteOrder::Big);
+        let field = big.schema().field_with_name("vector").unwrap().clone();
+        assert_eq!(field.metadata().get(BYTE_ORDER_KEY).map(String::as_str), Some("big"));
+        assert!(!little.schema().field_with_name("vector").unwrap().metadata().contains_key(BYTE_ORDER_KEY));
+
+        let vectors = |batch: &RecordBatch| {
+            let column = batch.column_by_name("vector").unwrap();
+            column.as_any().downcast_ref::<BinaryArray>().unwrap().clone()
+        };
+        let (little, big) = (vectors(&little), vectors(&big));
+        for row in 0..5 {
+            // Same values, each element's bytes reversed
+            assert_ne!(little.value(row), big.value(row));
+            let swapped: Vec<u8> = big.value(row).chunks(4).flat_map(|b| b.iter().rev().copied()).collect();
+            assert_eq!(little.value(row), swapped.as_slice());
+            let layout = VectorLayout {
+                byte_order: ByteOrder::Big,
+                ..VectorLayout::default()
+            };
+            assert_eq!(
+                core_gen::decode_vector_with(big.value(row), VectorElementType::Float32, layout),
+                core_gen::decode_vector(little.value(row), VectorElementType::Float32)
+            );
+        }
+    }
+
+    #[test]
+    fn test_dim_prefix_vectors() {
+        use arrow::array::BinaryArray;
+
+        let config = Config {
+            vector_dim: 16,
+            vector_layout: VectorLayout {
+                dim_prefix: true,
+                ..VectorLayout::default()
+            },
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config.clone());
+        let field = generator.schema().field_with_name("vector").unwrap().clone();
+        assert_eq!(field.metadata().get(DIM_PREFIX_KEY).map(String::as_str), Some("true"));
+        let batch = generator.generate_batch(3).unwrap();
+        let vectors = batch.column_by_name("vector").unwrap();
+        let vectors = vectors.as_any().downcast_ref::<BinaryArray>().unwrap();
+        for value in vectors.iter().map(Option::unwrap) {
+            // fvecs-style record: u32 dimension, then the elements
+            assert_eq!(value.len(), 4 + 16 * 4);
+            assert_eq!(u32::from_le_bytes(value[..4].try_into().unwrap()), 16);
+            let decoded = core_gen::decode_vector_with(value, VectorElementType::Float32, config.vector_layout);
+            assert_eq!(decoded.len(), 16);
+            assert!(decoded.iter().all(|v| (-1.0..1.0).contains(v)));
+        }
+    }
+
+    #[test]
+    fn test_normal_vectors() {
+        let config = Config {
+            vector_dim: 4096,
+            vector_distribution: VectorDistribution::Normal { mean: 0.5, std: 2.0 },
+            ..Config::default()
+        };
+        let values = DataGenerator::new(config.clone()).generate_vector();
+        let values = core_gen::decode_vector(&values, VectorElementType::Float32);
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let var = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64;
+        assert!((mean - 0.5).abs() < 0.15, "mean {}", mean);
+        assert!((var.sqrt() - 2.0).abs() < 0.15, "std {}", var.sqrt());
+        assert!(values.iter().any(|v| v.abs() > 1.0));
+
+        let invalid = Config {
+            vector_distribution: VectorDistribution::Normal { mean: 0.0, std: 0.0 },
+            ..config
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_normalized_vectors() {
+        use arrow::array::BinaryArray;
+
+        let config = Config {
+            vector_dim: 32,
+            vector_columns: 2,
+            normalize: true,
+            ..Config::default()
+        };
+        let norm = |values: &[f64]| values.iter().map(|v| v * v).sum::<f64>().sqrt();
+        let mut generator = DataGenerator::new(config);
+        let single = generator.generate_vector();
+        assert!((norm(&core_gen::decode_vector(&single, VectorElementType::Float32)) - 1.0).abs() < 1e-6);
+
+        let batch = generator.generate_batch(20).unwrap();
+        for name in ["vector", "vector_1"] {
+            let column = batch.column_by_name(name).unwrap();
+            let column = column.as_any().downcast_ref::<BinaryArray>().unwrap();
+            for value in column.iter().map(Option::unwrap) {
+                let values = core_gen::decode_vector(value, VectorElementType::Float32);
+                assert!((norm(&values) - 1.0).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn test_clustered_vectors() {
+        use arrow::array::BinaryArray;
+
+        let config = Config {
+            vector_dim: 16,
+            mixture: Some(MixtureConfig {
+                clusters: 4,
+                std: 0.01,
+            }),
+            ..Config::default()
+        };
+        let vectors = |file_index| {
+            let mut generator = DataGenerator::for_file(config.clone(), file_index).unwrap();
+            let batch = generator.generate_batch(200).unwrap();
+            let column = batch.column_by_name("vector").unwrap();
+            let column = column.as_any().downcast_ref::<BinaryArray>().unwrap();
+            column
+                .iter()
+                .map(|v| core_gen::decode_vector(v.unwrap(), VectorElementType::Float32))
+                .collect::<Vec<_>>()
+        };
+
+        // Rows of every file gather tightly around the same 4 centroids
+        let mut centers: Vec<Vec<f64>> = Vec::new();
+        for vector in vectors(0).into_iter().chain(vectors(1)) {
+            let near = |c: &Vec<f64>| {
+                c.iter().zip(&vector).map(|(a, b)| (a - b).powi(2)).sum::<f64>().sqrt() < 0.2
+            };
+            if !centers.iter().any(near) {
+                centers.push(vector);
+            }
+        }
+        assert_eq!(centers.len(), 4);
+
+        let invalid = Config {
+            trajectory: Some(TrajectoryConfig {
+                entities: 3,
+                step_std: 0.01,
+                start_ms: 0,
+                interval_ms: 500,
+            }),
+            ..config
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_fixed_size_list_vectors() {
+        use arrow::array::{AsArray, BinaryArray};
+        use arrow::datatypes::{DataType, Float32Type};
+
+        let binary = Config {
+            vector_dim: 8,
+            ..Config::default()
+        };
+        let config = Config {
+            vector_encoding: VectorEncoding::FixedSizeList,
+            ..binary.clone()
+        };
+        let mut generator = DataGenerator::new(config.clone());
+        let field = generator.schema().field_with_name("vector").unwrap().clone();
+        let DataType::FixedSizeList(item, 8) = field.data_type() else {
+            panic!("unexpected vector type {}", field.data_type());
+        };
+        assert_eq!(item.data_type(), &DataType::Float32);
+        assert_eq!(field.metadata().get(VECTOR_DIM_KEY).map(String::as_str), Some("8"));
+
+        // Same seed, same values: only the Arrow type changes
+        let batch = generator.generate_batch(5).unwrap();
+        let lists = batch.column_by_name("vector").unwrap().as_fixed_size_list();
+        let expected = DataGenerator::new(binary).generate_batch(5).unwrap();
+        let expected = expected.column_by_name("vector").unwrap();
+        let expected = expected.as_any().downcast_ref::<BinaryArray>().unwrap();
+        for row in 0..5 {
+            let values: Vec<f64> = lists
+                .value(row)
+                .as_primitive::<Float32Type>()
+                .values()
+                .iter()
+                .map(|&v| v as f64)
+                .collect();
+            assert_eq!(values, core_gen::decode_vector(expected.value(row), VectorElementType::Float32));
+        }
+
+        let invalid = Config {
+            large_binary: true,
+            ..config
+        };
+        assert!(invalid.validate().is_err());
+    }
+
+    #[test]
+    fn test_fixed_size_binary_vectors() {
+        use arrow::array::{AsArray, BinaryArray};
+        use arrow::datatypes::DataType;
+
+        let binary = Config {
+            vector_dim: 8,
+            vector_layout: VectorLayout {
+                byte_order: ByteOrder::Big,
+                dim_prefix: true,
+            },
+            ..Config::default()
+        };
+        let config = Config {
+            vector_encoding: VectorEncoding::FixedSizeBinary,
+            ..binary.clone()
+        };
+        let mut generator = DataGenerator::new(config);
+        let field = generator.schema().field_with_name("vector").unwrap().clone();
+        assert_eq!(field.data_type(), &DataType::FixedSizeBinary(4 + 8 * 4));
+        // Only the string column still limits batches
+        assert_eq!(generator.max_batch_rows(), i32::MAX as usize / binary.scalar_len);
+
+        // Same bytes as the binary encoding, without offsets
+        let batch = generator.generate_batch(5).unwrap();
+        let fixed = batch.column_by_name("vector").unwrap().as_fixed_size_binary();
+        let expected = DataGenerator::new(binary).generate_batch(5).unwrap();
+        let expected = expected.column_by_name("vector").unwrap();
+        let expected = expected.as_any().downcast_ref::<BinaryArray>().unwrap();
+        for row in 0..5 {
+            assert_eq!(fixed.value(row), expected.value(row));
+        }
+    }
+
+    #[test]
+    fn test_wide_column_subsets() {
+        let config = Config {
+            vector_dim: 4,
+            wide: Some(WideConfig {
+                columns: 300,
+                per_file: 20,
+                subset: ColumnSubset::Random,
+            }),
+            ..Config::default()
+        };
+        let wide_names = |schema: &Schema| {
+            schema.fields().iter().map(|f| f.name().clone()).filter(|n| n.starts_with("wide_")).collect::<Vec<_>>()
+        };
+        let first = DataGenerator::for_file(config.clone(), 0).unwrap();
+        let second = DataGenerator::for_file(config.clone(), 1).unwrap();
+        assert_eq!(wide_names(first.schema()).len(), 20);
+        assert_ne!(wide_names(first.schema()), wide_names(second.schema()));
+        // Same file, same subset
+        let again = DataGenerator::for_file(config.clone(), 0).unwrap();
+        assert_eq!(first.schema(), again.schema());
+        let merged = Schema::try_merge([first.schema().clone(), second.schema().clone()]).unwrap();
+        assert!(wide_names(&merged).len() > 20);
+
+        let evolving = Config {
+            wide: Some(WideConfig {
+                columns: 300,
+                per_file: 10,
+                subset: ColumnSubset::Evolving,
+            }),
+            ..config
+        };
+        let mut generator = DataGenerator::for_file(evolving, 5).unwrap();
+        assert_eq!(wide_names(generator.schema()), (0..15).map(wide::column_name).collect::<Vec<_>>());
+        let batch = generator.generate_batch(10).unwrap();
+        assert_eq!(batch.num_columns(), 2 + 15);
+    }
+
+    #[test]
+    fn test_text_tokens() {
+        use arrow::array::{AsArray, Int32Array};
+
+        let config = Config {
+            vector_dim: 4,
+            text: Some(TextConfig {
+                min_words: 3,
+                max_words: 12,
+                vocabulary: 500,
+                tokens: true,
+                locales: Vec::new(),
+            }),
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config.clone());
+        let batch = generator.generate_batch(200).unwrap();
+        let texts = batch.column_by_name("text").unwrap().as_string::<i32>().clone();
+        let tokens = batch.column_by_name("text_tokens").unwrap().as_list::<i32>().clone();
+        let field = generator.schema().field_with_name("text_tokens").unwrap();
+        assert_eq!(field.metadata()[TOKENIZER_SEED_KEY], config.seed.to_string());
+
+        let mut first_words = std::collections::HashMap::new();
+        for row in 0..200 {
+            let text = texts.value(row);
+            let words: Vec<&str> = text.split(' ').collect();
+            assert!((3..=12).contains(&words.len()));
+            *first_words.entry(words[0].to_string()).or_insert(0) += 1;
+            let ids = tokens.value(row);
+            let ids = ids.as_any().downcast_ref::<Int32Array>().unwrap();
+            assert_eq!(ids.values().to_vec(), text::tokenize(text, config.seed));
+        }
+        // Zipf frequencies: the most common word dominates
+        assert!(first_words.values().max().unwrap() * 20 > 200);
+        // Case-insensitive and the same words give the same ids
+        assert_eq!(text::tokenize("Kane, kane!", 7), text::tokenize("kane kane", 7));
+        assert_ne!(text::tokenize("kane", 7), text::tokenize("kane", 8));
+        assert!(batch.column_by_name("lang").is_none());
+    }
+
+    #[test]
+    fn test_text_locales() {
+        use arrow::array::AsArray;
+
+        let config = Config {
+            vector_dim: 4,
+            text: Some(TextConfig {
+                min_words: 2,
+                max_words: 6,
+                vocabulary: 300,
+                tokens: false,
+                locales: ["en", "ru", "ja", "zh"].iter().map(|l| l.parse().unwrap()).collect(),
+            }),
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config);
+        let batch = generator.generate_batch(200).unwrap();
+        let texts = batch.column_by_name("text").unwrap().as_string::<i32>().clone();
+        let langs = batch.column_by_name("lang").unwrap().as_string::<i32>().clone();
+
+        let mut seen = std::collections::BTreeSet::new();
+        for row in 0..200 {
+            let script: fn(char) -> bool = match langs.value(row) {
+                "en" => |c| c.is_ascii_lowercase(),
+                "ru" => |c| ('\u{0400}'..='\u{04FF}').contains(&c),
+                "ja" => |c| ('\u{3040}'..='\u{309F}').contains(&c),
+                "zh" => |c| ('\u{4E00}'..='\u{9FFF}').contains(&c),
+                other => panic!("unexpected lang {}", other),
+            };
+            // Every letter of the row is in the script of its language
+            assert!(texts.value(row).chars().filter(|c| *c != ' ').all(script), "{}", texts.value(row));
+            seen.insert(langs.value(row).to_string());
+        }
+        assert_eq!(seen.len(), 4);
+        assert!("xx".parse::<text::Locale>().is_err());
+    }
+
+    #[test]
+    fn test_derived_columns() {
+        use arrow::array::{BinaryArray, Float64Array, Int64Array};
+
+        let derived = |specs: &[&str]| specs.iter().map(|s| s.parse::<DerivedColumn>().unwrap()).collect::<Vec<_>>();
+        let config = Config {
+            vector_dim: 8,
+            pruning: Some(PruningConfig {
+                row_group_rows: 100,
+                start_ms: 0,
+                interval_ms: 1,
+            }),
+            // Declared before the column it reads
+            derived: derived(&["quarter = bucket % 4", "norm = l2(vector)", "bucket = hash(id) % 16"]),
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config.clone());
+        let batch = generator.generate_batch(50).unwrap();
+        let column = |name: &str| batch.column_by_name(name).unwrap().clone();
+
+        let vectors = column("vector");
+        let vectors = vectors.as_any().downcast_ref::<BinaryArray>().unwrap();
+        let norms = column("norm");
+        let norms = norms.as_any().downcast_ref::<Float64Array>().unwrap();
+        let buckets = column("bucket");
+        let buckets = buckets.as_any().downcast_ref::<Int64Array>().unwrap();
+        let quarters = column("quarter");
+        let quarters = quarters.as_any().downcast_ref::<Int64Array>().unwrap();
+        for row in 0..50 {
+            let values = core_gen::decode_vector(vectors.value(row), VectorElementType::Float32);
+            let norm = values.iter().map(|v| v * v).sum::<f64>().sqrt();
+            assert!((norms.value(row) - norm).abs() < 1e-9);
+            assert!((0..16).contains(&buckets.value(row)));
+            assert_eq!(quarters.value(row), buckets.value(row) % 4);
+        }
+        // Same id, same bucket in every file
+        let mut other = DataGenerator::for_file(config.clone(), 3).unwrap();
+        let again = other.generate_batch(50).unwrap();
+        assert_eq!(again.column_by_name("bucket").unwrap().as_ref(), buckets as &dyn arrow::array::Array);
+
+        let invalid = |specs: &[&str]| DataGenerator::try_new(Config { derived: derived(specs), ..Config::default() }).is_err();
+        assert!(invalid(&["a = hash(b)", "b = hash(a)"]));
+        assert!(invalid(&["a = hash(missing)"]));
+        assert!(invalid(&["a = l2(scalar)"]));
+        assert!(invalid(&["scalar = len(vector)"]));
+        assert!("a = frobnicate(vector)".parse::<DerivedColumn>().is_err());
+    }
+
+    #[test]
+    fn test_null_and_constant_columns() {
+        use arrow::array::{Array, BinaryArray};
+
+        let config = Config {
+            vector_dim: 4,
+            null_columns: vec!["scalar".to_string()],
+            constant_columns: vec!["vector".to_string()],
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config.clone());
+        assert!(generator.schema().field_with_name("scalar").unwrap().is_nullable());
+        let first = generator.generate_batch(10).unwrap();
+        let second = generator.generate_batch(10).unwrap();
+
+        let scalar = first.column_by_name("scalar").unwrap();
+        assert_eq!(scalar.null_count(), 10);
+        let value = |batch: &RecordBatch, row: usize| {
+            let vectors = batch.column_by_name("vector").unwrap();
+            vectors.as_any().downcast_ref::<BinaryArray>().unwrap().value(row).to_vec()
+        };
+        let constant = value(&first, 0);
+        assert!((0..10).all(|row| value(&first, row) == constant && value(&second, row) == constant));
+
+        let unknown = Config {
+            null_columns: vec!["missing".to_string()],
+            ..config.clone()
+        };
+        assert!(DataGenerator::try_new(unknown).is_err());
+        let both = Config {
+            constant_columns: vec!["scalar".to_string()],
+            ..config
+        };
+        assert!(DataGenerator::try_new(both).is_err());
+    }
+
+    #[test]
+    fn test_cross_file_duplicates() {
+        use arrow::array::{Array, BinaryArray, Int64Array};
+        use std::collections::HashMap;
+
+        let config = Config {
+            vector_dim: 8,
+            duplicates: Some(DuplicateConfig {
+                fraction: 0.5,
+                pool_size: 10,
+            }),
+            ..Config::default()
+        };
+        // Pool index -> vector bytes, checked against every file
+        let mut pool: HashMap<i64, Vec<u8>> = HashMap::new();
+        let mut repeated = 0;
+        for file_index in 0..3 {
+            let mut generator = DataGenerator::for_file(config.clone(), file_index).unwrap();
+            let batch = generator.generate_batch(200).unwrap();
+            let vectors = batch.column(0).as_any().downcast_ref::<BinaryArray>().unwrap();
+            let ids = batch.column_by_name("duplicate_id").unwrap();
+            let ids = ids.as_any().downcast_ref::<Int64Array>().unwrap();
+            for row in 0..batch.num_rows() {
+                if ids.is_null(row) {
+                    continue;
+                }
+                repeated += 1;
+                let vector = vectors.value(row).to_vec();
+                let expected = pool.entry(ids.value(row)).or_insert_with(|| vector.clone());
+                assert_eq!(*expected, vector);
+            }
+        }
+        assert!(repeated > 200 && repeated < 400);
+        assert_eq!(pool.len(), 10);
+    }
+
+    #[test]
+    #[cfg(feature = "tools")]
+    fn test_dataset_diff() {
+        let config = Config {
+            vector_dim: 8,
+            target_file_size: 4096,
+            ..Config::default()
+        };
+        let generate_into = |dir: &std::path::Path, config: &Config| {
+            let options = DatasetOptions {
+                output_dir: dir.to_path_buf(),
+                prefix: "data".to_string(),
+                total_rows: 300,
+                batch_size: 50,
+                layout: None,
+            };
+            dataset::generate(config, &options, &CancellationToken::new(), |_| {}).unwrap();
+        };
+        let a = tempfile::tempdir().unwrap();
+        let b = tempfile::tempdir().unwrap();
+        let c = tempfile::tempdir().unwrap();
+        generate_into(a.path(), &config);
+        generate_into(b.path(), &config);
+        generate_into(c.path(), &Config { seed: 7, ..config });
+
+        let options = diff::DiffOptions { sample_rows: Some(5) };
+        let same = diff::compare(a.path(), b.path(), &options).unwrap();
+        assert!(same.is_identical());
+        assert!(same.files_compared > 1);
+
+        let changed = diff::compare(a.path(), c.path(), &options).unwrap();
+        assert!(changed
+            .differences
+            .iter()
+            .any(|d| matches!(d, diff::Difference::Row { column, .. } if column == "vector")));
+
+        std::fs::remove_file(c.path().join("data-00000000.parquet")).unwrap();
+        let missing = diff::compare(a.path(), c.path(), &options).unwrap();
+        assert!(missing.differences.contains(&diff::Difference::MissingFile {
+            path: "data-00000000.parquet".into(),
+            only_in: diff::Side::Left,
+        }));
+    }
+
+    #[test]
+    #[cfg(feature = "ffi")]
+    fn test_c_stream_export() {
+        use arrow::ffi_stream::{ArrowArrayStreamReader, FFI_ArrowArrayStream};
+        use std::ffi::{CStr, CString};
+
+        let config = Config {
+            vector_dim: 8,
+            ..Config::default()
+        };
+        let stream = ffi::export_stream(config.clone(), 25, 10).unwrap();
+        let reader = ArrowArrayStreamReader::try_new(stream).unwrap();
+        let batches: Vec<RecordBatch> = reader.map(Result::unwrap).collect();
+        assert_eq!(batches.iter().map(|b| b.num_rows()).collect::<Vec<_>>(), [10, 10, 5]);
+
+        // Same rows as generating directly
+        let mut generator = DataGenerator::new(config);
+        for batch in &batches {
+            assert_eq!(batch, &generator.generate_batch(batch.num_rows()).unwrap());
+        }
+
+        let json = CString::new(r#"{"vector_dim": 4, "seed": 3}"#).unwrap();
+        let mut out = FFI_ArrowArrayStream::empty();
+        assert_eq!(unsafe { ffi::vdg_export_stream(json.as_ptr(), 3, 2, &mut out) }, 0);
+        let reader = ArrowArrayStreamReader::try_new(out).unwrap();
+        assert_eq!(reader.map(|b| b.unwrap().num_rows()).sum::<usize>(), 3);
+
+        let invalid = CString::new(r#"{"vector_dim": 0}"#).unwrap();
+        let mut out = FFI_ArrowArrayStream::empty();
+        assert_ne!(unsafe { ffi::vdg_export_stream(invalid.as_ptr(), 3, 2, &mut out) }, 0);
+        let message = unsafe { CStr::from_ptr(ffi::vdg_last_error()) };
+        assert!(message.to_str().unwrap().contains("dimension"));
+    }
+
+    #[test]
+    #[cfg(feature = "wasm")]
+    fn test_wasm_ipc_sample() {
+        use arrow::ipc::reader::StreamReader;
+
+        let config = Config {
+            vector_dim: 8,
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config.clone());
+        let bytes = wasm::ipc_bytes(&mut generator, 25, 10).unwrap();
+        let reader = StreamReader::try_new(std::io::Cursor::new(bytes), None).unwrap();
+        assert_eq!(reader.schema().as_ref(), generator.schema());
+        let batches: Vec<RecordBatch> = reader.map(Result::unwrap).collect();
+        assert_eq!(batches.iter().map(|b| b.num_rows()).collect::<Vec<_>>(), [10, 10, 5]);
+
+        // Same rows as generating directly
+        let mut generator = DataGenerator::new(config);
+        for batch in &batches {
+            assert_eq!(batch, &generator.generate_batch(batch.num_rows()).unwrap());
+        }
+
+        // No rows is still a readable stream
+        let bytes = wasm::ipc_bytes(&mut generator, 0, 10).unwrap();
+        let reader = StreamReader::try_new(bytes.as_slice(), None).unwrap();
+        assert_eq!(reader.count(), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "jni")]
+    fn test_jvm_batch_export() {
+        use arrow::array::StructArray;
+        use arrow::ffi::{from_ffi, FFI_ArrowArray, FFI_ArrowSchema};
+
+        let config = Config {
+            vector_dim: 8,
+            ..Config::default()
+        };
+        let generator = DataGenerator::new(config.clone());
+        let mut reader = ffi::GeneratorReader::new(generator, 15, 10);
+        let mut expected = DataGenerator::new(config);
+        for rows in [10, 5] {
+            let mut array = FFI_ArrowArray::empty();
+            let mut schema = FFI_ArrowSchema::empty();
+            assert!(unsafe { jvm::export_next(&mut reader, &mut array, &mut schema) }.unwrap());
+            let data = unsafe { from_ffi(array, &schema) }.unwrap();
+            let batch = RecordBatch::from(StructArray::from(data));
+            assert_eq!(batch, expected.generate_batch(rows).unwrap());
+        }
+        let (mut array, mut schema) = (FFI_ArrowArray::empty(), FFI_ArrowSchema::empty());
+        assert!(!unsafe { jvm::export_next(&mut reader, &mut array, &mut schema) }.unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "tools")]
+    fn test_footer_overhead() {
+        let config = Config {
+            vector_dim: 64,
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config.clone());
+        let temp_file = NamedTempFile::new().unwrap();
+        ParquetWriter::new(config)
+            .write_to_file(temp_file.path().to_str().unwrap(), &mut generator, 40, 10)
+            .unwrap();
+
+        let report = footer::inspect(temp_file.path()).unwrap();
+        assert_eq!((report.rows, report.row_groups), (40, 1));
+        let names: Vec<&str> = report.columns.iter().map(|c| c.column.as_str()).collect();
+        assert_eq!(names, ["vector", "scalar"]);
+        // "PAR1", column chunks, footer, footer length and "PAR1"
+        assert!(report.footer_bytes > 0);
+        assert!(4 + report.data_bytes() + report.footer_bytes + 8 <= report.file_bytes);
+        let scalar = &report.columns[1];
+        assert!(scalar.dictionary_bytes > 0 && scalar.dictionary_bytes < scalar.data_bytes);
+        assert!(scalar.statistics_bytes > 0 && scalar.metadata_bytes() > 0);
+        assert!(report.metadata_ratio() > 0.0);
+        assert_eq!(footer::inspect_all(temp_file.path()).unwrap(), vec![report]);
+    }
+
+    #[test]
+    #[cfg(feature = "tools")]
+    fn test_threaded_output_identical() {
+        let config = Config {
+            vector_dim: 8,
+            target_file_size: 8192,
+            sessions: Some(SessionConfig {
+                sessions_per_user: 3,
+                events_per_session: 4,
+            }),
+            ..Config::default()
+        };
+        let generate_into = |dir: &std::path::Path, threads: usize| {
+            let options = DatasetOptions {
+                output_dir: dir.to_path_buf(),
+                prefix: "data".to_string(),
+                total_rows: 500,
+                batch_size: 30,
+                layout: None,
+            };
+            let config = Config {
+                parallel_batches: (threads > 1).then_some(threads),
+                ..config.clone()
+            };
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+            pool.install(|| {
+                dataset::generate(&config, &options, &CancellationToken::new(), |_| {}).unwrap()
+            });
+        };
+        let single = tempfile::tempdir().unwrap();
+        generate_into(single.path(), 1);
+        // Same bytes in every file whatever the thread count
+        for threads in [2, 8] {
+            let threaded = tempfile::tempdir().unwrap();
+            generate_into(threaded.path(), threads);
+            let report = diff::compare(single.path(), threaded.path(), &diff::DiffOptions::default()).unwrap();
+            assert!(report.is_identical(), "{:?}", report.differences);
+            assert!(report.files_compared > 1);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "tools")]
+    fn test_config_format() {
+        let config = Config {
+            vector_dim: 64,
+            text: Some(TextConfig {
+                min_words: 2,
+                max_words: 5,
+                vocabulary: 100,
+                tokens: true,
+                locales: vec![Locale::Ru],
+            }),
+            ..Config::default()
+        };
+        let value = config_format::to_value(&config).unwrap();
+        assert_eq!(value["format_version"], config_format::CONFIG_FORMAT_VERSION);
+        let loaded = config_format::from_value(value).unwrap();
+        assert_eq!(serde_json::to_value(&loaded).unwrap(), serde_json::to_value(&config).unwrap());
+
+        // Unversioned configs from older releases are format 1; omitted fields default
+        let old = config_format::from_json(r#"{"vector_dim": 16, "seed": 9}"#).unwrap();
+        assert_eq!((old.vector_dim, old.seed, old.scalar_len), (16, 9, 32));
+        let versioned = config_format::from_json(r#"{"format_version": 1, "vector_dim": 16}"#).unwrap();
+        assert_eq!(versioned.vector_dim, 16);
+
+        let err = config_format::from_json(r#"{"vector_dims": 16, "text": {"min_words": 1, "max_words": 2, "vocabulary": 5, "tokens": false, "locale": "en"}}"#)
+            .unwrap_err();
+        assert!(format!("{:#}", err).contains("text.locale, vector_dims"), "{:#}", err);
+        let err = config_format::from_json(r#"{"format_version": 2}"#).unwrap_err();
+        assert!(format!("{:#}", err).contains("newer"));
+    }
+
+    #[test]
+    #[cfg(feature = "tools")]
+    fn test_dataset_manifest() {
+        use manifest::{DatasetManifest, ManifestWriter, RunStatus};
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            vector_dim: 8,
+            target_file_size: 4096,
+            ..Config::default()
+        };
+        let options = DatasetOptions {
+            output_dir: temp_dir.path().to_path_buf(),
+            prefix: "data".to_string(),
+            total_rows: 300,
+            batch_size: 50,
+            layout: None,
+        };
+        let writer = ManifestWriter::create(&config, &options).unwrap();
+        assert_eq!(DatasetManifest::load(temp_dir.path()).unwrap().status, RunStatus::InProgress);
+
+        let mut finished = 0;
+        let report = dataset::generate(&config, &options, &CancellationToken::new(), |event| {
+            if let dataset::DatasetEvent::FileFinished(file) = event {
+                writer.file_finished(file).unwrap();
+                finished += 1;
+                // Every completed file is on disk before the next one starts
+                let current = DatasetManifest::load(temp_dir.path()).unwrap();
+                assert_eq!(current.files.len(), finished);
+                assert_eq!(current.status, RunStatus::InProgress);
+            }
+        })
+        .unwrap();
+        assert!(finished > 1);
+        writer.finish(&report).unwrap();
+
+        let manifest = DatasetManifest::load(temp_dir.path()).unwrap();
+        assert_eq!(manifest.status, RunStatus::Complete);
+        assert_eq!(manifest.rows_written, 300);
+        assert_eq!(manifest.files[0].path, std::path::PathBuf::from("data-00000000.parquet"));
+        assert!(!temp_dir.path().join("_manifest.json.tmp").exists());
+    }
+
+    #[test]
+    #[cfg(feature = "tools")]
+    fn test_golden_manifest() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("golden.json");
+        let config = Config {
+            vector_dim: 16,
+            ..Config::default()
+        };
+        let manifest = golden::GoldenManifest::record(&config, 2, 250, 100).unwrap();
+        manifest.save(&path).unwrap();
+
+        let loaded = golden::GoldenManifest::load(&path).unwrap();
+        assert_eq!(loaded.samples, manifest.samples);
+        assert!(loaded.verify().unwrap().is_empty());
+
+        // Simulate an algorithm change by tampering with the recorded seed
+        let mut changed = loaded.clone();
+        changed.config.seed += 1;
+        assert_eq!(changed.verify().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_generator_v0_reproduces_baseline() {
+        use arrow::array::{BinaryArray, StringArray};
+        use rand::distributions::{Alphanumeric, Distribution, Uniform};
+
+        let config = Config {
+            vector_dim: 8,
+            scalar_len: 4,
+            seed: 42,
+            generator_version: GeneratorVersion::V0,
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::for_file(config, 1).unwrap();
+        let batch = generator.generate_batch(3).unwrap();
+        assert!(batch.schema().field(0).metadata().is_empty());
+
+        // Original algorithm: file seed = seed + file index, vector then scalar per row
+        let mut rng = StdRng::seed_from_u64(43);
+        let uniform = Uniform::new(-1.0f32, 1.0);
+        let vectors = batch.column(0).as_any().downcast_ref::<BinaryArray>().unwrap();
+        let scalars = batch.column(1).as_any().downcast_ref::<StringArray>().unwrap();
+        for row in 0..3 {
+            let expected: Vec<u8> = (0..8)
+                .flat_map(|_| uniform.sample(&mut rng).to_le_bytes())
+                .collect();
+            assert_eq!(vectors.value(row), expected.as_slice());
+            let scalar: String = Alphanumeric
+                .sample_iter(&mut rng)
+                .take(4)
+                .map(char::from)
+                .collect();
+            assert_eq!(scalars.value(row), scalar);
+        }
+
+        let with_pq = Config {
+            generator_version: GeneratorVersion::V0,
+            pq: Some(PqConfig { subquantizers: 8, nbits: 8 }),
+            ..Config::default()
+        };
+        assert!(with_pq.validate().is_err());
+        let with_duplicates = Config {
+            generator_version: GeneratorVersion::V0,
+            duplicates: Some(DuplicateConfig { fraction: 0.5, pool_size: 10 }),
+            ..Config::default()
+        };
+        let error = with_duplicates.validate().unwrap_err().to_string();
+        assert!(error.contains("`duplicates` is not supported by generator version v0"), "{}", error);
+        assert_eq!("v0".parse::<GeneratorVersion>().unwrap(), GeneratorVersion::V0);
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_parquet_writing() {
+        let config = Config::default();
+        let mut generator = DataGenerator::new(config.clone());
+        let writer = ParquetWriter::new(config);
+
+        let temp_file = NamedTempFile::new().unwrap();
+        let file_path = temp_file.path().to_str().unwrap();
+
+        let rows_written = writer.write_to_file(
+            file_path,
+            &mut generator,
+            100,
+            10,
+        ).unwrap();
+
+        assert_eq!(rows_written, 100);
+
+        // Verify file exists and has content
+        let metadata = std::fs::metadata(file_path).unwrap();
+        assert!(metadata.len() > 0);
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_stage_timings() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            vector_dim: 256,
+            target_file_size: 200_000,
+            ..Config::default()
+        };
+        let options = DatasetOptions {
+            output_dir: temp_dir.path().to_path_buf(),
+            prefix: "timed".to_string(),
+            total_rows: 500,
+            batch_size: 100,
+            layout: None,
+        };
+        let report =
+            dataset::generate(&config, &options, &CancellationToken::new(), |_| {}).unwrap();
+        assert!(report.files.len() > 1);
+        for file in &report.files {
+            let timings = file.timings;
+            assert!(timings.generate > std::time::Duration::ZERO);
+            assert!(timings.encode + timings.io > std::time::Duration::ZERO);
+            // Stages are measured inside the file's wall-clock time
+            assert!(timings.total() <= file.elapsed);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "numa")]
+    fn test_numa_generation_matches_sequential() {
+        assert_eq!(numa::parse_cpulist("0-3,8,10-11\n").unwrap(), vec![0, 1, 2, 3, 8, 10, 11]);
+        assert!(numa::parse_cpulist("4-2").is_err());
+
+        let config = Config {
+            vector_dim: 16,
+            target_file_size: 20_000,
+            sessions: Some(SessionConfig {
+                sessions_per_user: 2,
+                events_per_session: 5,
+            }),
+            ..Config::default()
+        };
+        let sequential_dir = tempfile::tempdir().unwrap();
+        let numa_dir = tempfile::tempdir().unwrap();
+        let options = |dir: &tempfile::TempDir| DatasetOptions {
+            output_dir: dir.path().to_path_buf(),
+            prefix: "rows".to_string(),
+            total_rows: 1000,
+            batch_size: 64,
+            layout: None,
+        };
+        let cancel = CancellationToken::new();
+        let sequential =
+            dataset::generate(&config, &options(&sequential_dir), &cancel, |_| {}).unwrap();
+        // Two fake nodes sharing CPU 0 exercise the file split anywhere
+        let nodes = [
+            numa::NumaNode { id: 0, cpus: vec![0] },
+            numa::NumaNode { id: 1, cpus: vec![0] },
+        ];
+        let report =
+            numa::generate(&config, &options(&numa_dir), &cancel, &nodes, |_| {}).unwrap();
+        assert!(report.files.len() > 2);
+        assert_eq!(report.total_rows, sequential.total_rows);
+        for (a, b) in sequential.files.iter().zip(&report.files) {
+            assert_eq!(a.path.file_name(), b.path.file_name());
+            assert_eq!(std::fs::read(&a.path).unwrap(), std::fs::read(&b.path).unwrap());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_write_to_bytes() {
+        let config = Config {
+            vector_dim: 8,
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config.clone());
+        let writer = ParquetWriter::new(config);
+        let bytes = writer.write_to_bytes(&mut generator, 25, 10).unwrap();
+        assert_eq!(&bytes[..4], b"PAR1");
+
+        let temp_file = NamedTempFile::new().unwrap();
+        std::fs::write(temp_file.path(), &bytes).unwrap();
+        let file = std::fs::File::open(temp_file.path()).unwrap();
+        let reader =
+            parquet::arrow::arrow_reader::ParquetRecordBatchReader::try_new(file, 1024).unwrap();
+        let rows: usize = reader.map(|b| b.unwrap().num_rows()).sum();
+        assert_eq!(rows, 25);
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_document_formats() {
+        use formats::{DocumentFormat, DocumentSink};
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            vector_dim: 4,
+            ..Config::default()
+        };
+        let formats = [
+            DocumentFormat::Weaviate {
+                class: "Chunk".to_string(),
+            },
+            DocumentFormat::Vespa {
+                namespace: "ns".to_string(),
+                document_type: "chunk".to_string(),
+            },
+        ];
+        for (i, format) in formats.into_iter().enumerate() {
+            let path = temp_dir.path().join(format!("docs-{}", i));
+            let mut generator = DataGenerator::new(config.clone());
+            let mut sink = DocumentSink::create(&path, format).unwrap();
+            sink::write_rows(
+                &mut sink,
+                &mut generator,
+                5,
+                2,
+                &CancellationToken::new(),
+                CancelPolicy::Finalize,
+            )
+            .unwrap();
+
+            let text = std::fs::read_to_string(&path).unwrap();
+            if i == 0 {
+                let body: serde_json::Value = serde_json::from_str(&text).unwrap();
+                let objects = body["objects"].as_array().unwrap();
+                assert_eq!(objects.len(), 5);
+                assert_eq!(objects[0]["class"], "Chunk");
+                assert_eq!(objects[0]["vector"].as_array().unwrap().len(), 4);
+                assert!(objects[0]["properties"]["scalar"].is_string());
+            } else {
+                let docs: Vec<serde_json::Value> = text
+                    .lines()
+                    .map(|line| serde_json::from_str(line).unwrap())
+                    .collect();
+                assert_eq!(docs.len(), 5);
+                assert_eq!(docs[4]["put"], "id:ns:chunk::4");
+                assert_eq!(docs[4]["fields"]["vector"]["values"].as_array().unwrap().len(), 4);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "flight")]
+    fn test_flight_requests() {
+        use sink::flight::{next_stream, request_metadata};
+
+        let headers = [
+            ("Authorization", "Bearer abc"),
+            ("x-tenant", "a"),
+            ("x-tenant", "b"),
+        ]
+        .map(|(key, value)| (key.to_string(), value.to_string()));
+        let metadata = request_metadata(&headers).unwrap();
+        assert_eq!((metadata.len(), metadata.keys_len()), (3, 2));
+        // The bearer token is sent under the lowercased name
+        assert_eq!(metadata.get("authorization").unwrap(), "Bearer abc");
+        // Repeated headers send every value, in order
+        let tenants: Vec<&str> = metadata
+            .get_all("x-tenant")
+            .iter()
+            .map(|value| value.to_str().unwrap())
+            .collect();
+        assert_eq!(tenants, ["a", "b"]);
+        // Names must be valid ASCII metadata keys, and values printable ASCII
+        for key in ["bad key", "x-token-bin", ""] {
+            let err = request_metadata(&[(key.to_string(), "v".to_string())]).unwrap_err();
+            assert!(format!("{:#}", err).contains("Invalid Flight header name"), "{:#}", err);
+        }
+        let err = request_metadata(&[("x-token".to_string(), "a\nb".to_string())]).unwrap_err();
+        assert!(format!("{:#}", err).contains("Invalid value for Flight header x-token"), "{:#}", err);
+
+        // Batches go to every stream in turn
+        let mut stream = 0;
+        let order: Vec<usize> = (0..7)
+            .map(|_| {
+                let current = stream;
+                stream = next_stream(stream, 3);
+                current
+            })
+            .collect();
+        assert_eq!(order, [0, 1, 2, 0, 1, 2, 0]);
+        assert_eq!(next_stream(0, 1), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "redis")]
+    fn test_redis_commands() {
+        use sink::redis::{hset_command, index_command, vadd_command};
+
+        let args = |cmd: &::redis::Cmd| -> Vec<Vec<u8>> {
+            cmd.args_iter()
+                .map(|arg| match arg {
+                    ::redis::Arg::Simple(bytes) => bytes.to_vec(),
+                    ::redis::Arg::Cursor => b"0".to_vec(),
+                })
+                .collect()
+        };
+        let config = Config {
+            vector_dim: 4,
+            ..Config::default()
+        };
+        let expected = DataGenerator::new(config.clone()).generate_batch(2).unwrap();
+        let field = expected.schema().field_with_name("vector").unwrap().clone();
+        let values = columns::vector_values(&field, expected.column_by_name("vector").unwrap(), 1)
+            .unwrap();
+        let fp32: Vec<u8> = values.iter().flat_map(|&v| (v as f32).to_le_bytes()).collect();
+
+        // Hash fields hold little-endian vectors whatever the Arrow encoding
+        for (encoding, layout) in [
+            (VectorEncoding::Binary, VectorLayout::default()),
+            (
+                VectorEncoding::Binary,
+                VectorLayout {
+                    byte_order: ByteOrder::Big,
+                    dim_prefix: true,
+                },
+            ),
+            (VectorEncoding::FixedSizeList, VectorLayout::default()),
+        ] {
+            let batch = DataGenerator::new(Config {
+                vector_encoding: encoding,
+                vector_layout: layout,
+                ..config.clone()
+            })
+            .generate_batch(2)
+            .unwrap();
+            let hset = args(&hset_command("rows:1", &batch, 1).unwrap());
+            assert_eq!(&hset[..2], &[b"HSET".to_vec(), b"rows:1".to_vec()]);
+            let at = hset.iter().position(|arg| arg == b"vector").unwrap();
+            assert_eq!(hset[at + 1], fp32, "{:?} {:?}", encoding, layout);
+            let scalar = hset.iter().position(|arg| arg == b"scalar").unwrap();
+            assert!(!hset[scalar + 1].is_empty());
+
+            let vadd = args(&vadd_command("set", "rows:1", &batch, 0, 1).unwrap());
+            let expected_vadd = [&b"VADD"[..], b"set", b"FP32", &fp32, b"rows:1"].map(<[u8]>::to_vec);
+            assert_eq!(vadd, expected_vadd);
+        }
+
+        // f64 vectors keep their precision in hashes and go to vector sets as f32
+        let batch = DataGenerator::new(Config {
+            vector_type: VectorElementType::Float64,
+            ..config.clone()
+        })
+        .generate_batch(1)
+        .unwrap();
+        let hset = args(&hset_command("rows:0", &batch, 0).unwrap());
+        let at = hset.iter().position(|arg| arg == b"vector").unwrap();
+        assert_eq!(hset[at + 1].len(), 4 * 8);
+        let vadd = args(&vadd_command("set", "rows:0", &batch, 0, 0).unwrap());
+        assert_eq!(vadd[3].len(), 4 * 4);
+
+        let index = args(&index_command("idx", "rows", batch.schema_ref()).unwrap());
+        let index: Vec<String> =
+            index.into_iter().map(|arg| String::from_utf8(arg).unwrap()).collect();
+        assert_eq!(&index[..7], ["FT.CREATE", "idx", "ON", "HASH", "PREFIX", "1", "rows:"]);
+        let at = index.iter().position(|arg| arg == "vector").unwrap();
+        assert_eq!(
+            &index[at..at + 10],
+            ["vector", "VECTOR", "FLAT", "6", "TYPE", "FLOAT64", "DIM", "4", "DISTANCE_METRIC", "COSINE"]
+        );
+
+        // Sparse vectors have no hash form
+        let batch = DataGenerator::new(Config {
+            sparse: Some(SparseConfig {
+                dim: 100,
+                density: 0.1,
+            }),
+            ..config
+        })
+        .generate_batch(1)
+        .unwrap();
+        let error = hset_command("rows:0", &batch, 0).err().unwrap();
+        assert!(format!("{:#}", error).contains("sparse_vector"));
+    }
+
+    #[test]
+    #[cfg(feature = "scylla")]
+    fn test_scylla_statements() {
+        use arrow::array::Array;
+        use arrow::datatypes::{DataType, Field};
+        use scylla::value::CqlValue;
+        use sink::scylla::{cql_type, cql_value, row_values, Statements};
+        use sink::ScyllaSinkOptions;
+
+        let batch_with = |config: Config| {
+            let config = Config {
+                vector_dim: 4,
+                ..config
+            };
+            DataGenerator::new(config).generate_batch(2).unwrap()
+        };
+
+        let options = ScyllaSinkOptions {
+            node: "127.0.0.1:9042".to_string(),
+            keyspace: "bench".to_string(),
+            table: "rows".to_string(),
+            replication_factor: 3,
+            concurrency: 4,
+        };
+        let config = Config {
+            vector_dim: 4,
+            ..Config::default()
+        };
+        let batch = DataGenerator::new(config).generate_batch(3).unwrap();
+        let statements = Statements::new(&options, batch.schema_ref()).unwrap();
+        assert_eq!(
+            statements.create_keyspace,
+            "CREATE KEYSPACE IF NOT EXISTS bench WITH replication = \
+             {'class': 'SimpleStrategy', 'replication_factor': 3}"
+        );
+        let columns: Vec<String> = batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| format!("{} {}", field.name(), cql_type(field).unwrap()))
+            .collect();
+        assert_eq!(
+            statements.create_table,
+            format!(
+                "CREATE TABLE IF NOT EXISTS bench.rows (id bigint PRIMARY KEY, {})",
+                columns.join(", ")
+            )
+        );
+        assert!(statements.create_table.contains("vector list<float>"));
+        assert!(statements.create_table.contains("scalar text"));
+        let placeholders = vec!["?"; batch.num_columns() + 1].join(", ");
+        assert!(statements.insert.starts_with("INSERT INTO bench.rows (id, "));
+        assert!(statements.insert.ends_with(&format!("VALUES ({})", placeholders)));
+
+        // The row number comes first, then one value per column
+        let values = row_values(&batch, 2, 17).unwrap();
+        assert_eq!(values.len(), batch.num_columns() + 1);
+        assert_eq!(values[0], Some(CqlValue::BigInt(17)));
+        assert!(values.iter().skip(1).all(Option::is_some));
+
+        // A generated id column is the primary key
+        let batch = DataGenerator::new(Config {
+            vector_dim: 4,
+            id_column: Some(IdColumn::Uuid),
+            ..Config::default()
+        })
+        .generate_batch(2)
+        .unwrap();
+        let statements = Statements::new(&options, batch.schema_ref()).unwrap();
+        assert!(statements.create_table.contains("(id text PRIMARY KEY, "));
+        assert_eq!(statements.create_table.matches("id ").count(), 1);
+        assert_eq!(statements.insert.matches('?').count(), batch.num_columns());
+        let values = row_values(&batch, 1, 17).unwrap();
+        assert_eq!(values.len(), batch.num_columns());
+        assert!(matches!(&values[0], Some(CqlValue::Text(id)) if id.len() == IdColumn::UUID_LEN));
+
+        // Vectors are lists of their values whatever the encoding and layout
+        let vector = |batch: &RecordBatch| {
+            let index = batch.schema().index_of("vector").unwrap();
+            row_values(batch, 1, 0).unwrap().swap_remove(index + 1)
+        };
+        let plain = vector(&batch_with(Config::default()));
+        assert!(matches!(&plain, Some(CqlValue::List(values)) if values.len() == 4));
+        for (encoding, layout) in [
+            (
+                VectorEncoding::Binary,
+                VectorLayout {
+                    byte_order: ByteOrder::Big,
+                    dim_prefix: true,
+                },
+            ),
+            (VectorEncoding::FixedSizeList, VectorLayout::default()),
+        ] {
+            let batch = batch_with(Config {
+                vector_encoding: encoding,
+                vector_layout: layout,
+                ..Config::default()
+            });
+            let statements = Statements::new(&options, batch.schema_ref()).unwrap();
+            assert!(statements.create_table.contains("vector list<float>"));
+            assert_eq!(vector(&batch), plain);
+        }
+        let batch = batch_with(Config {
+            vector_type: VectorElementType::Float64,
+            ..Config::default()
+        });
+        let statements = Statements::new(&options, batch.schema_ref()).unwrap();
+        assert!(statements.create_table.contains("vector list<double>"));
+        assert!(matches!(vector(&batch), Some(CqlValue::List(values)) if matches!(values[0], CqlValue::Double(_))));
+
+        // Sparse vectors are maps of their non-zero entries
+        let batch = batch_with(Config {
+            sparse: Some(SparseConfig {
+                dim: 100,
+                density: 0.2,
+            }),
+            ..Config::default()
+        });
+        let statements = Statements::new(&options, batch.schema_ref()).unwrap();
+        assert!(statements.create_table.contains("sparse_vector map<int, float>"));
+        let index = batch.schema().index_of("sparse_vector").unwrap();
+        let Some(CqlValue::Map(entries)) = row_values(&batch, 0, 0).unwrap().swap_remove(index + 1)
+        else {
+            panic!("sparse vector is not a map");
+        };
+        assert!(entries.iter().all(|(index, _)| matches!(index, CqlValue::Int(0..=99))));
+
+        // Scalars keep their width
+        let timestamp = DataType::Timestamp(arrow::datatypes::TimeUnit::Millisecond, None);
+        assert_eq!(cql_type(&Field::new("ts", timestamp, false)).unwrap(), "bigint");
+        assert!(cql_type(&Field::new("flag", DataType::Boolean, false)).is_err());
+        let scalar = |data_type: DataType, array: &dyn Array| {
+            cql_value(&Field::new("x", data_type, true), array, 0).unwrap()
+        };
+        let int = arrow::array::Int32Array::from(vec![5]);
+        let float = arrow::array::Float32Array::from(vec![0.5]);
+        let double = arrow::array::Float64Array::from(vec![0.5]);
+        let text = arrow::array::StringArray::from(vec![None::<&str>]);
+        assert_eq!(scalar(DataType::Int32, &int), Some(CqlValue::Int(5)));
+        assert_eq!(scalar(DataType::Float32, &float), Some(CqlValue::Float(0.5)));
+        assert_eq!(scalar(DataType::Float64, &double), Some(CqlValue::Double(0.5)));
+        assert_eq!(scalar(DataType::Utf8, &text), None);
+    }
+
+    #[test]
+    #[cfg(feature = "sqlite")]
+    fn test_sqlite_sink() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("fixtures.sqlite");
+        let config = Config {
+            vector_dim: 4,
+            vector_type: VectorElementType::Float64,
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config);
+        let mut sink = sink::SqliteSink::create(&path, "vectors").unwrap();
+        sink::write_rows(
+            &mut sink,
+            &mut generator,
+            25,
+            10,
+            &CancellationToken::new(),
+            CancelPolicy::Finalize,
+        )
+        .unwrap();
+        drop(sink);
+
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        let (rows, max_id, vector_bytes): (i64, i64, i64) = connection
+            .query_row(
+                "SELECT COUNT(*), MAX(id), MAX(LENGTH(vector)) FROM vectors",
+                [],
+                |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((rows, max_id, vector_bytes), (25, 24, 32));
+
+        // A generated id column is the primary key
+        for (id_column, table) in [(IdColumn::Monotonic, "monotonic"), (IdColumn::Uuid, "uuid")] {
+            let mut generator = DataGenerator::new(Config {
+                vector_dim: 4,
+                id_column: Some(id_column),
+                ..Config::default()
+            });
+            let mut sink = sink::SqliteSink::create(&path, table).unwrap();
+            sink::write_rows(
+                &mut sink,
+                &mut generator,
+                25,
+                10,
+                &CancellationToken::new(),
+                CancelPolicy::Finalize,
+            )
+            .unwrap();
+            drop(sink);
+            let (rows, ids): (i64, i64) = connection
+                .query_row(
+                    &format!("SELECT COUNT(*), COUNT(DISTINCT id) FROM {}", table),
+                    [],
+                    |r| Ok((r.get(0)?, r.get(1)?)),
+                )
+                .unwrap();
+            assert_eq!((rows, ids), (25, 25));
+            let key: i64 = connection
+                .query_row(
+                    &format!("SELECT pk FROM pragma_table_info('{}') WHERE name = 'id'", table),
+                    [],
+                    |r| r.get(0),
+                )
+                .unwrap();
+            assert_eq!(key, 1);
+        }
+
+        // Vectors are little-endian values whatever the encoding and layout
+        let encodings = [
+            ("plain", VectorEncoding::Binary, VectorLayout::default()),
+            (
+                "big_prefixed",
+                VectorEncoding::Binary,
+                VectorLayout {
+                    byte_order: ByteOrder::Big,
+                    dim_prefix: true,
+                },
+            ),
+            ("list", VectorEncoding::FixedSizeList, VectorLayout::default()),
+        ];
+        for (table, encoding, layout) in encodings {
+            let mut generator = DataGenerator::new(Config {
+                vector_dim: 4,
+                vector_encoding: encoding,
+                vector_layout: layout,
+                ..Config::default()
+            });
+            let mut sink = sink::SqliteSink::create(&path, table).unwrap();
+            sink::write_rows(
+                &mut sink,
+                &mut generator,
+                5,
+                5,
+                &CancellationToken::new(),
+                CancelPolicy::Finalize,
+            )
+            .unwrap();
+        }
+        let blobs = |table: &str| -> Vec<Vec<u8>> {
+            let mut statement = connection
+                .prepare(&format!("SELECT vector FROM {} ORDER BY id", table))
+                .unwrap();
+            let rows = statement.query_map([], |r| r.get(0)).unwrap();
+            rows.map(Result::unwrap).collect()
+        };
+        let plain = blobs("plain");
+        assert_eq!(plain.len(), 5);
+        assert!(plain.iter().all(|blob| blob.len() == 16));
+        assert_eq!(blobs("big_prefixed"), plain);
+        assert_eq!(blobs("list"), plain);
+    }
+
+    #[test]
+    #[cfg(feature = "http")]
+    fn test_http_sink() {
+        use std::io::{BufRead, BufReader, Read, Write};
+        use std::net::TcpListener;
+
+        // Minimal HTTP/1.1 server answering every POST with 200
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}/ingest", listener.local_addr().unwrap());
+        let (bodies_tx, bodies_rx) = std::sync::mpsc::channel::<String>();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let bodies_tx = bodies_tx.clone();
+                std::thread::spawn(move || {
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    loop {
+                        let mut length = 0;
+                        let mut line = String::new();
+                        loop {
+                            line.clear();
+                            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                                return;
+                            }
+                            if line == "\r\n" {
+                                break;
+                            }
+                            if let Some((key, value)) = line.split_once(':') {
+                                if key.eq_ignore_ascii_case("content-length") {
+                                    length = value.trim().parse().unwrap();
+                                }
+                            }
+                        }
+                        let mut body = vec![0; length];
+                        reader.read_exact(&mut body).unwrap();
+                        bodies_tx.send(String::from_utf8(body).unwrap()).unwrap();
+                        stream
+                            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                            .unwrap();
+                    }
+                });
+            }
+        });
+
+        let config = Config {
+            vector_dim: 3,
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config);
+        let mut sink = sink::HttpSink::connect(sink::HttpSinkOptions {
+            endpoint: endpoint.clone(),
+            template: Some(r#"{"doc": {{id}}, "embedding": {{vector}}}"#.to_string()),
+            headers: vec![("X-Test".to_string(), "1".to_string())],
+            concurrency: 3,
+            rate: None,
+        })
+        .unwrap();
+        sink::write_rows(
+            &mut sink,
+            &mut generator,
+            20,
+            8,
+            &CancellationToken::new(),
+            CancelPolicy::Finalize,
+        )
+        .unwrap();
+        assert_eq!(sink.requests_sent(), 20);
+
+        let mut ids: Vec<u64> = bodies_rx
+            .try_iter()
+            .map(|body| {
+                let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+                assert_eq!(json["embedding"].as_array().unwrap().len(), 3);
+                json["doc"].as_u64().unwrap()
+            })
+            .collect();
+        ids.sort();
+        assert_eq!(ids, (0..20).collect::<Vec<_>>());
+
+        // A generated id column replaces the row number
+        let mut generator = DataGenerator::new(Config {
+            vector_dim: 3,
+            id_column: Some(IdColumn::Uuid),
+            ..Config::default()
+        });
+        let mut sink = sink::HttpSink::connect(sink::HttpSinkOptions {
+            endpoint,
+            template: None,
+            headers: Vec::new(),
+            concurrency: 1,
+            rate: None,
+        })
+        .unwrap();
+        sink::write_rows(
+            &mut sink,
+            &mut generator,
+            5,
+            5,
+            &CancellationToken::new(),
+            CancelPolicy::Finalize,
+        )
+        .unwrap();
+        let bodies: Vec<String> = bodies_rx.try_iter().collect();
+        assert_eq!(bodies.len(), 5);
+        for body in bodies {
+            assert_eq!(body.matches("\"id\":").count(), 1);
+            let json: serde_json::Value = serde_json::from_str(&body).unwrap();
+            assert_eq!(json["id"].as_str().unwrap().len(), IdColumn::UUID_LEN);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_cancelled_write() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let token = CancellationToken::new();
+        token.cancel();
+
+        for policy in [CancelPolicy::Finalize, CancelPolicy::Discard] {
+            let config = Config {
+                cancel_policy: policy,
+                ..Config::default()
+            };
+            let mut generator = DataGenerator::new(config.clone());
+            let writer = ParquetWriter::new(config);
+            let file_path = temp_dir.path().join(format!("{:?}.parquet", policy));
+            let file_path = file_path.to_str().unwrap();
+
+            let err = writer
+                .write_to_file_cancellable(file_path, &mut generator, 100, 10, &token)
+                .unwrap_err();
+            let cancelled = err.downcast_ref::<Cancelled>().unwrap();
+            assert_eq!(cancelled.rows_written, 0);
+            assert_eq!(cancelled.policy, policy);
+            assert_eq!(
+                std::path::Path::new(file_path).exists(),
+                policy == CancelPolicy::Finalize
+            );
+        }
+    }
+
+    #[test]
+    fn test_shuffle_buffer() {
+        struct Collect(Vec<RecordBatch>);
+        impl Sink for Collect {
+            fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+                self.0.push(batch.clone());
+                Ok(())
+            }
+            fn finish(&mut self) -> Result<()> {
+                Ok(())
+            }
+        }
+
+        let config = Config {
+            vector_dim: 4,
+            pruning: Some(PruningConfig {
+                row_group_rows: 10,
+                start_ms: 0,
+                interval_ms: 1,
+            }),
+            shuffle_buffer: Some(16),
+            ..Config::default()
+        };
+        let ids = || {
+            let mut generator = DataGenerator::new(config.clone());
+            let mut sink = Collect(Vec::new());
+            let rows = sink::write_rows(&mut sink, &mut generator, 100, 10, &CancellationToken::new(), CancelPolicy::Finalize).unwrap();
+            assert_eq!(rows, 100);
+            sink.0
+                .iter()
+                .flat_map(|b| {
+                    let ids = b.column_by_name("id").unwrap();
+                    ids.as_any().downcast_ref::<arrow::array::Int64Array>().unwrap().values().to_vec()
+                })
+                .collect::<Vec<_>>()
+        };
+        let shuffled = ids();
+        assert_eq!(shuffled, ids());
+        assert_ne!(shuffled, (0..100).collect::<Vec<_>>());
+        let mut sorted = shuffled.clone();
+        sorted.sort();
+        assert_eq!(sorted, (0..100).collect::<Vec<_>>());
+        // Rows move at most the buffer plus one batch earlier
+        for (position, id) in shuffled.iter().enumerate() {
+            assert!(*id as usize <= position + 16 + 10);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_bounded_sink_backpressure() {
+        let config = Config::default();
+        let mut generator = DataGenerator::new(config.clone());
+        let writer = ParquetWriter::new(config);
+
+        let temp_file = NamedTempFile::new().unwrap();
+        let inner = writer.create_sink(temp_file.path(), generator.schema()).unwrap();
+        let mut sink = BoundedSink::new(inner, 2);
+
+        let rows_written = sink::write_rows(
+            &mut sink,
+            &mut generator,
+            100,
+            10,
+            &CancellationToken::new(),
+            CancelPolicy::Finalize,
+        )
+        .unwrap();
+
+        assert_eq!(rows_written, 100);
+        assert_eq!(sink.in_flight(), 0);
+        assert!(std::fs::metadata(temp_file.path()).unwrap().len() > 0);
+    }
+
+    #[test]
+    fn test_estimate_rows() {
+        let config = Config::default();
+        let generator = DataGenerator::new(config);
+
+        let estimated = generator.estimate_rows_per_file();
+        assert!(estimated > 0);
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_ground_truth() {
+        use ground_truth::{Device, Metric, SearchOptions};
+
+        let config = Config {
+            vector_dim: 8,
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config.clone());
+        let temp_file = NamedTempFile::new().unwrap();
+        let writer = ParquetWriter::new(config);
+        writer
+            .write_to_file(temp_file.path().to_str().unwrap(), &mut generator, 50, 20)
+            .unwrap();
+        let base = ground_truth::read_vectors(temp_file.path(), "vector", 16).unwrap();
+        assert_eq!(base.len(), 50);
+
+        // The same file twice: rows 0..50 and 50..100 hold the same vectors
+        let files = vec![temp_file.path().to_path_buf(), temp_file.path().to_path_buf()];
+        let queries = vec![base[3].clone(), base[7].clone()];
+        let mut options = SearchOptions { k: 3, metric: Metric::L2, batch_size: 16, device: Device::Cpu };
+        let neighbors = ground_truth::compute(&files, "vector", queries, &[], &options).unwrap();
+        for (closest, row) in neighbors.iter().zip([3, 7]) {
+            let ids: Vec<u64> = closest.iter().map(|n| n.id).collect();
+            assert_eq!(ids[..2], [row, row + 50]);
+            assert_eq!((closest[0].distance, closest[1].distance), (0.0, 0.0));
+            assert!(closest[2].distance > 0.0);
+        }
+
+        // Cosine distance ignores scale
+        let scaled: Vec<f32> = base[5].iter().map(|v| v * 4.0).collect();
+        options.k = 1;
+        options.metric = Metric::Cosine;
+        let neighbors = ground_truth::compute(&files, "vector", vec![scaled], &[], &options).unwrap();
+        assert_eq!(neighbors[0][0].id, 5);
+        assert!(neighbors[0][0].distance.abs() < 1e-5);
+
+        let ivecs = NamedTempFile::new().unwrap();
+        ground_truth::write_ivecs(ivecs.path(), &neighbors).unwrap();
+        let bytes = std::fs::read(ivecs.path()).unwrap();
+        assert_eq!(bytes, [1i32.to_le_bytes(), 5i32.to_le_bytes()].concat());
+
+        let parquet = NamedTempFile::new().unwrap();
+        ground_truth::write_parquet(&writer, parquet.path(), &neighbors).unwrap();
+        let batch = parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(
+            std::fs::File::open(parquet.path()).unwrap(),
+        )
+        .unwrap()
+        .build()
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+        assert_eq!(batch.schema().fields(), ground_truth::schema().fields());
+        assert_eq!(batch.num_rows(), 1);
+    }
+
+    #[test]
+    #[cfg(feature = "tui")]
+    fn test_dashboard_state() {
+        use dashboard::DashboardState;
+        use dataset::{DatasetEvent, FilePlan, FileReport};
+        use std::time::Duration;
+
+        let plan = |index: u64| FilePlan {
+            index,
+            path: format!("data-{}.parquet", index).into(),
+            rows: 100,
+            tenant: None,
+        };
+        let report = |index: u64| FileReport {
+            index,
+            path: format!("data-{}.parquet", index).into(),
+            rows: 100,
+            bytes: 1000,
+            elapsed: Duration::from_secs(2),
+            timings: Default::default(),
+            tenant: None,
+        };
+        let mut state = DashboardState::new(400, 4);
+        let start = state.started;
+        let worker_a = std::thread::current().id();
+        let worker_b = std::thread::spawn(|| std::thread::current().id()).join().unwrap();
+        assert_eq!(state.eta(start), None);
+
+        state.record(&DatasetEvent::FileStarted(&plan(0)), worker_a, start);
+        state.record(&DatasetEvent::FileStarted(&plan(1)), worker_b, start);
+        state.record(&DatasetEvent::FileFinished(&report(0)), worker_a, start);
+        assert_eq!((state.rows, state.files, state.bytes), (100, 1, 1000));
+        assert_eq!(state.workers.len(), 2);
+        assert_eq!(state.workers[0].0, worker_a);
+        assert!(state.workers[0].1.current.is_none());
+        assert_eq!(state.workers[0].1.rows_per_sec(), 50.0);
+        assert_eq!(
+            state.workers[1].1.current.as_ref().unwrap().0,
+            std::path::PathBuf::from("data-1.parquet")
+        );
+        assert_eq!(state.ratio(), 0.25);
+        // 100 rows in 10s: 300 rows left take 30s
+        let now = start + Duration::from_secs(10);
+        assert_eq!(state.eta(now), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_query_sets() {
+        use arrow::array::{Array, Int64Array};
+        use ground_truth::{Device, Metric, SearchOptions};
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let generate = |source: QuerySource| {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let config = Config {
+                vector_dim: 16,
+                target_file_size: 16 * 1024,
+                queries: Some(QueryConfig { count: 20, source }),
+                ..Config::default()
+            };
+            let options = DatasetOptions {
+                output_dir: temp_dir.path().to_path_buf(),
+                prefix: "base".to_string(),
+                total_rows: 300,
+                batch_size: 64,
+                layout: None,
+            };
+            let report =
+                dataset::generate(&config, &options, &CancellationToken::new(), |_| {}).unwrap();
+            let (path, count) = report.queries.clone().unwrap();
+            assert_eq!(path, temp_dir.path().join(dataset::QUERIES_FILE_NAME));
+            assert_eq!(count, 20);
+            let batch = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path).unwrap())
+                .unwrap()
+                .build()
+                .unwrap()
+                .next()
+                .unwrap()
+                .unwrap();
+            let schema = batch.schema();
+            assert_eq!(schema.field(1), report_vector_field(&report).as_ref());
+            let sources = batch.column(2).as_any().downcast_ref::<Int64Array>().unwrap().clone();
+            let queries = ground_truth::read_vectors(&path, "vector", 64).unwrap();
+            let files: Vec<_> = report.files.iter().map(|f| f.path.clone()).collect();
+            (temp_dir, files, queries, sources)
+        };
+        fn report_vector_field(report: &DatasetReport) -> arrow::datatypes::FieldRef {
+            let builder = ParquetRecordBatchReaderBuilder::try_new(
+                std::fs::File::open(&report.files[0].path).unwrap(),
+            )
+            .unwrap();
+            builder.schema().field_with_name("vector").unwrap().clone().into()
+        }
+
+        let options = SearchOptions { k: 1, metric: Metric::L2, batch_size: 64, device: Device::Cpu };
+        let (_dir, files, queries, sources) = generate(QuerySource::Sample);
+        assert_eq!(queries.len(), 20);
+        assert_eq!(sources.null_count(), 20);
+        let neighbors = ground_truth::compute(&files, "vector", queries, &[], &options).unwrap();
+        assert!(neighbors.iter().all(|n| n[0].distance > 0.0));
+
+        let epsilon = 1e-3;
+        let (_dir, files, queries, sources) = generate(QuerySource::Perturb { epsilon });
+        assert_eq!(sources.null_count(), 0);
+        let neighbors = ground_truth::compute(&files, "vector", queries, &[], &options).unwrap();
+        for (closest, source) in neighbors.iter().zip(sources.values()) {
+            // Every query lies next to the row it was made from
+            assert_eq!(closest[0].id, *source as u64);
+            assert!(closest[0].distance <= epsilon as f32 * 4.0 + 1e-6);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "object_store")]
+    fn test_object_store_output() {
+        use std::path::PathBuf;
+
+        let config = Config {
+            vector_dim: 8,
+            target_file_size: 8 * 1024,
+            ..Config::default()
+        };
+        let local = tempfile::tempdir().unwrap();
+        let remote = tempfile::tempdir().unwrap();
+        let generate = |config: &Config, output_dir: PathBuf| {
+            let options = DatasetOptions {
+                output_dir,
+                prefix: "data".to_string(),
+                total_rows: 200,
+                batch_size: 50,
+                layout: None,
+            };
+            dataset::generate(config, &options, &CancellationToken::new(), |_| {})
+        };
+        let url = PathBuf::from(format!("file://{}", remote.path().join("nested").display()));
+        assert!(sink::is_object_url(&url));
+        assert!(!sink::is_object_url(local.path()));
+
+        let expected = generate(&config, local.path().to_path_buf()).unwrap();
+        let uploaded = generate(&config, url.clone()).unwrap();
+        assert!(uploaded.files.len() > 1);
+        assert_eq!(uploaded.files.len(), expected.files.len());
+        for (file, expected) in uploaded.files.iter().zip(&expected.files) {
+            assert!(file.path.starts_with(&url));
+            let name = file.path.file_name().unwrap();
+            let written = std::fs::read(remote.path().join("nested").join(name)).unwrap();
+            assert_eq!(file.bytes, written.len() as u64);
+            assert_eq!(written, std::fs::read(&expected.path).unwrap());
+        }
+
+        let perturbed = Config {
+            queries: Some(QueryConfig {
+                count: 4,
+                source: QuerySource::Perturb { epsilon: 0.1 },
+            }),
+            ..config
+        };
+        assert!(generate(&perturbed, url).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn test_json_lines() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("rows.jsonl");
+        let config = Config {
+            vector_dim: 4,
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config.clone());
+        let expected = DataGenerator::new(config).generate_batch(5).unwrap();
+        let mut sink = formats::JsonLinesSink::create(&path).unwrap();
+        sink::write_rows(
+            &mut sink,
+            &mut generator,
+            5,
+            5,
+            &CancellationToken::new(),
+            CancelPolicy::Finalize,
+        )
+        .unwrap();
+
+        let rows: Vec<serde_json::Value> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(rows.len(), 5);
+        let scalars = expected.column_by_name("scalar").unwrap();
+        let scalars = scalars.as_any().downcast_ref::<arrow::array::StringArray>().unwrap();
+        for (row, json) in rows.iter().enumerate() {
+            assert_eq!(json["vector"].as_array().unwrap().len(), 4);
+            assert_eq!(json["scalar"], scalars.value(row));
+        }
+
+        // Keys follow the schema and f32 values keep f32 digits
+        let line = std::fs::read_to_string(&path).unwrap().lines().next().unwrap().to_string();
+        let positions: Vec<usize> = expected
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| line.find(&format!("\"{}\":", field.name())).unwrap())
+            .collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        for value in rows[0]["vector"].as_array().unwrap() {
+            let value = value.as_f64().unwrap() as f32;
+            assert!(line.contains(&serde_json::to_string(&value).unwrap()));
+        }
+    }
+
+    #[test]
+    fn test_scalar_prefix() {
+        let config = Config {
+            vector_dim: 4,
+            ..Config::default()
+        };
+        let plain = DataGenerator::new(config.clone()).generate_batch(20).unwrap();
+        let prefixed = DataGenerator::new(Config {
+            scalar_prefix: Some("tenantA:".to_string()),
+            ..config.clone()
+        })
+        .generate_batch(20)
+        .unwrap();
+        let scalars = |batch: &RecordBatch| {
+            let column = batch.column_by_name("scalar").unwrap();
+            let strings = column.as_any().downcast_ref::<arrow::array::StringArray>().unwrap();
+            strings.iter().map(|value| value.unwrap().to_string()).collect::<Vec<_>>()
+        };
+        for (plain, prefixed) in scalars(&plain).iter().zip(scalars(&prefixed)) {
+            assert_eq!(prefixed, format!("tenantA:{}", plain));
+        }
+        assert_eq!(prefixed.column_by_name("vector"), plain.column_by_name("vector"));
+
+        // String columns of a schema spec get the prefix as well
+        let spec = Config {
+            schema: Some("emb:vector<f32,4>,title:string(6),tag:string".parse().unwrap()),
+            ..config.clone()
+        };
+        let plain = DataGenerator::new(spec.clone()).generate_batch(20).unwrap();
+        let prefixed = DataGenerator::new(Config {
+            scalar_prefix: Some("tenantA:".to_string()),
+            ..spec
+        })
+        .generate_batch(20)
+        .unwrap();
+        for name in ["title", "tag"] {
+            let strings = |batch: &RecordBatch| {
+                let column = batch.column_by_name(name).unwrap();
+                let strings = column.as_any().downcast_ref::<arrow::array::StringArray>().unwrap();
+                strings.iter().map(|value| value.unwrap().to_string()).collect::<Vec<_>>()
+            };
+            for (plain, prefixed) in strings(&plain).iter().zip(strings(&prefixed)) {
+                assert_eq!(prefixed, format!("tenantA:{}", plain));
+            }
+        }
+        assert_eq!(prefixed.column_by_name("emb"), plain.column_by_name("emb"));
+
+        let v0 = Config {
+            generator_version: GeneratorVersion::V0,
+            scalar_prefix: Some("a:".to_string()),
+            ..config
+        };
+        assert!(v0.validate().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "csv")]
+    fn test_csv_output() {
+        use arrow::array::AsArray;
+        use base64::Engine;
+        use formats::{CsvSink, VectorText};
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            vector_dim: 3,
+            ..Config::default()
+        };
+        let expected = DataGenerator::new(config.clone()).generate_batch(4).unwrap();
+        let vectors = expected.column_by_name("vector").unwrap().as_binary::<i32>().clone();
+        for vector_text in [VectorText::Base64, VectorText::List] {
+            let path = temp_dir.path().join(format!("{:?}.csv", vector_text));
+            let mut sink = CsvSink::create(&path, vector_text).unwrap();
+            sink::write_rows(
+                &mut sink,
+                &mut DataGenerator::new(config.clone()),
+                4,
+                4,
+                &CancellationToken::new(),
+                CancelPolicy::Finalize,
+            )
+            .unwrap();
+
+            let text = std::fs::read_to_string(&path).unwrap();
+            let mut lines = text.lines();
+            assert_eq!(lines.next(), Some("vector,scalar"));
+            for (row, line) in lines.enumerate() {
+                let field = line.split(',').next().unwrap();
+                let field = line.strip_prefix('"').map_or(field, |quoted| quoted.split('"').next().unwrap());
+                let bytes = vectors.value(row);
+                match vector_text {
+                    VectorText::Base64 => assert_eq!(
+                        base64::engine::general_purpose::STANDARD.decode(field).unwrap(),
+                        bytes
+                    ),
+                    VectorText::List => {
+                        let values: Vec<f32> = field
+                            .trim_matches(['[', ']'])
+                            .split(',')
+                            .map(|v| v.parse().unwrap())
+                            .collect();
+                        let decoded = core_gen::decode_vector(bytes, VectorElementType::Float32);
+                        assert_eq!(values, decoded.iter().map(|&v| v as f32).collect::<Vec<_>>());
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_snapshot_fork() {
+        #[cfg(feature = "json")]
+        use snapshot::GeneratorSnapshot;
+
+        let config = Config {
+            vector_dim: 8,
+            ..Config::default()
+        };
+        let mut original = DataGenerator::new(config.clone());
+        original.generate_batches(&[10, 10]).unwrap();
+        original.generate_vector();
+        let snapshot = original.snapshot();
+        #[cfg(feature = "json")]
+        let snapshot: GeneratorSnapshot =
+            serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+        let expected = original.generate_batches(&[10, 10]).unwrap();
+        let expected_vector = original.generate_vector();
+
+        let restored = |branch: u64| {
+            let mut generator = DataGenerator::new(config.clone());
+            generator.restore(&snapshot).unwrap();
+            generator.fork(branch).unwrap();
+            let batches = generator.generate_batches(&[10, 10]).unwrap();
+            (batches, generator.generate_vector())
+        };
+        // Restoring continues exactly where the snapshot was taken
+        assert_eq!(restored(0), (expected.clone(), expected_vector.clone()));
+
+        // Branches are alternative continuations, each reproducible
+        let (forked, forked_vector) = restored(1);
+        assert_ne!(forked, expected);
+        assert_ne!(forked_vector, expected_vector);
+        assert_eq!(restored(1).0, forked);
+        assert_ne!(restored(2).0, forked);
+        assert_eq!(forked[0].schema(), expected[0].schema());
+
+        let mut other_file = DataGenerator::for_file(config, 1).unwrap();
+        assert!(other_file.restore(&snapshot).is_err());
+    }
+
+    #[test]
+    fn test_monotonic_per_key() {
+        use arrow::array::AsArray;
+        use arrow::datatypes::Int64Type;
+        use std::collections::HashMap;
+
+        let config = Config {
+            vector_dim: 4,
+            foreign_key: Some(ForeignKeyConfig {
+                column: "doc_id".to_string(),
+                parents: 20,
+                skew: Skew::Uniform,
+                parent_table: None,
+            }),
+            derived: vec!["version = hash(scalar) % 1000".parse().unwrap()],
+            monotonic: vec!["version:doc_id".parse().unwrap()],
+            ..Config::default()
+        };
+        let column = |batch: &RecordBatch, name: &str| {
+            batch.column_by_name(name).unwrap().as_primitive::<Int64Type>().values().to_vec()
+        };
+        let mut first = DataGenerator::new(config.clone());
+        let mut batches = first.generate_batches(&[50, 50]).unwrap();
+        // The next file continues from the largest values of this one
+        let mut second = DataGenerator::for_file(config.clone(), 1).unwrap();
+        second.resume_monotonic(first.take_monotonic_state().unwrap());
+        batches.extend(second.generate_batches(&[50]).unwrap());
+
+        let mut last: HashMap<i64, i64> = HashMap::new();
+        let mut raised = 0;
+        for batch in &batches {
+            for (key, version) in column(batch, "doc_id").into_iter().zip(column(batch, "version")) {
+                let previous = last.insert(key, version).unwrap_or(i64::MIN);
+                assert!(version >= previous, "doc {} went from {} to {}", key, previous, version);
+                raised += (version == previous) as usize;
+            }
+        }
+        assert!(raised > 0);
+
+        let unconstrained = DataGenerator::new(Config {
+            monotonic: Vec::new(),
+            ..config.clone()
+        })
+        .generate_batch(50)
+        .unwrap();
+        let constrained = DataGenerator::new(config.clone()).generate_batch(50).unwrap();
+        assert_eq!(column(&unconstrained, "doc_id"), column(&constrained, "doc_id"));
+
+        let bad = Config {
+            monotonic: vec!["scalar:doc_id".parse().unwrap()],
+            ..config
+        };
+        assert!(DataGenerator::try_new(bad).is_err());
+        assert!("version".parse::<Monotonic>().is_err());
+    }
+
+    #[test]
+    fn test_vecs_output() {
+        use arrow::array::AsArray;
+        use formats::{VecsFormat, VecsSink};
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            vector_dim: 12,
+            pq: Some(PqConfig { subquantizers: 6, nbits: 4 }),
+            ..Config::default()
+        };
+        // The rows the sink sees, generated in the same batches of 2
+        let batches = DataGenerator::new(config.clone()).generate_batches(&[2, 2, 1]).unwrap();
+        let schema = batches[0].schema();
+        let expected = arrow::compute::concat_batches(&schema, &batches).unwrap();
+        let write = |column: &str| {
+            let format = VecsFormat::for_field(schema.field_with_name(column).unwrap()).unwrap();
+            let path = temp_dir.path().join(format!("{}.{}", column, format.extension()));
+            let mut sink = VecsSink::create(&path, column, format).unwrap();
+            sink::write_rows(
+                &mut sink,
+                &mut DataGenerator::new(config.clone()),
+                5,
+                2,
+                &CancellationToken::new(),
+                CancelPolicy::Finalize,
+            )
+            .unwrap();
+            (format, std::fs::read(&path).unwrap())
+        };
+
+        let (format, fvecs) = write("vector");
+        assert_eq!(format, VecsFormat::Fvecs);
+        assert_eq!(fvecs.len(), 5 * (4 + 12 * 4));
+        for (row, record) in fvecs.chunks(4 + 12 * 4).enumerate() {
+            assert_eq!(record[..4], 12i32.to_le_bytes());
+            let stored = expected.column_by_name("vector").unwrap().as_binary::<i32>().value(row);
+            assert_eq!(&record[4..], stored);
+        }
+
+        let (format, bvecs) = write("pq_code");
+        assert_eq!(format, VecsFormat::Bvecs);
+        let codes = expected.column_by_name("pq_code").unwrap().as_fixed_size_binary().clone();
+        for (row, record) in bvecs.chunks(4 + 6).enumerate() {
+            assert_eq!(record[..4], 6i32.to_le_bytes());
+            for sub in 0..6 {
+                assert_eq!(record[4 + sub] as usize, pq::unpack_code(codes.value(row), sub, 4));
+            }
+        }
+        assert_eq!(VecsFormat::for_field(schema.field_with_name("scalar").unwrap()), None);
+    }
+
+    #[test]
+    fn test_preview() {
+        let config = Config {
+            vector_dim: 768,
+            scalar_len: 100,
+            ..Config::default()
+        };
+        let batch = DataGenerator::new(config).generate_batch(5).unwrap();
+        let preview = preview::format_preview(&batch).unwrap();
+        let mut lines = preview.lines();
+        assert_eq!(lines.next(), Some("vector: Binary (f32 vector, dim 768)"));
+        assert_eq!(lines.next(), Some("scalar: Utf8"));
+        assert!(preview.contains("… +764]"));
+        // Every line of the table stays narrow despite the wide cells
+        assert!(preview.lines().all(|line| line.chars().count() < 120));
+        assert_eq!(preview.lines().filter(|line| line.starts_with("| [")).count(), 5);
+    }
+
+    #[test]
+    #[cfg(feature = "hdf5")]
+    fn test_ann_benchmarks_hdf5() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("data.hdf5");
+        let config = Config {
+            vector_dim: 8,
+            queries: Some(QueryConfig {
+                count: 6,
+                source: QuerySource::Perturb { epsilon: 0.0 },
+            }),
+            ..Config::default()
+        };
+        let report = formats::write_ann_benchmarks(
+            &path,
+            &config,
+            50,
+            16,
+            5,
+            ground_truth::Metric::L2,
+            &CancellationToken::new(),
+        )
+        .unwrap();
+        assert_eq!((report.train_rows, report.test_rows, report.k), (50, 6, 5));
+
+        let file = hdf5::File::open(&path).unwrap();
+        let distance: hdf5::types::VarLenUnicode =
+            file.attr("distance").unwrap().read_scalar().unwrap();
+        assert_eq!(distance.as_str(), "euclidean");
+        let train = file.dataset("train").unwrap().read_2d::<f32>().unwrap();
+        let test = file.dataset("test").unwrap().read_2d::<f32>().unwrap();
+        let neighbors = file.dataset("neighbors").unwrap().read_2d::<i32>().unwrap();
+        let distances = file.dataset("distances").unwrap().read_2d::<f32>().unwrap();
+        assert_eq!(train.dim(), (50, 8));
+        assert_eq!(test.dim(), (6, 8));
+        assert_eq!(neighbors.dim(), (6, 5));
+
+        let expected = DataGenerator::new(config).generate_batch(50).unwrap();
+        let field = expected.schema().field(0).clone();
+        for row in 0..50 {
+            let values = columns::vector_values(&field, expected.column(0).as_ref(), row).unwrap();
+            let values: Vec<f32> = values.into_iter().map(|v| v as f32).collect();
+            assert_eq!(train.row(row).to_vec(), values);
+        }
+        // Unperturbed queries are base rows, so each is its own nearest neighbor
+        for query in 0..6 {
+            assert_eq!(distances[[query, 0]], 0.0);
+            assert_eq!(train.row(neighbors[[query, 0]] as usize), test.row(query));
+            assert!(distances.row(query).windows(2).into_iter().all(|d| d[0] <= d[1]));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "numpy")]
+    fn test_numpy_output() {
+        use arrow::array::AsArray;
+        use formats::{NpySink, NpzSink};
+        use std::io::Read;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            vector_dim: 3,
+            scalar_len: 5,
+            ..Config::default()
+        };
+        let write = |sink: &mut dyn Sink| {
+            sink::write_rows(
+                sink,
+                &mut DataGenerator::new(config.clone()),
+                7,
+                3,
+                &CancellationToken::new(),
+                CancelPolicy::Finalize,
+            )
+            .unwrap();
+        };
+        // Header dict and data of an .npy file
+        let parse = |bytes: &[u8]| {
+            assert_eq!(&bytes[..8], b"\x93NUMPY\x01\x00");
+            let len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+            assert_eq!((10 + len) % 64, 0);
+            let header = std::str::from_utf8(&bytes[10..10 + len]).unwrap().trim_end().to_string();
+            (header, bytes[10 + len..].to_vec())
+        };
+        // The rows the sinks see, generated in the same batches of 3
+        let batches = DataGenerator::new(config.clone()).generate_batches(&[3, 3, 1]).unwrap();
+        let expected = arrow::compute::concat_batches(&batches[0].schema(), &batches).unwrap();
+        let vectors: Vec<u8> = (0..7)
+            .flat_map(|row| expected.column(0).as_binary::<i32>().value(row).to_vec())
+            .collect();
+
+        let npy = temp_dir.path().join("data.npy");
+        write(&mut NpySink::create(&npy, "vector", 3).unwrap());
+        let (header, data) = parse(&std::fs::read(&npy).unwrap());
+        assert_eq!(header, "{'descr': '<f4', 'fortran_order': False, 'shape': (7, 3), }");
+        assert_eq!(data, vectors);
+
+        let npz = temp_dir.path().join("data.npz");
+        write(&mut NpzSink::create(&npz, "vector", 3, "scalar", 5).unwrap());
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&npz).unwrap()).unwrap();
+        let mut entry = |name: &str| {
+            let mut bytes = Vec::new();
+            archive.by_name(name).unwrap().read_to_end(&mut bytes).unwrap();
+            parse(&bytes)
+        };
+        assert_eq!(entry("vectors.npy").1, vectors);
+        let (header, data) = entry("scalars.npy");
+        assert_eq!(header, "{'descr': '<U5', 'fortran_order': False, 'shape': (7,), }");
+        let scalars: String = data
+            .chunks(4)
+            .map(|c| char::from_u32(u32::from_le_bytes(c.try_into().unwrap())).unwrap())
+            .collect();
+        let expected_scalars: String = expected.column(1).as_string::<i32>().iter().flatten().collect();
+        assert_eq!(scalars, expected_scalars);
+        // Staged arrays are removed once the archive is written
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_ground_truth_cache() {
+        use ground_truth::{Device, Metric, SearchOptions};
+        use std::path::PathBuf;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            vector_dim: 8,
+            ..Config::default()
+        };
+        let writer = ParquetWriter::new(config.clone());
+        let files: Vec<PathBuf> = (0..3)
+            .map(|index| {
+                let path = temp_dir.path().join(format!("part-{}.parquet", index));
+                let mut generator = DataGenerator::for_file(config.clone(), index).unwrap();
+                writer.write_to_file(path.to_str().unwrap(), &mut generator, 40, 16).unwrap();
+                path
+            })
+            .collect();
+        let queries = ground_truth::read_vectors(&files[1], "vector", 16).unwrap();
+        let perturbed: Vec<Vec<f32>> = queries.iter().map(|q| q.iter().map(|v| v + 0.01).collect()).collect();
+        let cache = temp_dir.path().join(ground_truth::CACHE_DIR_NAME);
+        let options = |k| SearchOptions { k, metric: Metric::L2, batch_size: 16, device: Device::Cpu };
+        let cached = |queries: &[Vec<f32>], k| {
+            ground_truth::compute_cached(&files, "vector", queries.to_vec(), &[], &options(k), &cache).unwrap()
+        };
+        let full = |queries: &[Vec<f32>], k| {
+            ground_truth::compute(&files, "vector", queries.to_vec(), &[], &options(k)).unwrap()
+        };
+
+        assert_eq!(cached(&perturbed[..4], 5), full(&perturbed[..4], 5));
+        assert_eq!(std::fs::read_dir(&cache).unwrap().count(), 3);
+        // More queries and a larger k reuse what applies
+        assert_eq!(cached(&perturbed[..10], 5), full(&perturbed[..10], 5));
+        assert_eq!(cached(&perturbed[..10], 50), full(&perturbed[..10], 50));
+        assert_eq!(cached(&perturbed[2..6], 3), full(&perturbed[2..6], 3));
+
+        // Rewriting a file invalidates its entries
+        let mut generator = DataGenerator::for_file(config.clone(), 7).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        writer.write_to_file(files[2].to_str().unwrap(), &mut generator, 30, 16).unwrap();
+        assert_eq!(cached(&perturbed[..10], 5), full(&perturbed[..10], 5));
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_ground_truth_device() {
+        use ground_truth::{Device, GroundTruth, Metric};
+
+        assert_eq!("gpu".parse::<Device>().unwrap(), Device::Gpu);
+        assert_eq!(Device::default().to_string(), "cpu");
+        assert!("tpu".parse::<Device>().is_err());
+
+        let queries = vec![vec![0.0, 0.0], vec![1.0, 1.0]];
+        let search = GroundTruth::on(queries.clone(), 1, Metric::L2, Device::Gpu);
+        if cfg!(feature = "gpu") {
+            // Machines without a GPU adapter fail to open one
+            let Ok(mut search) = search else { return };
+            let base = vec![Some(vec![0.9, 1.2]), None, Some(vec![0.1, -0.1])];
+            search.add(&base).unwrap();
+            let mut cpu = GroundTruth::on(queries, 1, Metric::L2, Device::Cpu).unwrap();
+            cpu.add(&base).unwrap();
+            let ids = |neighbors: Vec<Vec<ground_truth::Neighbor>>| -> Vec<u64> {
+                neighbors.iter().map(|n| n[0].id).collect()
+            };
+            assert_eq!(ids(search.finish()), [2, 0]);
+            assert_eq!(ids(cpu.finish()), [2, 0]);
+        } else {
+            assert!(search.is_err());
+        }
+    }
+
+    #[test]
+    fn test_schema_spec() {
+        use arrow::array::{Array, AsArray};
+        use arrow::datatypes::{DataType, Float32Type};
+
+        let spec: SchemaSpec = "id:int64, emb:vector<f32,8>,title:string(6),score:float32,small:vector<f64,3>"
+            .parse()
+            .unwrap();
+        assert_eq!(spec.to_string(), "id:int64,emb:vector<f32,8>,title:string(6),score:float32,small:vector<f64,3>");
+        assert_eq!(spec.primary_vector().unwrap().0, 1);
+        for invalid in ["id", "id:int16", "emb:vector<f8,8>", "title:string(x)", ""] {
+            assert!(invalid.parse::<SchemaSpec>().is_err(), "{}", invalid);
+        }
+
+        let config = Config {
+            vector_dim: 8,
+            schema: Some(spec),
+            derived: vec!["norm = l2(emb)".parse().unwrap()],
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config.clone());
+        let batch = generator.generate_batch(20).unwrap();
+        let schema = batch.schema();
+        let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+        assert_eq!(names, ["id", "emb", "title", "score", "small", "norm"]);
+        assert_eq!(schema.field(0).data_type(), &DataType::Int64);
+        assert_eq!(schema.field(3).data_type(), &DataType::Float32);
+        assert_eq!(schema.field(4).metadata()[VECTOR_DIM_KEY], "3");
+        assert!(batch.column(2).as_string::<i32>().iter().all(|v| v.unwrap().len() == 6));
+        let scores = batch.column(3).as_primitive::<Float32Type>();
+        assert!(scores.values().iter().all(|v| (0.0..1.0).contains(v)));
+
+        // The first spec vector is the primary stream: the same vectors as
+        // the default `vector` column
+        let mut default = DataGenerator::new(Config {
+            vector_dim: 8,
+            ..Config::default()
+        });
+        assert_eq!(
+            batch.column(1).as_ref(),
+            default.generate_batch(20).unwrap().column(0).as_ref()
+        );
+        assert!(batch.column(5).null_count() == 0);
+
+        let mismatched = Config {
+            vector_dim: 16,
+            ..config.clone()
+        };
+        assert!(DataGenerator::try_new(mismatched).is_err());
+        let duplicate = Config {
+            schema: Some("a:int32,a:int64".parse().unwrap()),
+            ..config
+        };
+        assert!(DataGenerator::try_new(duplicate).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_ground_truth_blocks() {
+        use ground_truth::{GroundTruth, Metric};
+        use rand::Rng;
+
+        // Dimension off the SIMD width, more queries and rows than one block
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut vectors = |n: usize| -> Vec<Vec<f32>> {
+            (0..n).map(|_| (0..13).map(|_| rng.gen_range(-1.0..1.0)).collect()).collect()
+        };
+        let queries = vectors(40);
+        let base = vectors(700);
+        for metric in [Metric::L2, Metric::Cosine] {
+            let mut search = GroundTruth::new(queries.clone(), 5, metric).unwrap();
+            for rows in base.chunks(300) {
+                let rows: Vec<Option<Vec<f32>>> = rows.iter().cloned().map(Some).collect();
+                search.add(&rows).unwrap();
+            }
+            for (query, closest) in queries.iter().zip(search.finish()) {
+                let mut expected: Vec<(f64, u64)> = base
+                    .iter()
+                    .enumerate()
+                    .map(|(id, row)| {
+                        let dot: f64 = query.iter().zip(row).map(|(a, b)| (a * b) as f64).sum();
+                        let norm = |v: &[f32]| v.iter().map(|x| (x * x) as f64).sum::<f64>().sqrt();
+                        let distance = match metric {
+                            Metric::L2 => query.iter().zip(row).map(|(a, b)| ((a - b) * (a - b)) as f64).sum::<f64>().sqrt(),
+                            Metric::Cosine => 1.0 - dot / (norm(query) * norm(row)),
+                        };
+                        (distance, id as u64)
+                    })
+                    .collect();
+                let exact = expected.clone();
+                expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+                assert_eq!(closest.len(), 5);
+                for (neighbor, (distance, _)) in closest.iter().zip(&expected) {
+                    // Same distances as an exact f64 search, each that of the row returned
+                    assert!((neighbor.distance as f64 - distance).abs() < 1e-4);
+                    assert!((exact[neighbor.id as usize].0 - distance).abs() < 1e-4);
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_id_column() {
+        use arrow::array::AsArray;
+        use arrow::datatypes::Int64Type;
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReader;
+
+        let config = Config {
+            vector_dim: 4,
+            target_file_size: 4096,
+            id_column: Some(IdColumn::Monotonic),
+            ..Config::default()
+        };
+        let temp_dir = tempfile::tempdir().unwrap();
+        let options = DatasetOptions {
+            output_dir: temp_dir.path().to_path_buf(),
+            prefix: "rows".to_string(),
+            total_rows: 300,
+            batch_size: 40,
+            layout: None,
+        };
+        let report = dataset::generate(&config, &options, &CancellationToken::new(), |_| {}).unwrap();
+        assert!(report.files.len() > 1);
+        let mut ids = Vec::new();
+        for file in &report.files {
+            let reader = ParquetRecordBatchReader::try_new(std::fs::File::open(&file.path).unwrap(), 1024).unwrap();
+            for batch in reader {
+                let batch = batch.unwrap();
+                assert_eq!(batch.schema().field(0).name(), "id");
+                ids.extend(batch.column(0).as_primitive::<Int64Type>().values().iter().copied());
+            }
+        }
+        assert_eq!(ids, (0..300).collect::<Vec<i64>>());
+
+        let config = Config {
+            vector_dim: 4,
+            id_column: Some(IdColumn::Uuid),
+            ..Config::default()
+        };
+        let uuids = |batch_size: usize| -> Vec<String> {
+            let mut generator = DataGenerator::new(config.clone());
+            (0..60 / batch_size)
+                .flat_map(|_| {
+                    let batch = generator.generate_batch(batch_size).unwrap();
+                    let values = batch.column_by_name("id").unwrap().as_string::<i32>().clone();
+                    values.iter().map(|v| v.unwrap().to_string()).collect::<Vec<_>>()
+                })
+                .collect()
+        };
+        let values = uuids(20);
+        assert_eq!(values, uuids(60));
+        let unique: std::collections::HashSet<&String> = values.iter().collect();
+        assert_eq!(unique.len(), 60);
+        assert!(values.iter().all(|v| v.len() == 36 && &v[14..15] == "4" && "89ab".contains(&v[19..20])));
+
+        let pruning = Config {
+            pruning: Some(PruningConfig { row_group_rows: 10, start_ms: 0, interval_ms: 1 }),
+            ..config
+        };
+        assert!(DataGenerator::try_new(pruning).is_err());
+    }
+
+    #[test]
+    fn test_null_ratio() {
+        use arrow::array::Array;
+
+        let config = Config {
+            vector_dim: 4,
+            null_ratios: vec!["scalar=0.25".parse().unwrap(), "vector=0.1".parse().unwrap()],
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config.clone());
+        assert!(generator.schema().field_with_name("vector").unwrap().is_nullable());
+        let batch = generator.generate_batch(4000).unwrap();
+        let fraction = |name: &str| batch.column_by_name(name).unwrap().null_count() as f64 / 4000.0;
+        assert!((fraction("scalar") - 0.25).abs() < 0.03);
+        assert!((fraction("vector") - 0.1).abs() < 0.02);
+
+        // Values that are not null are the generated ones
+        let expected = DataGenerator::new(Config { null_ratios: Vec::new(), ..config.clone() })
+            .generate_batch(4000)
+            .unwrap();
+        let (scalar, plain) = (batch.column_by_name("scalar").unwrap(), expected.column_by_name("scalar").unwrap());
+        let (scalar, plain) = (scalar.as_any().downcast_ref::<arrow::array::StringArray>().unwrap(), plain.as_any().downcast_ref::<arrow::array::StringArray>().unwrap());
+        assert!((0..4000).filter(|&row| scalar.is_valid(row)).all(|row| scalar.value(row) == plain.value(row)));
+
+        for invalid in ["scalar", "scalar=1.5", "scalar=x"] {
+            assert!(invalid.parse::<NullRatio>().is_err());
+        }
+        let unknown = Config { null_ratios: vec!["missing=0.5".parse().unwrap()], ..config };
+        assert!(DataGenerator::try_new(unknown).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_query_workload() {
+        use arrow::array::{Array, Float64Array, Int32Array, StringArray};
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+        use workload::{CompareOp, Literal};
+
+        let workload = WorkloadConfig {
+            k: "10:0.5,100:0.5".parse().unwrap(),
+            filter_column: Some("score".to_string()),
+            selectivity: workload::parse_selectivity("0.01..0.1").unwrap(),
+            filtered_fraction: 0.5,
+        };
+        let config = Config {
+            vector_dim: 16,
+            schema: Some("vector:vector<f32,16>,score:float32".parse().unwrap()),
+            queries: Some(QueryConfig { count: 200, source: QuerySource::Sample }),
+            workload: Some(workload.clone()),
+            ..Config::default()
+        };
+        let generate = |config: &Config| {
+            let temp_dir = tempfile::tempdir().unwrap();
+            let options = DatasetOptions {
+                output_dir: temp_dir.path().to_path_buf(),
+                prefix: "base".to_string(),
+                total_rows: 100,
+                batch_size: 64,
+                layout: None,
+            };
+            let report =
+                dataset::generate(config, &options, &CancellationToken::new(), |_| {}).unwrap();
+            let (path, _) = report.queries.unwrap();
+            let batches: Vec<_> = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path).unwrap())
+                .unwrap()
+                .build()
+                .unwrap()
+                .map(|batch| batch.unwrap())
+                .collect();
+            let vectors = ground_truth::read_vectors(&path, "vector", 64).unwrap();
+            (batches, vectors)
+        };
+
+        let (batches, vectors) = generate(&config);
+        let schema = batches[0].schema();
+        let names: Vec<&str> = schema.fields().iter().map(|f| f.name().as_str()).collect();
+        assert_eq!(names, ["query_id", "vector", "source_id", "k", "filter", "selectivity"]);
+        let mut filtered = 0;
+        for batch in &batches {
+            let ks = batch.column(3).as_any().downcast_ref::<Int32Array>().unwrap();
+            let filters = batch.column(4).as_any().downcast_ref::<StringArray>().unwrap();
+            let selectivities = batch.column(5).as_any().downcast_ref::<Float64Array>().unwrap();
+            assert!(ks.values().iter().all(|&k| k == 10 || k == 100));
+            for row in 0..batch.num_rows() {
+                assert_eq!(filters.is_null(row), selectivities.is_null(row));
+                if filters.is_null(row) {
+                    continue;
+                }
+                filtered += 1;
+                let predicate: Predicate = filters.value(row).parse().unwrap();
+                assert_eq!(predicate.to_string(), filters.value(row));
+                assert_eq!((predicate.column.as_str(), predicate.op), ("score", CompareOp::Lt));
+                // Scores are uniform in [0, 1): the threshold is the selectivity
+                let selectivity = selectivities.value(row);
+                assert_eq!(predicate.value, Literal::Float(selectivity));
+                assert!((0.01..=0.1).contains(&selectivity));
+            }
+        }
+        assert!((60..140).contains(&filtered), "{} filtered queries", filtered);
+
+        // The workload does not change the query vectors
+        let (_, plain) = generate(&Config { workload: None, ..config.clone() });
+        assert_eq!(vectors, plain);
+
+        // Filters need a column of known distribution and a query set
+        let vector_filter = WorkloadConfig { filter_column: Some("vector".to_string()), ..workload.clone() };
+        let temp_dir = tempfile::tempdir().unwrap();
+        let options = DatasetOptions {
+            output_dir: temp_dir.path().to_path_buf(),
+            prefix: "base".to_string(),
+            total_rows: 10,
+            batch_size: 10,
+            layout: None,
+        };
+        let bad = Config { workload: Some(vector_filter), ..config.clone() };
+        assert!(dataset::generate(&bad, &options, &CancellationToken::new(), |_| {}).is_err());
+        assert!(Config { queries: None, ..config }.validate().is_err());
+        assert!("0".parse::<KDistribution>().is_err());
+        assert!("100..10".parse::<KDistribution>().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_filtered_ground_truth() {
+        use arrow::array::{Array, Float32Array};
+        use ground_truth::{Device, Metric, SearchOptions};
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            vector_dim: 8,
+            target_file_size: 8 * 1024,
+            schema: Some("vector:vector<f32,8>,score:float32".parse().unwrap()),
+            null_ratios: vec!["score=0.2".parse().unwrap()],
+            queries: Some(QueryConfig { count: 30, source: QuerySource::Sample }),
+            workload: Some(WorkloadConfig {
+                filter_column: Some("score".to_string()),
+                selectivity: (0.05, 0.5),
+                filtered_fraction: 0.7,
+                ..WorkloadConfig::default()
+            }),
+            ..Config::default()
+        };
+        let options = DatasetOptions {
+            output_dir: temp_dir.path().to_path_buf(),
+            prefix: "base".to_string(),
+            total_rows: 400,
+            batch_size: 64,
+            layout: None,
+        };
+        let report = dataset::generate(&config, &options, &CancellationToken::new(), |_| {}).unwrap();
+        let files: Vec<_> = report.files.iter().map(|f| f.path.clone()).collect();
+        assert!(files.len() > 1);
+        let (path, _) = report.queries.unwrap();
+        let queries = ground_truth::read_vectors(&path, "vector", 64).unwrap();
+        let filters = ground_truth::read_filters(&path, 64).unwrap();
+        assert_eq!(filters.len(), 30);
+        assert!(filters.iter().any(Option::is_some) && filters.iter().any(Option::is_none));
+
+        let mut base = Vec::new();
+        let mut scores = Vec::new();
+        for file in &files {
+            base.extend(ground_truth::read_vectors(file, "vector", 64).unwrap());
+            let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(file).unwrap())
+                .unwrap()
+                .build()
+                .unwrap();
+            for batch in reader {
+                let batch = batch.unwrap();
+                let column = batch.column_by_name("score").unwrap();
+                let column = column.as_any().downcast_ref::<Float32Array>().unwrap();
+                scores.extend((0..column.len()).map(|row| column.is_valid(row).then(|| column.value(row))));
+            }
+        }
+        assert_eq!(base.len(), scores.len());
+
+        let k = 5;
+        let search = SearchOptions { k, metric: Metric::L2, batch_size: 50, device: Device::Cpu };
+        let neighbors = ground_truth::compute(&files, "vector", queries.clone(), &filters, &search).unwrap();
+        for ((query, filter), closest) in queries.iter().zip(&filters).zip(&neighbors) {
+            // Brute force over the rows passing the filter
+            let mut expected: Vec<(f32, u64)> = base
+                .iter()
+                .zip(&scores)
+                .enumerate()
+                .filter(|(_, (_, score))| match filter {
+                    Some(filter) => {
+                        let workload::Literal::Float(threshold) = filter.value else { unreachable!() };
+                        score.is_some_and(|score| (score as f64) < threshold)
+                    }
+                    None => true,
+                })
+                .map(|(id, (vector, _))| {
+                    let distance: f32 = query.iter().zip(vector).map(|(a, b)| (a - b) * (a - b)).sum();
+                    (distance, id as u64)
+                })
+                .collect();
+            expected.sort_by(|a, b| a.0.total_cmp(&b.0));
+            let expected: Vec<u64> = expected.iter().take(k).map(|&(_, id)| id).collect();
+            let ids: Vec<u64> = closest.iter().map(|n| n.id).collect();
+            assert_eq!(ids, expected);
+        }
+
+        // Cached results are keyed by filter too
+        let cache = temp_dir.path().join(ground_truth::CACHE_DIR_NAME);
+        let cached = ground_truth::compute_cached(&files, "vector", queries.clone(), &filters, &search, &cache).unwrap();
+        assert_eq!(cached, neighbors);
+        let unfiltered = ground_truth::compute_cached(&files, "vector", queries.clone(), &[], &search, &cache).unwrap();
+        assert_eq!(unfiltered, ground_truth::compute(&files, "vector", queries, &[], &search).unwrap());
+    }
+
+    #[test]
+    fn test_timestamp_column() {
+        use arrow::array::AsArray;
+        use arrow::datatypes::{TimeUnit, TimestampMillisecondType};
+
+        let timestamps = |values: TimestampValues| -> Vec<i64> {
+            let config = Config {
+                vector_dim: 4,
+                timestamp: Some(TimestampConfig { column: "created_at".to_string(), values }),
+                ..Config::default()
+            };
+            let mut generator = DataGenerator::new(config);
+            (0..3)
+                .flat_map(|_| {
+                    let batch = generator.generate_batch(50).unwrap();
+                    let field = batch.schema().field_with_name("created_at").unwrap().clone();
+                    assert_eq!(field.data_type(), &arrow::datatypes::DataType::Timestamp(TimeUnit::Millisecond, None));
+                    let column = batch.column_by_name("created_at").unwrap();
+                    column.as_primitive::<TimestampMillisecondType>().values().to_vec()
+                })
+                .collect()
+        };
+
+        let uniform = timestamps(TimestampValues::Uniform { start_ms: 1_000, end_ms: 2_000 });
+        assert_eq!(uniform.len(), 150);
+        assert!(uniform.iter().all(|ts| (1_000..2_000).contains(ts)));
+        assert!(uniform.windows(2).any(|w| w[0] > w[1]));
+
+        // Monotonic values continue across batches
+        let monotonic = timestamps(TimestampValues::Monotonic { start_ms: 5_000, step_ms: 10 });
+        assert_eq!(monotonic, (0..150).map(|row| 5_000 + row * 10).collect::<Vec<i64>>());
+
+        let empty = TimestampConfig {
+            column: "created_at".to_string(),
+            values: TimestampValues::Uniform { start_ms: 5, end_ms: 5 },
+        };
+        assert!(empty.validate().is_err());
+        let clash = Config {
+            timestamp: Some(TimestampConfig {
+                column: "scalar".to_string(),
+                values: TimestampValues::Monotonic { start_ms: 0, step_ms: 1 },
+            }),
+            ..Config::default()
+        };
+        assert!(DataGenerator::try_new(clash).is_err());
+        assert_eq!(
+            "10..20".parse::<TimeRange>().unwrap(),
+            TimeRange { start_ms: 10, end_ms: 20 }
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_deletion_ground_truth() {
+        use ground_truth::{Device, Metric, SearchOptions};
+        use std::collections::HashSet;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            vector_dim: 8,
+            target_file_size: 8 * 1024,
+            queries: Some(QueryConfig { count: 10, source: QuerySource::Sample }),
+            deletes: Some(DeletionConfig { epochs: 2, fraction: 0.25 }),
+            ..Config::default()
+        };
+        let options = DatasetOptions {
+            output_dir: temp_dir.path().to_path_buf(),
+            prefix: "base".to_string(),
+            total_rows: 200,
+            batch_size: 64,
+            layout: None,
+        };
+        let report = dataset::generate(&config, &options, &CancellationToken::new(), |_| {}).unwrap();
+        let (path, count) = report.deletes.clone().unwrap();
+        assert_eq!(path, temp_dir.path().join(dataset::DELETES_FILE_NAME));
+        assert_eq!(count, 100);
+        let deletes = deletes::read_deletes(&path).unwrap();
+        assert_eq!(deletes.len(), 2);
+        assert!(deletes.iter().all(|ids| ids.len() == 50 && ids.windows(2).all(|w| w[0] < w[1])));
+        let all: HashSet<u64> = deletes.iter().flatten().copied().collect();
+        assert_eq!(all.len(), 100);
+        assert!(all.iter().all(|&id| id < 200));
+        assert_eq!(deletes, DeletionConfig { epochs: 2, fraction: 0.25 }.deleted_ids(config.seed, 200));
+
+        let files: Vec<_> = report.files.iter().map(|f| f.path.clone()).collect();
+        let (queries_path, _) = report.queries.unwrap();
+        let queries = ground_truth::read_vectors(&queries_path, "vector", 64).unwrap();
+        let search = |k| SearchOptions { k, metric: Metric::L2, batch_size: 50, device: Device::Cpu };
+        let epochs =
+            ground_truth::compute_after_deletes(&files, "vector", queries.clone(), &[], &deletes, &search(5)).unwrap();
+        assert_eq!(epochs.len(), 2);
+        let ranking = ground_truth::compute(&files, "vector", queries, &[], &search(200)).unwrap();
+        for (epoch, neighbors) in epochs.iter().enumerate() {
+            let deleted: HashSet<u64> = deletes[..=epoch].iter().flatten().copied().collect();
+            for (closest, ranked) in neighbors.iter().zip(&ranking) {
+                // The closest rows that survive every deletion so far
+                let expected: Vec<_> = ranked.iter().filter(|n| !deleted.contains(&n.id)).take(5).copied().collect();
+                assert_eq!(closest, &expected);
+            }
+        }
+
+        let output = std::path::Path::new("out/ground_truth.parquet");
+        assert_eq!(ground_truth::epoch_path(output, 2), std::path::Path::new("out/ground_truth.epoch-2.parquet"));
+        assert!(DeletionConfig { epochs: 3, fraction: 0.5 }.validate().is_err());
+    }
+
+    #[test]
+    fn test_numeric_distributions() {
+        use arrow::array::AsArray;
+        use arrow::datatypes::{Float64Type, Int32Type};
+
+        let text = "price:float64(0..100),qty:int32(1..10),latency:float64(mean=50,std=10)";
+        let spec: SchemaSpec = text.parse().unwrap();
+        assert_eq!(spec.to_string(), text);
+        assert_eq!(
+            spec.columns[2].distribution,
+            Some(NumericDistribution::Normal { mean: 50.0, std: 10.0 })
+        );
+        for invalid in ["a:string(mean=1,std=1)", "a:float64(mean=1)", "a:int32(1..2"] {
+            assert!(invalid.parse::<SchemaSpec>().is_err(), "{}", invalid);
+        }
+        for invalid in ["a:int32(0.5..2)", "a:int32(0..1e10)", "a:float32(5..1)", "a:float64(mean=1,std=0)"] {
+            let config = Config { schema: Some(invalid.parse().unwrap()), ..Config::default() };
+            assert!(DataGenerator::try_new(config).is_err(), "{}", invalid);
+        }
+
+        let config = Config { schema: Some(spec), ..Config::default() };
+        let mut generator = DataGenerator::try_new(config).unwrap();
+        let batch = generator.generate_batch(2000).unwrap();
+        let price = batch.column_by_name("price").unwrap().as_primitive::<Float64Type>();
+        assert!(price.values().iter().all(|v| (0.0..100.0).contains(v)));
+        let qty = batch.column_by_name("qty").unwrap().as_primitive::<Int32Type>();
+        assert!(qty.values().iter().all(|v| (1..=10).contains(v)));
+        assert!(qty.values().contains(&1) && qty.values().contains(&10));
+        let latency = batch.column_by_name("latency").unwrap().as_primitive::<Float64Type>();
+        let n = latency.len() as f64;
+        let mean = latency.values().iter().sum::<f64>() / n;
+        let std = (latency.values().iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+        assert!((mean - 50.0).abs() < 1.0, "{}", mean);
+        assert!((std - 10.0).abs() < 1.0, "{}", std);
+
+        assert!((numeric::normal_quantile(0.975) - 1.959_964).abs() < 1e-5);
+        assert!((numeric::normal_cdf(1.959_964) - 0.975).abs() < 1e-6);
+        let qty = NumericDistribution::Uniform { min: 1.0, max: 10.0 };
+        assert_eq!(qty.int_quantile(0.3), 4);
+        assert!((qty.int_fraction_below(4) - 0.3).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_category_column() {
+        use arrow::array::AsArray;
+        use std::collections::HashSet;
+
+        let spec: SchemaSpec = "id:int64,status:category(5)".parse().unwrap();
+        assert_eq!(spec.to_string(), "id:int64,status:category(5)");
+        assert!("status:category(x)".parse::<SchemaSpec>().is_err());
+
+        let values = |seed: u64| -> HashSet<String> {
+            let config = Config { seed, schema: Some(spec.clone()), ..Config::default() };
+            let mut generator = DataGenerator::try_new(config).unwrap();
+            (0..3)
+                .flat_map(|_| {
+                    let batch = generator.generate_batch(200).unwrap();
+                    let column = batch.column_by_name("status").unwrap().as_string::<i32>().clone();
+                    column.iter().map(|v| v.unwrap().to_string()).collect::<Vec<_>>()
+                })
+                .collect()
+        };
+        // Every batch draws from the same five values
+        let first = values(7);
+        assert_eq!(first, values(7));
+        assert_eq!(first.len(), 5);
+        assert_ne!(first, values(8));
+
+        let categories = category::Categories::generate(1, 0, 10_000, Skew::Uniform);
+        let distinct: HashSet<&String> = categories.values().iter().collect();
+        assert_eq!(distinct.len(), 10_000);
+        assert!(categories.values().iter().all(|v| v.len() == categories.value_len()));
+
+        let empty = Config { schema: Some("status:category(0)".parse().unwrap()), ..Config::default() };
+        assert!(DataGenerator::try_new(empty).is_err());
+    }
+
+    #[test]
+    fn test_numeric_bounds() {
+        use arrow::array::AsArray;
+        use arrow::datatypes::{Float64Type, Int64Type};
+
+        let text = "latency:float64(mean=50,std=20,min=0,max=80,precision=2),rating:int64(min=1,max=5)";
+        let spec: SchemaSpec = text.parse().unwrap();
+        assert_eq!(spec.to_string(), text);
+        assert_eq!(
+            spec.columns[0].bounds,
+            NumericBounds { min: Some(0.0), max: Some(80.0), precision: Some(2) }
+        );
+
+        let config = Config { schema: Some(spec), ..Config::default() };
+        let mut generator = DataGenerator::try_new(config).unwrap();
+        let batch = generator.generate_batch(2000).unwrap();
+        let field = batch.schema().field_with_name("latency").unwrap().clone();
+        assert_eq!(field.metadata().get(MIN_KEY).map(String::as_str), Some("0"));
+        assert_eq!(field.metadata().get(MAX_KEY).map(String::as_str), Some("80"));
+        assert_eq!(field.metadata().get(PRECISION_KEY).map(String::as_str), Some("2"));
+        let latency = batch.column_by_name("latency").unwrap().as_primitive::<Float64Type>();
+        assert!(latency.values().iter().all(|v| (0.0..=80.0).contains(v)));
+        assert!(latency.values().iter().all(|v| (v * 100.0).round() / 100.0 == *v));
+        // Both limits are hit by clamping
+        assert!(latency.values().contains(&0.0) && latency.values().contains(&80.0));
+        let rating = batch.column_by_name("rating").unwrap().as_primitive::<Int64Type>();
+        assert!(rating.values().iter().all(|v| (1..=5).contains(v)));
+        assert!((1..=5).all(|v| rating.values().contains(&v)));
+
+        for invalid in ["a:int32(precision=2)", "a:float64(min=2,max=1)", "a:float64(min=0.125,precision=2)"] {
+            let config = Config { schema: Some(invalid.parse().unwrap()), ..Config::default() };
+            assert!(DataGenerator::try_new(config).is_err(), "{}", invalid);
+        }
+        assert!("a:float64(min=x)".parse::<SchemaSpec>().is_err());
+    }
+
+    #[test]
+    fn test_percentile_distribution() {
+        use arrow::array::AsArray;
+        use arrow::datatypes::Float64Type;
+
+        let text = "latency:float64(p50=12,p90=40,p99=250)";
+        let spec: SchemaSpec = text.parse().unwrap();
+        assert_eq!(spec.to_string(), text);
+        let distribution = spec.columns[0].distribution.clone().unwrap();
+        for (p, v) in [(0.5, 12.0), (0.9, 40.0), (0.99, 250.0)] {
+            assert!((distribution.quantile(p) - v).abs() < 1e-6 * v, "p{}", p);
+            assert!((distribution.cdf(v) - p).abs() < 1e-6, "p{}", p);
+        }
+        // Positive targets stay positive in the tails
+        assert!(distribution.quantile(1e-6) > 0.0);
+
+        let config = Config { schema: Some(spec), ..Config::default() };
+        let mut generator = DataGenerator::try_new(config).unwrap();
+        let batch = generator.generate_batch(20_000).unwrap();
+        let mut values = batch.column_by_name("latency").unwrap().as_primitive::<Float64Type>().values().to_vec();
+        values.sort_by(f64::total_cmp);
+        let percentile = |p: f64| values[(p * values.len() as f64) as usize];
+        assert!((percentile(0.5) / 12.0 - 1.0).abs() < 0.05, "{}", percentile(0.5));
+        assert!((percentile(0.9) / 40.0 - 1.0).abs() < 0.1, "{}", percentile(0.9));
+
+        // Two targets over negative values give a normal fit
+        let normal: NumericDistribution = "p50=-10,p84.1345=0".parse().unwrap();
+        assert!((normal.cdf(10.0) - numeric::normal_cdf(2.0)).abs() < 1e-4);
+
+        for invalid in ["a:float64(p50=10)", "a:float64(p50=10,p90=5)", "a:float64(p0=1,p50=2)"] {
+            let config = Config { schema: Some(invalid.parse().unwrap()), ..Config::default() };
+            assert!(DataGenerator::try_new(config).is_err(), "{}", invalid);
+        }
+        assert!("a:float64(p50=1,mean=2)".parse::<SchemaSpec>().is_err());
+    }
+
+    #[test]
+    fn test_zipf_category_column() {
+        use arrow::array::AsArray;
+        use std::collections::HashMap;
+
+        let spec: SchemaSpec = "status:category(100,s=1.5)".parse().unwrap();
+        assert_eq!(spec.to_string(), "status:category(100,s=1.5)");
+        assert_eq!(
+            spec.columns[0].column_type,
+            ColumnType::Category { cardinality: 100, skew: Skew::Zipf(1.5) }
+        );
+        assert!("status:category(100,t=1)".parse::<SchemaSpec>().is_err());
+
+        let config = Config { schema: Some(spec), ..Config::default() };
+        let mut generator = DataGenerator::try_new(config).unwrap();
+        let batch = generator.generate_batch(10_000).unwrap();
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for value in batch.column_by_name("status").unwrap().as_string::<i32>().iter() {
+            *counts.entry(value.unwrap()).or_default() += 1;
+        }
+        let mut counts: Vec<usize> = counts.into_values().collect();
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+        // The top value takes 1 / H(100, 1.5) ~ 41% of the rows
+        assert!((3_800..4_500).contains(&counts[0]), "{}", counts[0]);
+        assert!(counts[0] > 2 * counts[1]);
+
+        let negative = Config { schema: Some("status:category(10,s=-1)".parse().unwrap()), ..Config::default() };
+        assert!(DataGenerator::try_new(negative).is_err());
+    }
+
+    #[test]
+    fn test_correlated_columns() {
+        use arrow::array::AsArray;
+        use arrow::datatypes::{Float64Type, Int32Type};
+
+        let correlation: Correlation = "a:b=0.8,a:c=-0.5".parse().unwrap();
+        assert_eq!(correlation.columns, vec!["a", "b", "c"]);
+        assert_eq!(correlation.to_string(), "a:b=0.8,a:c=-0.5");
+        let config = Config {
+            schema: Some("a:float64(mean=0,std=1),b:float64(mean=10,std=3),c:int32(0..1000),d:float64".parse().unwrap()),
+            correlation: Some(correlation),
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::try_new(config).unwrap();
+        let batch = generator.generate_batch(20_000).unwrap();
+        let column = |name: &str| -> Vec<f64> {
+            let array = batch.column_by_name(name).unwrap();
+            match array.as_primitive_opt::<Float64Type>() {
+                Some(values) => values.values().to_vec(),
+                None => array.as_primitive::<Int32Type>().values().iter().map(|&v| v as f64).collect(),
+            }
+        };
+        let pearson = |x: &[f64], y: &[f64]| {
+            let n = x.len() as f64;
+            let (mx, my) = (x.iter().sum::<f64>() / n, y.iter().sum::<f64>() / n);
+            let cov: f64 = x.iter().zip(y).map(|(a, b)| (a - mx) * (b - my)).sum();
+            let vx: f64 = x.iter().map(|a| (a - mx).powi(2)).sum();
+            let vy: f64 = y.iter().map(|b| (b - my).powi(2)).sum();
+            cov / (vx * vy).sqrt()
+        };
+        let (a, b, c, d) = (column("a"), column("b"), column("c"), column("d"));
+        assert!((pearson(&a, &b) - 0.8).abs() < 0.02, "{}", pearson(&a, &b));
+        // Normal against uniform: slightly weaker, as for any non-normal pair
+        assert!((pearson(&a, &c) + 0.49).abs() < 0.03, "{}", pearson(&a, &c));
+        // Pairs left out stay uncorrelated
+        assert!(pearson(&b, &c).abs() < 0.03, "{}", pearson(&b, &c));
+        assert!(pearson(&a, &d).abs() < 0.03);
+        // Marginals are unchanged
+        assert!(c.iter().all(|v| (0.0..=1000.0).contains(v)));
+        let mean_b = b.iter().sum::<f64>() / b.len() as f64;
+        assert!((mean_b - 10.0).abs() < 0.1, "{}", mean_b);
+
+        for invalid in ["a:b=1.5", "a:a=0.5", "a:b=0.9,a:c=0.9,b:c=-0.9", "a:b"] {
+            assert!(invalid.parse::<Correlation>().is_err(), "{}", invalid);
+        }
+        let not_numeric = Config {
+            schema: Some("a:float64,s:string".parse().unwrap()),
+            correlation: Some("a:s=0.5".parse().unwrap()),
+            ..Config::default()
+        };
+        assert!(DataGenerator::try_new(not_numeric).is_err());
+    }
+
+    #[test]
+    fn test_float16_vectors() {
+        use arrow::array::{AsArray, BinaryArray};
+        use arrow::datatypes::{DataType, Float16Type};
+
+        let binary = Config {
+            vector_dim: 6,
+            vector_type: VectorElementType::Float16,
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(binary.clone());
+        let field = generator.schema().field_with_name("vector").unwrap().clone();
+        assert_eq!(field.metadata().get(ELEMENT_TYPE_KEY).map(String::as_str), Some("f16"));
+        let batch = generator.generate_batch(4).unwrap();
+        let vectors = batch.column_by_name("vector").unwrap();
+        let vectors = vectors.as_any().downcast_ref::<BinaryArray>().unwrap();
+        // The same draws as f32 vectors, narrowed to 2 bytes per element
+        let wide = DataGenerator::new(Config { vector_type: VectorElementType::Float32, ..binary.clone() })
+            .generate_batch(4)
+            .unwrap();
+        let wide = wide.column_by_name("vector").unwrap();
+        let wide = wide.as_any().downcast_ref::<BinaryArray>().unwrap();
+        for row in 0..4 {
+            assert_eq!(vectors.value(row).len(), 12);
+            let half = core_gen::decode_vector(vectors.value(row), VectorElementType::Float16);
+            let full = core_gen::decode_vector(wide.value(row), VectorElementType::Float32);
+            for (h, f) in half.iter().zip(&full) {
+                assert!((h - f).abs() <= 1e-3, "{} vs {}", h, f);
+            }
+        }
+
+        let mut bytes = Vec::new();
+        core_gen::encode_vector(&[0.5, -0.25, 1.0], VectorElementType::Float16, &mut bytes);
+        assert_eq!(core_gen::decode_vector(&bytes, VectorElementType::Float16), vec![0.5, -0.25, 1.0]);
+
+        let list = Config { vector_encoding: VectorEncoding::FixedSizeList, ..binary };
+        let batch = DataGenerator::new(list).generate_batch(4).unwrap();
+        let lists = batch.column_by_name("vector").unwrap().as_fixed_size_list();
+        assert_eq!(lists.value_type(), DataType::Float16);
+        let values: Vec<f64> = lists.value(0).as_primitive::<Float16Type>().values().iter().map(|v| v.to_f64()).collect();
+        assert_eq!(values, core_gen::decode_vector(vectors.value(0), VectorElementType::Float16));
+
+        let spec: SchemaSpec = "emb:vector<f16,4>".parse().unwrap();
+        assert_eq!(spec.columns[0].column_type, ColumnType::Vector { element: VectorElementType::Float16, dim: 4 });
+    }
+
+    #[test]
+    fn test_filled_columns_reuse_arrays() {
+        use arrow::array::Array;
+
+        let config = Config {
+            vector_dim: 8,
+            null_columns: vec!["scalar".to_string()],
+            constant_columns: vec!["vector".to_string()],
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config);
+        let batches: Vec<_> = [10, 10, 4, 16, 10].iter().map(|&rows| generator.generate_batch(rows).unwrap()).collect();
+        let values = |batch: &RecordBatch| batch.column_by_name("vector").unwrap().to_data().buffers()[1].as_ptr();
+        // Batches up to the first one's size share its buffers; larger ones get their own
+        assert_eq!(values(&batches[0]), values(&batches[1]));
+        assert_eq!(values(&batches[0]), values(&batches[2]));
+        assert_ne!(values(&batches[0]), values(&batches[3]));
+        assert_eq!(values(&batches[0]), values(&batches[4]));
+        assert_eq!(batches[2].num_rows(), 4);
+        for batch in &batches {
+            let vectors = batch.column_by_name("vector").unwrap();
+            let vectors = vectors.as_any().downcast_ref::<arrow::array::BinaryArray>().unwrap();
+            assert!((0..vectors.len()).all(|row| vectors.value(row) == vectors.value(0)));
+            assert_eq!(batch.column_by_name("scalar").unwrap().null_count(), batch.num_rows());
+        }
+    }
+
+    #[test]
+    fn test_float64_vectors() {
+        use arrow::array::{AsArray, BinaryArray};
+        use arrow::datatypes::{DataType, Float64Type};
+
+        let binary = Config {
+            vector_dim: 5,
+            vector_type: VectorElementType::Float64,
+            ..Config::default()
+        };
+        let batch = DataGenerator::new(binary.clone()).generate_batch(3).unwrap();
+        let vectors = batch.column_by_name("vector").unwrap();
+        let vectors = vectors.as_any().downcast_ref::<BinaryArray>().unwrap();
+        let list = Config { vector_encoding: VectorEncoding::FixedSizeList, ..binary };
+        let batch = DataGenerator::new(list).generate_batch(3).unwrap();
+        let lists = batch.column_by_name("vector").unwrap().as_fixed_size_list();
+        assert_eq!(lists.value_type(), DataType::Float64);
+        for row in 0..3 {
+            // 8-byte little-endian elements, the same values as the typed list
+            let bytes = vectors.value(row);
+            assert_eq!(bytes.len(), 5 * 8);
+            let values: Vec<f64> =
+                bytes.chunks_exact(8).map(|b| f64::from_le_bytes(b.try_into().unwrap())).collect();
+            assert_eq!(values, lists.value(row).as_primitive::<Float64Type>().values().to_vec());
+        }
+    }
+
+    #[test]
+    fn test_int8_vectors() {
+        use arrow::array::{AsArray, BinaryArray};
+        use arrow::datatypes::Float32Type;
+
+        let original =
+            Config { vector_dim: 8, vector_type: VectorElementType::Float64, ..Config::default() };
+        let batch = DataGenerator::new(original.clone()).generate_batch(50).unwrap();
+        let vectors = batch.column_by_name("vector").unwrap();
+        let vectors = vectors.as_any().downcast_ref::<BinaryArray>().unwrap();
+        let values = |row| core_gen::decode_vector(vectors.value(row), VectorElementType::Float64);
+
+        // One byte per element, the value scaled by 127 and rounded
+        let int8 = Config { vector_type: VectorElementType::Int8, ..original };
+        let batch = DataGenerator::new(int8.clone()).generate_batch(50).unwrap();
+        let codes = batch.column_by_name("vector").unwrap();
+        let codes = codes.as_any().downcast_ref::<BinaryArray>().unwrap();
+        for row in 0..50 {
+            let expected: Vec<u8> =
+                values(row).iter().map(|v| (v * 127.0).round() as i8 as u8).collect();
+            assert_eq!(codes.value(row), expected.as_slice());
+        }
+
+        // Per-vector scales restore the values from codes spanning [-127, 127]
+        let scaled = Config { int8_scale: true, ..int8.clone() };
+        let batch = DataGenerator::new(scaled).generate_batch(50).unwrap();
+        let codes = batch.column_by_name("vector").unwrap();
+        let codes = codes.as_any().downcast_ref::<BinaryArray>().unwrap();
+        let scales = batch.column_by_name("vector_scale").unwrap().as_primitive::<Float32Type>();
+        for row in 0..50 {
+            let scale = scales.value(row) as f64;
+            let code_values = codes.value(row).iter().map(|&b| b as i8);
+            assert!(code_values.clone().any(|code| code.unsigned_abs() == 127));
+            for (code, value) in code_values.zip(values(row)) {
+                assert!((code as f64 * scale - value).abs() <= scale * 0.5 + 1e-6);
+            }
+        }
+
+        assert!(int8.validate().is_ok());
+        assert!(Config { int8_scale: true, ..Config::default() }.validate().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_layout_strategies() {
+        use crate::layout::{BucketedLayout, Date, DateLayout, FlatLayout};
+        use std::sync::Arc;
+
+        let slot = FileSlot { prefix: "rows", index: 7, part: 3, tenant: Some(2), bucket: Some(5) };
+        let path = |layout: &dyn LayoutStrategy| layout.file_path(&slot);
+        assert_eq!(path(&FlatLayout), Path::new("rows-tenant2-bucket5-00000003.parquet"));
+        assert_eq!(path(&BucketedLayout), Path::new("tenant_id=2/rows-00000003_00005.parquet"));
+        let start: Date = "2024-02-28".parse().unwrap();
+        let date = DateLayout { start: start.to_days(), files_per_day: 2 };
+        let dated = Path::new("tenant_id=2/dt=2024-02-29/bucket-5/rows-00000003.parquet");
+        assert_eq!(path(&date), dated);
+        assert_eq!(Date::from_days(0).to_string(), "1970-01-01");
+        assert!("2023-02-29".parse::<Date>().is_err());
+
+        // Partitioned files of a dataset follow the layout, with the default layout's rows
+        let temp_dir = tempfile::tempdir().unwrap();
+        let config = Config {
+            vector_dim: 8,
+            tenants: Some(TenantConfig {
+                partitioned: true,
+                ..TenantConfig::new(3, Skew::Uniform)
+            }),
+            ..Config::default()
+        };
+        let options = DatasetOptions {
+            output_dir: temp_dir.path().to_path_buf(),
+            prefix: "part".to_string(),
+            total_rows: 300,
+            batch_size: 64,
+            layout: Some(Arc::new(FlatLayout)),
+        };
+        let report =
+            dataset::generate(&config, &options, &CancellationToken::new(), |_| {}).unwrap();
+        let hive = DatasetOptions { layout: None, ..options.clone() };
+        let planned = dataset::plan_files(&config, &hive, usize::MAX);
+        assert_eq!(report.files.len(), planned.len());
+        for (file, plan) in report.files.iter().zip(&planned) {
+            assert_eq!(file.path.parent(), Some(temp_dir.path()));
+            let name = format!("part-tenant{}-00000000.parquet", file.tenant.unwrap());
+            assert_eq!(file.path.file_name().unwrap(), name.as_str());
+            let dir = format!("tenant_id={}", plan.tenant.unwrap());
+            assert!(plan.path.ends_with(Path::new(&dir).join("part-00000000.parquet")));
+            assert_eq!(file.rows, plan.rows);
+        }
+    }
+
+    #[test]
+    fn test_binary_vectors() {
+        use arrow::array::{AsArray, BinaryArray};
+        use arrow::datatypes::{DataType, UInt8Type};
+
+        let original =
+            Config { vector_dim: 16, vector_type: VectorElementType::Float64, ..Config::default() };
+        let batch = DataGenerator::new(original.clone()).generate_batch(20).unwrap();
+        let vectors = batch.column_by_name("vector").unwrap();
+        let vectors = vectors.as_any().downcast_ref::<BinaryArray>().unwrap();
+
+        // dim / 8 bytes of sign bits, the first dimension in the top bit
+        let binary = Config { vector_type: VectorElementType::Binary, ..original };
+        let batch = DataGenerator::new(binary.clone()).generate_batch(20).unwrap();
+        let packed = batch.column_by_name("vector").unwrap();
+        let packed = packed.as_any().downcast_ref::<BinaryArray>().unwrap();
+        let list = Config { vector_encoding: VectorEncoding::FixedSizeList, ..binary.clone() };
+        let batch = DataGenerator::new(list).generate_batch(20).unwrap();
+        let lists = batch.column_by_name("vector").unwrap().as_fixed_size_list();
+        assert_eq!((lists.value_type(), lists.value_length()), (DataType::UInt8, 2));
+        for row in 0..20 {
+            let values = core_gen::decode_vector(vectors.value(row), VectorElementType::Float64);
+            let bytes = packed.value(row);
+            assert_eq!(bytes.len(), 2);
+            for (i, value) in values.iter().enumerate() {
+                assert_eq!(bytes[i / 8] >> (7 - i % 8) & 1 == 1, *value > 0.0);
+            }
+            assert_eq!(lists.value(row).as_primitive::<UInt8Type>().values().to_vec(), bytes);
+            let bits = core_gen::decode_vector(bytes, VectorElementType::Binary);
+            assert_eq!(bits, values.iter().map(|&v| f64::from(v > 0.0)).collect::<Vec<_>>());
+        }
+
+        assert!(Config { vector_dim: 12, ..binary }.validate().is_err());
+        let spec: SchemaSpec = "v:vector<binary,12>".parse().unwrap();
+        let spec = Config { vector_dim: 12, schema: Some(spec), ..Config::default() };
+        assert!(spec.validate().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_write_until_size() {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let config = Config { vector_dim: 64, ..Config::default() };
+        let writer = ParquetWriter::new(config.clone());
+        let temp_file = NamedTempFile::new().unwrap();
+        let path = temp_file.path().to_str().unwrap();
+        let budget = 2 * 1024 * 1024;
+        let rows = writer
+            .write_to_file_until_size(path, &mut DataGenerator::new(config.clone()), budget, 1000)
+            .unwrap();
+
+        // Close to the budget, holding the reported rows
+        let size = std::fs::metadata(path).unwrap().len();
+        assert!(size > budget * 3 / 4 && size < budget * 5 / 4, "{} bytes", size);
+        let file = std::fs::File::open(path).unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(file).unwrap();
+        assert_eq!(reader.metadata().file_metadata().num_rows() as usize, rows);
+
+        let shuffled = Config { shuffle_buffer: Some(100), ..config };
+        let mut generator = DataGenerator::new(shuffled.clone());
+        let writer = ParquetWriter::new(shuffled);
+        assert!(writer.write_to_file_until_size(path, &mut generator, budget, 1000).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "parquet")]
+    fn test_value_samples() {
+        let config = Config {
+            vector_dim: 16,
+            value_samples: Some(5),
+            ..Config::default()
+        };
+        let samples = |dir: &std::path::Path| {
+            let options = DatasetOptions {
+                output_dir: dir.to_path_buf(),
+                prefix: "rows".to_string(),
+                total_rows: 300,
+                batch_size: 50,
+                layout: None,
+            };
+            let report =
+                dataset::generate(&config, &options, &CancellationToken::new(), |_| {}).unwrap();
+            report
+                .files
+                .iter()
+                .map(|file| {
+                    let text = std::fs::read_to_string(audit::sample_path(&file.path)).unwrap();
+                    let header = format!("# 5 of {} rows sampled", file.rows);
+                    assert!(text.lines().any(|line| line == header));
+                    text
+                })
+                .collect::<Vec<_>>()
+        };
+        let (a, b) = (tempfile::tempdir().unwrap(), tempfile::tempdir().unwrap());
+        let texts = samples(a.path());
+        let text = &texts[0];
+        let column = text.lines().position(|line| line.starts_with("vector (")).unwrap();
+        let values: Vec<&str> = text
+            .lines()
+            .skip(column + 1)
+            .take_while(|line| line.starts_with("  "))
+            .collect();
+        assert_eq!(values.len(), 5);
+        // Vectors are previewed, not printed in full
+        assert!(values.iter().all(|value| value.ends_with(", … 8 more]")));
+        // Reruns sample the same rows
+        let other = samples(b.path());
+        let strip = |text: &str| text.lines().skip(1).collect::<Vec<_>>().join("\n");
+        assert_eq!(
+            texts.iter().map(|t| strip(t)).collect::<Vec<_>>(),
+            other.iter().map(|t| strip(t)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_sparse_vectors() {
+        use arrow::array::AsArray;
+        use arrow::datatypes::{Float32Type, Int32Type};
+
+        let sparse = SparseConfig {
+            dim: 1000,
+            density: 0.02,
+        };
+        let config = Config {
+            vector_dim: 4,
+            sparse: Some(sparse),
+            ..Config::default()
+        };
+        let mut generator = DataGenerator::new(config);
+        let batch = generator.generate_batch(500).unwrap();
+        let field = generator.schema().field_with_name("sparse_vector").unwrap();
+        assert_eq!(field.metadata()[SPARSE_DIM_KEY], "1000");
+        let column = batch.column_by_name("sparse_vector").unwrap().as_struct();
+        let indices = column.column_by_name("indices").unwrap().as_list::<i32>();
+        let values = column.column_by_name("values").unwrap().as_list::<i32>();
+        let mut nnz = 0;
+        for row in 0..batch.num_rows() {
+            let row_indices = indices.value(row);
+            let row_indices = row_indices.as_primitive::<Int32Type>().values();
+            let row_values = values.value(row);
+            let row_values = row_values.as_primitive::<Float32Type>().values();
+            assert_eq!(row_indices.len(), row_values.len());
+            assert!(row_indices.windows(2).all(|w| w[0] < w[1]));
+            assert!(row_indices.iter().all(|&i| (0..1000).contains(&i)));
+            assert!(row_values.iter().all(|&v| v > 0.0));
+            nnz += row_indices.len();
+        }
+        // About 20 non-zeros per row
+        let mean = nnz as f64 / batch.num_rows() as f64;
+        assert!((18.0..22.0).contains(&mean), "{}", mean);
+
+        let spec: SchemaSpec = "emb:vector<f32,4>,terms:sparse<30522,0.001>".parse().unwrap();
+        assert_eq!(spec.to_string(), "emb:vector<f32,4>,terms:sparse<30522,0.001>");
+        for invalid in ["terms:sparse<0,0.1>", "terms:sparse<100,0>", "terms:sparse<100,1.5>"] {
+            let spec: SchemaSpec = invalid.parse().unwrap();
+            let config = Config { vector_dim: 4, schema: Some(spec), ..Config::default() };
+            assert!(config.validate().is_err(), "{}", invalid);
+        }
+        assert!("terms:sparse<100>".parse::<SchemaSpec>().is_err());
     }
 }
\ No newline at end of file
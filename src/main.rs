@@ -1,21 +1,64 @@
-use arrow::compute::min;
-use clap::{Parser, ValueEnum};
-use indicatif::{ProgressBar, ProgressStyle};
-use std::path::PathBuf;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
 use std::time::Instant;
 use bytesize::ByteSize;
-use vector_data_gen::{Config, CompressionType, DataGenerator, ParquetWriter};
+use vector_data_gen::config_format;
+use vector_data_gen::dataset::{self, DatasetEvent};
+#[cfg(feature = "tui")]
+use vector_data_gen::dashboard::Dashboard;
+use vector_data_gen::diff::{self, DiffOptions};
+use vector_data_gen::footer;
+use vector_data_gen::formats::{
+    CsvSink, DocumentFormat, DocumentSink, JsonLinesSink, NpySink, NpzSink, VecsFormat, VecsSink, VectorText,
+};
+use vector_data_gen::golden::GoldenManifest;
+use vector_data_gen::ground_truth::{self, Device, Metric, SearchOptions};
+use vector_data_gen::layout::{BucketedLayout, Date, DateLayout, FlatLayout, LayoutStrategy};
+use vector_data_gen::manifest::{DatasetManifest, ManifestWriter, MANIFEST_FILE_NAME};
+use vector_data_gen::workload;
+use vector_data_gen::{
+    companion, BucketConfig, ByteOrder, CancelPolicy, CancellationToken, ClusterConfig, ColumnSubset, ColumnType, CompressionType, Config, Correlation, Curve, DataGenerator,
+    DatasetOptions, DegreeDistribution, DeletionConfig, DerivedColumn, DuplicateConfig, EdgeConfig, ForeignKeyConfig, GeneratorVersion, IdColumn, KDistribution, Locale, MixtureConfig, Monotonic, NullRatio, PageIndexConfig, ParquetWriter, PayloadConfig, PayloadEntropy, PqConfig, Quantization, PruningConfig, QueryConfig, QuerySource, SchemaSpec, SessionConfig, Skew, SparseConfig, TenantConfig, TextConfig, TimeRange, TimestampConfig, TimestampValues, TrajectoryConfig,
+    VectorDistribution, VectorElementType, VectorEncoding, VectorLayout, WideConfig, WorkloadConfig,
+};
 use anyhow::{Result, Context};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 /// Command line arguments
 #[derive(Parser, Debug)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, args_conflicts_with_subcommands = true)]
 struct Args {
-    /// Output directory for generated files
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Output directory for generated files; s3://, gs:// and az:// URLs
+    /// upload Parquet files directly (object_store feature)
     #[arg(short, long, default_value = "./output")]
     output_dir: PathBuf,
 
+    /// Output format; CSV, JSON, vecs and NumPy formats write a single
+    /// <prefix>[.<format>].{csv,json,jsonl,fvecs,bvecs,npy,npz} file
+    #[arg(long, value_enum, default_value_t = OutputFormat::Parquet)]
+    format: OutputFormat,
+
+    /// Weaviate class / Vespa document type of generated documents
+    #[arg(long, default_value = "Document")]
+    document_type: String,
+
+    /// Text form of vector columns in --format csv output
+    #[arg(long, value_enum, default_value_t = CsvVectors::Base64)]
+    csv_vectors: CsvVectors,
+
+    /// Vector column written by --format fvecs, npy and npz; PQ code columns are written as bvecs
+    #[arg(long, value_name = "NAME", default_value = "vector")]
+    vecs_column: String,
+
     /// Total number of rows to generate
     #[arg(short, long, default_value_t = 1000)]
     total_rows: usize,
@@ -24,6 +67,10 @@ struct Args {
     #[arg(short, long, default_value = "512MB")]
     file_size: String,
 
+    /// Target uncompressed row group size (e.g. 128MB); rows per group follow the schema width
+    #[arg(long, value_name = "SIZE", conflicts_with = "pruning_row_group_rows")]
+    row_group_size: Option<String>,
+
     /// Compression type to use
     #[arg(short, long, value_enum, default_value_t = Compression::Snappy)]
     compression: Compression,
@@ -32,10 +79,155 @@ struct Args {
     #[arg(long, default_value_t = 1024)]
     vector_dim: usize,
 
+    /// Element type of vector values
+    #[arg(long, value_enum, default_value_t = VectorType::F32)]
+    vector_type: VectorType,
+
+    /// Also write the first vector column at this precision (mixed-precision datasets)
+    #[arg(long, value_enum)]
+    mixed_precision: Option<VectorType>,
+
+    /// Emit a PQ code column with this many sub-quantizers
+    #[arg(long)]
+    pq_subquantizers: Option<usize>,
+
+    /// Bits per PQ sub-quantizer code
+    #[arg(long, default_value_t = 8)]
+    pq_nbits: u32,
+
+    /// Write dataset-wide structures (e.g. the PQ codebook) to companion files
+    #[arg(long)]
+    companion_files: bool,
+
+    /// Add a tenant_id column with this many tenants
+    #[arg(long)]
+    tenants: Option<u32>,
+
+    /// Distribution of rows over tenants: uniform or zipf(<s>)
+    #[arg(long, default_value = "uniform")]
+    tenant_skew: Skew,
+
+    /// Write each tenant's rows under its own tenant_id=<id> directory
+    #[arg(long, requires = "tenants")]
+    partition_by_tenant: bool,
+
+    /// Trajectory mode: rows cycle through this many entities whose vectors random-walk
+    #[arg(long)]
+    trajectory_entities: Option<usize>,
+
+    /// Standard deviation of each trajectory step, per component
+    #[arg(long, default_value_t = 0.01)]
+    trajectory_step: f64,
+
+    /// Milliseconds between consecutive rows of the same entity
+    #[arg(long, default_value_t = 60_000)]
+    trajectory_interval_ms: i64,
+
+    /// Timestamp of each entity's first row, in milliseconds since the epoch
+    #[arg(long, default_value_t = 1_700_000_000_000)]
+    trajectory_start_ms: i64,
+
+    /// Write pruning fixtures: `id`/`ts` columns and row groups of exactly this many rows
+    #[arg(long, value_name = "ROWS", conflicts_with = "trajectory_entities")]
+    pruning_row_group_rows: Option<usize>,
+
+    /// Timestamp of row 0 in pruning fixtures, in milliseconds since the epoch
+    #[arg(long, default_value_t = 1_700_000_000_000)]
+    pruning_start_ms: i64,
+
+    /// Milliseconds between consecutive rows in pruning fixtures
+    #[arg(long, default_value_t = 1_000)]
+    pruning_interval_ms: i64,
+
+    /// Write column and offset indexes (page-level statistics)
+    #[arg(long)]
+    page_index: bool,
+
+    /// Rows per data page when writing the page index
+    #[arg(long, default_value_t = 1_000, requires = "page_index")]
+    page_rows: usize,
+
+    /// Also write edges.parquet with this mean out-degree per row (graph mode)
+    #[arg(long)]
+    edge_degree: Option<f64>,
+
+    /// Use exactly --edge-degree out-edges per node instead of a Poisson count
+    #[arg(long, requires = "edge_degree")]
+    constant_degree: bool,
+
+    /// Popularity of edge destinations: uniform or zipf(<s>)
+    #[arg(long, default_value = "uniform")]
+    edge_skew: Skew,
+
+    /// Add a foreign-key column with this name (multi-table mode)
+    #[arg(long)]
+    fk_column: Option<String>,
+
+    /// Number of parent rows the foreign key references
+    #[arg(long, default_value_t = 1000)]
+    fk_parents: usize,
+
+    /// Distribution of rows over parent keys: uniform or zipf(<s>)
+    #[arg(long, default_value = "uniform")]
+    fk_skew: Skew,
+
+    /// Also write the parent table as <NAME>.parquet
+    #[arg(long, requires = "fk_column")]
+    parent_table: Option<String>,
+
+    /// Add user_id/session_id columns with this many sessions per user
+    #[arg(long)]
+    sessions_per_user: Option<u64>,
+
+    /// Events (rows) per session
+    #[arg(long, default_value_t = 20, requires = "sessions_per_user")]
+    events_per_session: u64,
+
+    /// Fraction of vectors drawn from a pool shared by all files (cross-file duplicates)
+    #[arg(long)]
+    duplicate_fraction: Option<f64>,
+
+    /// Number of distinct vectors in the duplicate pool
+    #[arg(long, default_value_t = 1000, requires = "duplicate_fraction")]
+    duplicate_pool: u64,
+
+    /// Number of vector columns per row
+    #[arg(long, default_value_t = 1)]
+    vector_columns: usize,
+
     /// Scalar string length in bytes
     #[arg(long, default_value_t = 32)]
     scalar_len: usize,
 
+    /// Namespace prepended to every scalar value, e.g. "tenantA:"
+    #[arg(long, value_name = "PREFIX")]
+    scalar_prefix: Option<String>,
+
+    /// Generate these columns instead of vector and scalar, e.g.
+    /// "id:int64,emb:vector<f32,768>,title:string(64),score:float32"
+    #[arg(long, value_name = "NAME:TYPE,...")]
+    columns: Option<SchemaSpec>,
+
+    /// Correlate numeric columns of --columns, e.g. "price:qty=0.8,price:rating=-0.3"
+    #[arg(long, value_name = "COLUMN:COLUMN=R,...", requires = "columns")]
+    correlation: Option<Correlation>,
+
+    /// Add a unique `id` column first: monotonic (Int64 row position across files) or uuid
+    #[arg(long, value_name = "KIND")]
+    id_column: Option<IdColumn>,
+
+    /// Add a Timestamp(Millisecond) column with this name
+    #[arg(long, value_name = "NAME")]
+    timestamp_column: Option<String>,
+
+    /// Range of random timestamps, in milliseconds since the epoch
+    #[arg(long, value_name = "START..END", default_value = "1700000000000..1731536000000", requires = "timestamp_column")]
+    timestamp_range: TimeRange,
+
+    /// Make timestamps increase by MS per row from the range start instead of drawing them at random
+    #[arg(long, value_name = "MS", requires = "timestamp_column")]
+    timestamp_step: Option<i64>,
+
     /// Random seed for reproducible data
     #[arg(long, default_value_t = 42)]
     seed: u64,
@@ -47,10 +239,485 @@ struct Args {
     /// Enable verbose output
     #[arg(short, long)]
     verbose: bool,
+
+    /// Read output settings and the generator configuration from a TOML or YAML file;
+    /// options given on the command line override the file
+    #[arg(long, value_name = "PATH")]
+    config: Option<PathBuf>,
+
+    /// Read output settings and the configuration from a saved profile (see `profile save`);
+    /// options given on the command line override it
+    #[arg(long, value_name = "NAME", conflicts_with = "config")]
+    profile: Option<String>,
+
+    /// Print the effective configuration as TOML, for use with --config, instead of generating
+    #[arg(long)]
+    dump_config: bool,
+
+    /// Prefix for generated file names
+    #[arg(long, default_value = "vector_data")]
+    prefix: String,
+
+    /// Remove the file in progress on Ctrl-C instead of finalizing it
+    #[arg(long)]
+    discard_on_cancel: bool,
+
+    /// Generator algorithm version (v0 reproduces data from the original release)
+    #[arg(long, default_value_t = GeneratorVersion::LATEST)]
+    generator_version: GeneratorVersion,
+
+    /// Verify deterministic output against a golden manifest instead of generating
+    #[arg(long, value_name = "MANIFEST")]
+    golden: Option<PathBuf>,
+
+    /// Record a golden manifest for the current options instead of generating
+    #[arg(long, value_name = "MANIFEST", conflicts_with = "golden")]
+    record_golden: Option<PathBuf>,
+
+    /// Stream rows to this Arrow Flight endpoint via DoPut instead of writing files
+    #[cfg(feature = "flight")]
+    #[arg(long, value_name = "URL")]
+    flight_endpoint: Option<String>,
+
+    /// Header sent with Flight requests, as KEY=VALUE (repeatable)
+    #[cfg(feature = "flight")]
+    #[arg(long = "flight-header", value_name = "KEY=VALUE", value_parser = parse_header)]
+    flight_headers: Vec<(String, String)>,
+
+    /// Number of parallel DoPut streams
+    #[cfg(feature = "flight")]
+    #[arg(long, default_value_t = 4)]
+    flight_streams: usize,
+
+    /// Write rows into Redis at this URL instead of writing files
+    #[cfg(feature = "redis")]
+    #[arg(long, value_name = "URL")]
+    redis_url: Option<String>,
+
+    /// Create a RediSearch index with this name over the row hashes
+    #[cfg(feature = "redis")]
+    #[arg(long, requires = "redis_url")]
+    redis_index: Option<String>,
+
+    /// Add vectors to this vector set (VADD) instead of writing hashes
+    #[cfg(feature = "redis")]
+    #[arg(long, requires = "redis_url", conflicts_with = "redis_index")]
+    redis_vector_set: Option<String>,
+
+    /// Rows per Redis pipeline
+    #[cfg(feature = "redis")]
+    #[arg(long, default_value_t = 500)]
+    redis_pipeline: usize,
+
+    /// Insert rows into ScyllaDB/Cassandra via this contact point instead of writing files
+    #[cfg(feature = "scylla")]
+    #[arg(long, value_name = "HOST:PORT")]
+    scylla_node: Option<String>,
+
+    /// Keyspace of the Scylla table (the table is named after --prefix)
+    #[cfg(feature = "scylla")]
+    #[arg(long, default_value = "vdg")]
+    scylla_keyspace: String,
+
+    /// Replication factor used when creating the keyspace
+    #[cfg(feature = "scylla")]
+    #[arg(long, default_value_t = 1)]
+    scylla_replication_factor: u32,
+
+    /// Concurrent Scylla inserts
+    #[cfg(feature = "scylla")]
+    #[arg(long, default_value_t = 64)]
+    scylla_concurrency: usize,
+
+    /// Write rows into this SQLite database (table named after --prefix) instead of Parquet files
+    #[cfg(feature = "sqlite")]
+    #[arg(long, value_name = "PATH")]
+    sqlite: Option<PathBuf>,
+
+    /// Write an ann-benchmarks HDF5 file (train, test, neighbors, distances) instead of
+    /// Parquet files, with --queries test vectors
+    #[cfg(feature = "hdf5")]
+    #[arg(long, value_name = "PATH", requires = "queries")]
+    hdf5: Option<PathBuf>,
+
+    /// Neighbors per query in the --hdf5 file
+    #[cfg(feature = "hdf5")]
+    #[arg(long, value_name = "K", default_value_t = 100)]
+    hdf5_neighbors: usize,
+
+    /// Distance of the --hdf5 neighbors: l2 (euclidean) or cosine (angular)
+    #[cfg(feature = "hdf5")]
+    #[arg(long, default_value_t = Metric::L2)]
+    hdf5_metric: Metric,
+
+    /// POST every row to this HTTP endpoint instead of writing files
+    #[cfg(feature = "http")]
+    #[arg(long, value_name = "URL")]
+    http_endpoint: Option<String>,
+
+    /// Request body template with {{id}}, {{row}} and {{<column>}} placeholders
+    #[cfg(feature = "http")]
+    #[arg(long, requires = "http_endpoint")]
+    http_template: Option<String>,
+
+    /// Header sent with HTTP requests, as KEY=VALUE (repeatable)
+    #[cfg(feature = "http")]
+    #[arg(long = "http-header", value_name = "KEY=VALUE", value_parser = parse_header)]
+    http_headers: Vec<(String, String)>,
+
+    /// Concurrent HTTP requests
+    #[cfg(feature = "http")]
+    #[arg(long, default_value_t = 8)]
+    http_concurrency: usize,
+
+    /// Maximum HTTP requests per second
+    #[cfg(feature = "http")]
+    #[arg(long)]
+    http_rate: Option<f64>,
+
+    /// Store vectors as LargeBinary (64-bit offsets) so batches of huge vectors can exceed 2GB
+    #[arg(long)]
+    large_binary: bool,
+
+    /// Byte order of encoded vector elements
+    #[arg(long, value_enum, default_value_t = Endianness::Little)]
+    byte_order: Endianness,
+
+    /// Start each binary vector with its dimension as a u32, like .fvecs records
+    #[arg(long)]
+    dim_prefix: bool,
+
+    /// Arrow type of vector columns: binary, fixed-size binary or a typed fixed-size list
+    #[arg(long, value_enum, default_value_t = Encoding::Binary)]
+    vector_encoding: Encoding,
+
+    /// Distribution of vector components
+    #[arg(long, value_enum, default_value_t = Distribution::Uniform)]
+    vector_distribution: Distribution,
+
+    /// Mean of normally distributed vector components
+    #[arg(long, default_value_t = 0.0)]
+    vector_mean: f64,
+
+    /// Standard deviation of normally distributed vector components
+    #[arg(long, default_value_t = 1.0)]
+    vector_std: f64,
+
+    /// Scale every vector to unit L2 norm, for cosine-similarity benchmarks
+    #[arg(long)]
+    normalize: bool,
+
+    /// Draw vectors around K seeded cluster centroids (mixture of Gaussians)
+    #[arg(long, value_name = "K")]
+    clusters: Option<usize>,
+
+    /// Standard deviation of vector components around their cluster centroid
+    #[arg(long, default_value_t = 0.1, requires = "clusters")]
+    cluster_std: f64,
+
+    /// Snap vector values to N evenly spaced levels over [-1, 1] (highly compressible)
+    #[arg(long, value_name = "N")]
+    quantize_levels: Option<u32>,
+
+    /// Round vector values to this many mantissa bits (highly compressible)
+    #[arg(long, value_name = "BITS", conflicts_with = "quantize_levels")]
+    mantissa_bits: Option<u32>,
+
+    /// Scale each int8 vector by its largest magnitude, adding a vector_scale column (SQ8)
+    #[arg(long)]
+    int8_scale: bool,
+
+    /// Add a binary payload column with this many bytes per row
+    #[arg(long, value_name = "BYTES")]
+    payload_bytes: Option<usize>,
+
+    /// Payload bytes: incompressible (cryptographic RNG) or repeating (compressible pattern)
+    #[arg(long, default_value_t = PayloadEntropy::Incompressible, requires = "payload_bytes")]
+    payload_entropy: PayloadEntropy,
+
+    /// Add a sparse_vector column (indices and values) of this dimension, e.g. a vocabulary size
+    #[arg(long, value_name = "DIM")]
+    sparse_dim: Option<usize>,
+
+    /// Expected fraction of non-zero dimensions of --sparse-dim vectors
+    #[arg(long, default_value_t = 0.01, requires = "sparse_dim")]
+    sparse_density: f64,
+
+    /// Add this many extra columns (Int64, Float64, Utf8) to the logical schema
+    #[arg(long, value_name = "N")]
+    wide_columns: Option<usize>,
+
+    /// Wide columns written to each file [default: all]
+    #[arg(long, value_name = "N", requires = "wide_columns")]
+    wide_columns_per_file: Option<usize>,
+
+    /// How each file's wide columns are chosen: random or evolving
+    #[arg(long, default_value_t = ColumnSubset::Random, requires = "wide_columns")]
+    wide_subset: ColumnSubset,
+
+    /// Add a `text` column of up to N words from a Zipf-distributed vocabulary
+    #[arg(long, value_name = "N")]
+    text_words: Option<usize>,
+
+    /// Fewest words per text value
+    #[arg(long, value_name = "N", default_value_t = 1, requires = "text_words")]
+    text_min_words: usize,
+
+    /// Distinct words in the text vocabulary
+    #[arg(long, value_name = "N", default_value_t = 10000, requires = "text_words")]
+    text_vocabulary: usize,
+
+    /// Also add `text_tokens` (List<Int32>) with the seeded tokenizer's ids of each text
+    #[arg(long, requires = "text_words")]
+    text_tokens: bool,
+
+    /// Write each text value in one of these locales and add a `lang` column, e.g. en,ru,ja
+    #[arg(long, value_name = "LOCALE,...", value_delimiter = ',', requires = "text_words")]
+    text_locales: Vec<Locale>,
+
+    /// Make this column entirely null (repeatable)
+    #[arg(long = "null-column", value_name = "NAME")]
+    null_columns: Vec<String>,
+
+    /// Make this fraction of a column's values null, e.g. "scalar=0.1" or "vector=0.01" (repeatable)
+    #[arg(long = "null-ratio", value_name = "COLUMN=RATIO")]
+    null_ratios: Vec<NullRatio>,
+
+    /// Repeat a single value in every row of this column (repeatable)
+    #[arg(long = "constant-column", value_name = "NAME")]
+    constant_columns: Vec<String>,
+
+    /// Write rows through a shuffle buffer of N rows, scrambling id/timestamp order locally
+    #[arg(long, value_name = "N")]
+    shuffle_buffer: Option<usize>,
+
+    /// Add a column computed from others, e.g. "norm = l2(vector)" or "bucket = hash(id) % 16" (repeatable)
+    #[arg(long = "derive", value_name = "NAME=EXPR")]
+    derived: Vec<DerivedColumn>,
+
+    /// Keep COLUMN non-decreasing within each group of rows sharing KEY, e.g. "version:id" (repeatable)
+    #[arg(long, value_name = "COLUMN:KEY")]
+    monotonic: Vec<Monotonic>,
+
+    /// Route rows to bucket files by the hash of this column, like bucketed Hive/Paimon tables
+    #[arg(long, value_name = "COLUMN", requires = "buckets")]
+    bucket_by: Option<String>,
+
+    /// Buckets (files) per partition for --bucket-by
+    #[arg(long, value_name = "N", requires = "bucket_by")]
+    buckets: Option<u32>,
+
+    /// Directory structure and file names of Parquet data files
+    #[arg(long, value_enum, default_value_t = Layout::Hive)]
+    layout: Layout,
+
+    /// First daily partition of --layout date
+    #[arg(long, value_name = "YYYY-MM-DD", default_value = "2024-01-01")]
+    layout_start: Date,
+
+    /// Data files per daily partition of --layout date
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    files_per_day: usize,
+
+    /// Sort the rows of each file along a space-filling curve over these columns, e.g. tenant_id,ts
+    #[arg(long, value_name = "COLUMN,...", value_delimiter = ',')]
+    cluster_by: Vec<String>,
+
+    /// Space-filling curve for --cluster-by: z-order or hilbert
+    #[arg(long, default_value_t = Curve::ZOrder)]
+    cluster_curve: Curve,
+
+    /// Reservoir-sample N rows into holdout.parquet while generating the dataset
+    #[arg(long, value_name = "N")]
+    holdout_rows: Option<usize>,
+
+    /// Write N sampled rows of every data file to a <file>.sample.txt sidecar for review
+    #[arg(long, value_name = "N")]
+    sample_values: Option<usize>,
+
+    /// Write index.parquet mapping each value of this column to its file, row group and row offset
+    #[arg(long, value_name = "COLUMN")]
+    index_column: Option<String>,
+
+    /// Write N query vectors to queries.parquet once the dataset is done
+    #[arg(long, value_name = "N")]
+    queries: Option<usize>,
+
+    /// Make queries by perturbing random base vectors by up to this much per component,
+    /// instead of sampling them fresh
+    #[arg(long, value_name = "EPSILON", requires = "queries")]
+    query_epsilon: Option<f64>,
+
+    /// Number of neighbors each query asks for: K, MIN..MAX or K:WEIGHT,... (e.g. 10:0.8,100:0.2)
+    #[arg(long, value_name = "K", requires = "queries")]
+    query_k: Option<KDistribution>,
+
+    /// Give queries a filter on this column (a float or int column of --columns, or tenant_id)
+    #[arg(long, value_name = "COLUMN", requires = "queries")]
+    query_filter_column: Option<String>,
+
+    /// Expected fraction of base rows matching a query filter, as S or MIN..MAX (log-uniform)
+    #[arg(long, value_name = "S", default_value = "0.01", value_parser = workload::parse_selectivity, requires = "query_filter_column")]
+    query_selectivity: (f64, f64),
+
+    /// Fraction of queries with a filter
+    #[arg(long, value_name = "FRACTION", default_value_t = 1.0, requires = "query_filter_column")]
+    query_filter_fraction: f64,
+
+    /// Write deletes.parquet with N epochs of base row ids to delete
+    #[arg(long, value_name = "N")]
+    delete_epochs: Option<usize>,
+
+    /// Fraction of the base rows deleted in each epoch
+    #[arg(long, value_name = "FRACTION", default_value_t = 0.1, requires = "delete_epochs")]
+    delete_fraction: f64,
+
+    /// Do not write _manifest.json, which is otherwise refreshed after every file
+    #[arg(long)]
+    no_manifest: bool,
+
+    /// Write edge-case files (empty, single row, one huge row group, tiny row groups) instead of a dataset
+    #[arg(long)]
+    edge_case_files: bool,
+
+    /// Row groups of one row each in the tiny-row-group edge-case file
+    #[arg(long, default_value_t = 2000)]
+    tiny_row_groups: usize,
+
+    /// Generator threads; batches are generated this many at a time (default: one batch, all CPUs)
+    #[arg(long, value_name = "N")]
+    threads: Option<usize>,
+
+    /// Split files across NUMA nodes, pinning each node's generator threads to its CPUs
+    #[cfg(feature = "numa")]
+    #[arg(long)]
+    numa: bool,
+
+    /// Show a live dashboard with per-worker throughput, memory use and ETA instead of the progress bar
+    #[cfg(feature = "tui")]
+    #[arg(long, conflicts_with_all = ["verbose", "progress_json"])]
+    tui: bool,
+
+    /// Report progress on stderr as JSON Lines for wrapper tools instead of the progress bar
+    #[arg(long)]
+    progress_json: bool,
+
+    /// Files sampled when recording a golden manifest
+    #[arg(long, default_value_t = 3)]
+    golden_files: u64,
+
+    /// Rows hashed per sampled file when recording a golden manifest
+    #[arg(long, default_value_t = 1000)]
+    golden_rows: usize,
+}
+
+/// Subcommands other than generation
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Compare two generated datasets and report differences
+    Diff {
+        /// First dataset directory
+        dir_a: PathBuf,
+
+        /// Second dataset directory
+        dir_b: PathBuf,
+
+        /// Also compare this many sampled rows of files that differ
+        #[arg(long)]
+        sample_rows: Option<usize>,
+    },
+    /// Report footer size and metadata overhead of Parquet files
+    Inspect {
+        /// Parquet file or dataset directory
+        path: PathBuf,
+    },
+    /// Manage named profiles in ~/.config/vector-data-gen/profiles.toml
+    Profile {
+        #[command(subcommand)]
+        action: ProfileCommand,
+    },
+    /// Compute exact nearest neighbors of query vectors over a generated dataset
+    GroundTruth(GroundTruthArgs),
+    /// Print a few generated rows, e.g. `preview --rows 5 -- --vector-dim 768 --clusters 8`
+    Preview {
+        /// Rows to generate
+        #[arg(long, default_value_t = 5)]
+        rows: usize,
+
+        /// Generation options, as given to a regular run
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        options: Vec<String>,
+    },
+}
+
+/// Options of the ground-truth subcommand
+#[derive(clap::Args, Debug)]
+struct GroundTruthArgs {
+    /// Dataset directory, as written with its manifest
+    dataset: PathBuf,
+
+    /// Parquet file holding the query vectors, queries.parquet in the dataset directory by default
+    #[arg(long)]
+    queries: Option<PathBuf>,
+
+    /// Vector column of the dataset and query files
+    #[arg(long, default_value = "vector")]
+    column: String,
+
+    /// Neighbors per query
+    #[arg(long, default_value_t = 100)]
+    k: usize,
+
+    /// Distance: l2 or cosine
+    #[arg(long, default_value_t = Metric::L2)]
+    metric: Metric,
+
+    /// Output file, ground_truth.parquet in the dataset directory by default;
+    /// .ivecs files hold neighbor ids only, anything else is Parquet
+    #[arg(long)]
+    output: Option<PathBuf>,
+
+    /// Search every base file for every query instead of reusing and updating the
+    /// per-file results cached in the dataset's _ground_truth_cache directory
+    #[arg(long)]
+    no_cache: bool,
+
+    /// Compute distances on cpu or gpu (gpu feature)
+    #[arg(long, default_value_t = Device::Cpu)]
+    device: Device,
+
+    /// Search all base rows for every query, ignoring the filter column of the query file
+    #[arg(long)]
+    ignore_filters: bool,
+
+    /// Deletion workload, deletes.parquet in the dataset directory by default if it exists;
+    /// ground truth is also written for every deletion epoch
+    #[arg(long, value_name = "PATH")]
+    deletes: Option<PathBuf>,
+
+    /// Do not write per-epoch ground truth for a deletion workload
+    #[arg(long, conflicts_with = "deletes")]
+    ignore_deletes: bool,
+}
+
+/// Actions of the profile subcommand
+#[derive(Subcommand, Debug)]
+enum ProfileCommand {
+    /// Save generation options under a name, e.g. `profile save bigrun -- --vector-dim 768`
+    Save {
+        /// Profile name
+        name: String,
+
+        /// Generation options, as given to a regular run
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        options: Vec<String>,
+    },
+    /// List saved profiles
+    List,
 }
 
 /// Compression type enum for CLI
-#[derive(ValueEnum, Clone, Debug)]
+#[derive(ValueEnum, Clone, Copy, Debug)]
 enum Compression {
     Snappy,
     Gzip,
@@ -59,6 +726,133 @@ enum Compression {
     Uncompressed,
 }
 
+/// Output format enum for CLI
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    Parquet,
+    /// Weaviate /v1/batch/objects request body
+    Weaviate,
+    /// Vespa JSON Lines feed
+    Vespa,
+    /// One JSON object per row
+    Jsonl,
+    /// Comma-separated values with a header line
+    Csv,
+    /// fvecs (or bvecs) records of one vector column
+    Fvecs,
+    /// 2-D float32 NumPy array of one vector column
+    Npy,
+    /// NumPy archive of the vectors and the scalar strings
+    Npz,
+}
+
+/// Text form of CSV vectors enum for CLI
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum CsvVectors {
+    /// Base64 of the vector bytes
+    Base64,
+    /// Bracketed float list, e.g. [0.5,1.25]
+    List,
+}
+
+impl From<CsvVectors> for VectorText {
+    fn from(value: CsvVectors) -> Self {
+        match value {
+            CsvVectors::Base64 => VectorText::Base64,
+            CsvVectors::List => VectorText::List,
+        }
+    }
+}
+
+/// Vector element type enum for CLI
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum VectorType {
+    F16,
+    F32,
+    F64,
+    I8,
+    Binary,
+}
+
+impl From<VectorType> for VectorElementType {
+    fn from(value: VectorType) -> Self {
+        match value {
+            VectorType::F16 => VectorElementType::Float16,
+            VectorType::F32 => VectorElementType::Float32,
+            VectorType::F64 => VectorElementType::Float64,
+            VectorType::I8 => VectorElementType::Int8,
+            VectorType::Binary => VectorElementType::Binary,
+        }
+    }
+}
+
+/// Vector byte order enum for CLI
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Endianness {
+    Little,
+    Big,
+}
+
+impl From<Endianness> for ByteOrder {
+    fn from(value: Endianness) -> Self {
+        match value {
+            Endianness::Little => ByteOrder::Little,
+            Endianness::Big => ByteOrder::Big,
+        }
+    }
+}
+
+/// Vector component distribution enum for CLI
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Distribution {
+    Uniform,
+    Normal,
+}
+
+/// Dataset layout enum for CLI
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Layout {
+    Hive,
+    Flat,
+    Bucketed,
+    Date,
+}
+
+/// Layout strategy of `args`, `None` for the default Hive layout
+fn layout_strategy(args: &Args) -> Result<Option<Arc<dyn LayoutStrategy>>> {
+    Ok(match args.layout {
+        Layout::Hive => None,
+        Layout::Flat => Some(Arc::new(FlatLayout)),
+        Layout::Bucketed => Some(Arc::new(BucketedLayout)),
+        Layout::Date => {
+            let layout = DateLayout {
+                start: args.layout_start.to_days(),
+                files_per_day: args.files_per_day,
+            };
+            layout.validate()?;
+            Some(Arc::new(layout))
+        }
+    })
+}
+
+/// Vector column encoding enum for CLI
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Encoding {
+    Binary,
+    FixedSizeList,
+    FixedSizeBinary,
+}
+
+impl From<Encoding> for VectorEncoding {
+    fn from(value: Encoding) -> Self {
+        match value {
+            Encoding::Binary => VectorEncoding::Binary,
+            Encoding::FixedSizeList => VectorEncoding::FixedSizeList,
+            Encoding::FixedSizeBinary => VectorEncoding::FixedSizeBinary,
+        }
+    }
+}
+
 impl From<Compression> for CompressionType {
     fn from(value: Compression) -> Self {
         match value {
@@ -77,40 +871,1069 @@ fn parse_file_size(size_str: &str) -> Result<u64> {
     Ok(size.as_u64())
 }
 
-fn main() -> Result<()> {
-    let args = Args::parse();
+fn run_diff(dir_a: &Path, dir_b: &Path, sample_rows: Option<usize>) -> Result<()> {
+    let report = diff::compare(dir_a, dir_b, &DiffOptions { sample_rows })?;
+    for difference in &report.differences {
+        println!("{}", difference);
+    }
+    if !report.is_identical() {
+        anyhow::bail!(
+            "datasets differ: {} differences across {} compared files",
+            report.differences.len(),
+            report.files_compared
+        );
+    }
+    println!("{} files identical", report.files_compared);
+    Ok(())
+}
+
+#[cfg(any(feature = "flight", feature = "http"))]
+fn parse_header(header: &str) -> Result<(String, String)> {
+    let (key, value) = header
+        .split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("Invalid header '{}', expected KEY=VALUE", header))?;
+    Ok((key.trim().to_string(), value.trim().to_string()))
+}
+
+/// Stream all rows into one sink instead of writing files
+fn stream_to_sink(
+    args: &Args,
+    config: &Config,
+    sink: &mut dyn vector_data_gen::Sink,
+    target: &str,
+    cancel: &CancellationToken,
+) -> Result<()> {
+    let mut generator = DataGenerator::try_new(config.clone())?;
+    let start = Instant::now();
+    match vector_data_gen::sink::write_rows(
+        sink,
+        &mut generator,
+        args.total_rows,
+        args.batch_size,
+        cancel,
+        config.cancel_policy,
+    ) {
+        Ok(rows) => {
+            println!("Streamed {} rows to {} in {:.2?}", rows, target, start.elapsed());
+            Ok(())
+        }
+        Err(e) => match e.downcast_ref::<vector_data_gen::Cancelled>() {
+            Some(cancelled) => {
+                println!("{}", cancelled);
+                Ok(())
+            }
+            None => Err(e),
+        },
+    }
+}
+
+fn verify_golden(path: &PathBuf) -> Result<()> {
+    let manifest = GoldenManifest::load(path)?;
+    let mismatches = manifest.verify()?;
+    for mismatch in &mismatches {
+        println!("{}", mismatch);
+    }
+    if !mismatches.is_empty() {
+        anyhow::bail!(
+            "output changed for {} of {} golden samples (recorded by version {})",
+            mismatches.len(),
+            manifest.samples.len(),
+            manifest.crate_version
+        );
+    }
+    println!("{} golden samples match", manifest.samples.len());
+    Ok(())
+}
+
+fn run_inspect(path: &Path) -> Result<()> {
+    let reports = footer::inspect_all(path)?;
+    for report in &reports {
+        print!("{}", report);
+    }
+    let data: u64 = reports.iter().map(|r| r.data_bytes()).sum();
+    let metadata: u64 = reports.iter().map(|r| r.metadata_bytes()).sum();
+    println!(
+        "{} files: {} metadata bytes for {} data bytes ({:.3}%)",
+        reports.len(),
+        metadata,
+        data,
+        metadata as f64 / data.max(1) as f64 * 100.0
+    );
+    Ok(())
+}
+
+fn run_ground_truth(args: &GroundTruthArgs) -> Result<()> {
+    let GroundTruthArgs { dataset, column, k, metric, .. } = args;
+    let output = args.output.clone().unwrap_or_else(|| dataset.join("ground_truth.parquet"));
+    let manifest = DatasetManifest::load(dataset)?;
+    let base: Vec<PathBuf> = manifest.files.iter().map(|file| dataset.join(&file.path)).collect();
+    let queries = args
+        .queries
+        .clone()
+        .unwrap_or_else(|| dataset.join(vector_data_gen::dataset::QUERIES_FILE_NAME));
+    let filters = if args.ignore_filters {
+        Vec::new()
+    } else {
+        ground_truth::read_filters(&queries, manifest.batch_size)?
+    };
+    let queries = ground_truth::read_vectors(&queries, column, manifest.batch_size)?;
+    let query_count = queries.len();
+    let default_deletes = dataset.join(vector_data_gen::dataset::DELETES_FILE_NAME);
+    let deletes = match &args.deletes {
+        Some(path) => Some(path.clone()),
+        None if !args.ignore_deletes && default_deletes.exists() => Some(default_deletes),
+        None => None,
+    };
+    let options = SearchOptions {
+        k: *k,
+        metric: *metric,
+        batch_size: manifest.batch_size,
+        device: args.device,
+    };
+    let epochs = match &deletes {
+        Some(path) => {
+            let deletes = vector_data_gen::deletes::read_deletes(path)?;
+            ground_truth::compute_after_deletes(&base, column, queries.clone(), &filters, &deletes, &options)?
+        }
+        None => Vec::new(),
+    };
+    let neighbors = if args.no_cache {
+        ground_truth::compute(&base, column, queries, &filters, &options)?
+    } else {
+        let cache = dataset.join(ground_truth::CACHE_DIR_NAME);
+        ground_truth::compute_cached(&base, column, queries, &filters, &options, &cache)?
+    };
+    let writer = ParquetWriter::new(manifest.config);
+    let write = |path: &Path, neighbors: &[Vec<ground_truth::Neighbor>]| {
+        if path.extension().is_some_and(|ext| ext == "ivecs") {
+            ground_truth::write_ivecs(path, neighbors)
+        } else {
+            ground_truth::write_parquet(&writer, path, neighbors)
+        }
+    };
+    write(&output, &neighbors)?;
+    let filtered = filters.iter().flatten().count();
+    println!(
+        "Wrote {} nearest neighbors ({}) of {} queries ({} filtered) over {} rows to {:?}",
+        k, metric, query_count, filtered, manifest.rows_written, output
+    );
+    for (epoch, neighbors) in epochs.iter().enumerate() {
+        let path = ground_truth::epoch_path(&output, epoch + 1);
+        write(&path, neighbors)?;
+        println!("Wrote ground truth after deletion epoch {} to {:?}", epoch + 1, path);
+    }
+    Ok(())
+}
+
+/// Progress events written by --progress-json, one JSON object per line
+#[derive(Serialize, Debug)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum ProgressEvent<'a> {
+    /// Generation is about to start
+    Started {
+        total_rows: usize,
+        total_files: usize,
+        output_dir: &'a Path,
+    },
+    /// A file is about to be written
+    FileStarted { index: u64, path: &'a Path },
+    /// A file was written completely
+    FileFinished {
+        index: u64,
+        path: &'a Path,
+        rows: usize,
+        bytes: u64,
+        elapsed_ms: u64,
+        /// Rows written so far across all files
+        rows_done: usize,
+        total_rows: usize,
+    },
+    /// Every file and co-generated table is done, or the run was cancelled
+    Finished {
+        rows: usize,
+        files: usize,
+        elapsed_ms: u64,
+        cancelled: bool,
+    },
+}
+
+fn emit_progress(event: &ProgressEvent<'_>) {
+    // One write per line, so events from concurrent workers never interleave
+    let line = serde_json::to_string(event).expect("progress events serialize") + "\n";
+    let _ = std::io::Write::write_all(&mut std::io::stderr().lock(), line.as_bytes());
+}
+
+/// Output settings saved in configuration files
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default, deny_unknown_fields)]
+struct OutputSettings {
+    output_dir: Option<PathBuf>,
+    prefix: Option<String>,
+    total_rows: Option<usize>,
+    batch_size: Option<usize>,
+}
+
+/// Contents of a --config file
+///
+/// The configuration is kept as written, a `Config` object in the
+/// configuration format, so the fields it leaves out can be told apart from
+/// those it sets.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+struct RunFile {
+    #[serde(default)]
+    output: OutputSettings,
+    config: Option<Value>,
+}
+
+/// Merge the configuration file at `path` under the command line options
+fn apply_config_file(path: &PathBuf, args: &mut Args, matches: &ArgMatches) -> Result<Config> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {:?}", path))?;
+    let file: RunFile = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("toml") => toml::from_str(&text).map_err(anyhow::Error::from),
+        Some("yaml" | "yml") => serde_yaml::from_str(&text).map_err(anyhow::Error::from),
+        _ => anyhow::bail!("config file {:?} must end in .toml, .yaml or .yml", path),
+    }
+    .with_context(|| format!("Invalid config file: {:?}", path))?;
+    apply_run_file(file, args, matches)
+}
+
+/// Configuration fields and the options that set them
+///
+/// Optional features are listed under the options that enable them, so that
+/// e.g. `--tenant-skew` alone does not drop the tenants of a config file.
+const CONFIG_OPTIONS: &[(&str, &[&str])] = &[
+    ("vector_dim", &["vector_dim", "columns"]),
+    ("scalar_len", &["scalar_len"]),
+    ("scalar_prefix", &["scalar_prefix"]),
+    ("id_column", &["id_column"]),
+    ("timestamp", &["timestamp_column"]),
+    ("schema", &["columns"]),
+    ("correlation", &["correlation"]),
+    ("target_file_size", &["file_size"]),
+    ("compression", &["compression"]),
+    ("seed", &["seed"]),
+    ("cancel_policy", &["discard_on_cancel"]),
+    ("vector_columns", &["vector_columns"]),
+    ("vector_type", &["vector_type"]),
+    ("mixed_precision", &["mixed_precision"]),
+    ("large_binary", &["large_binary"]),
+    ("vector_layout", &["byte_order", "dim_prefix"]),
+    ("vector_encoding", &["vector_encoding"]),
+    ("vector_distribution", &["vector_distribution", "vector_mean", "vector_std"]),
+    ("mixture", &["clusters"]),
+    ("normalize", &["normalize"]),
+    ("quantization", &["quantize_levels", "mantissa_bits"]),
+    ("int8_scale", &["int8_scale"]),
+    ("pq", &["pq_subquantizers"]),
+    ("tenants", &["tenants"]),
+    ("trajectory", &["trajectory_entities"]),
+    ("edges", &["edge_degree"]),
+    ("foreign_key", &["fk_column"]),
+    ("sessions", &["sessions_per_user"]),
+    ("duplicates", &["duplicate_fraction"]),
+    ("pruning", &["pruning_row_group_rows"]),
+    ("page_index", &["page_index"]),
+    ("row_group_size", &["row_group_size"]),
+    ("payload", &["payload_bytes"]),
+    ("sparse", &["sparse_dim"]),
+    ("wide", &["wide_columns"]),
+    ("text", &["text_words"]),
+    ("null_columns", &["null_columns"]),
+    ("null_ratios", &["null_ratios"]),
+    ("constant_columns", &["constant_columns"]),
+    ("shuffle_buffer", &["shuffle_buffer"]),
+    ("holdout_rows", &["holdout_rows"]),
+    ("value_samples", &["sample_values"]),
+    ("index_column", &["index_column"]),
+    ("queries", &["queries"]),
+    ("workload", &["query_k", "query_filter_column"]),
+    ("deletes", &["delete_epochs"]),
+    ("derived", &["derived"]),
+    ("monotonic", &["monotonic"]),
+    ("bucketing", &["bucket_by"]),
+    ("clustering", &["cluster_by"]),
+    ("parallel_batches", &["threads"]),
+    ("generator_version", &["generator_version"]),
+];
+
+/// Whether the option `id` was given, rather than left at its default
+fn is_explicit(matches: &ArgMatches, id: &str) -> bool {
+    matches!(
+        matches.value_source(id),
+        Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+    )
+}
+
+/// Merge `file` under the command line options
+///
+/// Options given on the command line override the file, as do the
+/// configuration fields they set; the file overrides the defaults of the
+/// rest.
+fn apply_run_file(file: RunFile, args: &mut Args, matches: &ArgMatches) -> Result<Config> {
+    let output = file.output;
+    if !is_explicit(matches, "output_dir") {
+        if let Some(output_dir) = output.output_dir {
+            args.output_dir = output_dir;
+        }
+    }
+    if !is_explicit(matches, "prefix") {
+        if let Some(prefix) = output.prefix {
+            args.prefix = prefix;
+        }
+    }
+    if !is_explicit(matches, "total_rows") {
+        if let Some(total_rows) = output.total_rows {
+            args.total_rows = total_rows;
+        }
+    }
+    if !is_explicit(matches, "batch_size") {
+        if let Some(batch_size) = output.batch_size {
+            args.batch_size = batch_size;
+        }
+    }
+
+    // The file goes over the command line defaults, so a field it leaves out
+    // keeps the value a run without the file would have
+    let defaults = config_from_args(&Args::try_parse_from(["vector_data_gen"])?)?;
+    let config = match file.config {
+        Some(config) => config_format::from_value_over(config, &defaults)?,
+        None => defaults,
+    };
+    let mut merged = config_format::to_value(&config)?;
+    let (Value::Object(mut cli), Value::Object(fields)) =
+        (config_format::to_value(&config_from_args(args)?)?, &mut merged)
+    else {
+        unreachable!("configurations serialize to JSON objects");
+    };
+    for (key, options) in CONFIG_OPTIONS {
+        if options.iter().any(|id| is_explicit(matches, id)) {
+            if let Some(value) = cli.remove(*key) {
+                fields.insert(key.to_string(), value);
+            }
+        }
+    }
+    config_format::from_value(merged)
+}
+
+/// TOML of the output settings in `args` and `config`, readable by --config
+fn dump_config(args: &Args, config: &Config) -> Result<String> {
+    Ok(toml::to_string(&run_file_value(args, config)?)?)
+}
+
+/// The output settings in `args` and `config` as a JSON object without nulls
+fn run_file_value(args: &Args, config: &Config) -> Result<Value> {
+    let file = RunFile {
+        output: OutputSettings {
+            output_dir: Some(args.output_dir.clone()),
+            prefix: Some(args.prefix.clone()),
+            total_rows: Some(args.total_rows),
+            batch_size: Some(args.batch_size),
+        },
+        config: Some(config_format::to_value(config)?),
+    };
+    // TOML has no null: unset options are left out
+    let mut value = serde_json::to_value(&file)?;
+    strip_nulls(&mut value);
+    Ok(value)
+}
+
+/// Location of the named profiles: `vector-data-gen/profiles.toml` in
+/// `$XDG_CONFIG_HOME`, or in `~/.config`
+fn profiles_path() -> Result<PathBuf> {
+    let base = match std::env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(
+            std::env::var_os("HOME").context("HOME is not set; cannot locate profiles")?,
+        )
+        .join(".config"),
+    };
+    Ok(base.join("vector-data-gen").join("profiles.toml"))
+}
+
+/// Profiles in `path` by name; none if the file does not exist
+fn load_profiles(path: &Path) -> Result<toml::Table> {
+    if !path.exists() {
+        return Ok(toml::Table::new());
+    }
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read profiles: {:?}", path))?;
+    toml::from_str(&text).with_context(|| format!("Invalid profiles: {:?}", path))
+}
+
+/// The profile `name` in `path`
+fn load_profile(path: &Path, name: &str) -> Result<RunFile> {
+    let profiles = load_profiles(path)?;
+    let Some(profile) = profiles.get(name) else {
+        let names: Vec<&str> = profiles.keys().map(String::as_str).collect();
+        anyhow::bail!(
+            "no profile named '{}' in {:?} (profiles: {})",
+            name,
+            path,
+            if names.is_empty() { "none".to_string() } else { names.join(", ") }
+        );
+    };
+    profile
+        .clone()
+        .try_into()
+        .with_context(|| format!("Invalid profile '{}' in {:?}", name, path))
+}
+
+/// Add or replace the profile `name` in `path`, keeping the others
+fn save_profile(path: &Path, name: &str, args: &Args, config: &Config) -> Result<()> {
+    let mut profiles = load_profiles(path)?;
+    profiles.insert(name.to_string(), toml::Value::try_from(run_file_value(args, config)?)?);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create directory: {:?}", dir))?;
+    }
+    std::fs::write(path, toml::to_string(&profiles)?)
+        .with_context(|| format!("Failed to write profiles: {:?}", path))
+}
+
+/// Prefix of the environment variables that set options, e.g. `VDG_TOTAL_ROWS`
+const ENV_PREFIX: &str = "VDG_";
+
+/// Environment variable of every option, keyed by the option id
+///
+/// Built once, since the parser keeps `'static` references to the names.
+fn env_names() -> &'static HashMap<String, String> {
+    static NAMES: OnceLock<HashMap<String, String>> = OnceLock::new();
+    NAMES.get_or_init(|| {
+        Args::command()
+            .get_arguments()
+            .map(|arg| arg.get_id().to_string())
+            .map(|id| {
+                let name = format!("{}{}", ENV_PREFIX, id.to_ascii_uppercase());
+                (id, name)
+            })
+            .collect()
+    })
+}
+
+/// Argument parser that also reads every option from its `VDG_*` variable
+///
+/// Values from the environment count as given on the command line, so they
+/// override --config files and profiles; the command line overrides them.
+fn command_with_env() -> clap::Command {
+    Args::command()
+        .mut_args(|arg| match arg.get_id().as_str() {
+            "help" | "version" => arg,
+            id => match env_names().get(id) {
+                Some(name) => arg.env(name.as_str()).hide_env(true),
+                None => arg,
+            },
+        })
+        .after_help(
+            "Every option can also be set with a VDG_<OPTION> environment variable, e.g. \
+             VDG_TOTAL_ROWS=1000000 or VDG_VERBOSE=true.\n\
+             Precedence: command line > environment > --config / --profile > defaults.",
+        )
+}
+
+/// Parse the command line and `VDG_*` environment variables
+fn parse_args() -> (Args, ArgMatches) {
+    let matches = command_with_env().get_matches();
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    (args, matches)
+}
+
+/// Configuration from the command line, its config file or its profile
+///
+/// `matches` tells which options of `args` were given.
+fn effective_config(args: &mut Args, matches: &ArgMatches) -> Result<Config> {
+    if let Some(path) = args.config.clone() {
+        return apply_config_file(&path, args, matches);
+    }
+    if let Some(name) = args.profile.clone() {
+        return apply_run_file(load_profile(&profiles_path()?, &name)?, args, matches);
+    }
+    config_from_args(args)
+}
+
+/// Print `rows` rows generated with the effective config of `options`
+fn run_preview(rows: usize, options: &[String]) -> Result<()> {
+    // Unlike profiles, a preview shows what a run in this environment would do
+    let matches = command_with_env().try_get_matches_from(
+        std::iter::once("vector_data_gen").chain(options.iter().map(String::as_str)),
+    )?;
+    let mut args = Args::from_arg_matches(&matches)?;
+    let config = effective_config(&mut args, &matches)?;
+    config.validate()?;
+    let batch = DataGenerator::try_new(config)?.generate_batch(rows)?;
+    print!("{}", vector_data_gen::preview::format_preview(&batch)?);
+    Ok(())
+}
+
+fn run_profile(action: &ProfileCommand) -> Result<()> {
+    let path = profiles_path()?;
+    match action {
+        ProfileCommand::Save { name, options } => {
+            let matches = Args::command().try_get_matches_from(
+                std::iter::once("vector_data_gen").chain(options.iter().map(String::as_str)),
+            )?;
+            let mut args = Args::from_arg_matches(&matches)?;
+            let config = effective_config(&mut args, &matches)?;
+            config.validate()?;
+            save_profile(&path, name, &args, &config)?;
+            println!("Saved profile '{}' to {:?}", name, path);
+        }
+        ProfileCommand::List => {
+            for (name, profile) in load_profiles(&path)? {
+                let profile: RunFile = profile
+                    .try_into()
+                    .with_context(|| format!("Invalid profile '{}' in {:?}", name, path))?;
+                let config = match profile.config {
+                    Some(config) => config_format::from_value(config)
+                        .with_context(|| format!("Invalid profile '{}' in {:?}", name, path))?,
+                    None => Config::default(),
+                };
+                println!(
+                    "{}: {} rows of {}-dim vectors",
+                    name,
+                    profile.output.total_rows.unwrap_or_default(),
+                    config.vector_dim
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn strip_nulls(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            fields.retain(|_, value| !value.is_null());
+            fields.values_mut().for_each(strip_nulls);
+        }
+        Value::Array(values) => values.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
+
+/// Generator configuration described by the command line options
+fn config_from_args(args: &Args) -> Result<Config> {
     // Parse file size
     let target_file_size = parse_file_size(&args.file_size)?;
 
-    // Create output directory
-    std::fs::create_dir_all(&args.output_dir)
-        .with_context(|| format!("Failed to create output directory: {:?}", args.output_dir))?;
-
     // Create configuration
-    let config = Config::new(
+    let mut config = Config::new(
         args.vector_dim,
         args.scalar_len,
         target_file_size,
         args.compression.into(),
         args.seed,
     );
+    config.row_group_size = args.row_group_size.as_deref().map(parse_file_size).transpose()?;
+    config.scalar_prefix = args.scalar_prefix.clone();
+    if let Some(schema) = &args.columns {
+        // The first vector column of the spec sets the dimension
+        if let Some((_, column)) = schema.primary_vector() {
+            if let ColumnType::Vector { dim, .. } = column.column_type {
+                config.vector_dim = dim;
+            }
+        }
+        config.schema = Some(schema.clone());
+    }
+    config.correlation = args.correlation.clone();
+    config.id_column = args.id_column;
+    config.timestamp = args.timestamp_column.clone().map(|column| TimestampConfig {
+        column,
+        values: match args.timestamp_step {
+            Some(step_ms) => TimestampValues::Monotonic {
+                start_ms: args.timestamp_range.start_ms,
+                step_ms,
+            },
+            None => TimestampValues::Uniform {
+                start_ms: args.timestamp_range.start_ms,
+                end_ms: args.timestamp_range.end_ms,
+            },
+        },
+    });
+    config.vector_columns = args.vector_columns;
+    config.vector_type = args.vector_type.into();
+    config.mixed_precision = args.mixed_precision.map(Into::into);
+    config.pq = args.pq_subquantizers.map(|subquantizers| PqConfig {
+        subquantizers,
+        nbits: args.pq_nbits,
+    });
+    config.tenants = args.tenants.map(|tenants| TenantConfig {
+        partitioned: args.partition_by_tenant,
+        ..TenantConfig::new(tenants, args.tenant_skew)
+    });
+    config.trajectory = args.trajectory_entities.map(|entities| TrajectoryConfig {
+        entities,
+        step_std: args.trajectory_step,
+        start_ms: args.trajectory_start_ms,
+        interval_ms: args.trajectory_interval_ms,
+    });
+    config.edges = args.edge_degree.map(|degree| EdgeConfig {
+        degree: if args.constant_degree {
+            DegreeDistribution::Constant(degree.round() as usize)
+        } else {
+            DegreeDistribution::Poisson(degree)
+        },
+        destination_skew: args.edge_skew,
+    });
+    config.foreign_key = args.fk_column.clone().map(|column| ForeignKeyConfig {
+        column,
+        parents: args.fk_parents,
+        skew: args.fk_skew,
+        parent_table: args.parent_table.clone(),
+    });
+    config.sessions = args.sessions_per_user.map(|sessions_per_user| SessionConfig {
+        sessions_per_user,
+        events_per_session: args.events_per_session,
+    });
+    config.duplicates = args.duplicate_fraction.map(|fraction| DuplicateConfig {
+        fraction,
+        pool_size: args.duplicate_pool,
+    });
+    config.pruning = args.pruning_row_group_rows.map(|row_group_rows| PruningConfig {
+        row_group_rows,
+        start_ms: args.pruning_start_ms,
+        interval_ms: args.pruning_interval_ms,
+    });
+    config.page_index = args.page_index.then_some(PageIndexConfig {
+        page_rows: args.page_rows,
+    });
+    config.large_binary = args.large_binary;
+    config.vector_layout = VectorLayout {
+        byte_order: args.byte_order.into(),
+        dim_prefix: args.dim_prefix,
+    };
+    config.vector_encoding = args.vector_encoding.into();
+    config.vector_distribution = match args.vector_distribution {
+        Distribution::Uniform => VectorDistribution::Uniform,
+        Distribution::Normal => VectorDistribution::Normal {
+            mean: args.vector_mean,
+            std: args.vector_std,
+        },
+    };
+    config.normalize = args.normalize;
+    config.mixture = args.clusters.map(|clusters| MixtureConfig {
+        clusters,
+        std: args.cluster_std,
+    });
+    config.quantization = match (args.quantize_levels, args.mantissa_bits) {
+        (Some(levels), _) => Some(Quantization::Levels(levels)),
+        (None, Some(bits)) => Some(Quantization::MantissaBits(bits)),
+        (None, None) => None,
+    };
+    config.int8_scale = args.int8_scale;
+    config.payload = args.payload_bytes.map(|len| PayloadConfig {
+        len,
+        entropy: args.payload_entropy,
+    });
+    config.sparse = args.sparse_dim.map(|dim| SparseConfig {
+        dim,
+        density: args.sparse_density,
+    });
+    config.wide = args.wide_columns.map(|columns| WideConfig {
+        columns,
+        per_file: args.wide_columns_per_file.unwrap_or(columns),
+        subset: args.wide_subset,
+    });
+    config.text = args.text_words.map(|max_words| TextConfig {
+        min_words: args.text_min_words,
+        max_words,
+        vocabulary: args.text_vocabulary,
+        tokens: args.text_tokens,
+        locales: args.text_locales.clone(),
+    });
+    config.null_columns = args.null_columns.clone();
+    config.null_ratios = args.null_ratios.clone();
+    config.constant_columns = args.constant_columns.clone();
+    config.shuffle_buffer = args.shuffle_buffer;
+    config.derived = args.derived.clone();
+    config.monotonic = args.monotonic.clone();
+    config.bucketing = args.bucket_by.clone().map(|column| BucketConfig {
+        column,
+        buckets: args.buckets.unwrap_or(1),
+    });
+    if !args.cluster_by.is_empty() {
+        config.clustering = Some(ClusterConfig {
+            columns: args.cluster_by.clone(),
+            curve: args.cluster_curve,
+        });
+    }
+    config.holdout_rows = args.holdout_rows;
+    config.value_samples = args.sample_values;
+    config.index_column = args.index_column.clone();
+    config.queries = args.queries.map(|count| QueryConfig {
+        count,
+        source: match args.query_epsilon {
+            Some(epsilon) => QuerySource::Perturb { epsilon },
+            None => QuerySource::Sample,
+        },
+    });
+    config.deletes = args.delete_epochs.map(|epochs| DeletionConfig {
+        epochs,
+        fraction: args.delete_fraction,
+    });
+    if args.query_k.is_some() || args.query_filter_column.is_some() {
+        config.workload = Some(WorkloadConfig {
+            k: args.query_k.clone().unwrap_or_default(),
+            filter_column: args.query_filter_column.clone(),
+            selectivity: args.query_selectivity,
+            filtered_fraction: args.query_filter_fraction,
+        });
+    }
+    config.parallel_batches = args.threads;
+    config.generator_version = args.generator_version;
+    if args.discard_on_cancel {
+        config.cancel_policy = CancelPolicy::Discard;
+    }
+    Ok(config)
+}
+
+fn main() -> Result<()> {
+    let (mut args, matches) = parse_args();
+    match &args.command {
+        Some(Command::Diff { dir_a, dir_b, sample_rows }) => {
+            return run_diff(dir_a, dir_b, *sample_rows);
+        }
+        Some(Command::Inspect { path }) => return run_inspect(path),
+        Some(Command::Profile { action }) => return run_profile(action),
+        Some(Command::GroundTruth(options)) => return run_ground_truth(options),
+        Some(Command::Preview { rows, options }) => return run_preview(*rows, options),
+        None => {}
+    }
+    if let Some(path) = &args.golden {
+        return verify_golden(path);
+    }
+    let config = effective_config(&mut args, &matches)?;
+    if let Some(threads) = args.threads {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .context("Failed to start generator threads")?;
+    }
+
+    config.validate()?;
+
+    if args.dump_config {
+        print!("{}", dump_config(&args, &config)?);
+        return Ok(());
+    }
+
+    // Object stores have no directories; the manifest is local only
+    let remote = vector_data_gen::sink::is_object_url(&args.output_dir);
+    if remote {
+        if args.format != OutputFormat::Parquet {
+            anyhow::bail!("Only Parquet files can be written to object stores");
+        }
+        args.no_manifest = true;
+    } else {
+        std::fs::create_dir_all(&args.output_dir).with_context(|| {
+            format!("Failed to create output directory: {:?}", args.output_dir)
+        })?;
+    }
+
+    let max_batch_rows = DataGenerator::try_new(config.clone())?.max_batch_rows();
+    if args.batch_size > max_batch_rows {
+        eprintln!(
+            "Warning: batches of {} rows exceed the 2GB Arrow array limit at this width; \
+             generating batches of {} rows instead (--large-binary lifts the limit for vectors)",
+            args.batch_size, max_batch_rows
+        );
+    }
+
+    if let Some(path) = &args.record_golden {
+        let manifest =
+            GoldenManifest::record(&config, args.golden_files, args.golden_rows, args.batch_size)?;
+        manifest.save(path)?;
+        println!("Recorded {} golden samples to {:?}", manifest.samples.len(), path);
+        return Ok(());
+    }
+
+    // Stop cleanly at the next batch boundary on Ctrl-C
+    let cancel = CancellationToken::new();
+    {
+        let cancel = cancel.clone();
+        ctrlc::set_handler(move || cancel.cancel())
+            .context("Failed to install Ctrl-C handler")?;
+    }
 
     if args.verbose {
         println!("Configuration:");
         println!("  Vector dimension: {}", config.vector_dim);
+        println!("  Vector columns: {}", config.vector_columns);
+        println!("  Vector type: {}", config.vector_type.name());
+        if config.vector_layout.byte_order != ByteOrder::Little {
+            println!("  Byte order: {}", config.vector_layout.byte_order.name());
+        }
+        if config.vector_layout.dim_prefix {
+            println!("  Dimension prefix: u32");
+        }
+        if let VectorDistribution::Normal { mean, std } = config.vector_distribution {
+            println!("  Vector distribution: normal (mean {}, std {})", mean, std);
+        }
+        if config.normalize {
+            println!("  Normalized: unit L2 norm");
+        }
+        if let Some(mixture) = &config.mixture {
+            println!("  Clusters: {} (std {})", mixture.clusters, mixture.std);
+        }
+        if config.vector_encoding != VectorEncoding::Binary {
+            println!("  Vector encoding: {}", config.vector_encoding.name());
+        }
+        if let Some(mixed) = config.mixed_precision {
+            println!("  Mixed precision copy: {}", mixed.name());
+        }
+        if config.int8_scale {
+            println!("  Int8 scale: per vector (vector_scale column)");
+        }
+        if let Some(pq) = config.pq {
+            println!("  PQ codes: m={} nbits={}", pq.subquantizers, pq.nbits);
+        }
+        if let Some(tenants) = config.tenants {
+            println!(
+                "  Tenants: {} ({}{})",
+                tenants.tenants,
+                tenants.skew,
+                if tenants.partitioned { ", partitioned" } else { "" }
+            );
+        }
+        if let Some(trajectory) = config.trajectory {
+            println!(
+                "  Trajectories: {} entities, step std {}, every {} ms",
+                trajectory.entities, trajectory.step_std, trajectory.interval_ms
+            );
+        }
+        if let Some(page_index) = config.page_index {
+            println!("  Page index: {} rows per page", page_index.page_rows);
+        }
+        if let Some(pruning) = config.pruning {
+            println!("  Pruning fixture: {} rows per row group", pruning.row_group_rows);
+        }
+        if let Some(schema) = &config.schema {
+            println!("  Columns: {}", schema);
+        }
+        if let Some(id) = config.id_column {
+            println!("  Id column: {}", id);
+        }
+        if let Some(timestamp) = &config.timestamp {
+            match timestamp.values {
+                TimestampValues::Uniform { start_ms, end_ms } => {
+                    println!("  Timestamp column: {} (random in {}..{} ms)", timestamp.column, start_ms, end_ms)
+                }
+                TimestampValues::Monotonic { start_ms, step_ms } => {
+                    println!("  Timestamp column: {} (from {} ms, +{} ms per row)", timestamp.column, start_ms, step_ms)
+                }
+            }
+        }
         println!("  Scalar length: {} bytes", config.scalar_len);
-        println!("  Target file size: {}", ByteSize::b(target_file_size));
+        if let Some(prefix) = &config.scalar_prefix {
+            println!("  Scalar prefix: {:?}", prefix);
+        }
+        println!("  Target file size: {}", ByteSize::b(config.target_file_size));
+        if let Some(bytes) = config.row_group_size {
+            println!(
+                "  Row group size: {} (~{} rows)",
+                ByteSize::b(bytes),
+                ParquetWriter::new(config.clone()).row_group_rows()
+            );
+        }
         println!("  Compression: {:?}", config.compression);
         println!("  Random seed: {}", config.seed);
+        println!("  Generator version: {}", config.generator_version);
         println!("  Prefix: {}", args.prefix);
         println!("  Output directory: {:?}", args.output_dir);
+        if args.layout != Layout::Hive {
+            println!("  Layout: {:?}", args.layout);
+        }
         println!("  Total rows to generate: {}", args.total_rows);
         println!("  Batch size: {}", args.batch_size);
         println!();
     }
 
+    #[cfg(feature = "flight")]
+    if let Some(endpoint) = &args.flight_endpoint {
+        let mut sink = vector_data_gen::sink::FlightSink::connect(&vector_data_gen::sink::FlightSinkOptions {
+            endpoint: endpoint.clone(),
+            path: vec![args.prefix.clone()],
+            headers: args.flight_headers.clone(),
+            streams: args.flight_streams,
+            queue: 2,
+        })?;
+        return stream_to_sink(&args, &config, &mut sink, endpoint, &cancel);
+    }
+
+    #[cfg(feature = "redis")]
+    if let Some(url) = &args.redis_url {
+        use vector_data_gen::sink::{RedisSink, RedisSinkOptions, RedisTarget};
+        let target = match &args.redis_vector_set {
+            Some(key) => RedisTarget::VectorSet { key: key.clone() },
+            None => RedisTarget::Hash {
+                index: args.redis_index.clone(),
+            },
+        };
+        let mut sink = RedisSink::connect(RedisSinkOptions {
+            url: url.clone(),
+            key_prefix: args.prefix.clone(),
+            target,
+            pipeline: args.redis_pipeline,
+        })?;
+        return stream_to_sink(&args, &config, &mut sink, url, &cancel);
+    }
+
+    #[cfg(feature = "http")]
+    if let Some(endpoint) = &args.http_endpoint {
+        use vector_data_gen::sink::{HttpSink, HttpSinkOptions};
+        let mut sink = HttpSink::connect(HttpSinkOptions {
+            endpoint: endpoint.clone(),
+            template: args.http_template.clone(),
+            headers: args.http_headers.clone(),
+            concurrency: args.http_concurrency,
+            rate: args.http_rate,
+        })?;
+        return stream_to_sink(&args, &config, &mut sink, endpoint, &cancel);
+    }
+
+    #[cfg(feature = "sqlite")]
+    if let Some(path) = &args.sqlite {
+        let mut sink = vector_data_gen::sink::SqliteSink::create(path, &args.prefix)?;
+        return stream_to_sink(&args, &config, &mut sink, &path.display().to_string(), &cancel);
+    }
+
+    #[cfg(feature = "scylla")]
+    if let Some(node) = &args.scylla_node {
+        use vector_data_gen::sink::{ScyllaSink, ScyllaSinkOptions};
+        let mut sink = ScyllaSink::connect(ScyllaSinkOptions {
+            node: node.clone(),
+            keyspace: args.scylla_keyspace.clone(),
+            table: args.prefix.clone(),
+            replication_factor: args.scylla_replication_factor,
+            concurrency: args.scylla_concurrency,
+        })?;
+        return stream_to_sink(&args, &config, &mut sink, node, &cancel);
+    }
+
+    #[cfg(feature = "hdf5")]
+    if let Some(path) = &args.hdf5 {
+        let start = Instant::now();
+        let report = match vector_data_gen::formats::write_ann_benchmarks(
+            path,
+            &config,
+            args.total_rows,
+            args.batch_size,
+            args.hdf5_neighbors,
+            args.hdf5_metric,
+            &cancel,
+        ) {
+            Ok(report) => report,
+            Err(e) => match e.downcast_ref::<vector_data_gen::Cancelled>() {
+                Some(cancelled) => {
+                    println!("{}", cancelled);
+                    return Ok(());
+                }
+                None => return Err(e),
+            },
+        };
+        println!(
+            "Wrote {} train rows, {} test rows and their {} nearest neighbors to {:?} in {:.2?}",
+            report.train_rows,
+            report.test_rows,
+            report.k,
+            report.path,
+            start.elapsed()
+        );
+        return Ok(());
+    }
+
+    if args.edge_case_files {
+        let files = vector_data_gen::edge_cases::write_edge_cases(
+            &args.output_dir,
+            &args.prefix,
+            &config,
+            &vector_data_gen::edge_cases::EdgeCaseOptions {
+                huge_rows: args.total_rows,
+                tiny_row_groups: args.tiny_row_groups,
+                batch_size: args.batch_size,
+            },
+        )?;
+        for file in &files {
+            println!(
+                "{}: {} rows in {} row groups -> {:?}",
+                file.case, file.rows, file.row_groups, file.path
+            );
+        }
+        return Ok(());
+    }
+
+    if args.format == OutputFormat::Jsonl {
+        let path = args.output_dir.join(format!("{}.jsonl", args.prefix));
+        let mut sink = JsonLinesSink::create(&path)?;
+        return stream_to_sink(&args, &config, &mut sink, &path.display().to_string(), &cancel);
+    }
+
+    if args.format == OutputFormat::Csv {
+        let path = args.output_dir.join(format!("{}.csv", args.prefix));
+        let mut sink = CsvSink::create(&path, args.csv_vectors.into())?;
+        return stream_to_sink(&args, &config, &mut sink, &path.display().to_string(), &cancel);
+    }
+
+    if args.format == OutputFormat::Fvecs {
+        let generator = DataGenerator::try_new(config.clone())?;
+        let field = generator.schema().field_with_name(&args.vecs_column)?;
+        let Some(format) = VecsFormat::for_field(field) else {
+            anyhow::bail!("column '{}' has no fvecs or bvecs form", args.vecs_column);
+        };
+        let path = args.output_dir.join(format!("{}.{}", args.prefix, format.extension()));
+        let mut sink = VecsSink::create(&path, &args.vecs_column, format)?;
+        return stream_to_sink(&args, &config, &mut sink, &path.display().to_string(), &cancel);
+    }
+
+    if args.format == OutputFormat::Npy {
+        let path = args.output_dir.join(format!("{}.npy", args.prefix));
+        let mut sink = NpySink::create(&path, &args.vecs_column, config.vector_dim)?;
+        return stream_to_sink(&args, &config, &mut sink, &path.display().to_string(), &cancel);
+    }
+
+    if args.format == OutputFormat::Npz {
+        let path = args.output_dir.join(format!("{}.npz", args.prefix));
+        let prefix = config.scalar_prefix.as_deref().unwrap_or_default();
+        let mut sink = NpzSink::create(
+            &path,
+            &args.vecs_column,
+            config.vector_dim,
+            "scalar",
+            prefix.chars().count() + config.scalar_len,
+        )?;
+        return stream_to_sink(&args, &config, &mut sink, &path.display().to_string(), &cancel);
+    }
+
+    let document_format = match args.format {
+        OutputFormat::Parquet
+        | OutputFormat::Jsonl
+        | OutputFormat::Csv
+        | OutputFormat::Fvecs
+        | OutputFormat::Npy
+        | OutputFormat::Npz => None,
+        OutputFormat::Weaviate => Some((
+            DocumentFormat::Weaviate {
+                class: args.document_type.clone(),
+            },
+            "weaviate.json",
+        )),
+        OutputFormat::Vespa => Some((
+            DocumentFormat::Vespa {
+                namespace: args.prefix.clone(),
+                document_type: args.document_type.clone(),
+            },
+            "vespa.jsonl",
+        )),
+    };
+    if let Some((format, extension)) = document_format {
+        let path = args.output_dir.join(format!("{}.{}", args.prefix, extension));
+        let mut sink = DocumentSink::create(&path, format)?;
+        return stream_to_sink(&args, &config, &mut sink, &path.display().to_string(), &cancel);
+    }
+
     // Create data generator and estimate rows per file
-    let generator = DataGenerator::new(config.clone());
+    let generator = DataGenerator::try_new(config.clone())?;
     let rows_per_file = generator.estimate_rows_per_file();
 
     if args.verbose {
@@ -131,66 +1954,200 @@ fn main() -> Result<()> {
     let writer = ParquetWriter::new(config.clone());
     let total_start = Instant::now();
 
-    let mut num_files = 0;
-    let mut total_rows_written = 0;
-    while true {
-        let start_time = Instant::now();
-        let file_seed = args.seed + num_files as u64;
-        let mut file_generator = DataGenerator::new(Config::new(
-            args.vector_dim,
-            args.scalar_len,
-            target_file_size,
-            config.compression,
-            file_seed,
-        ));
-        let file_name = format!("{}-{:08}.parquet", args.prefix, num_files);
-        let file_path = args.output_dir.join(file_name);
+    if args.companion_files {
+        if let Some(codebook) = generator.codebook() {
+            let path = args.output_dir.join(format!("{}-codebook.parquet", args.prefix));
+            let rows = companion::write_codebook(
+                &path,
+                codebook,
+                writer.writer_properties().clone(),
+            )?;
+            if args.verbose {
+                println!("Wrote PQ codebook ({} centroids) to {:?}", rows, path);
+            }
+        }
+    }
+
+    let options = DatasetOptions {
+        output_dir: args.output_dir.clone(),
+        prefix: args.prefix.clone(),
+        total_rows: args.total_rows,
+        batch_size: args.batch_size,
+        layout: layout_strategy(&args)?,
+    };
+    let manifest = match args.no_manifest {
+        true => None,
+        false => Some(ManifestWriter::create(&config, &options)?),
+    };
+    let planned_files = dataset::plan_files(&config, &options, rows_per_file).len();
+    if args.progress_json {
+        progress.set_draw_target(ProgressDrawTarget::hidden());
+        emit_progress(&ProgressEvent::Started {
+            total_rows: args.total_rows,
+            total_files: planned_files,
+            output_dir: &args.output_dir,
+        });
+    }
+    #[cfg(feature = "tui")]
+    let dashboard = match args.tui {
+        true => {
+            progress.set_draw_target(ProgressDrawTarget::hidden());
+            Some(Dashboard::start(args.total_rows, planned_files, cancel.clone())?)
+        }
+        false => None,
+    };
+    let on_event = |event: DatasetEvent<'_>| {
+        #[cfg(feature = "tui")]
+        if let Some(dashboard) = &dashboard {
+            dashboard.on_event(&event);
+        }
+        match event {
+            DatasetEvent::FileStarted(plan) => {
+                if args.verbose {
+                    println!("Generating file {}: {:?}", plan.index + 1, plan.path);
+                }
+                if args.progress_json {
+                    emit_progress(&ProgressEvent::FileStarted {
+                        index: plan.index,
+                        path: &plan.path,
+                    });
+                }
+            }
+            DatasetEvent::FileFinished(file) => {
+                if args.verbose {
+                    println!(
+                        "  Generated {} rows ({} bytes) in {:.2?} ({:.2} rows/sec)",
+                        file.rows,
+                        ByteSize::b(file.bytes),
+                        file.elapsed,
+                        file.rows as f64 / file.elapsed.as_secs_f64()
+                    );
+                    println!("    {}", file.timings);
+                }
+                if let Some(manifest) = &manifest {
+                    if let Err(e) = manifest.file_finished(file) {
+                        eprintln!("Warning: failed to update manifest: {:#}", e);
+                    }
+                }
+                progress.inc(file.rows as u64);
+                if args.progress_json {
+                    emit_progress(&ProgressEvent::FileFinished {
+                        index: file.index,
+                        path: &file.path,
+                        rows: file.rows,
+                        bytes: file.bytes,
+                        elapsed_ms: file.elapsed.as_millis() as u64,
+                        rows_done: progress.position() as usize,
+                        total_rows: args.total_rows,
+                    });
+                }
+            }
+        }
+    };
+    #[cfg(feature = "numa")]
+    let report = if args.numa {
+        let nodes = vector_data_gen::numa::topology()?;
         if args.verbose {
-            println!("Generating file {}: {:?}", num_files + 1, file_path);
+            for node in &nodes {
+                println!("NUMA node {}: {} CPUs", node.id, node.cpus.len());
+            }
         }
+        vector_data_gen::numa::generate(&config, &options, &cancel, &nodes, on_event)?
+    } else {
+        dataset::generate(&config, &options, &cancel, on_event)?
+    };
+    #[cfg(not(feature = "numa"))]
+    let report = dataset::generate(&config, &options, &cancel, on_event)?;
+    #[cfg(feature = "tui")]
+    if let Some(dashboard) = dashboard {
+        dashboard.finish()?;
+    }
 
-        let remaining_rows = args.total_rows - total_rows_written;
-        let num_rows_to_write = {
-            if remaining_rows>rows_per_file {
-            rows_per_file
-        } else {
-            remaining_rows
-        }};
-    
-
-        let rows_written = writer.write_to_file(
-            file_path.to_str().unwrap(),
-            &mut file_generator,
-            num_rows_to_write,
-            args.batch_size,
-        )?;
-        total_rows_written += rows_written;
-        if total_rows_written >= args.total_rows {
-            break;
+    if let Some((path, count)) = &report.edges {
+        if args.verbose {
+            println!("Wrote {} edges to {:?}", count, path);
         }
-        num_files += 1;
+    }
 
-        let elapsed = start_time.elapsed();
-        let file_size = std::fs::metadata(&file_path)?.len();
+    if let Some((path, count)) = &report.parent_table {
+        if args.verbose {
+            println!("Wrote {} parent rows to {:?}", count, path);
+        }
+    }
 
+    if let Some((path, count)) = &report.holdout {
         if args.verbose {
-            println!(
-                "  Generated {} rows ({} bytes) in {:.2?} ({:.2} rows/sec)",
-                rows_written,
-                ByteSize::b(file_size),
-                elapsed,
-                rows_written as f64 / elapsed.as_secs_f64()
-            );
+            println!("Wrote {} holdout rows to {:?}", count, path);
         }
-        progress.inc(rows_written as u64);
     }
 
+    if let Some((path, count)) = &report.index {
+        if args.verbose {
+            println!("Wrote {} index rows to {:?}", count, path);
+        }
+    }
+
+    if let Some((path, count)) = &report.queries {
+        if args.verbose {
+            println!("Wrote {} queries to {:?}", count, path);
+        }
+    }
+
+    if let Some((path, count)) = &report.deletes {
+        if args.verbose {
+            println!("Wrote {} deleted row ids to {:?}", count, path);
+        }
+    }
 
-    progress.finish_with_message("Data generation complete!");
+    if args.verbose && !remote && !report.files.is_empty() {
+        let mut footers = 0;
+        let mut metadata = 0;
+        let mut data = 0;
+        for file in &report.files {
+            let footer = footer::inspect(&file.path)?;
+            footers += footer.footer_bytes;
+            metadata += footer.metadata_bytes();
+            data += footer.data_bytes();
+        }
+        println!(
+            "Footers: {} bytes, metadata {} bytes ({:.3}% of {} data bytes)",
+            footers,
+            metadata,
+            metadata as f64 / data.max(1) as f64 * 100.0,
+            data
+        );
+    }
+
+    if let Some(manifest) = manifest {
+        manifest.finish(&report)?;
+        if args.verbose {
+            println!("Wrote manifest to {:?}", args.output_dir.join(MANIFEST_FILE_NAME));
+        }
+    }
+
+    if let Some(cancelled) = report.cancelled {
+        progress.abandon();
+        println!("\n{}", cancelled);
+    } else {
+        progress.finish_with_message("Data generation complete!");
+    }
 
     let total_elapsed = total_start.elapsed();
+    if args.progress_json {
+        emit_progress(&ProgressEvent::Finished {
+            rows: report.total_rows,
+            files: report.files.len(),
+            elapsed_ms: total_elapsed.as_millis() as u64,
+            cancelled: report.cancelled.is_some(),
+        });
+    }
     println!("\nTotal time: {:.2?}", total_elapsed);
-    println!("Generated {} files in {:?}", num_files, args.output_dir);
+    println!(
+        "Generated {} rows in {} files in {:?}",
+        report.total_rows,
+        report.files.len(),
+        args.output_dir
+    );
 
     Ok(())
 }
@@ -207,6 +2164,171 @@ mod tests {
         assert_eq!(parse_file_size("100KB").unwrap(), 100_000);
     }
 
+    #[test]
+    #[cfg(feature = "flight")]
+    fn test_parse_header() {
+        assert_eq!(
+            parse_header("authorization=Bearer abc").unwrap(),
+            ("authorization".to_string(), "Bearer abc".to_string())
+        );
+        assert!(parse_header("no-separator").is_err());
+    }
+
+    fn parse(options: &[&str]) -> (Args, ArgMatches) {
+        let matches = Args::command()
+            .try_get_matches_from(std::iter::once("vector_data_gen").chain(options.iter().copied()))
+            .unwrap();
+        (Args::from_arg_matches(&matches).unwrap(), matches)
+    }
+
+    #[test]
+    fn test_config_options() {
+        // Every configuration field is tied to the options that set it
+        let Value::Object(fields) = config_format::to_value(&Config::default()).unwrap() else {
+            unreachable!()
+        };
+        let keys: Vec<&str> = CONFIG_OPTIONS.iter().map(|(key, _)| *key).collect();
+        for key in fields.keys().filter(|key| *key != config_format::FORMAT_VERSION_FIELD) {
+            assert!(keys.contains(&key.as_str()), "{} has no options", key);
+        }
+        let command = Args::command();
+        for id in CONFIG_OPTIONS.iter().flat_map(|(_, options)| options.iter()) {
+            assert!(command.get_arguments().any(|arg| arg.get_id() == id), "{}", id);
+        }
+    }
+
+    #[test]
+    fn test_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.toml");
+        std::fs::write(
+            &path,
+            "[output]\ntotal_rows = 500\n\n[config]\nvector_dim = 64\nseed = 7\n",
+        )
+        .unwrap();
+
+        // Options given on the command line win over the file
+        let (mut args, matches) = parse(&["--seed", "9"]);
+        let config = apply_config_file(&path, &mut args, &matches).unwrap();
+        assert_eq!((config.vector_dim, config.seed), (64, 9));
+        assert_eq!((args.total_rows, args.batch_size), (500, 10000));
+
+        // Even when they repeat the default value
+        let (mut args, matches) = parse(&["--vector-dim", "1024", "--seed", "42"]);
+        let config = apply_config_file(&path, &mut args, &matches).unwrap();
+        assert_eq!((config.vector_dim, config.seed), (1024, 42));
+
+        // The dump reproduces the effective configuration
+        let dumped = dir.path().join("dumped.toml");
+        std::fs::write(&dumped, dump_config(&args, &config).unwrap()).unwrap();
+        let (mut reloaded, matches) = parse(&[]);
+        let config_again = apply_config_file(&dumped, &mut reloaded, &matches).unwrap();
+        assert_eq!(
+            config_format::to_value(&config_again).unwrap(),
+            config_format::to_value(&config).unwrap()
+        );
+        assert_eq!(reloaded.total_rows, 500);
+
+        // An empty file changes nothing
+        std::fs::write(&path, "").unwrap();
+        let (mut args, matches) = parse(&["--vector-dim", "64"]);
+        let config = apply_config_file(&path, &mut args, &matches).unwrap();
+        let (mut without, matches) = parse(&["--vector-dim", "64"]);
+        let expected = effective_config(&mut without, &matches).unwrap();
+        assert_eq!(
+            dump_config(&args, &config).unwrap(),
+            dump_config(&without, &expected).unwrap()
+        );
+
+        std::fs::write(&path, "[config]\nvector_dims = 64\n").unwrap();
+        let (mut args, matches) = parse(&[]);
+        let error = apply_config_file(&path, &mut args, &matches).unwrap_err();
+        assert!(format!("{:#}", error).contains("vector_dims"));
+    }
+
+    #[test]
+    fn test_profiles() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vector-data-gen").join("profiles.toml");
+        for (name, options) in [
+            ("small", ["--vector-dim", "32", "--total-rows", "100"]),
+            ("bigrun", ["--vector-dim", "768", "--total-rows", "1000000"]),
+        ] {
+            let (mut args, matches) = parse(&options);
+            let config = effective_config(&mut args, &matches).unwrap();
+            save_profile(&path, name, &args, &config).unwrap();
+        }
+
+        let (mut args, matches) = parse(&["--seed", "5"]);
+        let profile = load_profile(&path, "bigrun").unwrap();
+        let config = apply_run_file(profile, &mut args, &matches).unwrap();
+        assert_eq!((config.vector_dim, config.seed), (768, 5));
+        assert_eq!(args.total_rows, 1_000_000);
+
+        let error = load_profile(&path, "huge").err().unwrap();
+        assert!(format!("{:#}", error).contains("profiles: bigrun, small"));
+    }
+
+    #[test]
+    fn test_env_options() {
+        // Variables read only by the environment-aware parser
+        std::env::set_var("VDG_TOTAL_ROWS", "42");
+        std::env::set_var("VDG_SEED", "7");
+        std::env::set_var("VDG_VERBOSE", "true");
+        let parse = |argv: &[&str]| {
+            let matches = command_with_env().try_get_matches_from(argv).unwrap();
+            (Args::from_arg_matches(&matches).unwrap(), matches)
+        };
+
+        let (args, _) = parse(&["vector_data_gen", "--seed", "9"]);
+        assert_eq!((args.total_rows, args.seed, args.verbose), (42, 9, true));
+
+        // The environment wins over the config file, which fills in the rest
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("run.toml");
+        std::fs::write(
+            &path,
+            "[output]\ntotal_rows = 500\nprefix = \"file\"\n\n[config]\nseed = 3\nvector_dim = 64\n",
+        )
+        .unwrap();
+        let (mut args, matches) = parse(&["vector_data_gen", "--config", path.to_str().unwrap()]);
+        let config = effective_config(&mut args, &matches).unwrap();
+        assert_eq!((args.total_rows, args.prefix.as_str()), (42, "file"));
+        assert_eq!((config.seed, config.vector_dim), (7, 64));
+
+        // Even when it holds the default value
+        std::env::set_var("VDG_SEED", "42");
+        let (mut args, matches) = parse(&["vector_data_gen", "--config", path.to_str().unwrap()]);
+        let config = effective_config(&mut args, &matches).unwrap();
+        assert_eq!((config.seed, config.vector_dim), (42, 64));
+
+        for name in ["VDG_TOTAL_ROWS", "VDG_SEED", "VDG_VERBOSE"] {
+            std::env::remove_var(name);
+        }
+    }
+
+    #[test]
+    fn test_progress_event_lines() {
+        let event = ProgressEvent::FileStarted {
+            index: 3,
+            path: Path::new("out/data-00000003.parquet"),
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"event":"file_started","index":3,"path":"out/data-00000003.parquet"}"#
+        );
+        let event = ProgressEvent::Finished {
+            rows: 10,
+            files: 1,
+            elapsed_ms: 5,
+            cancelled: false,
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"event":"finished","rows":10,"files":1,"elapsed_ms":5,"cancelled":false}"#
+        );
+    }
+
     #[test]
     fn test_parse_invalid_file_size() {
         assert!(parse_file_size("invalid").is_err());
@@ -0,0 +1,196 @@
+//! Dataset manifests kept current while files are written
+//!
+//! The manifest lists the configuration of a run and every completed file.
+//! It is rewritten after each file, by writing a temporary file and renaming
+//! it over the old one, so monitoring tools and resume logic always read a
+//! complete manifest describing finished work, even if the process is killed
+//! mid-run.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::dataset::{DatasetOptions, DatasetReport, FileReport};
+use crate::Config;
+
+/// File name of the manifest within the output directory
+pub const MANIFEST_FILE_NAME: &str = "_manifest.json";
+
+/// State of the run a manifest describes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    /// Files are still being written
+    InProgress,
+    /// Every planned file and co-generated table was written
+    Complete,
+    /// The run stopped on cancellation
+    Cancelled,
+}
+
+/// A completed file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestFile {
+    /// Index of the file within the dataset
+    pub index: u64,
+    /// Path relative to the output directory
+    pub path: PathBuf,
+    pub rows: usize,
+    pub bytes: u64,
+    pub tenant: Option<u32>,
+}
+
+/// Configuration and progress of a dataset run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetManifest {
+    /// Crate version that wrote the manifest
+    pub crate_version: String,
+    #[serde(with = "crate::config_format")]
+    pub config: Config,
+    /// Rows planned across all files
+    pub total_rows: usize,
+    pub batch_size: usize,
+    pub status: RunStatus,
+    /// Completed files, ordered by index
+    pub files: Vec<ManifestFile>,
+    /// Rows in completed files
+    pub rows_written: usize,
+    /// Co-generated tables (edges, parent table, holdout, index, queries),
+    /// relative paths
+    pub co_tables: Vec<PathBuf>,
+    /// Time of the last update, in milliseconds since the Unix epoch
+    pub updated_at_ms: u64,
+}
+
+impl DatasetManifest {
+    /// An empty manifest for a run of `config` with `options`
+    pub fn new(config: &Config, options: &DatasetOptions) -> Self {
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            config: config.clone(),
+            total_rows: options.total_rows,
+            batch_size: options.batch_size,
+            status: RunStatus::InProgress,
+            files: Vec::new(),
+            rows_written: 0,
+            co_tables: Vec::new(),
+            updated_at_ms: 0,
+        }
+    }
+
+    /// Load the manifest of the dataset in `dir`
+    pub fn load(dir: impl AsRef<Path>) -> Result<Self> {
+        let path = dir.as_ref().join(MANIFEST_FILE_NAME);
+        let text = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read manifest: {:?}", path))?;
+        serde_json::from_str(&text).with_context(|| format!("Invalid manifest: {:?}", path))
+    }
+
+    /// Atomically replace the manifest in `dir`
+    pub fn save(&mut self, dir: impl AsRef<Path>) -> Result<()> {
+        self.updated_at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_millis() as u64);
+        let path = dir.as_ref().join(MANIFEST_FILE_NAME);
+        let temp = path.with_extension("json.tmp");
+        std::fs::write(&temp, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write manifest: {:?}", temp))?;
+        std::fs::rename(&temp, &path)
+            .with_context(|| format!("Failed to replace manifest: {:?}", path))
+    }
+
+    /// Add a completed file
+    pub fn record_file(&mut self, file: &FileReport, output_dir: &Path) {
+        let path = file
+            .path
+            .strip_prefix(output_dir)
+            .unwrap_or(file.path.as_path());
+        let position = self.files.partition_point(|f| f.index < file.index);
+        self.files.insert(
+            position,
+            ManifestFile {
+                index: file.index,
+                path: path.to_path_buf(),
+                rows: file.rows,
+                bytes: file.bytes,
+                tenant: file.tenant,
+            },
+        );
+        self.rows_written += file.rows;
+    }
+
+    /// Mark the run finished with the outcome in `report`
+    ///
+    /// Files kept on cancellation are added if they are not listed yet.
+    pub fn finish(&mut self, report: &DatasetReport, output_dir: &Path) {
+        for file in &report.files {
+            if !self.files.iter().any(|f| f.index == file.index) {
+                self.record_file(file, output_dir);
+            }
+        }
+        let co_tables = [
+            &report.edges,
+            &report.parent_table,
+            &report.holdout,
+            &report.index,
+            &report.queries,
+            &report.deletes,
+        ];
+        self.co_tables = co_tables
+            .into_iter()
+            .flatten()
+            .map(|(path, _)| {
+                path.strip_prefix(output_dir)
+                    .unwrap_or(path.as_path())
+                    .to_path_buf()
+            })
+            .collect();
+        self.status = match report.cancelled {
+            Some(_) => RunStatus::Cancelled,
+            None => RunStatus::Complete,
+        };
+    }
+}
+
+/// Keeps the manifest of a run in its output directory up to date
+///
+/// Safe to share between the threads of a NUMA run.
+pub struct ManifestWriter {
+    dir: PathBuf,
+    manifest: Mutex<DatasetManifest>,
+}
+
+impl ManifestWriter {
+    /// Write an in-progress manifest for a run of `config` with `options`
+    pub fn create(config: &Config, options: &DatasetOptions) -> Result<Self> {
+        std::fs::create_dir_all(&options.output_dir).with_context(|| {
+            format!(
+                "Failed to create output directory: {:?}",
+                options.output_dir
+            )
+        })?;
+        let mut manifest = DatasetManifest::new(config, options);
+        manifest.save(&options.output_dir)?;
+        Ok(Self {
+            dir: options.output_dir.clone(),
+            manifest: Mutex::new(manifest),
+        })
+    }
+
+    /// Record a completed file and rewrite the manifest
+    pub fn file_finished(&self, file: &FileReport) -> Result<()> {
+        let mut manifest = self.manifest.lock().unwrap();
+        manifest.record_file(file, &self.dir);
+        manifest.save(&self.dir)
+    }
+
+    /// Record the outcome of the run and rewrite the manifest
+    pub fn finish(self, report: &DatasetReport) -> Result<DatasetManifest> {
+        let mut manifest = self.manifest.into_inner().unwrap();
+        manifest.finish(report, &self.dir);
+        manifest.save(&self.dir)?;
+        Ok(manifest)
+    }
+}
@@ -0,0 +1,108 @@
+//! Clustered vectors drawn from a mixture of Gaussians
+//!
+//! ANN indexes behave very differently on data with cluster structure than on
+//! IID noise: partition-based indexes find real clusters to split on, and
+//! recall depends on how well the query's cluster is covered. In clustered
+//! mode, `K` centroids are drawn once per dataset from the seed and the
+//! configured vector distribution, and every row picks a centroid uniformly
+//! and adds independent Gaussian noise with the configured standard deviation
+//! to each component.
+
+use anyhow::{bail, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::{Distribution, Normal};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::core_gen::{stream_seed, VectorDistribution};
+
+/// Stream identifier for cluster centroids
+const CENTROID_STREAM: u64 = u64::MAX - 8;
+
+/// Mixture-of-Gaussians parameters
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct MixtureConfig {
+    /// Number of clusters
+    pub clusters: usize,
+    /// Standard deviation of every component around its centroid
+    pub std: f64,
+}
+
+impl MixtureConfig {
+    /// Check the parameters
+    pub fn validate(&self) -> Result<()> {
+        if self.clusters == 0 {
+            bail!("clustered vectors need at least one cluster");
+        }
+        if !self.std.is_finite() || self.std < 0.0 {
+            bail!("cluster std must be a non-negative number");
+        }
+        Ok(())
+    }
+}
+
+/// Centroids of a dataset and the noise around them
+#[derive(Debug, Clone)]
+pub(crate) struct Mixture {
+    clusters: usize,
+    dim: usize,
+    /// Centroids laid out as `[cluster][dim]`
+    centroids: Vec<f64>,
+    noise: Normal<f64>,
+}
+
+impl Mixture {
+    /// Draw the centroids of every file generated with `seed`
+    pub(crate) fn new(
+        config: &MixtureConfig,
+        dim: usize,
+        distribution: VectorDistribution,
+        seed: u64,
+    ) -> Result<Self> {
+        let mut rng = StdRng::seed_from_u64(stream_seed(seed, &[CENTROID_STREAM]));
+        let mut centroids = Vec::with_capacity(config.clusters * dim);
+        for _ in 0..config.clusters {
+            centroids.extend(distribution.sample(&mut rng, dim));
+        }
+        Ok(Self {
+            clusters: config.clusters,
+            dim,
+            centroids,
+            noise: Normal::new(0.0, config.std)?,
+        })
+    }
+
+    /// The `cluster`-th centroid
+    pub(crate) fn centroid(&self, cluster: usize) -> &[f64] {
+        &self.centroids[cluster * self.dim..(cluster + 1) * self.dim]
+    }
+
+    /// Sample a vector around a uniformly chosen centroid
+    pub(crate) fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<f64> {
+        let centroid = self.centroid(rng.gen_range(0..self.clusters));
+        centroid
+            .iter()
+            .map(|c| c + self.noise.sample(rng))
+            .collect()
+    }
+}
+
+/// Source of the vectors of a column: a plain distribution or a mixture
+#[derive(Debug, Clone, Default)]
+pub(crate) struct VectorSampler {
+    pub(crate) distribution: VectorDistribution,
+    pub(crate) mixture: Option<Arc<Mixture>>,
+    /// Scale every vector of the column, whatever its source, to unit L2 norm
+    pub(crate) normalize: bool,
+}
+
+impl VectorSampler {
+    /// Sample a vector of `dim` values
+    pub(crate) fn sample<R: Rng + ?Sized>(&self, rng: &mut R, dim: usize) -> Vec<f64> {
+        match &self.mixture {
+            Some(mixture) => mixture.sample(rng),
+            None => self.distribution.sample(rng, dim),
+        }
+    }
+}
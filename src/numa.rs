@@ -0,0 +1,211 @@
+//! NUMA-aware dataset generation for multi-socket machines
+//!
+//! Files are dealt round-robin to the NUMA nodes. Each node gets a driver
+//! thread and a rayon pool whose threads are pinned to that node's CPUs, so
+//! column buffers, encoded pages and file writes are allocated and touched by
+//! node-local threads (first-touch placement) instead of bouncing across the
+//! interconnect. Every file is still written by exactly one node and holds the
+//! same rows as a sequential run.
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::fs;
+use std::sync::Mutex;
+
+use crate::dataset::{
+    check_row_total, file_generator, plan_files, write_co_tables, write_file, DatasetEvent,
+    DatasetOptions, DatasetReport, FileOutcome,
+};
+use crate::{CancellationToken, Cancelled, Config, DataGenerator, ParquetWriter};
+
+/// Directory listing the NUMA nodes on Linux
+const NODE_DIR: &str = "/sys/devices/system/node";
+
+/// One NUMA node and its CPUs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumaNode {
+    pub id: usize,
+    pub cpus: Vec<usize>,
+}
+
+/// NUMA nodes of this machine that have CPUs, from sysfs
+pub fn topology() -> Result<Vec<NumaNode>> {
+    let entries = fs::read_dir(NODE_DIR)
+        .with_context(|| format!("NUMA topology unavailable ({} not found)", NODE_DIR))?;
+    let mut nodes = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name();
+        let Some(id) = name
+            .to_str()
+            .and_then(|n| n.strip_prefix("node"))
+            .and_then(|n| n.parse().ok())
+        else {
+            continue;
+        };
+        let cpulist = fs::read_to_string(entry.path().join("cpulist"))?;
+        let cpus = parse_cpulist(&cpulist)?;
+        if !cpus.is_empty() {
+            nodes.push(NumaNode { id, cpus });
+        }
+    }
+    if nodes.is_empty() {
+        bail!("no NUMA nodes with CPUs found in {}", NODE_DIR);
+    }
+    nodes.sort_by_key(|node| node.id);
+    Ok(nodes)
+}
+
+/// Parse a kernel CPU list such as `0-15,32-47`
+pub fn parse_cpulist(list: &str) -> Result<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for part in list.trim().split(',').filter(|p| !p.is_empty()) {
+        match part.split_once('-') {
+            Some((first, last)) => {
+                let (first, last): (usize, usize) = (first.parse()?, last.parse()?);
+                if first > last {
+                    bail!("invalid CPU range '{}'", part);
+                }
+                cpus.extend(first..=last);
+            }
+            None => cpus.push(part.parse()?),
+        }
+    }
+    Ok(cpus)
+}
+
+/// Generate a dataset with the files split across `nodes`
+///
+/// Produces the same files as [`crate::dataset::generate`]; events arrive from
+/// all nodes concurrently. Trajectory mode is not supported, because each file
+/// continues the entity walks of the previous one.
+pub fn generate(
+    config: &Config,
+    options: &DatasetOptions,
+    cancel: &CancellationToken,
+    nodes: &[NumaNode],
+    on_event: impl Fn(DatasetEvent<'_>) + Sync,
+) -> Result<DatasetReport> {
+    if config.trajectory.is_some() {
+        bail!("NUMA generation cannot be combined with trajectory mode");
+    }
+    if config.holdout_rows.is_some() {
+        // Files finish in no fixed order, so the sample would not be reproducible
+        bail!("NUMA generation cannot be combined with holdout sampling");
+    }
+    if config.bucketing.is_some() {
+        bail!("NUMA generation cannot be combined with bucketing");
+    }
+    if !config.monotonic.is_empty() {
+        // Each file continues the largest values of the previous one
+        bail!("NUMA generation cannot be combined with ordering constraints");
+    }
+    if nodes.is_empty() {
+        bail!("NUMA generation needs at least one node");
+    }
+    crate::dataset::check_output(config, options)?;
+    let rows_per_file = DataGenerator::try_new(config.clone())?.estimate_rows_per_file();
+    let writer = ParquetWriter::new(config.clone());
+
+    // Global first row of every file, as in a sequential run
+    let mut next_row = 0u64;
+    let plans: Vec<_> = plan_files(config, options, rows_per_file)
+        .into_iter()
+        .map(|plan| {
+            let first_row = next_row;
+            next_row += plan.rows as u64;
+            (plan, first_row)
+        })
+        .collect();
+
+    let report = Mutex::new(DatasetReport::default());
+    std::thread::scope(|scope| -> Result<()> {
+        let handles: Vec<_> = nodes
+            .iter()
+            .enumerate()
+            .map(|(slot, node)| {
+                let plans = plans.iter().skip(slot).step_by(nodes.len());
+                let (writer, report, on_event) = (&writer, &report, &on_event);
+                scope.spawn(move || -> Result<()> {
+                    pin_to(node, 0);
+                    let pool = node_pool(node)?;
+                    pool.install(|| {
+                        for (plan, first_row) in plans {
+                            if cancel.is_cancelled() {
+                                break;
+                            }
+                            on_event(DatasetEvent::FileStarted(plan));
+                            let mut generator = file_generator(config, plan, *first_row)?;
+                            match write_file(
+                                writer,
+                                config,
+                                plan,
+                                &mut generator,
+                                options,
+                                cancel,
+                                None,
+                            )? {
+                                FileOutcome::Written(file) => {
+                                    on_event(DatasetEvent::FileFinished(&file));
+                                    let mut report = report.lock().unwrap();
+                                    report.total_rows += file.rows;
+                                    report.files.push(file);
+                                }
+                                FileOutcome::Cancelled(cancelled, partial) => {
+                                    let mut report = report.lock().unwrap();
+                                    if let Some(file) = partial {
+                                        report.total_rows += file.rows;
+                                        report.files.push(file);
+                                    }
+                                    report.cancelled.get_or_insert(cancelled);
+                                    break;
+                                }
+                            }
+                        }
+                        Ok(())
+                    })
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle
+                .join()
+                .map_err(|_| anyhow!("NUMA node thread panicked"))??;
+        }
+        Ok(())
+    })?;
+
+    let mut report = report.into_inner().unwrap();
+    report.files.sort_by_key(|file| file.index);
+    if cancel.is_cancelled() && report.cancelled.is_none() {
+        // Every node stopped between files
+        report.cancelled = Some(Cancelled {
+            rows_written: 0,
+            policy: config.cancel_policy,
+        });
+    }
+    if report.cancelled.is_none() {
+        check_row_total(&report, options)?;
+        write_co_tables(config, options, &writer, &mut report, None)?;
+    }
+    Ok(report)
+}
+
+/// Rayon pool with one thread per CPU of `node`, each pinned to its CPU
+fn node_pool(node: &NumaNode) -> Result<rayon::ThreadPool> {
+    let id = node.id;
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(node.cpus.len())
+        .thread_name(move |i| format!("vdg-numa{}-{}", id, i))
+        .start_handler({
+            let node = node.clone();
+            move |i| pin_to(&node, i)
+        })
+        .build()
+        .context("Failed to build NUMA thread pool")
+}
+
+/// Pin the current thread to the `i`-th CPU of `node` (best effort)
+fn pin_to(node: &NumaNode, i: usize) {
+    let cpu = node.cpus[i % node.cpus.len()];
+    core_affinity::set_for_current(core_affinity::CoreId { id: cpu });
+}
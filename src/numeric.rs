@@ -0,0 +1,491 @@
+//! Value distributions of numeric columns
+//!
+//! Numeric columns of a schema spec are uniform over the whole type range
+//! (integers) or `[0, 1)` (floats) unless the spec gives a distribution:
+//!
+//! - `MIN..MAX`: uniform in `[MIN, MAX)` for floats, `[MIN, MAX]` for
+//!   integers, e.g. `price:float64(0..100)`
+//! - `mean=M,std=S`: normal, e.g. `latency:float32(mean=50,std=10)`
+//! - `pP=V,...`: target percentiles, e.g.
+//!   `latency:float64(p50=12,p90=40,p99=250)`. The fitted quantile function
+//!   passes through every target and is linear in the normal z-score between
+//!   and beyond them, over the log of the values if all targets are positive:
+//!   two targets give a normal or log-normal fit, more bend it to match skewed
+//!   production columns.
+//!
+//! Integer columns round normal and percentile values to the nearest value
+//! and clamp them to the type range.
+//!
+//! Distributions know their quantiles, so query workloads can put filters of
+//! a chosen selectivity on the column.
+//!
+//! Independently of the distribution, [`NumericBounds`] declare hard limits:
+//! `min=A` and `max=B` clamp every value into `[A, B]`, and `precision=N`
+//! rounds float values to `N` decimal places, e.g.
+//! `latency:float64(mean=50,std=20,min=0,precision=2)`. A column with both
+//! bounds and no distribution is uniform between them. The bounds are
+//! recorded in the field metadata, so constraint checks can be tested
+//! against data known to satisfy them.
+
+use anyhow::{bail, Context, Result};
+use arrow::datatypes::DataType;
+use rand::Rng;
+use rand_distr::{Distribution, Normal, StandardNormal};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Distribution of the values of a numeric column
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum NumericDistribution {
+    /// Uniform in `[min, max)`, or `[min, max]` for integers
+    Uniform { min: f64, max: f64 },
+    /// Normal with mean `mean` and standard deviation `std`
+    Normal { mean: f64, std: f64 },
+    /// `(percentile, value)` targets in increasing order, percentiles in
+    /// `(0, 100)`
+    Quantiles(Vec<(f64, f64)>),
+}
+
+impl NumericDistribution {
+    /// Check the parameters for a column of `data_type`
+    pub fn validate(&self, data_type: &DataType) -> Result<()> {
+        let integer = data_type.is_integer();
+        match *self {
+            NumericDistribution::Uniform { min, max } => {
+                if !(min.is_finite() && max.is_finite() && min < max) {
+                    bail!(
+                        "numeric range {}..{} must be finite and non-empty",
+                        min,
+                        max
+                    );
+                }
+                if integer {
+                    let (low, high) = int_range(data_type);
+                    if min.fract() != 0.0 || max.fract() != 0.0 || min < low || max > high {
+                        bail!(
+                            "range {}..{} of a {} column must hold whole numbers of the type",
+                            min,
+                            max,
+                            data_type
+                        );
+                    }
+                }
+            }
+            NumericDistribution::Normal { mean, std } => {
+                if !(mean.is_finite() && std.is_finite() && std > 0.0) {
+                    bail!("normal distribution needs a finite mean and a positive std");
+                }
+            }
+            NumericDistribution::Quantiles(ref targets) => {
+                if targets.len() < 2 {
+                    bail!("percentile distribution needs at least two targets");
+                }
+                if targets
+                    .iter()
+                    .any(|&(p, v)| !(p > 0.0 && p < 100.0 && v.is_finite()))
+                {
+                    bail!("percentiles must be in (0, 100) with finite values");
+                }
+                if targets
+                    .windows(2)
+                    .any(|w| w[0].0 >= w[1].0 || w[0].1 >= w[1].1)
+                {
+                    bail!("percentile targets must increase in both percentile and value");
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// A value of a column of `data_type`: integers are rounded and clamped
+    /// to the type range
+    pub fn sample<R: Rng>(&self, rng: &mut R, data_type: &DataType) -> f64 {
+        let integer = data_type.is_integer();
+        match *self {
+            NumericDistribution::Uniform { min, max } if integer => {
+                rng.gen_range(min as i64..=max as i64) as f64
+            }
+            NumericDistribution::Uniform { min, max } => rng.gen_range(min..max),
+            _ => {
+                let value = match self {
+                    NumericDistribution::Normal { mean, std } => Normal::new(*mean, *std)
+                        .expect("validated normal parameters")
+                        .sample(rng),
+                    _ => self.quantile_of_z(rng.sample(StandardNormal)),
+                };
+                if integer {
+                    let (low, high) = int_range(data_type);
+                    value.round().clamp(low, high)
+                } else {
+                    value
+                }
+            }
+        }
+    }
+
+    /// Value below which a fraction `p` of the (continuous) values fall
+    pub fn quantile(&self, p: f64) -> f64 {
+        match *self {
+            NumericDistribution::Uniform { min, max } => min + p * (max - min),
+            NumericDistribution::Normal { mean, std } => mean + std * normal_quantile(p),
+            NumericDistribution::Quantiles(_) => self.quantile_of_z(normal_quantile(p)),
+        }
+    }
+
+    /// Fraction of the (continuous) values below `x`
+    pub fn cdf(&self, x: f64) -> f64 {
+        match *self {
+            NumericDistribution::Uniform { min, max } => ((x - min) / (max - min)).clamp(0.0, 1.0),
+            NumericDistribution::Normal { mean, std } => normal_cdf((x - mean) / std),
+            NumericDistribution::Quantiles(ref targets) => {
+                let log = targets.iter().all(|&(_, v)| v > 0.0);
+                if log && x <= 0.0 {
+                    return 0.0;
+                }
+                let y = if log { x.ln() } else { x };
+                let points = quantile_points(targets);
+                let (a, b) = segment(&points, |(_, y)| y, y);
+                normal_cdf(a.0 + (y - a.1) * (b.0 - a.0) / (b.1 - a.1))
+            }
+        }
+    }
+
+    /// Value of a column of `data_type` at probability `p`, the inverse of its
+    /// CDF: maps correlated scores to values with this distribution
+    pub fn value_at(&self, p: f64, data_type: &DataType) -> f64 {
+        let integer = data_type.is_integer();
+        match *self {
+            NumericDistribution::Uniform { min, max } if integer => {
+                (min + (p * (max - min + 1.0)).floor()).min(max)
+            }
+            _ => {
+                let value = self.quantile(p);
+                if integer {
+                    let (low, high) = int_range(data_type);
+                    value.round().clamp(low, high)
+                } else {
+                    value
+                }
+            }
+        }
+    }
+
+    /// Distribution of a column of `data_type` without one: uniform over the
+    /// type range (integers) or `[0, 1)` (floats)
+    pub fn default_for(data_type: &DataType) -> Self {
+        if data_type.is_integer() {
+            let (min, max) = int_range(data_type);
+            NumericDistribution::Uniform { min, max }
+        } else {
+            NumericDistribution::Uniform { min: 0.0, max: 1.0 }
+        }
+    }
+
+    /// Value of a percentile distribution at the standard normal z-score `z`
+    fn quantile_of_z(&self, z: f64) -> f64 {
+        let NumericDistribution::Quantiles(targets) = self else {
+            unreachable!("z-scores map percentile distributions only");
+        };
+        let points = quantile_points(targets);
+        let (a, b) = segment(&points, |(z, _)| z, z);
+        let y = a.1 + (z - a.0) * (b.1 - a.1) / (b.0 - a.0);
+        if targets.iter().all(|&(_, v)| v > 0.0) {
+            y.exp()
+        } else {
+            y
+        }
+    }
+
+    /// Integer below which about a fraction `p` of the values of an integer
+    /// column fall
+    pub fn int_quantile(&self, p: f64) -> i64 {
+        match *self {
+            NumericDistribution::Uniform { min, max } => {
+                (min + p * (max - min + 1.0)).round() as i64
+            }
+            // Values below `t` are the samples below `t - 0.5` before rounding
+            _ => (self.quantile(p) + 0.5).round() as i64,
+        }
+    }
+
+    /// Fraction of the values of an integer column below `t`
+    pub fn int_fraction_below(&self, t: i64) -> f64 {
+        match *self {
+            NumericDistribution::Uniform { min, max } => {
+                ((t as f64 - min) / (max - min + 1.0)).clamp(0.0, 1.0)
+            }
+            _ => self.cdf(t as f64 - 0.5),
+        }
+    }
+}
+
+/// `(z-score, value)` points of percentile targets, values in log space if
+/// all are positive
+fn quantile_points(targets: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let log = targets.iter().all(|&(_, v)| v > 0.0);
+    targets
+        .iter()
+        .map(|&(p, v)| (normal_quantile(p / 100.0), if log { v.ln() } else { v }))
+        .collect()
+}
+
+/// The two consecutive points whose `key` range holds `x`, the first or last
+/// two beyond the ends
+fn segment(
+    points: &[(f64, f64)],
+    key: impl Fn((f64, f64)) -> f64,
+    x: f64,
+) -> ((f64, f64), (f64, f64)) {
+    let upper = points[1..points.len() - 1]
+        .iter()
+        .take_while(|&&point| key(point) < x)
+        .count()
+        + 1;
+    (points[upper - 1], points[upper])
+}
+
+/// Hard limits on the values of a numeric column
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub struct NumericBounds {
+    /// Smallest value
+    pub min: Option<f64>,
+    /// Largest value
+    pub max: Option<f64>,
+    /// Decimal places of float values
+    pub precision: Option<u32>,
+}
+
+impl NumericBounds {
+    /// Largest supported precision, about the decimal digits of an `f64`
+    pub const MAX_PRECISION: u32 = 15;
+
+    /// True if no limit is set
+    pub fn is_none(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check the limits for a column of `data_type`
+    pub fn validate(&self, data_type: &DataType) -> Result<()> {
+        for bound in [self.min, self.max].into_iter().flatten() {
+            if !bound.is_finite() {
+                bail!("bound {} must be finite", bound);
+            }
+            if data_type.is_integer() {
+                let (low, high) = int_range(data_type);
+                if bound.fract() != 0.0 || bound < low || bound > high {
+                    bail!(
+                        "bound {} of a {} column must be a whole number of the type",
+                        bound,
+                        data_type
+                    );
+                }
+            }
+            if let Some(precision) = self.precision {
+                if round_to(bound, precision) != bound {
+                    bail!("bound {} has more than {} decimal places", bound, precision);
+                }
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min, self.max) {
+            if min > max {
+                bail!("min {} is above max {}", min, max);
+            }
+        }
+        match self.precision {
+            Some(_) if data_type.is_integer() => {
+                bail!("precision applies to float columns only")
+            }
+            Some(precision) if precision > Self::MAX_PRECISION => bail!(
+                "precision {} is above the maximum of {}",
+                precision,
+                Self::MAX_PRECISION
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// `value` rounded to the precision and clamped to the bounds
+    pub fn apply(&self, value: f64) -> f64 {
+        let mut value = match self.precision {
+            Some(precision) => round_to(value, precision),
+            None => value,
+        };
+        if let Some(min) = self.min {
+            value = value.max(min);
+        }
+        if let Some(max) = self.max {
+            value = value.min(max);
+        }
+        value
+    }
+}
+
+/// `value` rounded to `precision` decimal places
+fn round_to(value: f64, precision: u32) -> f64 {
+    let scale = 10f64.powi(precision as i32);
+    (value * scale).round() / scale
+}
+
+/// A value of a column of `data_type` without a distribution: uniform over
+/// the type range (integers) or `[0, 1)` (floats)
+pub(crate) fn default_sample<R: Rng>(rng: &mut R, data_type: &DataType) -> f64 {
+    match data_type {
+        DataType::Int32 => rng.gen::<i32>() as f64,
+        DataType::Int64 => rng.gen::<i64>() as f64,
+        _ => rng.gen::<f64>(),
+    }
+}
+
+/// Smallest and largest value of an integer type, as `f64`
+pub(crate) fn int_range(data_type: &DataType) -> (f64, f64) {
+    match data_type {
+        DataType::Int32 => (i32::MIN as f64, i32::MAX as f64),
+        _ => (i64::MIN as f64, i64::MAX as f64),
+    }
+}
+
+/// Standard normal CDF, from the complementary error function
+pub fn normal_cdf(z: f64) -> f64 {
+    0.5 * erfc(-z / std::f64::consts::SQRT_2)
+}
+
+/// Complementary error function, accurate to about 1e-7 (Numerical Recipes
+/// `erfcc`)
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let poly = -z * z - 1.265_512_23
+        + t * (1.000_023_68
+            + t * (0.374_091_96
+                + t * (0.096_784_18
+                    + t * (-0.186_288_06
+                        + t * (0.278_868_07
+                            + t * (-1.135_203_98
+                                + t * (1.488_515_87 + t * (-0.822_152_23 + t * 0.170_872_77))))))));
+    let value = t * poly.exp();
+    if x >= 0.0 {
+        value
+    } else {
+        2.0 - value
+    }
+}
+
+/// Standard normal quantile (Acklam's rational approximation, relative
+/// error below 1.2e-9); infinite at 0 and 1
+pub fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    const LOW: f64 = 0.024_25;
+    if p <= 0.0 {
+        return f64::NEG_INFINITY;
+    }
+    if p >= 1.0 {
+        return f64::INFINITY;
+    }
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    if p < LOW {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - LOW {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    }
+}
+
+impl fmt::Display for NumericDistribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NumericDistribution::Uniform { min, max } => write!(f, "{}..{}", min, max),
+            NumericDistribution::Normal { mean, std } => write!(f, "mean={},std={}", mean, std),
+            NumericDistribution::Quantiles(targets) => {
+                for (i, (p, v)) in targets.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "p{}={}", p, v)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl FromStr for NumericDistribution {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let number = |n: &str| -> Result<f64> {
+            n.trim()
+                .parse()
+                .with_context(|| format!("invalid number '{}' in distribution '{}'", n.trim(), s))
+        };
+        if let Some((min, max)) = s.split_once("..") {
+            return Ok(NumericDistribution::Uniform {
+                min: number(min)?,
+                max: number(max)?,
+            });
+        }
+        let (mut mean, mut std) = (None, None);
+        let mut targets = Vec::new();
+        for part in s.split(',') {
+            match part.split_once('=').map(|(k, v)| (k.trim(), v)) {
+                Some(("mean", value)) => mean = Some(number(value)?),
+                Some(("std", value)) => std = Some(number(value)?),
+                Some((key, value)) if key.starts_with('p') => {
+                    targets.push((number(&key[1..])?, number(value)?))
+                }
+                _ => bail!(
+                    "invalid distribution '{}', expected MIN..MAX, mean=M,std=S or pP=V,...",
+                    s
+                ),
+            }
+        }
+        match (mean, std) {
+            (None, None) if !targets.is_empty() => {
+                targets.sort_by(|a, b| a.0.total_cmp(&b.0));
+                Ok(NumericDistribution::Quantiles(targets))
+            }
+            (Some(mean), Some(std)) if targets.is_empty() => {
+                Ok(NumericDistribution::Normal { mean, std })
+            }
+            _ => bail!(
+                "distribution '{}' needs both mean and std, or only percentiles",
+                s
+            ),
+        }
+    }
+}
@@ -0,0 +1,194 @@
+//! Column ordering constraints
+//!
+//! A constraint `column:key` declares that `column` never decreases within
+//! a group of rows sharing a value of `key`, in generation order, e.g. a
+//! version number per id. Merge-tree and LSM compaction tests need input
+//! sequences like that. Constraints are enforced as batches are generated:
+//! each value is raised to the largest value of its key seen so far, so
+//! rows keep their sampled values wherever they already satisfy the
+//! constraint. The largest values carry over from batch to batch and, like
+//! trajectories, from file to file of a dataset.
+//!
+//! Constrained columns must be integer, floating-point or timestamp columns;
+//! keys may be integer or string columns. Null values and null keys are left
+//! alone. Constraints apply after derived columns, so either may be one.
+
+use anyhow::{anyhow, bail, Result};
+use arrow::array::{Array, ArrayRef, ArrowPrimitiveType, AsArray, PrimitiveArray};
+use arrow::datatypes::{
+    DataType, Float32Type, Float64Type, Int32Type, Int64Type, Schema, TimeUnit,
+    TimestampMillisecondType,
+};
+use arrow::record_batch::RecordBatch;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// `column` is non-decreasing within each group of rows sharing `key`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Monotonic {
+    pub column: String,
+    pub key: String,
+}
+
+impl fmt::Display for Monotonic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.column, self.key)
+    }
+}
+
+impl FromStr for Monotonic {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (column, key) = s
+            .split_once(':')
+            .ok_or_else(|| anyhow!("invalid ordering constraint '{}', expected COLUMN:KEY", s))?;
+        let (column, key) = (column.trim(), key.trim());
+        if column.is_empty() || key.is_empty() {
+            bail!("invalid ordering constraint '{}', expected COLUMN:KEY", s);
+        }
+        if column == key {
+            bail!("column '{}' cannot be ordered by itself", column);
+        }
+        Ok(Self {
+            column: column.to_string(),
+            key: key.to_string(),
+        })
+    }
+}
+
+/// Largest value so far of one constraint, per key
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct Maxima {
+    /// Integer and timestamp columns
+    ints: HashMap<String, i64>,
+    floats: HashMap<String, f64>,
+}
+
+/// Constraints of a generator and the largest values seen so far
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MonotonicState {
+    /// Positions of the constrained column and its key in the schema
+    positions: Vec<(usize, usize)>,
+    maxima: Vec<Maxima>,
+}
+
+impl MonotonicState {
+    /// Check `constraints` against `schema` and start with no values seen
+    pub fn new(constraints: &[Monotonic], schema: &Schema) -> Result<Self> {
+        let mut positions = Vec::with_capacity(constraints.len());
+        for constraint in constraints {
+            let position = |name: &str| {
+                schema.index_of(name).map_err(|_| {
+                    anyhow!("ordering constraint {}: no column '{}'", constraint, name)
+                })
+            };
+            let (column, key) = (position(&constraint.column)?, position(&constraint.key)?);
+            match schema.field(column).data_type() {
+                DataType::Int32
+                | DataType::Int64
+                | DataType::Float32
+                | DataType::Float64
+                | DataType::Timestamp(TimeUnit::Millisecond, _) => {}
+                other => bail!(
+                    "ordering constraint {}: column '{}' is {}, not a number or timestamp",
+                    constraint,
+                    constraint.column,
+                    other
+                ),
+            }
+            match schema.field(key).data_type() {
+                DataType::Int32 | DataType::Int64 | DataType::Utf8 => {}
+                other => bail!(
+                    "ordering constraint {}: key '{}' is {}, not an integer or string",
+                    constraint,
+                    constraint.key,
+                    other
+                ),
+            }
+            positions.push((column, key));
+        }
+        Ok(Self {
+            maxima: vec![Maxima::default(); positions.len()],
+            positions,
+        })
+    }
+
+    /// Raise the constrained values of `batch`, the next rows in order
+    pub fn apply(&mut self, batch: RecordBatch) -> Result<RecordBatch> {
+        let mut columns = batch.columns().to_vec();
+        for (&(column, key), maxima) in self.positions.iter().zip(&mut self.maxima) {
+            let keys = batch.column(key).as_ref();
+            let values = columns[column].as_ref();
+            let ints = &mut maxima.ints;
+            let floats = &mut maxima.floats;
+            columns[column] = match values.data_type() {
+                DataType::Int32 => {
+                    raise::<Int32Type, _>(values, keys, ints, |v| v as i64, |v| v as i32)?
+                }
+                DataType::Int64 => raise::<Int64Type, _>(values, keys, ints, |v| v, |v| v)?,
+                DataType::Timestamp(_, _) => {
+                    raise::<TimestampMillisecondType, _>(values, keys, ints, |v| v, |v| v)?
+                }
+                DataType::Float32 => {
+                    raise::<Float32Type, _>(values, keys, floats, |v| v as f64, |v| v as f32)?
+                }
+                DataType::Float64 => raise::<Float64Type, _>(values, keys, floats, |v| v, |v| v)?,
+                other => bail!("cannot order a {} column", other),
+            };
+        }
+        Ok(RecordBatch::try_new(batch.schema(), columns)?)
+    }
+}
+
+/// `values` with each value raised to the largest earlier value of its key,
+/// tracked in `maxima` widened to `W`
+fn raise<T: ArrowPrimitiveType, W: PartialOrd + Copy>(
+    values: &dyn Array,
+    keys: &dyn Array,
+    maxima: &mut HashMap<String, W>,
+    widen: impl Fn(T::Native) -> W,
+    narrow: impl Fn(W) -> T::Native,
+) -> Result<ArrayRef> {
+    let array = values.as_primitive::<T>();
+    let mut raised = Vec::with_capacity(array.len());
+    for row in 0..array.len() {
+        if array.is_null(row) {
+            raised.push(None);
+            continue;
+        }
+        let v = widen(array.value(row));
+        let v = match key(keys, row)? {
+            Some(key) => {
+                let max = maxima.entry(key).or_insert(v);
+                if v > *max {
+                    *max = v;
+                }
+                *max
+            }
+            None => v,
+        };
+        raised.push(Some(narrow(v)));
+    }
+    let raised = raised
+        .into_iter()
+        .collect::<PrimitiveArray<T>>()
+        .with_data_type(values.data_type().clone());
+    Ok(Arc::new(raised))
+}
+
+/// Group of the row `row` of the key column `keys`; `None` if null
+fn key(keys: &dyn Array, row: usize) -> Result<Option<String>> {
+    if keys.is_null(row) {
+        return Ok(None);
+    }
+    Ok(Some(match keys.data_type() {
+        DataType::Int32 => keys.as_primitive::<Int32Type>().value(row).to_string(),
+        DataType::Int64 => keys.as_primitive::<Int64Type>().value(row).to_string(),
+        DataType::Utf8 => keys.as_string::<i32>().value(row).to_string(),
+        other => bail!("cannot group rows by a {} key", other),
+    }))
+}
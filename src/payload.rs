@@ -0,0 +1,90 @@
+//! Opaque binary payloads with controlled compressibility
+//!
+//! A `payload` column brackets the compression-ratio sensitivity of
+//! downstream storage with both extremes: bytes straight from a
+//! cryptographically secure generator (ChaCha, as behind `StdRng`), which no
+//! codec can shrink, or a short per-row pattern repeated to the full length,
+//! which every codec collapses.
+
+use anyhow::{bail, Result};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Length of the pattern repeated in compressible payloads
+pub const PATTERN_LEN: usize = 4;
+
+/// How payload bytes are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum PayloadEntropy {
+    /// Uniform bytes from a cryptographic RNG, incompressible
+    #[default]
+    Incompressible,
+    /// A random [`PATTERN_LEN`]-byte pattern repeated, highly compressible
+    Repeating,
+}
+
+/// `payload` column parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayloadConfig {
+    /// Bytes per value
+    pub len: usize,
+    /// Compressibility of the bytes
+    pub entropy: PayloadEntropy,
+}
+
+impl PayloadConfig {
+    /// Check the parameters
+    pub fn validate(&self) -> Result<()> {
+        if self.len == 0 {
+            bail!("payload length must be greater than zero");
+        }
+        Ok(())
+    }
+}
+
+/// Fill `out` with one payload value of `len` bytes
+pub fn sample_payload<R: Rng + ?Sized>(
+    rng: &mut R,
+    len: usize,
+    entropy: PayloadEntropy,
+    out: &mut Vec<u8>,
+) {
+    out.clear();
+    out.resize(len, 0);
+    match entropy {
+        PayloadEntropy::Incompressible => rng.fill_bytes(out),
+        PayloadEntropy::Repeating => {
+            let mut pattern = [0u8; PATTERN_LEN];
+            rng.fill_bytes(&mut pattern);
+            for chunk in out.chunks_mut(PATTERN_LEN) {
+                chunk.copy_from_slice(&pattern[..chunk.len()]);
+            }
+        }
+    }
+}
+
+impl fmt::Display for PayloadEntropy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PayloadEntropy::Incompressible => "incompressible",
+            PayloadEntropy::Repeating => "repeating",
+        })
+    }
+}
+
+impl FromStr for PayloadEntropy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "incompressible" | "random" => Ok(PayloadEntropy::Incompressible),
+            "repeating" | "compressible" => Ok(PayloadEntropy::Repeating),
+            other => bail!(
+                "unknown payload entropy '{}', expected incompressible or repeating",
+                other
+            ),
+        }
+    }
+}
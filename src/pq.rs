@@ -0,0 +1,142 @@
+//! Synthetic product-quantization codebooks and codes
+//!
+//! A [`Codebook`] splits vectors into `m` equal sub-vectors and holds
+//! `2^nbits` centroids per sub-space. Codebooks are drawn from a seeded RNG
+//! instead of being trained, but codes are computed by real nearest-centroid
+//! assignment, so PQ-aware readers see codes consistent with the vectors.
+
+use anyhow::{bail, Result};
+use rand::distributions::{Distribution, Uniform};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+
+/// Product quantization parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PqConfig {
+    /// Number of sub-quantizers (m); must divide the vector dimension
+    pub subquantizers: usize,
+    /// Bits per sub-quantizer code (1..=16)
+    pub nbits: u32,
+}
+
+impl PqConfig {
+    /// Check the parameters against a vector dimension
+    pub fn validate(&self, dim: usize) -> Result<()> {
+        if self.subquantizers == 0 || !dim.is_multiple_of(self.subquantizers) {
+            bail!(
+                "PQ sub-quantizers ({}) must be non-zero and divide the vector dimension ({})",
+                self.subquantizers,
+                dim
+            );
+        }
+        if !(1..=16).contains(&self.nbits) {
+            bail!("PQ nbits must be between 1 and 16, got {}", self.nbits);
+        }
+        Ok(())
+    }
+
+    /// Number of bytes in one packed code
+    pub fn code_bytes(&self) -> usize {
+        (self.subquantizers * self.nbits as usize).div_ceil(8)
+    }
+
+    /// Number of centroids per sub-quantizer (`2^nbits`)
+    pub fn centroids_per_subquantizer(&self) -> usize {
+        1 << self.nbits
+    }
+}
+
+/// Seeded PQ codebook
+#[derive(Debug, Clone)]
+pub struct Codebook {
+    config: PqConfig,
+    sub_dim: usize,
+    /// Centroids laid out as `[subquantizer][centroid][sub_dim]`
+    centroids: Vec<f32>,
+}
+
+impl Codebook {
+    /// Draw a codebook for `dim`-dimensional vectors from `seed`
+    ///
+    /// Centroid components follow the same uniform [-1, 1) distribution as
+    /// generated vector values.
+    pub fn generate(dim: usize, config: PqConfig, seed: u64) -> Result<Self> {
+        config.validate(dim)?;
+        let sub_dim = dim / config.subquantizers;
+        let count = config.subquantizers * config.centroids_per_subquantizer() * sub_dim;
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let uniform = Uniform::new(-1.0f32, 1.0);
+        let centroids = (0..count).map(|_| uniform.sample(&mut rng)).collect();
+
+        Ok(Self {
+            config,
+            sub_dim,
+            centroids,
+        })
+    }
+
+    /// Parameters this codebook was built with
+    pub fn config(&self) -> PqConfig {
+        self.config
+    }
+
+    /// Dimension of each sub-vector
+    pub fn sub_dim(&self) -> usize {
+        self.sub_dim
+    }
+
+    /// Centroid `code` of sub-quantizer `subquantizer`
+    pub fn centroid(&self, subquantizer: usize, code: usize) -> &[f32] {
+        let k = self.config.centroids_per_subquantizer();
+        let start = (subquantizer * k + code) * self.sub_dim;
+        &self.centroids[start..start + self.sub_dim]
+    }
+
+    /// Encode a vector as packed codes, appending `code_bytes()` bytes to `out`
+    ///
+    /// Codes are packed least-significant bit first, sub-quantizer 0 first.
+    pub fn encode(&self, vector: &[f64], out: &mut Vec<u8>) {
+        let start = out.len();
+        out.resize(start + self.config.code_bytes(), 0);
+        let packed = &mut out[start..];
+
+        let k = self.config.centroids_per_subquantizer();
+        let nbits = self.config.nbits as usize;
+        for (sub, chunk) in vector.chunks(self.sub_dim).enumerate() {
+            let mut best = 0;
+            let mut best_dist = f64::INFINITY;
+            for code in 0..k {
+                let dist: f64 = self
+                    .centroid(sub, code)
+                    .iter()
+                    .zip(chunk)
+                    .map(|(&c, &v)| (v - c as f64) * (v - c as f64))
+                    .sum();
+                if dist < best_dist {
+                    best_dist = dist;
+                    best = code;
+                }
+            }
+
+            for bit in 0..nbits {
+                if best >> bit & 1 == 1 {
+                    let pos = sub * nbits + bit;
+                    packed[pos / 8] |= 1 << (pos % 8);
+                }
+            }
+        }
+    }
+}
+
+/// Unpack the code of sub-quantizer `subquantizer` from a packed code
+pub fn unpack_code(packed: &[u8], subquantizer: usize, nbits: u32) -> usize {
+    let nbits = nbits as usize;
+    (0..nbits)
+        .filter(|bit| {
+            let pos = subquantizer * nbits + bit;
+            packed[pos / 8] >> (pos % 8) & 1 == 1
+        })
+        .fold(0, |code, bit| code | 1 << bit)
+}
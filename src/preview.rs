@@ -0,0 +1,92 @@
+//! Terminal preview of generated rows
+//!
+//! Before a long run it helps to look at a few rows: which columns the
+//! configuration produces, their types, and whether the values look as
+//! intended. Vector cells are shown as their first few elements and the
+//! dimension, other cells as their display form cut to a fixed width, so a
+//! row of 1024-dimensional vectors still fits a terminal.
+
+use anyhow::Result;
+use arrow::array::{Array, ArrayRef, StringArray};
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use arrow::util::display::{ArrayFormatter, FormatOptions};
+use arrow::util::pretty::pretty_format_batches;
+use std::fmt::Write;
+use std::sync::Arc;
+
+use crate::columns::{vector_encoding, vector_values};
+
+/// Leading elements shown of each vector
+pub const PREVIEW_ELEMENTS: usize = 4;
+
+/// Characters shown of other cells
+pub const PREVIEW_WIDTH: usize = 32;
+
+/// Schema listing followed by a table of the rows of `batch`
+pub fn format_preview(batch: &RecordBatch) -> Result<String> {
+    let schema = batch.schema();
+    let mut out = String::new();
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        write!(out, "{}: {}", field.name(), field.data_type())?;
+        if let Some((element, _)) = vector_encoding(field) {
+            let dim = (0..column.len())
+                .find_map(|row| vector_values(field, column.as_ref(), row))
+                .map_or(0, |values| values.len());
+            write!(out, " ({} vector, dim {})", element.name(), dim)?;
+        }
+        if field.is_nullable() {
+            out.push_str(" nullable");
+        }
+        out.push('\n');
+    }
+
+    let mut fields = Vec::with_capacity(schema.fields().len());
+    let mut columns: Vec<ArrayRef> = Vec::with_capacity(batch.num_columns());
+    for (field, column) in schema.fields().iter().zip(batch.columns()) {
+        fields.push(Field::new(field.name(), DataType::Utf8, true));
+        columns.push(Arc::new(preview_column(field, column.as_ref())?));
+    }
+    let table = RecordBatch::try_new(Arc::new(Schema::new(fields)), columns)?;
+    writeln!(out, "{}", pretty_format_batches(&[table])?)?;
+    Ok(out)
+}
+
+/// Shortened display form of every cell of `array`
+fn preview_column(field: &Field, array: &dyn Array) -> Result<StringArray> {
+    let vector = vector_encoding(field).is_some();
+    let options = FormatOptions::default().with_null("null");
+    let formatter = ArrayFormatter::try_new(array, &options)?;
+    (0..array.len())
+        .map(|row| {
+            if array.is_null(row) {
+                return Ok(Some("null".to_string()));
+            }
+            if vector {
+                let values = vector_values(field, array, row).unwrap_or_default();
+                return Ok(Some(vector_preview(&values)));
+            }
+            Ok(Some(truncate(&formatter.value(row).to_string())))
+        })
+        .collect()
+}
+
+/// `[v0, v1, v2, v3, … +N]` with the elements rounded for display
+fn vector_preview(values: &[f64]) -> String {
+    let shown: Vec<String> = values
+        .iter()
+        .take(PREVIEW_ELEMENTS)
+        .map(|v| format!("{:.4}", v))
+        .collect();
+    match values.len().saturating_sub(PREVIEW_ELEMENTS) {
+        0 => format!("[{}]", shown.join(", ")),
+        more => format!("[{}, … +{}]", shown.join(", "), more),
+    }
+}
+
+fn truncate(text: &str) -> String {
+    match text.char_indices().nth(PREVIEW_WIDTH) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
@@ -0,0 +1,141 @@
+//! Row-group pruning fixtures with known per-group value ranges
+//!
+//! In pruning mode every row gets an `id` equal to its global row index and a
+//! `ts` of `start_ms + id * interval_ms`, and Parquet row groups hold exactly
+//! `row_group_rows` rows counted from the start of each file. Both columns
+//! increase strictly, so row groups cover disjoint ranges that
+//! [`PruningConfig::row_group_ranges`] computes without reading the files:
+//! a predicate such as `id < 1000` must skip every group whose `min_id` is at
+//! least 1000.
+//!
+//! [`PageIndexConfig`] additionally writes the column and offset indexes
+//! (page-level statistics) with pages of a fixed row count, so the same
+//! reasoning applies per page via [`PruningConfig::page_ranges`].
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// Layout of a pruning fixture
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PruningConfig {
+    /// Rows in each row group (the last group of a file may be shorter)
+    pub row_group_rows: usize,
+    /// Timestamp of row 0, in milliseconds since the epoch
+    pub start_ms: i64,
+    /// Milliseconds between consecutive rows
+    pub interval_ms: i64,
+}
+
+/// Value ranges of one row group of a pruning fixture file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RowGroupRange {
+    /// Index of the row group within its file
+    pub row_group: usize,
+    /// Rows in the group
+    pub rows: usize,
+    /// Smallest and largest `id`
+    pub min_id: i64,
+    pub max_id: i64,
+    /// Smallest and largest `ts`, in milliseconds since the epoch
+    pub min_ts_ms: i64,
+    pub max_ts_ms: i64,
+}
+
+/// Page-level statistics and page layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageIndexConfig {
+    /// Rows in each data page (the last page of a row group may be shorter)
+    pub page_rows: usize,
+}
+
+impl PageIndexConfig {
+    /// Check the parameters
+    pub fn validate(&self) -> Result<()> {
+        if self.page_rows == 0 {
+            bail!("rows per page must be greater than zero");
+        }
+        Ok(())
+    }
+}
+
+/// Value ranges of one data page of a pruning fixture file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PageRange {
+    /// Index of the row group within its file
+    pub row_group: usize,
+    /// Index of the page within its row group
+    pub page: usize,
+    /// Rows in the page
+    pub rows: usize,
+    /// Smallest and largest `id`
+    pub min_id: i64,
+    pub max_id: i64,
+    /// Smallest and largest `ts`, in milliseconds since the epoch
+    pub min_ts_ms: i64,
+    pub max_ts_ms: i64,
+}
+
+impl PruningConfig {
+    /// Check the parameters
+    pub fn validate(&self) -> Result<()> {
+        if self.row_group_rows == 0 {
+            bail!("pruning row group size must be greater than zero");
+        }
+        if self.interval_ms <= 0 {
+            bail!("pruning timestamp interval must be positive");
+        }
+        Ok(())
+    }
+
+    /// `ts` of the row at global index `row`
+    pub fn timestamp_ms(&self, row: u64) -> i64 {
+        self.start_ms + row as i64 * self.interval_ms
+    }
+
+    /// Ranges of the row groups of a file holding `rows` rows starting at
+    /// global row `first_row`
+    pub fn row_group_ranges(&self, first_row: u64, rows: u64) -> Vec<RowGroupRange> {
+        let group_rows = self.row_group_rows as u64;
+        (0..rows.div_ceil(group_rows))
+            .map(|group| {
+                let first = first_row + group * group_rows;
+                let last = (first + group_rows).min(first_row + rows) - 1;
+                RowGroupRange {
+                    row_group: group as usize,
+                    rows: (last - first + 1) as usize,
+                    min_id: first as i64,
+                    max_id: last as i64,
+                    min_ts_ms: self.timestamp_ms(first),
+                    max_ts_ms: self.timestamp_ms(last),
+                }
+            })
+            .collect()
+    }
+
+    /// Ranges of the data pages of a file holding `rows` rows starting at
+    /// global row `first_row`, written with `page_rows` rows per page
+    ///
+    /// Pages restart at every row group boundary.
+    pub fn page_ranges(&self, first_row: u64, rows: u64, page_rows: usize) -> Vec<PageRange> {
+        let page_rows = page_rows as u64;
+        let mut pages = Vec::new();
+        for group in self.row_group_ranges(first_row, rows) {
+            let group_first = group.min_id as u64;
+            let group_rows = group.rows as u64;
+            for page in 0..group_rows.div_ceil(page_rows) {
+                let first = group_first + page * page_rows;
+                let last = (first + page_rows).min(group_first + group_rows) - 1;
+                pages.push(PageRange {
+                    row_group: group.row_group,
+                    page: page as usize,
+                    rows: (last - first + 1) as usize,
+                    min_id: first as i64,
+                    max_id: last as i64,
+                    min_ts_ms: self.timestamp_ms(first),
+                    max_ts_ms: self.timestamp_ms(last),
+                });
+            }
+        }
+        pages
+    }
+}
@@ -0,0 +1,271 @@
+//! Query vectors written alongside a dataset
+//!
+//! Search benchmarks need query vectors that match the base data. Queries are
+//! either sampled fresh from the vector distribution of the dataset (its
+//! mixture, normalization and quantization included), or made by picking
+//! base rows uniformly at random and adding uniform noise in
+//! `[-epsilon, epsilon]` to every component, so each query has a known
+//! near neighbor. Perturbed rows are read back from the written files.
+//!
+//! Queries are written to `queries.parquet` with a `query_id`, the vector in
+//! the encoding of the base `vector` column, and the `source_id` of the
+//! perturbed base row (position in the dataset; null for sampled queries).
+//! A [workload](crate::workload) adds each query's `k`, filter and expected
+//! filter selectivity.
+
+#[cfg(feature = "parquet")]
+use anyhow::Context;
+use anyhow::{bail, Result};
+#[cfg(feature = "parquet")]
+use arrow::array::{ArrayRef, Int64Array};
+use arrow::datatypes::{DataType, Field, Schema};
+#[cfg(feature = "parquet")]
+use arrow::record_batch::RecordBatch;
+#[cfg(feature = "parquet")]
+use parquet::arrow::{arrow_reader::ParquetRecordBatchReaderBuilder, ProjectionMask};
+#[cfg(feature = "parquet")]
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "parquet")]
+use std::{fs::File, path::Path, sync::Arc};
+
+#[cfg(feature = "parquet")]
+use crate::columns::{
+    columns_for, vector_values, BatchContext, ColumnGenerator, PRIMARY_VECTOR_STREAM,
+};
+#[cfg(feature = "parquet")]
+use crate::core_gen::stream_seed;
+#[cfg(feature = "parquet")]
+use crate::dataset::FileReport;
+#[cfg(feature = "parquet")]
+use crate::sink::Sink;
+use crate::workload;
+#[cfg(feature = "parquet")]
+use crate::workload::WorkloadPlan;
+#[cfg(feature = "parquet")]
+use crate::{Config, ParquetWriter};
+
+/// Stream identifier for query vectors and perturbed row picks
+#[cfg(feature = "parquet")]
+const QUERY_STREAM: u64 = u64::MAX - 9;
+
+/// Stream identifier for the `k` and filters of a workload
+#[cfg(feature = "parquet")]
+const WORKLOAD_STREAM: u64 = u64::MAX - 11;
+
+/// How query vectors are made
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum QuerySource {
+    /// Fresh vectors from the distribution of the base vectors
+    #[default]
+    Sample,
+    /// Random base vectors plus uniform noise in `[-epsilon, epsilon]`
+    Perturb { epsilon: f64 },
+}
+
+/// Query set written with a dataset
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct QueryConfig {
+    /// Number of query vectors
+    pub count: usize,
+    pub source: QuerySource,
+}
+
+impl QueryConfig {
+    /// Check the parameters
+    pub fn validate(&self) -> Result<()> {
+        if self.count == 0 {
+            bail!("query set must hold at least one query");
+        }
+        if let QuerySource::Perturb { epsilon } = self.source {
+            if !epsilon.is_finite() || epsilon < 0.0 {
+                bail!("query epsilon must be a non-negative number");
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Schema of the query file for a base vector column `vector`, with the
+/// workload columns if `workload` is set
+pub fn schema(vector: &Field, workload: bool) -> Schema {
+    let mut fields = vec![
+        Field::new("query_id", DataType::Int64, false),
+        vector.clone(),
+        Field::new("source_id", DataType::Int64, true),
+    ];
+    if workload {
+        fields.extend(workload::fields());
+    }
+    Schema::new(fields)
+}
+
+/// Write the query set of `config` over the dataset `files` to `path`,
+/// returning the number of queries
+#[cfg(feature = "parquet")]
+pub fn write_queries(
+    writer: &ParquetWriter,
+    path: &Path,
+    config: &Config,
+    queries: &QueryConfig,
+    files: &[FileReport],
+    batch_size: usize,
+) -> Result<usize> {
+    let total: usize = files.iter().map(|file| file.rows).sum();
+    let mut sink = None;
+    generate_queries(
+        config,
+        queries,
+        total,
+        batch_size,
+        |column, ids| read_rows(files, column, ids, batch_size),
+        |batch| {
+            let sink = match &mut sink {
+                Some(sink) => sink,
+                None => sink.insert(writer.create_sink(path, &batch.schema())?),
+            };
+            sink.write(&batch)
+        },
+    )?;
+    if let Some(mut sink) = sink {
+        sink.finish()?;
+    }
+    Ok(queries.count)
+}
+
+/// Generate the query set of `config` over `total` base rows as batches of
+/// the query file [`schema`], passing each to `write`
+///
+/// `base_rows` returns the values of the base vectors at the given dataset
+/// positions, laid out as `[query][dim]`, for perturbed queries.
+#[cfg(feature = "parquet")]
+pub(crate) fn generate_queries(
+    config: &Config,
+    queries: &QueryConfig,
+    total: usize,
+    batch_size: usize,
+    base_rows: impl FnOnce(&ColumnGenerator, &[u64]) -> Result<Vec<f64>>,
+    mut write: impl FnMut(RecordBatch) -> Result<()>,
+) -> Result<()> {
+    let primary = columns_for(config)?
+        .into_iter()
+        .find(|column| column.stream == PRIMARY_VECTOR_STREAM);
+    let Some(mut column) = primary else {
+        bail!("query sets need a vector column");
+    };
+    // Null and constant fills and null ratios describe the base files, not
+    // their queries
+    column.fill = None;
+    column.null_ratio = None;
+    let plan = match &config.workload {
+        Some(workload) => Some(WorkloadPlan::new(config, workload)?),
+        None => None,
+    };
+    let schema = Arc::new(schema(&column.field, plan.is_some()));
+    let mut rng = StdRng::seed_from_u64(stream_seed(config.seed, &[QUERY_STREAM]));
+    let mut workload_rng = StdRng::seed_from_u64(stream_seed(config.seed, &[WORKLOAD_STREAM]));
+
+    let sources = match queries.source {
+        QuerySource::Sample => None,
+        QuerySource::Perturb { epsilon } => {
+            if total == 0 {
+                bail!("perturbed queries need at least one base row");
+            }
+            let ids: Vec<u64> = (0..queries.count)
+                .map(|_| rng.gen_range(0..total as u64))
+                .collect();
+            let mut vectors = base_rows(&column, &ids)?;
+            for value in &mut vectors {
+                *value += rng.gen_range(-epsilon..=epsilon);
+            }
+            Some((ids, vectors))
+        }
+    };
+
+    let dim = config.vector_dim;
+    let batch_size = batch_size.max(1).min(column.max_rows());
+    let mut start = 0;
+    while start < queries.count {
+        let rows = batch_size.min(queries.count - start);
+        let mut ctx = BatchContext::default();
+        let source_ids: ArrayRef = match &sources {
+            Some((ids, vectors)) => {
+                ctx.vectors = Some(vectors[start * dim..(start + rows) * dim].to_vec());
+                Arc::new(Int64Array::from_iter_values(
+                    ids[start..start + rows].iter().map(|&id| id as i64),
+                ))
+            }
+            None => Arc::new(Int64Array::new_null(rows)),
+        };
+        let mut columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from_iter_values(
+                start as i64..(start + rows) as i64,
+            )),
+            column.generate(&mut rng, rows, &ctx)?,
+            source_ids,
+        ];
+        if let Some(plan) = &plan {
+            columns.extend(plan.generate(&mut workload_rng, rows));
+        }
+        write(RecordBatch::try_new(schema.clone(), columns)?)?;
+        start += rows;
+    }
+    Ok(())
+}
+
+/// Values of the base vectors at dataset positions `ids`, laid out as
+/// `[query][dim]` in the order of `ids`
+#[cfg(feature = "parquet")]
+fn read_rows(
+    files: &[FileReport],
+    column: &ColumnGenerator,
+    ids: &[u64],
+    batch_size: usize,
+) -> Result<Vec<f64>> {
+    let mut files: Vec<&FileReport> = files.iter().collect();
+    files.sort_by_key(|file| file.index);
+    let mut picks: Vec<(u64, usize)> = ids.iter().enumerate().map(|(i, &id)| (id, i)).collect();
+    picks.sort_unstable();
+
+    let name = column.field.name();
+    let mut found: Vec<Vec<f64>> = vec![Vec::new(); ids.len()];
+    let mut next = 0;
+    let mut offset = 0u64;
+    for file in files {
+        let end = offset + file.rows as u64;
+        if next == picks.len() || picks[next].0 >= end {
+            offset = end;
+            continue;
+        }
+        let builder = ParquetRecordBatchReaderBuilder::try_new(
+            File::open(&file.path)
+                .with_context(|| format!("Failed to open file: {:?}", file.path))?,
+        )?;
+        let Some((position, field)) = builder.schema().column_with_name(name) else {
+            bail!("vector column '{}' is missing from {:?}", name, file.path);
+        };
+        let field = field.clone();
+        let mask = ProjectionMask::roots(builder.parquet_schema(), [position]);
+        let reader = builder
+            .with_projection(mask)
+            .with_batch_size(batch_size.max(1))
+            .build()?;
+        for batch in reader {
+            let batch = batch?;
+            let batch_end = offset + batch.num_rows() as u64;
+            while next < picks.len() && picks[next].0 < batch_end {
+                let (id, query) = picks[next];
+                let row = (id - offset) as usize;
+                found[query] = vector_values(&field, batch.column(0).as_ref(), row)
+                    .with_context(|| format!("base row {} has no vector to perturb", id))?;
+                next += 1;
+            }
+            offset = batch_end;
+        }
+        offset = end;
+    }
+    if next < picks.len() {
+        bail!("base row {} is beyond the written rows", picks[next].0);
+    }
+    Ok(found.concat())
+}
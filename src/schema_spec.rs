@@ -0,0 +1,440 @@
+//! User-defined schemas
+//!
+//! By default a generator writes the `vector` and `scalar` columns (plus
+//! whatever optional columns the config adds). A schema spec replaces those
+//! two with a list of named, typed columns, written as
+//! `name:type,name:type,...`, e.g.
+//! `id:int64,emb:vector<f32,768>,title:string(64),score:float32`.
+//!
+//! Supported types:
+//!
+//! - `int32`, `int64`: uniformly random integers over the whole type range
+//! - `float32`, `float64`: uniformly random values in [0, 1)
+//! - any of the numeric types with a [distribution](crate::numeric) in
+//!   parentheses: `float64(0..100)`, `int32(mean=500,std=50)`
+//! - `string(N)`: random alphanumeric strings of `N` bytes; plain `string`
+//!   uses `scalar_len`
+//! - `vector<f32,D>`, `vector<f64,D>`: `D`-dimensional vectors, encoded like
+//!   the default vector column
+//! - `sparse<D,DENSITY>`: [sparse vectors](crate::sparse) of dimension `D`
+//!   with about `DENSITY * D` non-zeros
+//!
+//! The first vector column of the spec takes the place of the default
+//! `vector` column: it draws from the primary vector stream, so query sets,
+//! PQ codes, duplicates and trajectories refer to it, and its dimension must
+//! be the config's `vector_dim`.
+
+use anyhow::{anyhow, bail, Context, Result};
+use arrow::datatypes::DataType;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::numeric::{NumericBounds, NumericDistribution};
+use crate::skew::Skew;
+use crate::sparse::SparseConfig;
+use crate::{Config, VectorElementType};
+
+/// Type of a column in a schema spec
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ColumnType {
+    Int32,
+    Int64,
+    Float32,
+    Float64,
+    /// Random string of `len` bytes, `scalar_len` if `None`
+    String {
+        len: Option<usize>,
+    },
+    /// One of `cardinality` distinct words with `skew` frequencies, see
+    /// [`crate::category`]
+    Category {
+        cardinality: usize,
+        #[serde(default)]
+        skew: Skew,
+    },
+    Vector {
+        element: VectorElementType,
+        dim: usize,
+    },
+    /// Indices and values of the non-zeros of a sparse vector
+    Sparse(SparseConfig),
+}
+
+impl ColumnType {
+    /// Arrow type of numeric columns
+    pub fn numeric_data_type(&self) -> Option<DataType> {
+        match self {
+            ColumnType::Int32 => Some(DataType::Int32),
+            ColumnType::Int64 => Some(DataType::Int64),
+            ColumnType::Float32 => Some(DataType::Float32),
+            ColumnType::Float64 => Some(DataType::Float64),
+            ColumnType::String { .. }
+            | ColumnType::Category { .. }
+            | ColumnType::Vector { .. }
+            | ColumnType::Sparse(_) => None,
+        }
+    }
+}
+
+/// A named column of a schema spec
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnSpec {
+    pub name: String,
+    pub column_type: ColumnType,
+    /// Values of a numeric column; the type's default if `None`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub distribution: Option<NumericDistribution>,
+    /// Hard limits on the values of a numeric column
+    #[serde(default, skip_serializing_if = "NumericBounds::is_none")]
+    pub bounds: NumericBounds,
+}
+
+/// Columns generated in place of the default `vector` and `scalar` columns
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemaSpec {
+    pub columns: Vec<ColumnSpec>,
+}
+
+impl SchemaSpec {
+    /// The first vector column, which draws from the primary vector stream
+    pub fn primary_vector(&self) -> Option<(usize, &ColumnSpec)> {
+        self.columns
+            .iter()
+            .enumerate()
+            .find(|(_, column)| matches!(column.column_type, ColumnType::Vector { .. }))
+    }
+
+    /// Check that `config` can generate this schema
+    pub fn validate(&self, config: &Config) -> Result<()> {
+        if self.columns.is_empty() {
+            bail!("schema spec needs at least one column");
+        }
+        let mut names = HashSet::new();
+        for column in &self.columns {
+            if !is_identifier(&column.name) {
+                bail!("invalid column name '{}'", column.name);
+            }
+            if !names.insert(column.name.as_str()) {
+                bail!("duplicate column '{}' in schema spec", column.name);
+            }
+            if let ColumnType::Category { cardinality, skew } = column.column_type {
+                if cardinality == 0 {
+                    bail!("category column '{}' needs at least one value", column.name);
+                }
+                if let Skew::Zipf(s) = skew {
+                    if !(s.is_finite() && s >= 0.0) {
+                        bail!(
+                            "zipf exponent of category column '{}' must be non-negative",
+                            column.name
+                        );
+                    }
+                }
+            }
+            if let ColumnType::Vector { dim: 0, .. } = column.column_type {
+                bail!(
+                    "vector column '{}' needs a dimension above zero",
+                    column.name
+                );
+            }
+            if let ColumnType::Vector {
+                element: VectorElementType::Binary,
+                dim,
+            } = column.column_type
+            {
+                if dim % 8 != 0 {
+                    bail!(
+                        "binary vector column '{}' needs a dimension that is a multiple of 8",
+                        column.name
+                    );
+                }
+            }
+            if let ColumnType::Sparse(sparse) = column.column_type {
+                sparse
+                    .validate()
+                    .with_context(|| format!("column '{}'", column.name))?;
+            }
+            if let Some(distribution) = &column.distribution {
+                let Some(data_type) = column.column_type.numeric_data_type() else {
+                    bail!(
+                        "only numeric columns take a distribution, not '{}'",
+                        column.name
+                    );
+                };
+                distribution
+                    .validate(&data_type)
+                    .with_context(|| format!("column '{}'", column.name))?;
+            }
+            if !column.bounds.is_none() {
+                let Some(data_type) = column.column_type.numeric_data_type() else {
+                    bail!("only numeric columns take bounds, not '{}'", column.name);
+                };
+                column
+                    .bounds
+                    .validate(&data_type)
+                    .with_context(|| format!("column '{}'", column.name))?;
+            }
+        }
+        if config.vector_columns != 1 || config.mixed_precision.is_some() {
+            bail!("extra and mixed-precision vector columns cannot be combined with a schema spec");
+        }
+        match self.primary_vector() {
+            Some((_, column)) => {
+                let ColumnType::Vector { dim, element } = column.column_type else {
+                    unreachable!("primary vector column is a vector");
+                };
+                if config.int8_scale && element != VectorElementType::Int8 {
+                    bail!(
+                        "int8_scale needs int8 vectors, but column '{}' is {}",
+                        column.name,
+                        element.name()
+                    );
+                }
+                if dim != config.vector_dim {
+                    bail!(
+                        "first vector column '{}' has dimension {}, but vector_dim is {}",
+                        column.name,
+                        dim,
+                        config.vector_dim
+                    );
+                }
+            }
+            None => {
+                if config.pq.is_some()
+                    || config.int8_scale
+                    || config.trajectory.is_some()
+                    || config.duplicates.is_some()
+                    || config.queries.is_some()
+                {
+                    bail!(
+                        "PQ codes, int8 scales, trajectories, duplicates and query sets \
+                         need a vector column"
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ColumnType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnType::Int32 => f.write_str("int32"),
+            ColumnType::Int64 => f.write_str("int64"),
+            ColumnType::Float32 => f.write_str("float32"),
+            ColumnType::Float64 => f.write_str("float64"),
+            ColumnType::String { len: None } => f.write_str("string"),
+            ColumnType::String { len: Some(len) } => write!(f, "string({})", len),
+            ColumnType::Category {
+                cardinality,
+                skew: Skew::Uniform,
+            } => write!(f, "category({})", cardinality),
+            ColumnType::Category {
+                cardinality,
+                skew: Skew::Zipf(s),
+            } => write!(f, "category({},s={})", cardinality, s),
+            ColumnType::Vector { element, dim } => write!(f, "vector<{},{}>", element.name(), dim),
+            ColumnType::Sparse(sparse) => write!(f, "sparse<{},{}>", sparse.dim, sparse.density),
+        }
+    }
+}
+
+impl fmt::Display for SchemaSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, column) in self.columns.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{}:{}", column.name, column.column_type)?;
+            let mut args: Vec<String> = column.distribution.iter().map(|d| d.to_string()).collect();
+            let bounds = &column.bounds;
+            args.extend(bounds.min.map(|min| format!("min={}", min)));
+            args.extend(bounds.max.map(|max| format!("max={}", max)));
+            args.extend(bounds.precision.map(|p| format!("precision={}", p)));
+            if !args.is_empty() {
+                write!(f, "({})", args.join(","))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ColumnType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let number = |digits: &str| -> Result<usize> {
+            digits
+                .trim()
+                .parse()
+                .with_context(|| format!("invalid size '{}' in column type '{}'", digits.trim(), s))
+        };
+        Ok(match s {
+            "int32" => ColumnType::Int32,
+            "int64" => ColumnType::Int64,
+            "float32" => ColumnType::Float32,
+            "float64" => ColumnType::Float64,
+            "string" => ColumnType::String { len: None },
+            _ => {
+                if let Some(len) = s.strip_prefix("string(").and_then(|r| r.strip_suffix(')')) {
+                    ColumnType::String {
+                        len: Some(number(len)?),
+                    }
+                } else if let Some(args) = s
+                    .strip_prefix("category(")
+                    .and_then(|r| r.strip_suffix(')'))
+                {
+                    // `N` or `N,s=S` for Zipf frequencies with exponent `S`
+                    let (cardinality, skew) = match args.split_once(',') {
+                        Some((cardinality, exponent)) => {
+                            let Some(exponent) = exponent.trim().strip_prefix("s=") else {
+                                bail!("expected category(N,s=EXPONENT), got '{}'", s);
+                            };
+                            let exponent = exponent.trim().parse().with_context(|| {
+                                format!("invalid zipf exponent '{}'", exponent.trim())
+                            })?;
+                            (cardinality, Skew::Zipf(exponent))
+                        }
+                        None => (args, Skew::Uniform),
+                    };
+                    ColumnType::Category {
+                        cardinality: number(cardinality)?,
+                        skew,
+                    }
+                } else if let Some(args) =
+                    s.strip_prefix("vector<").and_then(|r| r.strip_suffix('>'))
+                {
+                    let (element, dim) = args
+                        .split_once(',')
+                        .ok_or_else(|| anyhow!("expected vector<ELEMENT,DIM>, got '{}'", s))?;
+                    let element =
+                        VectorElementType::from_name(element.trim()).ok_or_else(|| {
+                            anyhow!("unknown vector element type '{}'", element.trim())
+                        })?;
+                    ColumnType::Vector {
+                        element,
+                        dim: number(dim)?,
+                    }
+                } else if let Some(args) =
+                    s.strip_prefix("sparse<").and_then(|r| r.strip_suffix('>'))
+                {
+                    let (dim, density) = args
+                        .split_once(',')
+                        .ok_or_else(|| anyhow!("expected sparse<DIM,DENSITY>, got '{}'", s))?;
+                    let density = density
+                        .trim()
+                        .parse()
+                        .with_context(|| format!("invalid density '{}'", density.trim()))?;
+                    ColumnType::Sparse(SparseConfig {
+                        dim: number(dim)?,
+                        density,
+                    })
+                } else {
+                    bail!(
+                        "unknown column type '{}', expected int32, int64, float32, float64, \
+                         string(N), category(N[,s=S]), vector<f16|f32|f64|i8|binary,DIM> \
+                         or sparse<DIM,DENSITY>",
+                        s
+                    )
+                }
+            }
+        })
+    }
+}
+
+impl FromStr for ColumnSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let (name, column_type) = s
+            .split_once(':')
+            .ok_or_else(|| anyhow!("invalid column '{}', expected NAME:TYPE", s.trim()))?;
+        let column_type = column_type.trim();
+        // Numeric types take their distribution and bounds in parentheses
+        let numeric = ["int32(", "int64(", "float32(", "float64("]
+            .into_iter()
+            .find(|prefix| column_type.starts_with(prefix));
+        let mut distribution = None;
+        let mut bounds = NumericBounds::default();
+        let column_type = match numeric {
+            Some(prefix) => {
+                let Some(args) = column_type[prefix.len()..].strip_suffix(')') else {
+                    bail!("unclosed distribution in column type '{}'", column_type);
+                };
+                let bound = |value: &str| -> Result<f64> {
+                    value
+                        .trim()
+                        .parse()
+                        .with_context(|| format!("invalid bound '{}'", value.trim()))
+                };
+                let mut rest = Vec::new();
+                for part in args.split(',').map(str::trim) {
+                    if let Some(min) = part.strip_prefix("min=") {
+                        bounds.min = Some(bound(min)?);
+                    } else if let Some(max) = part.strip_prefix("max=") {
+                        bounds.max = Some(bound(max)?);
+                    } else if let Some(precision) = part.strip_prefix("precision=") {
+                        bounds.precision = Some(precision.trim().parse().with_context(|| {
+                            format!("invalid precision '{}'", precision.trim())
+                        })?);
+                    } else {
+                        rest.push(part);
+                    }
+                }
+                if !rest.is_empty() {
+                    distribution = Some(rest.join(",").parse()?);
+                }
+                &prefix[..prefix.len() - 1]
+            }
+            None => column_type,
+        };
+        Ok(Self {
+            name: name.trim().to_string(),
+            column_type: column_type.parse()?,
+            distribution,
+            bounds,
+        })
+    }
+}
+
+impl FromStr for SchemaSpec {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let columns = split_columns(s)
+            .into_iter()
+            .map(str::parse)
+            .collect::<Result<Vec<ColumnSpec>>>()?;
+        if columns.is_empty() {
+            bail!("schema spec needs at least one column");
+        }
+        Ok(Self { columns })
+    }
+}
+
+/// Split `s` at the commas outside of `<>` and `()`
+fn split_columns(s: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let (mut depth, mut start) = (0usize, 0);
+    for (i, c) in s.char_indices() {
+        match c {
+            '<' | '(' => depth += 1,
+            '>' | ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&s[start..]);
+    parts.retain(|part| !part.trim().is_empty());
+    parts
+}
+
+fn is_identifier(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
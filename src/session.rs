@@ -0,0 +1,41 @@
+//! Session and user columns for event-log shaped data
+//!
+//! Rows are read as a log of events: every `events_per_session` consecutive
+//! rows form a session and every `sessions_per_user` consecutive sessions
+//! belong to one user. Ids are pure functions of the global row index, so they
+//! continue across batches and files without carried state.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// Session/user layout of the generated rows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionConfig {
+    /// Sessions belonging to each user
+    pub sessions_per_user: u64,
+    /// Events (rows) in each session
+    pub events_per_session: u64,
+}
+
+impl SessionConfig {
+    /// Check the parameters
+    pub fn validate(&self) -> Result<()> {
+        if self.sessions_per_user == 0 {
+            bail!("sessions per user must be greater than zero");
+        }
+        if self.events_per_session == 0 {
+            bail!("events per session must be greater than zero");
+        }
+        Ok(())
+    }
+
+    /// Session id of the row at global index `row`
+    pub fn session_id(&self, row: u64) -> u64 {
+        row / self.events_per_session
+    }
+
+    /// User id of the row at global index `row`
+    pub fn user_id(&self, row: u64) -> u64 {
+        self.session_id(row) / self.sessions_per_user
+    }
+}
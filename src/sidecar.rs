@@ -0,0 +1,128 @@
+//! Secondary index sidecar mapping key values to row locations
+//!
+//! Once the rows of a dataset are written, the key column of every file is
+//! read back together with the row group layout of its footer, and one row
+//! per data row records where that key lives: the file, the row group and the
+//! offset within the row group. External-index lookup paths can then be
+//! tested against the dataset without building the index separately.
+
+use anyhow::{bail, Context, Result};
+use arrow::array::{ArrayRef, Int32Array, Int64Array, StringArray};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ProjectionMask;
+use std::fs::File;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::dataset::FileReport;
+use crate::sink::Sink;
+use crate::{ParquetFileSink, ParquetWriter};
+
+/// Schema of the sidecar for a key column `key`
+///
+/// The `key` column keeps the type and name of the indexed column.
+pub fn index_schema(key: &Field) -> Schema {
+    Schema::new(vec![
+        Field::new(key.name(), key.data_type().clone(), true),
+        Field::new("file", DataType::Utf8, false),
+        Field::new("row_group", DataType::Int32, false),
+        Field::new("row_offset", DataType::Int64, false),
+    ])
+}
+
+/// Write the index of `column` over `files` to `path`, returning its row count
+///
+/// Files are indexed in file order and rows in file order; file paths are
+/// relative to `output_dir`. Nothing is written if `files` is empty.
+pub fn write_index(
+    writer: &ParquetWriter,
+    path: &Path,
+    column: &str,
+    files: &[FileReport],
+    output_dir: &Path,
+    batch_size: usize,
+) -> Result<usize> {
+    let mut files: Vec<&FileReport> = files.iter().collect();
+    files.sort_by_key(|file| file.index);
+
+    let mut output: Option<(SchemaRef, ParquetFileSink)> = None;
+    let mut count = 0;
+    for file in files {
+        let builder = ParquetRecordBatchReaderBuilder::try_new(
+            File::open(&file.path)
+                .with_context(|| format!("Failed to open file: {:?}", file.path))?,
+        )?;
+        let Some((position, key)) = builder.schema().column_with_name(column) else {
+            bail!(
+                "indexed column '{}' is missing from {:?}",
+                column,
+                file.path
+            );
+        };
+        if output.is_none() {
+            let schema = Arc::new(index_schema(key));
+            let sink = writer.create_sink(path, &schema)?;
+            output = Some((schema, sink));
+        }
+        let (schema, sink) = output.as_mut().expect("sidecar sink created");
+        if schema.field(0).data_type() != key.data_type() {
+            bail!(
+                "indexed column '{}' changes type in {:?}",
+                column,
+                file.path
+            );
+        }
+
+        let group_rows: Vec<i64> = builder
+            .metadata()
+            .row_groups()
+            .iter()
+            .map(|group| group.num_rows())
+            .collect();
+        let mask = ProjectionMask::roots(builder.parquet_schema(), [position]);
+        let reader = builder
+            .with_projection(mask)
+            .with_batch_size(batch_size.max(1))
+            .build()?;
+
+        let name = file
+            .path
+            .strip_prefix(output_dir)
+            .unwrap_or(file.path.as_path())
+            .to_string_lossy()
+            .into_owned();
+        let (mut group, mut offset) = (0, 0);
+        for batch in reader {
+            let batch = batch?;
+            let rows = batch.num_rows();
+            let mut groups = Vec::with_capacity(rows);
+            let mut offsets = Vec::with_capacity(rows);
+            for _ in 0..rows {
+                while offset == group_rows[group] {
+                    group += 1;
+                    offset = 0;
+                }
+                groups.push(group as i32);
+                offsets.push(offset);
+                offset += 1;
+            }
+            let columns: Vec<ArrayRef> = vec![
+                batch.column(0).clone(),
+                Arc::new(StringArray::from_iter_values(
+                    std::iter::repeat_n(name.as_str(), rows),
+                )),
+                Arc::new(Int32Array::from(groups)),
+                Arc::new(Int64Array::from(offsets)),
+            ];
+            sink.write(&RecordBatch::try_new(schema.clone(), columns)?)?;
+            count += rows;
+        }
+    }
+
+    if let Some((_, mut sink)) = output {
+        sink.finish()?;
+    }
+    Ok(count)
+}
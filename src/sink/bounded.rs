@@ -0,0 +1,123 @@
+//! Background-thread sink wrapper with a bounded queue
+
+use anyhow::{anyhow, Result};
+use arrow::record_batch::RecordBatch;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{sync_channel, SyncSender};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+use super::{Readiness, Sink};
+
+enum Message {
+    Batch(RecordBatch),
+    Abort,
+}
+
+/// Runs an inner sink on a background thread behind a bounded queue
+///
+/// Generation and writing overlap, but at most `capacity` batches are queued:
+/// once the queue is full the sink reports [`Readiness::Busy`] until the
+/// inner sink catches up.
+pub struct BoundedSink {
+    sender: Option<SyncSender<Message>>,
+    handle: Option<JoinHandle<Result<()>>>,
+    in_flight: Arc<AtomicUsize>,
+    capacity: usize,
+}
+
+impl BoundedSink {
+    /// Move `inner` onto a writer thread with room for `capacity` queued batches
+    pub fn new<S: Sink + Send + 'static>(mut inner: S, capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        let (sender, receiver) = sync_channel::<Message>(capacity);
+        let in_flight = Arc::new(AtomicUsize::new(0));
+
+        let worker_in_flight = Arc::clone(&in_flight);
+        let handle = std::thread::spawn(move || -> Result<()> {
+            for message in receiver {
+                match message {
+                    Message::Batch(batch) => {
+                        let result = inner.write(&batch);
+                        worker_in_flight.fetch_sub(1, Ordering::SeqCst);
+                        result?;
+                    }
+                    Message::Abort => return inner.abort(),
+                }
+            }
+            inner.finish()
+        });
+
+        Self {
+            sender: Some(sender),
+            handle: Some(handle),
+            in_flight,
+            capacity,
+        }
+    }
+
+    /// Number of batches queued or being written
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+
+    fn join(&mut self) -> Result<()> {
+        self.sender.take();
+        match self.handle.take() {
+            Some(handle) => handle
+                .join()
+                .map_err(|_| anyhow!("sink writer thread panicked"))?,
+            None => Ok(()),
+        }
+    }
+
+    fn send(&mut self, message: Message) -> Result<()> {
+        let sender = self
+            .sender
+            .as_ref()
+            .ok_or_else(|| anyhow!("bounded sink already closed"))?;
+        if sender.send(message).is_err() {
+            // The writer thread exited early; surface its error
+            self.join()?;
+            return Err(anyhow!("sink writer thread stopped unexpectedly"));
+        }
+        Ok(())
+    }
+}
+
+impl Sink for BoundedSink {
+    fn readiness(&mut self) -> Result<Readiness> {
+        if self.handle.as_ref().is_some_and(|h| h.is_finished()) {
+            // Writer thread died; report its error instead of waiting forever
+            self.join()?;
+            return Err(anyhow!("sink writer thread stopped unexpectedly"));
+        }
+        if self.in_flight() >= self.capacity {
+            Ok(Readiness::Busy)
+        } else {
+            Ok(Readiness::Ready)
+        }
+    }
+
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        self.send(Message::Batch(batch.clone()))
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.join()
+    }
+
+    fn abort(&mut self) -> Result<()> {
+        if self.sender.is_some() {
+            self.send(Message::Abort)?;
+        }
+        self.join()
+    }
+}
+
+impl Drop for BoundedSink {
+    fn drop(&mut self) {
+        let _ = self.join();
+    }
+}
@@ -0,0 +1,159 @@
+//! Arrow Flight DoPut sink
+
+use anyhow::{anyhow, Context, Result};
+use arrow::record_batch::RecordBatch;
+use arrow_flight::client::FlightClient;
+use arrow_flight::encode::FlightDataEncoderBuilder;
+use arrow_flight::error::FlightError;
+use arrow_flight::FlightDescriptor;
+use futures::{stream, StreamExt};
+use tokio::runtime::Runtime;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tonic::metadata::{AsciiMetadataKey, AsciiMetadataValue, MetadataMap};
+use tonic::transport::Endpoint;
+
+use super::{Readiness, Sink};
+
+/// Where and how to stream batches over Flight
+#[derive(Debug, Clone)]
+pub struct FlightSinkOptions {
+    /// Server URL, e.g. `http://localhost:8815`
+    pub endpoint: String,
+    /// Path of the flight descriptor sent with every stream
+    pub path: Vec<String>,
+    /// Headers added to every request, e.g. `authorization: Bearer <token>`
+    pub headers: Vec<(String, String)>,
+    /// Number of concurrent DoPut streams; batches are distributed round-robin
+    pub streams: usize,
+    /// Batches queued per stream before the sink reports busy
+    pub queue: usize,
+}
+
+/// Sink streaming batches to an Arrow Flight server via DoPut
+///
+/// Each of the configured streams is a separate DoPut call on a shared
+/// HTTP/2 connection. The sink is busy while the next stream's queue is full,
+/// so a slow server throttles generation.
+pub struct FlightSink {
+    runtime: Runtime,
+    senders: Vec<mpsc::Sender<RecordBatch>>,
+    tasks: Vec<JoinHandle<Result<usize>>>,
+    next: usize,
+}
+
+impl FlightSink {
+    /// Connect to the server and open the DoPut streams
+    pub fn connect(options: &FlightSinkOptions) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()?;
+        let channel = runtime
+            .block_on(Endpoint::from_shared(options.endpoint.clone())?.connect())
+            .with_context(|| format!("Failed to connect to Flight server: {}", options.endpoint))?;
+
+        let metadata = request_metadata(&options.headers)?;
+        let mut senders = Vec::new();
+        let mut tasks = Vec::new();
+        for _ in 0..options.streams.max(1) {
+            let mut client = FlightClient::new(channel.clone());
+            *client.metadata_mut() = metadata.clone();
+            let (sender, receiver) = mpsc::channel::<RecordBatch>(options.queue.max(1));
+            let batches = stream::unfold(receiver, |mut receiver| async move {
+                let batch = receiver.recv().await?;
+                Some((Ok::<_, FlightError>(batch), receiver))
+            });
+            let descriptor = FlightDescriptor::new_path(options.path.clone());
+            let flight_data = FlightDataEncoderBuilder::new()
+                .with_flight_descriptor(Some(descriptor))
+                .build(batches);
+
+            tasks.push(runtime.spawn(async move {
+                let mut results = client.do_put(flight_data).await?;
+                let mut acknowledged = 0;
+                while let Some(result) = results.next().await {
+                    result?;
+                    acknowledged += 1;
+                }
+                Ok(acknowledged)
+            }));
+            senders.push(sender);
+        }
+
+        Ok(Self {
+            runtime,
+            senders,
+            tasks,
+            next: 0,
+        })
+    }
+
+    /// Close every stream and wait for the server to acknowledge them
+    fn join(&mut self) -> Result<()> {
+        self.senders.clear();
+        for task in self.tasks.drain(..) {
+            self.runtime
+                .block_on(task)
+                .map_err(|e| anyhow!("Flight stream task failed: {}", e))??;
+        }
+        Ok(())
+    }
+}
+
+impl Sink for FlightSink {
+    fn readiness(&mut self) -> Result<Readiness> {
+        match self.senders.get(self.next) {
+            Some(sender) if sender.capacity() == 0 => Ok(Readiness::Busy),
+            _ => Ok(Readiness::Ready),
+        }
+    }
+
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        let sender = self
+            .senders
+            .get(self.next)
+            .ok_or_else(|| anyhow!("Flight sink already closed"))?;
+        if sender.blocking_send(batch.clone()).is_err() {
+            // The stream ended early; surface the server's error
+            self.join()?;
+            return Err(anyhow!("Flight stream closed before all batches were sent"));
+        }
+        self.next = next_stream(self.next, self.senders.len());
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.join()
+    }
+
+    fn abort(&mut self) -> Result<()> {
+        self.senders.clear();
+        for task in self.tasks.drain(..) {
+            task.abort();
+        }
+        Ok(())
+    }
+}
+
+/// gRPC metadata sent with every request, one entry per header
+///
+/// Names are lowercased as HTTP/2 requires; a repeated name sends every
+/// value, in order.
+pub(crate) fn request_metadata(headers: &[(String, String)]) -> Result<MetadataMap> {
+    let mut metadata = MetadataMap::new();
+    for (key, value) in headers {
+        let name: AsciiMetadataKey = key
+            .parse()
+            .with_context(|| format!("Invalid Flight header name: {}", key))?;
+        let value: AsciiMetadataValue = value
+            .parse()
+            .with_context(|| format!("Invalid value for Flight header {}", key))?;
+        metadata.append(name, value);
+    }
+    Ok(metadata)
+}
+
+/// Stream receiving the batch after one sent on stream `current` of `streams`
+pub(crate) fn next_stream(current: usize, streams: usize) -> usize {
+    (current + 1) % streams
+}
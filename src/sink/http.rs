@@ -0,0 +1,304 @@
+//! HTTP ingestion sink: POSTs every row to a user-configured endpoint
+//!
+//! Turns the generator into a simple ingestion load driver. Each row becomes
+//! one request whose JSON body is rendered from a template; requests are sent
+//! by a pool of worker threads, optionally limited to a fixed request rate.
+//!
+//! Template placeholders are `{{id}}` (the generated `id` column if there is
+//! one, else the global row number), `{{row}}` (the whole row as a JSON
+//! object) and `{{<column>}}` (that column's JSON value, vectors as number
+//! arrays). Without a template the body is `{{row}}`, with an added `id`
+//! field when the row has no `id` column.
+
+use anyhow::{anyhow, bail, Result};
+use arrow::record_batch::RecordBatch;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use super::value::{json_row, JsonValue};
+use super::{Readiness, Sink};
+use crate::id_column::ID_COLUMN_NAME;
+
+/// HTTP sink connection and pacing options
+#[derive(Debug, Clone)]
+pub struct HttpSinkOptions {
+    /// URL requests are POSTed to
+    pub endpoint: String,
+    /// Request body template, `None` to send the row object
+    pub template: Option<String>,
+    /// Extra request headers
+    pub headers: Vec<(String, String)>,
+    /// Number of requests in flight at once
+    pub concurrency: usize,
+    /// Maximum requests per second across all workers, `None` for unlimited
+    pub rate: Option<f64>,
+}
+
+/// Sink POSTing one templated JSON request per row
+pub struct HttpSink {
+    template: Vec<Segment>,
+    sender: Option<SyncSender<String>>,
+    workers: Vec<JoinHandle<Result<()>>>,
+    error: Arc<Mutex<Option<anyhow::Error>>>,
+    sent: Arc<AtomicU64>,
+    next_row: u64,
+}
+
+impl HttpSink {
+    /// Validate the options and start the worker pool
+    pub fn connect(options: HttpSinkOptions) -> Result<Self> {
+        if options.concurrency == 0 {
+            bail!("HTTP concurrency must be at least 1");
+        }
+        if let Some(rate) = options.rate {
+            if !(rate.is_finite() && rate > 0.0) {
+                bail!("HTTP request rate must be a positive number");
+            }
+        }
+        let template = parse_template(options.template.as_deref().unwrap_or("{{row}}"))?;
+
+        let agent = ureq::AgentBuilder::new()
+            .max_idle_connections_per_host(options.concurrency)
+            .build();
+        let (sender, receiver) = sync_channel::<String>(options.concurrency * 4);
+        let receiver = Arc::new(Mutex::new(receiver));
+        let pacer = options
+            .rate
+            .map(|rate| Arc::new(Pacer::new(Duration::from_secs_f64(1.0 / rate))));
+        let error = Arc::new(Mutex::new(None));
+        let sent = Arc::new(AtomicU64::new(0));
+
+        let workers = (0..options.concurrency)
+            .map(|_| {
+                let worker = Worker {
+                    agent: agent.clone(),
+                    endpoint: options.endpoint.clone(),
+                    headers: options.headers.clone(),
+                    receiver: Arc::clone(&receiver),
+                    pacer: pacer.clone(),
+                    error: Arc::clone(&error),
+                    sent: Arc::clone(&sent),
+                };
+                std::thread::spawn(move || worker.run())
+            })
+            .collect();
+
+        Ok(Self {
+            template,
+            sender: Some(sender),
+            workers,
+            error,
+            sent,
+            next_row: 0,
+        })
+    }
+
+    /// Number of requests answered with a success status so far
+    pub fn requests_sent(&self) -> u64 {
+        self.sent.load(Ordering::SeqCst)
+    }
+
+    /// Return the first request failure, if any
+    fn check_error(&self) -> Result<()> {
+        match self.error.lock().unwrap().take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    fn render(&self, batch: &RecordBatch, row: usize) -> Result<String> {
+        let id = self.next_row + row as u64;
+        let columns = json_row(batch, row)?;
+        // A generated `id` column replaces the row number
+        let own_id = columns
+            .iter()
+            .find(|(name, _)| *name == ID_COLUMN_NAME)
+            .map(|(_, json)| json);
+
+        let mut body = String::new();
+        for segment in &self.template {
+            match segment {
+                Segment::Text(text) => body.push_str(text),
+                Segment::Id => match own_id {
+                    Some(json) => body.push_str(&serde_json::to_string(json)?),
+                    None => body.push_str(&id.to_string()),
+                },
+                Segment::Row => {
+                    let mut object = Vec::with_capacity(columns.len() + 1);
+                    if own_id.is_none() {
+                        object.push((ID_COLUMN_NAME, JsonValue::Int(id as i64)));
+                    }
+                    object.extend(columns.iter().cloned());
+                    body.push_str(&serde_json::to_string(&JsonValue::Object(object))?);
+                }
+                Segment::Column(name) => {
+                    let (_, json) = columns
+                        .iter()
+                        .find(|(column, _)| column == name)
+                        .ok_or_else(|| anyhow!("template references unknown column '{}'", name))?;
+                    body.push_str(&serde_json::to_string(json)?);
+                }
+            }
+        }
+        Ok(body)
+    }
+
+    fn join(&mut self) -> Result<()> {
+        self.sender.take();
+        for worker in self.workers.drain(..) {
+            worker
+                .join()
+                .map_err(|_| anyhow!("HTTP worker thread panicked"))??;
+        }
+        self.check_error()
+    }
+}
+
+impl Sink for HttpSink {
+    fn readiness(&mut self) -> Result<Readiness> {
+        self.check_error()?;
+        Ok(Readiness::Ready)
+    }
+
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        for row in 0..batch.num_rows() {
+            let body = self.render(batch, row)?;
+            let sender = self
+                .sender
+                .as_ref()
+                .ok_or_else(|| anyhow!("HTTP sink already closed"))?;
+            if sender.send(body).is_err() {
+                // Every worker stopped after a failure
+                self.join()?;
+                bail!("HTTP workers stopped unexpectedly");
+            }
+        }
+        self.next_row += batch.num_rows() as u64;
+        self.check_error()
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.join()
+    }
+}
+
+impl Drop for HttpSink {
+    fn drop(&mut self) {
+        let _ = self.join();
+    }
+}
+
+/// Piece of a parsed body template
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Text(String),
+    Id,
+    Row,
+    Column(String),
+}
+
+fn parse_template(template: &str) -> Result<Vec<Segment>> {
+    let mut segments = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        if start > 0 {
+            segments.push(Segment::Text(rest[..start].to_string()));
+        }
+        let end = rest[start..]
+            .find("}}")
+            .ok_or_else(|| anyhow!("unterminated placeholder in HTTP body template"))?;
+        let name = rest[start + 2..start + end].trim();
+        segments.push(match name {
+            "id" => Segment::Id,
+            "row" => Segment::Row,
+            "" => bail!("empty placeholder in HTTP body template"),
+            column => Segment::Column(column.to_string()),
+        });
+        rest = &rest[start + end + 2..];
+    }
+    if !rest.is_empty() {
+        segments.push(Segment::Text(rest.to_string()));
+    }
+    Ok(segments)
+}
+
+/// Spaces request start times evenly across all workers
+struct Pacer {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl Pacer {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Block until this caller's send slot
+    fn wait(&self) {
+        let slot = {
+            let mut next = self.next.lock().unwrap();
+            let slot = (*next).max(Instant::now());
+            *next = slot + self.interval;
+            slot
+        };
+        std::thread::sleep(slot.saturating_duration_since(Instant::now()));
+    }
+}
+
+struct Worker {
+    agent: ureq::Agent,
+    endpoint: String,
+    headers: Vec<(String, String)>,
+    receiver: Arc<Mutex<Receiver<String>>>,
+    pacer: Option<Arc<Pacer>>,
+    error: Arc<Mutex<Option<anyhow::Error>>>,
+    sent: Arc<AtomicU64>,
+}
+
+impl Worker {
+    fn run(self) -> Result<()> {
+        loop {
+            let body = match self.receiver.lock().unwrap().recv() {
+                Ok(body) => body,
+                Err(_) => return Ok(()),
+            };
+            if let Some(pacer) = &self.pacer {
+                pacer.wait();
+            }
+            if let Err(e) = self.post(&body) {
+                self.error.lock().unwrap().get_or_insert(e);
+                return Ok(());
+            }
+            self.sent.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn post(&self, body: &str) -> Result<()> {
+        let mut request = self
+            .agent
+            .post(&self.endpoint)
+            .set("Content-Type", "application/json");
+        for (key, value) in &self.headers {
+            request = request.set(key, value);
+        }
+        match request.send_string(body) {
+            Ok(_) => Ok(()),
+            Err(ureq::Error::Status(status, response)) => {
+                let text = response.into_string().unwrap_or_default();
+                bail!(
+                    "{} returned HTTP {}: {}",
+                    self.endpoint,
+                    status,
+                    text.trim()
+                )
+            }
+            Err(e) => Err(anyhow!("request to {} failed: {}", self.endpoint, e)),
+        }
+    }
+}
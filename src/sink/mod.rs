@@ -0,0 +1,252 @@
+//! Destinations for generated record batches
+//!
+//! Every output target (local Parquet files, remote services, other file
+//! formats) implements [`Sink`]. Sinks report their [`Readiness`] before each
+//! batch so that a slow destination throttles generation instead of letting
+//! batches pile up in memory.
+
+use anyhow::Result;
+use arrow::record_batch::RecordBatch;
+use std::collections::VecDeque;
+use std::thread;
+use std::time::Duration;
+
+use crate::cluster::ClusterSink;
+use crate::{CancelPolicy, CancellationToken, Cancelled, DataGenerator};
+
+mod bounded;
+#[cfg(feature = "flight")]
+pub(crate) mod flight;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "object_store")]
+mod object_writer;
+#[cfg(feature = "parquet")]
+mod parquet_file;
+#[cfg(feature = "redis")]
+pub(crate) mod redis;
+#[cfg(feature = "scylla")]
+pub(crate) mod scylla;
+mod shuffle;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+#[cfg(any(
+    feature = "redis",
+    feature = "scylla",
+    feature = "sqlite",
+    feature = "json",
+    feature = "http"
+))]
+pub(crate) mod value;
+
+pub use bounded::BoundedSink;
+#[cfg(feature = "flight")]
+pub use flight::{FlightSink, FlightSinkOptions};
+#[cfg(feature = "http")]
+pub use http::{HttpSink, HttpSinkOptions};
+#[cfg(feature = "object_store")]
+pub use object_writer::ObjectWriter;
+#[cfg(feature = "parquet")]
+pub use parquet_file::ParquetFileSink;
+#[cfg(feature = "redis")]
+pub use redis::{RedisSink, RedisSinkOptions, RedisTarget};
+#[cfg(feature = "scylla")]
+pub use scylla::{ScyllaSink, ScyllaSinkOptions};
+pub use shuffle::ShuffleSink;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteSink;
+
+/// Whether a sink can accept another batch right now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Readiness {
+    /// The next batch can be written without unbounded buffering
+    Ready,
+    /// The sink is saturated; the producer should wait before writing
+    Busy,
+}
+
+/// URL schemes of object stores that files can be written to
+pub const OBJECT_URL_SCHEMES: &[&str] = &[
+    "s3", "s3a", "gs", "az", "adl", "azure", "abfs", "abfss", "file",
+];
+
+/// Whether `path` is an object store URL such as `s3://bucket/key` rather
+/// than a local path
+pub fn is_object_url(path: &std::path::Path) -> bool {
+    path.to_str()
+        .and_then(|path| path.split_once("://"))
+        .is_some_and(|(scheme, _)| OBJECT_URL_SCHEMES.contains(&scheme))
+}
+
+/// A destination that accepts record batches
+pub trait Sink {
+    /// Report whether the sink can accept another batch
+    ///
+    /// Sinks that write synchronously are always ready; buffered or remote
+    /// sinks return [`Readiness::Busy`] while their queue is full.
+    fn readiness(&mut self) -> Result<Readiness> {
+        Ok(Readiness::Ready)
+    }
+
+    /// Write a batch to the sink
+    fn write(&mut self, batch: &RecordBatch) -> Result<()>;
+
+    /// Flush outstanding data and close the sink
+    fn finish(&mut self) -> Result<()>;
+
+    /// Stop writing and discard partial output where the sink supports it
+    ///
+    /// The default implementation finishes the sink, keeping what was written.
+    fn abort(&mut self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<S: Sink + ?Sized> Sink for &mut S {
+    fn readiness(&mut self) -> Result<Readiness> {
+        (**self).readiness()
+    }
+
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        (**self).write(batch)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        (**self).finish()
+    }
+
+    fn abort(&mut self) -> Result<()> {
+        (**self).abort()
+    }
+}
+
+impl<S: Sink + ?Sized> Sink for Box<S> {
+    fn readiness(&mut self) -> Result<Readiness> {
+        (**self).readiness()
+    }
+
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        (**self).write(batch)
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        (**self).finish()
+    }
+
+    fn abort(&mut self) -> Result<()> {
+        (**self).abort()
+    }
+}
+
+/// Block until the sink reports it is ready, backing off while it is busy
+///
+/// Returns `false` if cancellation was requested while waiting.
+pub fn wait_ready(sink: &mut dyn Sink, cancel: &CancellationToken) -> Result<bool> {
+    let mut backoff = Duration::from_micros(50);
+    while sink.readiness()? == Readiness::Busy {
+        if cancel.is_cancelled() {
+            return Ok(false);
+        }
+        thread::sleep(backoff);
+        backoff = (backoff * 2).min(Duration::from_millis(20));
+    }
+    Ok(!cancel.is_cancelled())
+}
+
+/// Generate `num_rows` rows in batches of `batch_size` and write them to `sink`
+///
+/// Batches are capped at [`DataGenerator::max_batch_rows`], so very wide
+/// vectors are generated in chunks that fit Arrow's 2GB array limit. With
+/// `Config::parallel_batches` set, that many batches are generated at once
+/// and then written in order; the output is the same as one at a time. With
+/// `Config::shuffle_buffer` set, rows pass through a [`ShuffleSink`] seeded
+/// for the generator's file; with `Config::clustering` set, they pass
+/// through a [`ClusterSink`] that sorts the whole file.
+///
+/// The sink is finished on success. If `cancel` is triggered the sink is
+/// finished or aborted according to `policy` and a [`Cancelled`] error is
+/// returned.
+pub fn write_rows(
+    sink: &mut dyn Sink,
+    data_generator: &mut DataGenerator,
+    num_rows: usize,
+    batch_size: usize,
+    cancel: &CancellationToken,
+    policy: CancelPolicy,
+) -> Result<usize> {
+    if let Some(capacity) = data_generator.config.shuffle_buffer {
+        let rng = shuffle::shuffle_rng(data_generator.config.seed, data_generator.file_index);
+        let mut shuffled = ShuffleSink::new(sink, capacity, rng);
+        return write_batches(
+            &mut shuffled,
+            data_generator,
+            num_rows,
+            batch_size,
+            cancel,
+            policy,
+        );
+    }
+    if let Some(clustering) = &data_generator.config.clustering {
+        clustering.check_schema(data_generator.schema())?;
+        let mut clustered = ClusterSink::new(sink, clustering.clone());
+        return write_batches(
+            &mut clustered,
+            data_generator,
+            num_rows,
+            batch_size,
+            cancel,
+            policy,
+        );
+    }
+    write_batches(sink, data_generator, num_rows, batch_size, cancel, policy)
+}
+
+fn write_batches(
+    sink: &mut dyn Sink,
+    data_generator: &mut DataGenerator,
+    num_rows: usize,
+    batch_size: usize,
+    cancel: &CancellationToken,
+    policy: CancelPolicy,
+) -> Result<usize> {
+    // Larger batches would overflow 32-bit array offsets
+    let batch_size = batch_size.max(1).min(data_generator.max_batch_rows());
+    let in_flight = data_generator.config.parallel_batches.unwrap_or(1).max(1);
+    let mut total_rows = 0;
+    let mut remaining_rows = num_rows;
+    let mut generated = VecDeque::new();
+
+    while remaining_rows > 0 || !generated.is_empty() {
+        if !wait_ready(sink, cancel)? {
+            match policy {
+                CancelPolicy::Finalize => sink.finish()?,
+                CancelPolicy::Discard => sink.abort()?,
+            }
+            return Err(Cancelled {
+                rows_written: total_rows,
+                policy,
+            }
+            .into());
+        }
+
+        if generated.is_empty() {
+            let mut batch_sizes = Vec::with_capacity(in_flight);
+            while batch_sizes.len() < in_flight && remaining_rows > 0 {
+                let current_batch_size = batch_size.min(remaining_rows);
+                batch_sizes.push(current_batch_size);
+                remaining_rows -= current_batch_size;
+            }
+            generated.extend(data_generator.generate_batches(&batch_sizes)?);
+        }
+        let Some(batch) = generated.pop_front() else {
+            break;
+        };
+
+        sink.write(&batch)?;
+        total_rows += batch.num_rows();
+    }
+
+    sink.finish()?;
+
+    Ok(total_rows)
+}
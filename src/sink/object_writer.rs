@@ -0,0 +1,140 @@
+//! Streaming uploads to object stores
+//!
+//! Parquet files written to an `s3://`, `gs://` or `az://` URL are uploaded
+//! with a multipart upload while they are written. Bytes are buffered until
+//! a part is full and full parts are sent in the background, a few at a
+//! time, so uploads overlap with encoding of the next row groups and a file
+//! never has to fit in memory or on local disk. The upload is completed when
+//! the file is finished and aborted when the file is discarded.
+//!
+//! Credentials, regions and endpoints are read from the usual environment
+//! variables of each store (`AWS_ACCESS_KEY_ID`, `AWS_REGION`,
+//! `AWS_ENDPOINT`, `GOOGLE_SERVICE_ACCOUNT`, `AZURE_STORAGE_ACCOUNT_NAME`,
+//! ...).
+
+use anyhow::{anyhow, Context, Result};
+use object_store::{MultipartUpload, PutPayload};
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::sync::OnceLock;
+use tokio::runtime::Runtime;
+use tokio::task::JoinHandle;
+use url::Url;
+
+/// Size of every part but the last; S3 requires at least 5 MiB
+const PART_SIZE: usize = 8 * 1024 * 1024;
+
+/// Parts uploaded concurrently before writes wait for the oldest
+const MAX_IN_FLIGHT: usize = 4;
+
+/// Runtime shared by all uploads of the process
+fn runtime() -> Result<&'static Runtime> {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    if let Some(runtime) = RUNTIME.get() {
+        return Ok(runtime);
+    }
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?;
+    Ok(RUNTIME.get_or_init(|| runtime))
+}
+
+/// Writer uploading its bytes to one object with a multipart upload
+pub struct ObjectWriter {
+    url: String,
+    runtime: &'static Runtime,
+    upload: Box<dyn MultipartUpload>,
+    buffer: Vec<u8>,
+    parts: VecDeque<JoinHandle<object_store::Result<()>>>,
+    bytes: u64,
+}
+
+impl ObjectWriter {
+    /// Start a multipart upload to the object at `url`
+    pub fn create(url: &str) -> Result<Self> {
+        let parsed = Url::parse(url).with_context(|| format!("Invalid object URL: {}", url))?;
+        // Store builders take their settings as lowercase environment names
+        let options = std::env::vars().map(|(key, value)| (key.to_ascii_lowercase(), value));
+        let (store, path) = object_store::parse_url_opts(&parsed, options)
+            .with_context(|| format!("Failed to open object store for {}", url))?;
+        let runtime = runtime()?;
+        let upload = runtime
+            .block_on(store.put_multipart(&path))
+            .with_context(|| format!("Failed to start upload: {}", url))?;
+        Ok(Self {
+            url: url.to_string(),
+            runtime,
+            upload,
+            buffer: Vec::with_capacity(PART_SIZE),
+            parts: VecDeque::new(),
+            bytes: 0,
+        })
+    }
+
+    /// URL of the object being written
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Upload the buffered bytes as the next part
+    fn send_part(&mut self) -> Result<()> {
+        while self.parts.len() >= MAX_IN_FLIGHT {
+            self.wait_oldest()?;
+        }
+        let part = std::mem::replace(&mut self.buffer, Vec::with_capacity(PART_SIZE));
+        let upload = self.upload.put_part(PutPayload::from(part));
+        self.parts.push_back(self.runtime.spawn(upload));
+        Ok(())
+    }
+
+    fn wait_oldest(&mut self) -> Result<()> {
+        if let Some(part) = self.parts.pop_front() {
+            self.runtime
+                .block_on(part)
+                .map_err(|e| anyhow!("upload task failed: {}", e))?
+                .with_context(|| format!("Failed to upload part of {}", self.url))?;
+        }
+        Ok(())
+    }
+
+    /// Upload the remaining bytes and complete the object, returning its size
+    pub fn finish(&mut self) -> Result<u64> {
+        if !self.buffer.is_empty() || self.bytes == 0 {
+            self.send_part()?;
+        }
+        while !self.parts.is_empty() {
+            self.wait_oldest()?;
+        }
+        self.runtime
+            .block_on(self.upload.complete())
+            .with_context(|| format!("Failed to complete upload: {}", self.url))?;
+        Ok(self.bytes)
+    }
+
+    /// Cancel the upload; parts already sent are deleted by the store
+    pub fn abort(&mut self) -> Result<()> {
+        for part in self.parts.drain(..) {
+            part.abort();
+        }
+        self.buffer.clear();
+        self.runtime
+            .block_on(self.upload.abort())
+            .with_context(|| format!("Failed to abort upload: {}", self.url))
+    }
+}
+
+impl Write for ObjectWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        self.bytes += buf.len() as u64;
+        if self.buffer.len() >= PART_SIZE {
+            self.send_part().map_err(io::Error::other)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // Parts are sent as they fill; a short part is only allowed last
+        Ok(())
+    }
+}
@@ -0,0 +1,209 @@
+//! Parquet file sink, writing to local files or object stores
+
+use anyhow::{Context, Result};
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::ArrowWriter;
+use parquet::file::properties::WriterProperties;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "object_store")]
+use super::ObjectWriter;
+use super::{is_object_url, Sink};
+use crate::timing::{counted, StageTimings, TimedWrite};
+
+/// Where the encoded bytes of a Parquet file go
+enum Output {
+    File(File),
+    /// Multipart upload to an object store URL
+    #[cfg(feature = "object_store")]
+    Object(ObjectWriter),
+}
+
+impl Output {
+    fn create(path: &Path) -> Result<Self> {
+        if is_object_url(path) {
+            #[cfg(feature = "object_store")]
+            return Ok(Output::Object(ObjectWriter::create(
+                &path.to_string_lossy(),
+            )?));
+            #[cfg(not(feature = "object_store"))]
+            anyhow::bail!(
+                "writing to {} needs the object_store feature",
+                path.display()
+            );
+        }
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create file: {}", path.display()))?;
+        Ok(Output::File(file))
+    }
+
+    /// Complete the output, returning its size in bytes
+    fn finish(self) -> Result<u64> {
+        match self {
+            Output::File(file) => Ok(file.metadata()?.len()),
+            #[cfg(feature = "object_store")]
+            Output::Object(mut object) => object.finish(),
+        }
+    }
+
+    /// Discard the output
+    fn abort(&mut self, path: &Path) -> Result<()> {
+        match self {
+            Output::File(_) => std::fs::remove_file(path)
+                .with_context(|| format!("Failed to remove cancelled file: {}", path.display())),
+            #[cfg(feature = "object_store")]
+            Output::Object(object) => object.abort(),
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Output::File(file) => file.write(buf),
+            #[cfg(feature = "object_store")]
+            Output::Object(object) => object.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Output::File(file) => file.flush(),
+            #[cfg(feature = "object_store")]
+            Output::Object(object) => object.flush(),
+        }
+    }
+}
+
+/// Sink writing batches to a single Parquet file
+///
+/// `path` is a local path or, with the `object_store` feature, an object
+/// store URL (see [`is_object_url`]) that the file is uploaded to as it is
+/// written.
+pub struct ParquetFileSink {
+    path: PathBuf,
+    writer: Option<ArrowWriter<TimedWrite<Output>>>,
+    /// Time spent in the Arrow writer, file IO included
+    writing: Duration,
+    io: Arc<AtomicU64>,
+    bytes: u64,
+    /// Rows per data page, if batches are sliced at page boundaries
+    page_rows: Option<usize>,
+}
+
+impl ParquetFileSink {
+    /// Create the file at `path` and prepare to write batches with `schema`
+    pub fn create(
+        path: impl AsRef<Path>,
+        schema: SchemaRef,
+        props: WriterProperties,
+    ) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let output = TimedWrite::new(Output::create(&path)?);
+        let io = output.counter();
+        let writer = ArrowWriter::try_new(output, schema, Some(props))?;
+
+        Ok(Self {
+            path,
+            writer: Some(writer),
+            writing: Duration::ZERO,
+            io,
+            bytes: 0,
+            page_rows: None,
+        })
+    }
+
+    /// Slice incoming batches at every `page_rows` rows of a row group
+    ///
+    /// The writer only checks its page row limit between write batches, so
+    /// pages hold exactly `page_rows` rows (the last of a row group fewer)
+    /// only if no write batch straddles a page boundary.
+    pub fn with_page_rows(mut self, page_rows: Option<usize>) -> Self {
+        self.page_rows = page_rows.filter(|&rows| rows > 0);
+        self
+    }
+
+    /// Path of the file being written
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Size of the finished file in bytes; 0 before the sink is finished
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes
+    }
+
+    /// Encoded size of the file so far: the bytes written plus the estimated
+    /// size of the buffered row group, or the final size once finished
+    pub fn encoded_size(&self) -> u64 {
+        match &self.writer {
+            Some(writer) => (writer.bytes_written() + writer.in_progress_size()) as u64,
+            None => self.bytes,
+        }
+    }
+
+    /// Encoding and IO time spent on this file so far
+    pub fn timings(&self) -> StageTimings {
+        let io = counted(&self.io);
+        StageTimings {
+            encode: self.writing.saturating_sub(io),
+            io,
+            ..StageTimings::default()
+        }
+    }
+
+    fn writer(&mut self) -> Result<&mut ArrowWriter<TimedWrite<Output>>> {
+        self.writer
+            .as_mut()
+            .with_context(|| format!("Parquet sink already closed: {}", self.path.display()))
+    }
+}
+
+impl Sink for ParquetFileSink {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        let start = Instant::now();
+        let page_rows = self.page_rows;
+        let writer = self.writer()?;
+        match page_rows {
+            Some(page_rows) => {
+                let mut offset = 0;
+                while offset < batch.num_rows() {
+                    let filled = writer.in_progress_rows() % page_rows;
+                    let len = (page_rows - filled).min(batch.num_rows() - offset);
+                    writer.write(&batch.slice(offset, len))?;
+                    offset += len;
+                }
+            }
+            None => writer.write(batch)?,
+        }
+        self.writing += start.elapsed();
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if let Some(writer) = self.writer.take() {
+            let start = Instant::now();
+            let output = writer.into_inner()?.into_inner();
+            // Completing an upload is IO too
+            let completing = Instant::now();
+            self.bytes = output.finish()?;
+            self.io
+                .fetch_add(completing.elapsed().as_nanos() as u64, Ordering::Relaxed);
+            self.writing += start.elapsed();
+        }
+        Ok(())
+    }
+
+    fn abort(&mut self) -> Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.inner_mut().get_mut().abort(&self.path)?;
+        }
+        Ok(())
+    }
+}
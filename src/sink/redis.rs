@@ -0,0 +1,206 @@
+//! Redis sink for vector search ingestion
+//!
+//! Rows become either hashes indexed by RediSearch (`FT.CREATE` + `HSET`) or
+//! elements of a vector set (`VADD`). Commands are sent in pipelines of
+//! `pipeline` rows to keep round trips off the critical path.
+
+use anyhow::{anyhow, bail, Context, Result};
+use arrow::datatypes::{DataType, Schema};
+use arrow::record_batch::RecordBatch;
+
+use super::value::{is_vector, value, vector_blob, Value};
+use super::Sink;
+use crate::columns::vector_encoding;
+use crate::core_gen::VectorElementType;
+use crate::{ELEMENT_TYPE_KEY, VECTOR_DIM_KEY};
+
+/// How rows are stored in Redis
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RedisTarget {
+    /// One hash per row under `<prefix>:<row>`; creates a RediSearch index
+    /// over the hashes if a name is given
+    Hash { index: Option<String> },
+    /// Add the first vector column of each row to the vector set `key`, with
+    /// `<prefix>:<row>` as element name
+    VectorSet { key: String },
+}
+
+/// Connection and layout of a Redis sink
+#[derive(Debug, Clone)]
+pub struct RedisSinkOptions {
+    /// Connection URL, e.g. `redis://127.0.0.1:6379`
+    pub url: String,
+    /// Prefix of hash keys and element names
+    pub key_prefix: String,
+    pub target: RedisTarget,
+    /// Rows per pipeline round trip
+    pub pipeline: usize,
+}
+
+/// Sink writing rows into Redis
+pub struct RedisSink {
+    connection: redis::Connection,
+    options: RedisSinkOptions,
+    next_row: u64,
+    index_created: bool,
+}
+
+impl RedisSink {
+    /// Connect to the server
+    pub fn connect(options: RedisSinkOptions) -> Result<Self> {
+        let connection = redis::Client::open(options.url.as_str())?
+            .get_connection()
+            .with_context(|| format!("Failed to connect to Redis: {}", options.url))?;
+        Ok(Self {
+            connection,
+            options,
+            next_row: 0,
+            index_created: false,
+        })
+    }
+
+    /// Create the RediSearch index over the row hashes
+    fn create_index(&mut self, name: &str, schema: &Schema) -> Result<()> {
+        index_command(name, &self.options.key_prefix, schema)?
+            .query::<()>(&mut self.connection)
+            .with_context(|| format!("Failed to create index {}", name))
+    }
+}
+
+/// `FT.CREATE` of a RediSearch index `name` over the hashes under `key_prefix`
+pub(crate) fn index_command(name: &str, key_prefix: &str, schema: &Schema) -> Result<redis::Cmd> {
+    let mut cmd = redis::cmd("FT.CREATE");
+    cmd.arg(name)
+        .arg("ON")
+        .arg("HASH")
+        .arg("PREFIX")
+        .arg(1)
+        .arg(format!("{}:", key_prefix))
+        .arg("SCHEMA");
+    for field in schema.fields() {
+        if is_vector(field) {
+            let element = match field.metadata().get(ELEMENT_TYPE_KEY).map(String::as_str) {
+                Some("f16") => "FLOAT16",
+                Some("f64") => "FLOAT64",
+                Some("i8") => "INT8",
+                Some("binary") => bail!(
+                    "RediSearch has no binary vector type for column {}",
+                    field.name()
+                ),
+                _ => "FLOAT32",
+            };
+            let dim = field
+                .metadata()
+                .get(VECTOR_DIM_KEY)
+                .ok_or_else(|| anyhow!("vector column {} has no dimension", field.name()))?;
+            cmd.arg(field.name())
+                .arg("VECTOR")
+                .arg("FLAT")
+                .arg(6)
+                .arg("TYPE")
+                .arg(element)
+                .arg("DIM")
+                .arg(dim)
+                .arg("DISTANCE_METRIC")
+                .arg("COSINE");
+        } else {
+            match field.data_type() {
+                DataType::Utf8 => cmd.arg(field.name()).arg("TEXT"),
+                DataType::Int32
+                | DataType::Int64
+                | DataType::Float32
+                | DataType::Float64
+                | DataType::Timestamp(_, _) => cmd.arg(field.name()).arg("NUMERIC"),
+                // Other binary columns (e.g. PQ codes) are stored but not indexed
+                _ => &mut cmd,
+            };
+        }
+    }
+    Ok(cmd)
+}
+
+/// `HSET` of the row at `row` of `batch` under `key`
+///
+/// Vectors are written as RediSearch expects them, little-endian values of
+/// the column's element type whatever its Arrow encoding and layout.
+pub(crate) fn hset_command(key: &str, batch: &RecordBatch, row: usize) -> Result<redis::Cmd> {
+    let mut cmd = redis::cmd("HSET");
+    cmd.arg(key);
+    for (field, column) in batch.schema_ref().fields().iter().zip(batch.columns()) {
+        if let Some((element, _)) = vector_encoding(field) {
+            if let Some(bytes) = vector_blob(field, element, column.as_ref(), row) {
+                cmd.arg(field.name()).arg(bytes);
+            }
+            continue;
+        }
+        let value = value(column.as_ref(), row)
+            .with_context(|| format!("column {} cannot be stored in a Redis hash", field.name()))?;
+        match value {
+            Value::Null => continue,
+            Value::Int(v) => cmd.arg(field.name()).arg(v),
+            Value::Float(v) => cmd.arg(field.name()).arg(v),
+            Value::Str(v) => cmd.arg(field.name()).arg(v),
+            Value::Bytes(v) => cmd.arg(field.name()).arg(v),
+        };
+    }
+    Ok(cmd)
+}
+
+/// `VADD` of the vector at `row` of column `index` to `set` as element `key`,
+/// `None` if the vector is null
+pub(crate) fn vadd_command(
+    set: &str,
+    key: &str,
+    batch: &RecordBatch,
+    index: usize,
+    row: usize,
+) -> Option<redis::Cmd> {
+    // VADD takes little-endian f32 whatever the column's encoding
+    let field = batch.schema_ref().field(index);
+    let fp32 = vector_blob(field, VectorElementType::Float32, batch.column(index).as_ref(), row)?;
+    let mut cmd = redis::cmd("VADD");
+    cmd.arg(set).arg("FP32").arg(fp32).arg(key);
+    Some(cmd)
+}
+
+impl Sink for RedisSink {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        if let RedisTarget::Hash { index: Some(name) } = &self.options.target {
+            if !self.index_created {
+                let name = name.clone();
+                self.create_index(&name, &batch.schema())?;
+                self.index_created = true;
+            }
+        }
+        let schema = batch.schema();
+        let vector_column = schema.fields().iter().position(|f| is_vector(f));
+
+        let mut pipe = redis::pipe();
+        for row in 0..batch.num_rows() {
+            let key = format!("{}:{}", self.options.key_prefix, self.next_row);
+            self.next_row += 1;
+            match &self.options.target {
+                RedisTarget::Hash { .. } => {
+                    pipe.add_command(hset_command(&key, batch, row)?).ignore();
+                }
+                RedisTarget::VectorSet { key: set } => {
+                    let index =
+                        vector_column.ok_or_else(|| anyhow!("vector sets need a vector column"))?;
+                    if let Some(cmd) = vadd_command(set, &key, batch, index, row) {
+                        pipe.add_command(cmd).ignore();
+                    }
+                }
+            }
+            if (row + 1) % self.options.pipeline.max(1) == 0 {
+                pipe.query::<()>(&mut self.connection)?;
+                pipe.clear();
+            }
+        }
+        pipe.query::<()>(&mut self.connection)?;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,243 @@
+//! ScyllaDB / Cassandra sink for wide-column ingestion benchmarks
+//!
+//! Creates `<keyspace>.<table>` with a `bigint` primary key `id` (the running
+//! row number) and one column per generated column, then inserts rows with a
+//! prepared statement, keeping up to `concurrency` inserts in flight. A
+//! generated `id` column is the primary key itself instead. Vectors are
+//! stored as lists of their values whatever the Arrow encoding.
+
+use anyhow::{anyhow, Context, Result};
+use arrow::array::{Array, AsArray};
+use arrow::datatypes::{DataType, Field, Float32Type, Int32Type, Schema};
+use arrow::record_batch::RecordBatch;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use scylla::client::session::Session;
+use scylla::client::session_builder::SessionBuilder;
+use scylla::statement::prepared::PreparedStatement;
+use scylla::value::CqlValue;
+use tokio::runtime::Runtime;
+
+use super::value::{id_column, value, Value};
+use super::Sink;
+use crate::columns::{vector_encoding, vector_values};
+use crate::core_gen::VectorElementType;
+use crate::SPARSE_DIM_KEY;
+
+/// Connection and table layout of a Scylla sink
+#[derive(Debug, Clone)]
+pub struct ScyllaSinkOptions {
+    /// Contact point, e.g. `127.0.0.1:9042`
+    pub node: String,
+    pub keyspace: String,
+    pub table: String,
+    /// Replication factor used if the keyspace has to be created
+    pub replication_factor: u32,
+    /// Inserts in flight at once
+    pub concurrency: usize,
+}
+
+/// Sink inserting rows into a Scylla table
+pub struct ScyllaSink {
+    runtime: Runtime,
+    session: Session,
+    options: ScyllaSinkOptions,
+    insert: Option<PreparedStatement>,
+    next_id: i64,
+}
+
+impl ScyllaSink {
+    /// Connect to the cluster
+    pub fn connect(options: ScyllaSinkOptions) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()?;
+        let session = runtime
+            .block_on(SessionBuilder::new().known_node(&options.node).build())
+            .with_context(|| format!("Failed to connect to Scylla: {}", options.node))?;
+        Ok(Self {
+            runtime,
+            session,
+            options,
+            insert: None,
+            next_id: 0,
+        })
+    }
+
+    /// Create the keyspace and table for `schema` and prepare the insert
+    fn prepare(&self, schema: &Schema) -> Result<PreparedStatement> {
+        let statements = Statements::new(&self.options, schema)?;
+        let session = &self.session;
+        self.runtime.block_on(async {
+            session.query_unpaged(statements.create_keyspace, ()).await?;
+            session.query_unpaged(statements.create_table, ()).await?;
+            Ok::<_, anyhow::Error>(session.prepare(statements.insert).await?)
+        })
+    }
+}
+
+/// CQL statements of a sink writing batches of one schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Statements {
+    pub create_keyspace: String,
+    pub create_table: String,
+    /// Insert of one row, bound to the values of [`row_values`]
+    pub insert: String,
+}
+
+impl Statements {
+    pub(crate) fn new(options: &ScyllaSinkOptions, schema: &Schema) -> Result<Self> {
+        let ScyllaSinkOptions {
+            keyspace, table, ..
+        } = options;
+        let key = id_column(schema);
+        let mut columns = Vec::new();
+        let mut names = Vec::new();
+        if key.is_none() {
+            columns.push("id bigint PRIMARY KEY".to_string());
+            names.push("id");
+        }
+        for (i, field) in schema.fields().iter().enumerate() {
+            let primary_key = if key == Some(i) { " PRIMARY KEY" } else { "" };
+            let cql_type = cql_type(field)?;
+            columns.push(format!("{} {}{}", field.name(), cql_type, primary_key));
+            names.push(field.name());
+        }
+        Ok(Self {
+            create_keyspace: format!(
+                "CREATE KEYSPACE IF NOT EXISTS {} WITH replication = \
+                 {{'class': 'SimpleStrategy', 'replication_factor': {}}}",
+                keyspace, options.replication_factor
+            ),
+            create_table: format!(
+                "CREATE TABLE IF NOT EXISTS {}.{} ({})",
+                keyspace,
+                table,
+                columns.join(", ")
+            ),
+            insert: format!(
+                "INSERT INTO {}.{} ({}) VALUES ({})",
+                keyspace,
+                table,
+                names.join(", "),
+                vec!["?"; names.len()].join(", ")
+            ),
+        })
+    }
+}
+
+/// Values bound to the insert of `row` of `batch`, the row number `id` first
+/// unless the batch has its own `id` column
+pub(crate) fn row_values(batch: &RecordBatch, row: usize, id: i64) -> Result<Vec<Option<CqlValue>>> {
+    let mut values = Vec::with_capacity(batch.num_columns() + 1);
+    if id_column(batch.schema_ref()).is_none() {
+        values.push(Some(CqlValue::BigInt(id)));
+    }
+    for (field, column) in batch.schema_ref().fields().iter().zip(batch.columns()) {
+        values.push(cql_value(field, column.as_ref(), row)?);
+    }
+    Ok(values)
+}
+
+impl Sink for ScyllaSink {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        let insert = match self.insert.take() {
+            Some(insert) => insert,
+            None => self.prepare(&batch.schema())?,
+        };
+
+        let mut rows = Vec::with_capacity(batch.num_rows());
+        for row in 0..batch.num_rows() {
+            rows.push(row_values(batch, row, self.next_id)?);
+            self.next_id += 1;
+        }
+
+        let session = &self.session;
+        let statement = &insert;
+        let result = self.runtime.block_on(
+            stream::iter(rows)
+                .map(|values| async move { session.execute_unpaged(statement, values).await })
+                .buffer_unordered(self.options.concurrency.max(1))
+                .try_for_each(|_| async { Ok(()) }),
+        );
+        self.insert = Some(insert);
+        result.map_err(|e| anyhow!("Scylla insert failed: {}", e))
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// CQL column type of `field`
+///
+/// Vectors of any encoding become `list<float>` (`list<double>` for f64
+/// elements), token lists `list<int>` and sparse vectors `map<int, float>`.
+pub(crate) fn cql_type(field: &Field) -> Result<&'static str> {
+    if let Some((element, _)) = vector_encoding(field) {
+        return Ok(match element {
+            VectorElementType::Float64 => "list<double>",
+            _ => "list<float>",
+        });
+    }
+    Ok(match field.data_type() {
+        DataType::Int32 => "int",
+        DataType::Int64 | DataType::Timestamp(_, _) => "bigint",
+        DataType::Float32 => "float",
+        DataType::Float64 => "double",
+        DataType::Utf8 => "text",
+        DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_) => "blob",
+        DataType::List(item) if item.data_type() == &DataType::Int32 => "list<int>",
+        DataType::Struct(_) if field.metadata().contains_key(SPARSE_DIM_KEY) => "map<int, float>",
+        other => anyhow::bail!("unsupported column type for Scylla: {}", other),
+    })
+}
+
+/// CQL value of the cell at `row` of a column, `None` for null
+pub(crate) fn cql_value(field: &Field, array: &dyn Array, row: usize) -> Result<Option<CqlValue>> {
+    if array.is_null(row) {
+        return Ok(None);
+    }
+    if let Some((element, _)) = vector_encoding(field) {
+        let Some(values) = vector_values(field, array, row) else {
+            return Ok(None);
+        };
+        let values = values.into_iter().map(|v| match element {
+            VectorElementType::Float64 => CqlValue::Double(v),
+            _ => CqlValue::Float(v as f32),
+        });
+        return Ok(Some(CqlValue::List(values.collect())));
+    }
+    match field.data_type() {
+        DataType::List(_) => {
+            let list = array.as_list::<i32>().value(row);
+            let items = list.as_primitive::<Int32Type>().values().iter();
+            Ok(Some(CqlValue::List(items.map(|&v| CqlValue::Int(v)).collect())))
+        }
+        DataType::Struct(_) => {
+            let sparse = array.as_struct();
+            let indices = sparse.column(0).as_list::<i32>().value(row);
+            let values = sparse.column(1).as_list::<i32>().value(row);
+            let entries = indices
+                .as_primitive::<Int32Type>()
+                .values()
+                .iter()
+                .zip(values.as_primitive::<Float32Type>().values())
+                .map(|(&index, &value)| (CqlValue::Int(index), CqlValue::Float(value)));
+            Ok(Some(CqlValue::Map(entries.collect())))
+        }
+        data_type => Ok(scalar_value(data_type, value(array, row)?)),
+    }
+}
+
+/// CQL value of a scalar cell of an Arrow type, `None` for null
+fn scalar_value(data_type: &DataType, value: Value<'_>) -> Option<CqlValue> {
+    Some(match (data_type, value) {
+        (_, Value::Null) => return None,
+        (DataType::Int32, Value::Int(v)) => CqlValue::Int(v as i32),
+        (_, Value::Int(v)) => CqlValue::BigInt(v),
+        (DataType::Float32, Value::Float(v)) => CqlValue::Float(v as f32),
+        (_, Value::Float(v)) => CqlValue::Double(v),
+        (_, Value::Str(v)) => CqlValue::Text(v.to_string()),
+        (_, Value::Bytes(v)) => CqlValue::Blob(v.to_vec()),
+    })
+}
@@ -0,0 +1,126 @@
+//! Shuffle buffer scrambling the local order of rows
+
+use anyhow::Result;
+use arrow::compute::interleave_record_batch;
+use arrow::record_batch::RecordBatch;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use super::{Readiness, Sink};
+use crate::core_gen::stream_seed;
+
+/// Stream identifier for the shuffle order of each file
+const SHUFFLE_STREAM: u64 = u64::MAX - 6;
+
+/// RNG deciding the shuffle order of file `file_index`
+pub(crate) fn shuffle_rng(seed: u64, file_index: u64) -> StdRng {
+    StdRng::seed_from_u64(stream_seed(seed, &[SHUFFLE_STREAM, file_index]))
+}
+
+/// Holds back up to `capacity` rows and writes them in random order
+///
+/// Each incoming batch joins the buffer and as many rows as it brought are
+/// drawn at random from the buffer and written, like the shuffle buffers of
+/// streaming input pipelines. Rows therefore move at most about `capacity`
+/// positions earlier, while id and timestamp ordering is scrambled locally.
+/// Finishing writes the remaining rows in random order.
+///
+/// Incoming batches are kept as they are and the held-back rows are tracked
+/// as `(batch, row)` slots, so a write only copies the rows it emits. The
+/// kept batches are compacted into one once most of their rows are gone.
+pub struct ShuffleSink<S: Sink> {
+    inner: S,
+    capacity: usize,
+    rng: StdRng,
+    batches: Vec<RecordBatch>,
+    /// Rows of `batches` still held back, in no particular order
+    slots: Vec<(usize, usize)>,
+    /// Rows in `batches`, emitted or not
+    stored_rows: usize,
+    /// Largest incoming batch; buffered rows are flushed in batches this size
+    max_batch_rows: usize,
+}
+
+impl<S: Sink> ShuffleSink<S> {
+    /// Shuffle rows written to `inner` through a buffer of `capacity` rows
+    pub fn new(inner: S, capacity: usize, rng: StdRng) -> Self {
+        Self {
+            inner,
+            capacity,
+            rng,
+            batches: Vec::new(),
+            slots: Vec::new(),
+            stored_rows: 0,
+            max_batch_rows: 1,
+        }
+    }
+
+    /// Rows currently held back
+    pub fn buffered_rows(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Write `count` rows drawn at random from the buffer, keeping the rest
+    fn emit(&mut self, count: usize) -> Result<()> {
+        let count = count.min(self.slots.len());
+        if count == 0 {
+            return Ok(());
+        }
+        let picked = (0..count)
+            .map(|_| {
+                let slot = self.rng.gen_range(0..self.slots.len());
+                self.slots.swap_remove(slot)
+            })
+            .collect::<Vec<_>>();
+        let batches = self.batches.iter().collect::<Vec<_>>();
+        for chunk in picked.chunks(self.max_batch_rows) {
+            self.inner.write(&interleave_record_batch(&batches, chunk)?)?;
+        }
+        self.compact()
+    }
+
+    /// Drop emitted rows once they make up most of the kept batches
+    fn compact(&mut self) -> Result<()> {
+        if self.slots.is_empty() {
+            self.batches.clear();
+            self.stored_rows = 0;
+        } else if self.stored_rows > 2 * self.slots.len() {
+            let batches = self.batches.iter().collect::<Vec<_>>();
+            let rest = interleave_record_batch(&batches, &self.slots)?;
+            self.stored_rows = rest.num_rows();
+            self.batches = vec![rest];
+            self.slots = (0..self.stored_rows).map(|row| (0, row)).collect();
+        }
+        Ok(())
+    }
+}
+
+impl<S: Sink> Sink for ShuffleSink<S> {
+    fn readiness(&mut self) -> Result<Readiness> {
+        self.inner.readiness()
+    }
+
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        self.max_batch_rows = self.max_batch_rows.max(batch.num_rows());
+        let index = self.batches.len();
+        self.slots.extend((0..batch.num_rows()).map(|row| (index, row)));
+        self.stored_rows += batch.num_rows();
+        self.batches.push(batch.clone());
+        if self.slots.len() > self.capacity {
+            self.emit(self.slots.len() - self.capacity)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        self.emit(self.slots.len())?;
+        self.inner.finish()
+    }
+
+    fn abort(&mut self) -> Result<()> {
+        self.batches.clear();
+        self.slots.clear();
+        self.stored_rows = 0;
+        self.inner.abort()
+    }
+}
@@ -0,0 +1,141 @@
+//! SQLite database sink for lightweight test fixtures
+//!
+//! Creates a table with an `INTEGER PRIMARY KEY` column `id` (the row number)
+//! and one column per generated column, vectors stored as BLOBs of
+//! little-endian values of their element type, whatever the Arrow encoding,
+//! byte order or dimension prefix of the column. A generated `id` column is
+//! the primary key itself instead. Each batch is inserted in one transaction.
+
+use anyhow::{Context, Result};
+use arrow::array::Array;
+use arrow::datatypes::{DataType, Field, Schema};
+use arrow::record_batch::RecordBatch;
+use rusqlite::types::Value as SqlValue;
+use rusqlite::Connection;
+use std::path::Path;
+
+use super::value::{id_column, value, vector_blob, Value};
+use super::Sink;
+use crate::columns::vector_encoding;
+
+/// Sink inserting rows into a SQLite table
+pub struct SqliteSink {
+    connection: Connection,
+    table: String,
+    insert: Option<String>,
+    next_id: i64,
+}
+
+impl SqliteSink {
+    /// Open (or create) the database at `path`; rows go to `table`
+    pub fn create(path: impl AsRef<Path>, table: &str) -> Result<Self> {
+        let path = path.as_ref();
+        let connection = Connection::open(path)
+            .with_context(|| format!("Failed to open SQLite database: {}", path.display()))?;
+        Ok(Self {
+            connection,
+            table: table.to_string(),
+            insert: None,
+            next_id: 0,
+        })
+    }
+
+    /// Create the table for `schema` and return the insert statement
+    fn create_table(&self, schema: &Schema) -> Result<String> {
+        let key = id_column(schema);
+        let mut columns = Vec::new();
+        let mut names = Vec::new();
+        if key.is_none() {
+            columns.push("\"id\" INTEGER PRIMARY KEY".to_string());
+            names.push("\"id\"".to_string());
+        }
+        for (i, field) in schema.fields().iter().enumerate() {
+            let primary_key = if key == Some(i) { " PRIMARY KEY" } else { "" };
+            columns.push(format!(
+                "\"{}\" {}{}",
+                field.name(),
+                sql_type(field)?,
+                primary_key
+            ));
+            names.push(format!("\"{}\"", field.name()));
+        }
+        self.connection.execute(
+            &format!(
+                "CREATE TABLE IF NOT EXISTS \"{}\" ({})",
+                self.table,
+                columns.join(", ")
+            ),
+            [],
+        )?;
+        Ok(format!(
+            "INSERT INTO \"{}\" ({}) VALUES ({})",
+            self.table,
+            names.join(", "),
+            vec!["?"; names.len()].join(", ")
+        ))
+    }
+}
+
+impl Sink for SqliteSink {
+    fn write(&mut self, batch: &RecordBatch) -> Result<()> {
+        let insert = match self.insert.take() {
+            Some(insert) => insert,
+            None => self.create_table(&batch.schema())?,
+        };
+
+        let row_ids = id_column(batch.schema_ref()).is_none();
+        let transaction = self.connection.transaction()?;
+        {
+            let mut statement = transaction.prepare_cached(&insert)?;
+            for row in 0..batch.num_rows() {
+                let mut values = Vec::with_capacity(batch.num_columns() + 1);
+                if row_ids {
+                    values.push(SqlValue::Integer(self.next_id));
+                    self.next_id += 1;
+                }
+                for (field, column) in batch.schema_ref().fields().iter().zip(batch.columns()) {
+                    values.push(sql_value(field, column.as_ref(), row)?);
+                }
+                statement.execute(rusqlite::params_from_iter(values))?;
+            }
+        }
+        transaction.commit()?;
+        self.insert = Some(insert);
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// SQLite column type of `field`
+fn sql_type(field: &Field) -> Result<&'static str> {
+    if vector_encoding(field).is_some() {
+        return Ok("BLOB");
+    }
+    Ok(match field.data_type() {
+        DataType::Int32 | DataType::Int64 | DataType::Timestamp(_, _) => "INTEGER",
+        DataType::Float32 | DataType::Float64 => "REAL",
+        DataType::Utf8 => "TEXT",
+        DataType::Binary | DataType::LargeBinary | DataType::FixedSizeBinary(_) => "BLOB",
+        other => anyhow::bail!("unsupported column type for SQLite: {}", other),
+    })
+}
+
+/// SQLite value of the cell at `row` of a column
+fn sql_value(field: &Field, array: &dyn Array, row: usize) -> Result<SqlValue> {
+    if let Some((element, _)) = vector_encoding(field) {
+        return Ok(match vector_blob(field, element, array, row) {
+            Some(bytes) => SqlValue::Blob(bytes),
+            None => SqlValue::Null,
+        });
+    }
+    Ok(match value(array, row)? {
+        Value::Null => SqlValue::Null,
+        Value::Int(v) => SqlValue::Integer(v),
+        Value::Float(v) => SqlValue::Real(v),
+        Value::Str(v) => SqlValue::Text(v.to_string()),
+        Value::Bytes(v) => SqlValue::Blob(v.to_vec()),
+    })
+}
@@ -0,0 +1,164 @@
+//! Row-wise access to batch values for row-oriented sinks
+
+use anyhow::{bail, Result};
+use arrow::array::{Array, AsArray};
+use arrow::datatypes::{
+    DataType, Field, Float32Type, Float64Type, Int32Type, Int64Type, TimeUnit,
+    TimestampMillisecondType,
+};
+#[cfg(any(feature = "sqlite", feature = "scylla"))]
+use arrow::datatypes::Schema;
+
+#[cfg(feature = "json")]
+use arrow::record_batch::RecordBatch;
+#[cfg(feature = "json")]
+use serde::{Serialize, Serializer};
+
+#[cfg(feature = "json")]
+use crate::columns::vector_encoding;
+#[cfg(any(feature = "json", feature = "redis", feature = "sqlite"))]
+use crate::columns::vector_values;
+#[cfg(any(feature = "redis", feature = "sqlite"))]
+use crate::core_gen::encode_vector;
+#[cfg(any(feature = "json", feature = "redis", feature = "sqlite"))]
+use crate::core_gen::VectorElementType;
+#[cfg(any(feature = "sqlite", feature = "scylla"))]
+use crate::id_column::ID_COLUMN_NAME;
+use crate::VECTOR_DIM_KEY;
+
+/// One cell of a batch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Value<'a> {
+    Null,
+    Int(i64),
+    Float(f64),
+    Str(&'a str),
+    Bytes(&'a [u8]),
+}
+
+/// Read the value at `row` of `array`
+pub(crate) fn value(array: &dyn Array, row: usize) -> Result<Value<'_>> {
+    if array.is_null(row) {
+        return Ok(Value::Null);
+    }
+    Ok(match array.data_type() {
+        DataType::Int32 => Value::Int(array.as_primitive::<Int32Type>().value(row) as i64),
+        DataType::Int64 => Value::Int(array.as_primitive::<Int64Type>().value(row)),
+        DataType::Timestamp(TimeUnit::Millisecond, _) => {
+            Value::Int(array.as_primitive::<TimestampMillisecondType>().value(row))
+        }
+        DataType::Float32 => Value::Float(array.as_primitive::<Float32Type>().value(row) as f64),
+        DataType::Float64 => Value::Float(array.as_primitive::<Float64Type>().value(row)),
+        DataType::Utf8 => Value::Str(array.as_string::<i32>().value(row)),
+        DataType::Binary => Value::Bytes(array.as_binary::<i32>().value(row)),
+        DataType::LargeBinary => Value::Bytes(array.as_binary::<i64>().value(row)),
+        DataType::FixedSizeBinary(_) => Value::Bytes(array.as_fixed_size_binary().value(row)),
+        other => bail!("unsupported column type for row output: {}", other),
+    })
+}
+
+/// Whether `field` holds encoded vectors
+pub(crate) fn is_vector(field: &Field) -> bool {
+    field.metadata().contains_key(VECTOR_DIM_KEY)
+}
+
+/// Vector at `row` of a vector column as little-endian `element` values,
+/// whatever the column's Arrow encoding and layout; `None` if it is null
+#[cfg(any(feature = "redis", feature = "sqlite"))]
+pub(crate) fn vector_blob(
+    field: &Field,
+    element: VectorElementType,
+    array: &dyn Array,
+    row: usize,
+) -> Option<Vec<u8>> {
+    let values = vector_values(field, array, row)?;
+    let mut bytes = Vec::with_capacity(element.vector_bytes(values.len()));
+    encode_vector(&values, element, &mut bytes);
+    Some(bytes)
+}
+
+/// Position of the generated `id` column in `schema`, which keyed sinks use
+/// as the row key instead of adding their own
+#[cfg(any(feature = "sqlite", feature = "scylla"))]
+pub(crate) fn id_column(schema: &Schema) -> Option<usize> {
+    schema.index_of(ID_COLUMN_NAME).ok()
+}
+
+/// JSON form of one cell
+///
+/// Keeps the precision of the column, so 32-bit floats are written with the
+/// digits of an `f32` rather than widened, and writes object keys in order.
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum JsonValue<'a> {
+    Null,
+    Int(i64),
+    Float32(f32),
+    Float(f64),
+    Str(&'a str),
+    Floats32(Vec<f32>),
+    Floats(Vec<f64>),
+    /// Object with its keys in the given order
+    Object(Vec<(&'a str, JsonValue<'a>)>),
+}
+
+#[cfg(feature = "json")]
+impl Serialize for JsonValue<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            JsonValue::Null => serializer.serialize_unit(),
+            JsonValue::Int(v) => serializer.serialize_i64(*v),
+            JsonValue::Float32(v) => serializer.serialize_f32(*v),
+            JsonValue::Float(v) => serializer.serialize_f64(*v),
+            JsonValue::Str(v) => serializer.serialize_str(v),
+            JsonValue::Floats32(v) => v.serialize(serializer),
+            JsonValue::Floats(v) => v.serialize(serializer),
+            JsonValue::Object(fields) => serializer.collect_map(fields.iter().map(|(k, v)| (k, v))),
+        }
+    }
+}
+
+/// JSON form of the value at `row` of a column
+///
+/// Vectors become number arrays; binary columns that are not vectors (e.g.
+/// PQ codes) have no JSON form and return `None`.
+#[cfg(feature = "json")]
+pub(crate) fn json_value<'a>(
+    field: &Field,
+    array: &'a dyn Array,
+    row: usize,
+) -> Result<Option<JsonValue<'a>>> {
+    if is_vector(field) {
+        let Some(values) = vector_values(field, array, row) else {
+            return Ok(Some(JsonValue::Null));
+        };
+        // Half and single precision values widen to f64 exactly, so they
+        // narrow back without loss
+        return Ok(Some(match vector_encoding(field) {
+            Some((VectorElementType::Float16 | VectorElementType::Float32, _)) => {
+                JsonValue::Floats32(values.into_iter().map(|v| v as f32).collect())
+            }
+            _ => JsonValue::Floats(values),
+        }));
+    }
+    Ok(Some(match value(array, row)? {
+        Value::Null => JsonValue::Null,
+        Value::Int(v) => JsonValue::Int(v),
+        Value::Float(v) if array.data_type() == &DataType::Float32 => JsonValue::Float32(v as f32),
+        Value::Float(v) => JsonValue::Float(v),
+        Value::Str(v) => JsonValue::Str(v),
+        Value::Bytes(_) => return Ok(None),
+    }))
+}
+
+/// JSON object of the cells at `row` of `batch`, in schema order
+#[cfg(feature = "json")]
+pub(crate) fn json_row(batch: &RecordBatch, row: usize) -> Result<Vec<(&str, JsonValue<'_>)>> {
+    let mut cells = Vec::with_capacity(batch.num_columns());
+    for (field, column) in batch.schema_ref().fields().iter().zip(batch.columns()) {
+        if let Some(json) = json_value(field, column.as_ref(), row)? {
+            cells.push((field.name().as_str(), json));
+        }
+    }
+    Ok(cells)
+}
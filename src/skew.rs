@@ -0,0 +1,133 @@
+//! Skewed frequency distributions over a finite set of keys
+//!
+//! Used wherever generated rows are spread over keys (tenants, foreign keys,
+//! categories) with realistic hot spots.
+
+use anyhow::{bail, Context, Result};
+use rand::distributions::{Distribution, Uniform, WeightedIndex};
+use rand::Rng;
+use rand_distr::Zipf;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Frequency distribution over keys ranked `0..n`
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+pub enum Skew {
+    /// Every key is equally likely
+    #[default]
+    Uniform,
+    /// Key of rank `r` (1-based) has weight `1 / r^s`
+    Zipf(f64),
+}
+
+impl Skew {
+    /// Relative weight of each of `n` keys
+    pub fn weights(&self, n: usize) -> Vec<f64> {
+        match *self {
+            Skew::Uniform => vec![1.0; n],
+            Skew::Zipf(s) => (1..=n).map(|rank| (rank as f64).powf(-s)).collect(),
+        }
+    }
+
+    /// Sampler returning key indices `0..n` with this skew
+    pub fn sampler(&self, n: usize) -> Result<WeightedIndex<f64>> {
+        WeightedIndex::new(self.weights(n)).context("cannot build a distribution over zero keys")
+    }
+
+    /// Split `total` into `n` exact integer counts proportional to the weights
+    ///
+    /// Uses the largest-remainder method, so the counts always sum to `total`.
+    pub fn apportion(&self, total: usize, n: usize) -> Vec<usize> {
+        let weights = self.weights(n);
+        let sum: f64 = weights.iter().sum();
+        if n == 0 || sum <= 0.0 {
+            return vec![0; n];
+        }
+
+        let quotas: Vec<f64> = weights.iter().map(|w| w / sum * total as f64).collect();
+        let mut counts: Vec<usize> = quotas.iter().map(|q| q.floor() as usize).collect();
+        let assigned: usize = counts.iter().sum();
+
+        let mut by_remainder: Vec<usize> = (0..n).collect();
+        by_remainder.sort_by(|&a, &b| {
+            let ra = quotas[a] - quotas[a].floor();
+            let rb = quotas[b] - quotas[b].floor();
+            rb.total_cmp(&ra).then(a.cmp(&b))
+        });
+        for &i in by_remainder.iter().take(total.saturating_sub(assigned)) {
+            counts[i] += 1;
+        }
+        counts
+    }
+}
+
+impl FromStr for Skew {
+    type Err = anyhow::Error;
+
+    /// Parse `uniform`, `zipf` (exponent 1.0) or `zipf(<s>)`
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim().to_ascii_lowercase();
+        if s == "uniform" {
+            return Ok(Skew::Uniform);
+        }
+        if s == "zipf" {
+            return Ok(Skew::Zipf(1.0));
+        }
+        if let Some(exponent) = s.strip_prefix("zipf(").and_then(|r| r.strip_suffix(')')) {
+            let exponent: f64 = exponent
+                .trim()
+                .parse()
+                .with_context(|| format!("invalid zipf exponent '{}'", exponent))?;
+            if !exponent.is_finite() || exponent < 0.0 {
+                bail!(
+                    "zipf exponent must be a non-negative number, got {}",
+                    exponent
+                );
+            }
+            return Ok(Skew::Zipf(exponent));
+        }
+        bail!("unknown skew '{}', expected 'uniform' or 'zipf(<s>)'", s)
+    }
+}
+
+impl fmt::Display for Skew {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Skew::Uniform => write!(f, "uniform"),
+            Skew::Zipf(s) => write!(f, "zipf({})", s),
+        }
+    }
+}
+
+/// Draws key indices `0..n` with a [`Skew`] without materializing per-key weights
+///
+/// Suitable for very large key spaces (graph nodes, foreign keys) where a
+/// weight table would not fit in memory.
+#[derive(Debug, Clone)]
+pub enum KeySampler {
+    Uniform(Uniform<usize>),
+    Zipf(Zipf<f64>),
+}
+
+impl KeySampler {
+    /// Create a sampler over `n` keys; `n` must be non-zero
+    pub fn new(n: usize, skew: Skew) -> Result<Self> {
+        if n == 0 {
+            bail!("cannot sample from zero keys");
+        }
+        Ok(match skew {
+            Skew::Uniform => KeySampler::Uniform(Uniform::new(0, n)),
+            Skew::Zipf(s) => KeySampler::Zipf(Zipf::new(n as u64, s)?),
+        })
+    }
+
+    /// Draw a key index; key 0 is the most frequent under Zipf skew
+    pub fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        match self {
+            KeySampler::Uniform(uniform) => uniform.sample(rng),
+            // Zipf ranks are 1-based
+            KeySampler::Zipf(zipf) => zipf.sample(rng) as usize - 1,
+        }
+    }
+}
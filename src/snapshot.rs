@@ -0,0 +1,126 @@
+//! Snapshots of a generator's position, for forking generation
+//!
+//! A [`GeneratorSnapshot`] records where a [`DataGenerator`] is in its
+//! output: the next batch and row, the state of its sequential RNG (used by
+//! v0 files and [`DataGenerator::generate_vector`]), the trajectory walk,
+//! the largest values of ordering constraints and the branch it is on.
+//! Snapshots serialize with serde, so an application can persist one and
+//! later restore it into a generator of the same configuration and file to
+//! continue exactly where the snapshot was taken.
+//!
+//! [`DataGenerator::fork`] switches a generator to another branch: every row
+//! after the fork point is drawn from streams mixed with the branch number,
+//! while earlier rows and dataset-wide structures (PQ codebooks, mixture
+//! centroids, the duplicate pool) stay the same. Restoring one snapshot and
+//! forking it into several branches gives alternative continuations of one
+//! dataset, e.g. for A/B ingestion tests. Branch 0 is the original
+//! continuation.
+
+use anyhow::{bail, Result};
+use rand::SeedableRng;
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::core_gen::stream_seed;
+use crate::trajectory::TrajectoryState;
+use crate::{DataGenerator, GeneratorVersion, MonotonicState};
+
+/// Stream identifier mixed into the seed of forked branches
+const FORK_STREAM: u64 = u64::MAX - 10;
+
+/// Position of a generator in its output
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GeneratorSnapshot {
+    /// File the generator writes; snapshots only restore into the same file
+    pub file_index: u64,
+    /// Index of the next batch within the file
+    pub batch_index: u64,
+    /// Global index of the next row
+    pub next_row: u64,
+    /// Branch the generator is on; 0 unless forked
+    pub branch: u64,
+    /// Seed, stream and word position of the sequential RNG
+    pub rng_seed: [u8; 32],
+    pub rng_stream: u64,
+    pub rng_word_pos: u128,
+    pub trajectory: Option<TrajectoryState>,
+    pub monotonic: Option<MonotonicState>,
+}
+
+/// Seed of the row streams of `branch` of a dataset seeded with `seed`
+pub(crate) fn branch_seed(seed: u64, branch: u64) -> u64 {
+    match branch {
+        0 => seed,
+        branch => stream_seed(seed, &[FORK_STREAM, branch]),
+    }
+}
+
+impl DataGenerator {
+    /// Record the current position of the generator
+    pub fn snapshot(&self) -> GeneratorSnapshot {
+        GeneratorSnapshot {
+            file_index: self.file_index,
+            batch_index: self.batch_index,
+            next_row: self.next_row,
+            branch: self.branch,
+            rng_seed: self.rng.get_seed(),
+            rng_stream: self.rng.get_stream(),
+            rng_word_pos: self.rng.get_word_pos(),
+            trajectory: self.trajectory.clone(),
+            monotonic: self.monotonic.clone(),
+        }
+    }
+
+    /// Continue from `snapshot`, taken from a generator of the same
+    /// configuration and file
+    ///
+    /// The next batch is the one the snapshotted generator would have
+    /// produced next.
+    pub fn restore(&mut self, snapshot: &GeneratorSnapshot) -> Result<()> {
+        if snapshot.file_index != self.file_index {
+            bail!(
+                "snapshot of file {} cannot be restored into file {}",
+                snapshot.file_index,
+                self.file_index
+            );
+        }
+        if snapshot.trajectory.is_some() != self.config.trajectory.is_some() {
+            bail!("snapshot and generator disagree on trajectory mode");
+        }
+        if snapshot.monotonic.is_some() == self.config.monotonic.is_empty() {
+            bail!("snapshot and generator disagree on ordering constraints");
+        }
+        let mut rng = ChaCha12Rng::from_seed(snapshot.rng_seed);
+        rng.set_stream(snapshot.rng_stream);
+        rng.set_word_pos(snapshot.rng_word_pos);
+        self.rng = rng;
+        self.batch_index = snapshot.batch_index;
+        self.next_row = snapshot.next_row;
+        self.branch = snapshot.branch;
+        self.trajectory = snapshot.trajectory.clone();
+        self.monotonic = snapshot.monotonic.clone();
+        Ok(())
+    }
+
+    /// Continue on `branch` from the current position
+    ///
+    /// Rows generated from here on differ from those of other branches;
+    /// forking to the current branch changes nothing. Forks need generator
+    /// version v1, whose rows come from per-batch streams.
+    pub fn fork(&mut self, branch: u64) -> Result<()> {
+        if self.config.generator_version == GeneratorVersion::V0 {
+            bail!("forking needs generator version v1");
+        }
+        if branch != self.branch {
+            self.branch = branch;
+            let seed = stream_seed(branch_seed(self.config.seed, branch), &[self.batch_index]);
+            self.rng = ChaCha12Rng::seed_from_u64(seed);
+        }
+        Ok(())
+    }
+
+    /// Branch the generator is on; 0 unless forked
+    pub fn branch(&self) -> u64 {
+        self.branch
+    }
+}
@@ -0,0 +1,62 @@
+//! Sparse vectors for learned and lexical retrieval
+//!
+//! A sparse vector column holds the non-zero entries of a `dim`-dimensional
+//! vector per row, as a struct of two lists: ascending `indices` (Int32) and
+//! their `values` (Float32), the shape SPLADE encoders and BM25 term weights
+//! are ingested in. Each row has Binomial(`dim`, `density`) non-zeros at
+//! distinct uniformly drawn indices, weighted from an exponential
+//! distribution: a few strong terms among many weak ones, all positive.
+
+use anyhow::{bail, Result};
+use rand::seq::index;
+use rand::Rng;
+use rand_distr::{Binomial, Distribution, Exp1};
+use serde::{Deserialize, Serialize};
+
+/// Sparse vector column parameters
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SparseConfig {
+    /// Dimensions of the vector space, e.g. the vocabulary size
+    pub dim: usize,
+    /// Expected fraction of non-zero dimensions per vector, in (0, 1]
+    pub density: f64,
+}
+
+impl SparseConfig {
+    /// Check the parameters
+    pub fn validate(&self) -> Result<()> {
+        if self.dim == 0 || self.dim > i32::MAX as usize {
+            bail!("sparse dimension must be between 1 and {}", i32::MAX);
+        }
+        if !(self.density > 0.0 && self.density <= 1.0) {
+            bail!("sparse density must be in (0, 1], got {}", self.density);
+        }
+        Ok(())
+    }
+
+    /// Expected non-zero entries per vector
+    pub fn mean_nnz(&self) -> f64 {
+        self.dim as f64 * self.density
+    }
+}
+
+/// Fill `indices` and `values` with the non-zero entries of one vector
+pub fn sample_sparse<R: Rng + ?Sized>(
+    rng: &mut R,
+    config: &SparseConfig,
+    indices: &mut Vec<i32>,
+    values: &mut Vec<f32>,
+) {
+    indices.clear();
+    values.clear();
+    let nnz = Binomial::new(config.dim as u64, config.density)
+        .expect("validated density")
+        .sample(rng) as usize;
+    indices.extend(
+        index::sample(rng, config.dim, nnz)
+            .into_iter()
+            .map(|i| i as i32),
+    );
+    indices.sort_unstable();
+    values.extend((0..nnz).map(|_| -> f32 { Exp1.sample(rng) }));
+}
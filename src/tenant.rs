@@ -0,0 +1,55 @@
+//! Multi-tenant dataset shapes
+//!
+//! Rows carry a `tenant_id` whose frequencies follow a [`Skew`], matching the
+//! few-large / many-small tenant mix of SaaS vector databases.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::skew::Skew;
+
+/// Tenant dimension of a dataset
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TenantConfig {
+    /// Number of tenants; ids are `0..tenants`, with tenant 0 the largest
+    pub tenants: u32,
+    /// Distribution of rows over tenants
+    pub skew: Skew,
+    /// Write each tenant's rows to its own `tenant_id=<id>` directory
+    pub partitioned: bool,
+    /// Generate rows for this tenant only
+    ///
+    /// Set by dataset generation when writing a tenant's partition; left as
+    /// `None`, tenant ids are sampled per row.
+    pub only: Option<u32>,
+}
+
+impl TenantConfig {
+    /// Create an unpartitioned tenant configuration
+    pub fn new(tenants: u32, skew: Skew) -> Self {
+        Self {
+            tenants,
+            skew,
+            partitioned: false,
+            only: None,
+        }
+    }
+
+    /// Check the tenant count and fixed tenant
+    pub fn validate(&self) -> Result<()> {
+        if self.tenants == 0 {
+            bail!("tenant count must be greater than zero");
+        }
+        if let Some(tenant) = self.only {
+            if tenant >= self.tenants {
+                bail!("tenant {} out of range 0..{}", tenant, self.tenants);
+            }
+        }
+        Ok(())
+    }
+
+    /// Exact number of rows each tenant receives out of `total_rows`
+    pub fn row_counts(&self, total_rows: usize) -> Vec<usize> {
+        self.skew.apportion(total_rows, self.tenants as usize)
+    }
+}
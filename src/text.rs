@@ -0,0 +1,285 @@
+//! Word-based text with an optional token-id companion column
+//!
+//! A `text` column holds sentences of words drawn from a fixed vocabulary
+//! with Zipf-distributed frequencies, so term statistics look like natural
+//! language to full-text indexes: a few very common words and a long tail.
+//! Words are built from syllables, with the most frequent words the
+//! shortest.
+//!
+//! With tokens enabled, a `text_tokens` column (`List<Int32>`) holds the
+//! token ids of the same text, produced by [`tokenize`]: words are split on
+//! non-alphanumeric characters, lowercased and hashed with the seed into
+//! [`TOKEN_BUCKETS`] ids. Anyone can reproduce the ids of a query string, so
+//! hybrid BM25 + vector pipelines get consistent token and embedding columns.
+//!
+//! With locales configured, every value is written in one locale picked per
+//! row, spelled in that locale's script, and a `lang` column records the
+//! locale of each row. Language filters are then truthfully correlated with
+//! the text they select.
+
+use anyhow::{bail, Result};
+use rand::distributions::{Distribution, WeightedIndex};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::derived::{fnv1a, fnv1a_extend};
+use crate::skew::Skew;
+
+/// Number of distinct token ids
+pub const TOKEN_BUCKETS: u32 = 1 << 18;
+
+/// Language of generated text, spelled in its own script
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Locale {
+    /// English, Latin script
+    #[default]
+    En,
+    /// Russian, Cyrillic script
+    Ru,
+    /// Greek
+    El,
+    /// Arabic
+    Ar,
+    /// Hindi, Devanagari script
+    Hi,
+    /// Japanese, Hiragana
+    Ja,
+    /// Korean, Hangul
+    Ko,
+    /// Chinese, Han characters
+    Zh,
+}
+
+impl Locale {
+    /// Every supported locale
+    pub const ALL: [Locale; 8] = [
+        Locale::En,
+        Locale::Ru,
+        Locale::El,
+        Locale::Ar,
+        Locale::Hi,
+        Locale::Ja,
+        Locale::Ko,
+        Locale::Zh,
+    ];
+
+    /// ISO 639-1 code, as written to the `lang` column
+    pub fn code(self) -> &'static str {
+        match self {
+            Locale::En => "en",
+            Locale::Ru => "ru",
+            Locale::El => "el",
+            Locale::Ar => "ar",
+            Locale::Hi => "hi",
+            Locale::Ja => "ja",
+            Locale::Ko => "ko",
+            Locale::Zh => "zh",
+        }
+    }
+
+    /// Syllables words of this locale are spelled with
+    fn syllables(self) -> &'static [&'static str; 16] {
+        match self {
+            Locale::En => &[
+                "ka", "ne", "to", "ri", "mo", "sa", "li", "ve", "du", "pa", "go", "ze", "bi", "fu",
+                "ho", "ja",
+            ],
+            Locale::Ru => &[
+                "ка", "не", "то", "ри", "мо", "са", "ли", "ве", "ду", "па", "го", "зе", "би", "фу",
+                "хо", "я",
+            ],
+            Locale::El => &[
+                "κα", "νε", "το", "ρι", "μο", "σα", "λι", "βε", "δυ", "πα", "γο", "ζε", "βι", "φυ",
+                "χο", "ια",
+            ],
+            Locale::Ar => &[
+                "كا", "ني", "تو", "ري", "مو", "سا", "لي", "في", "دو", "با", "غو", "زي", "بي", "فو",
+                "هو", "جا",
+            ],
+            Locale::Hi => &[
+                "का", "ने", "तो", "री", "मो", "सा", "ली", "वे", "दू", "पा", "गो", "जे", "बी", "फू",
+                "हो", "जा",
+            ],
+            Locale::Ja => &[
+                "か", "ね", "と", "り", "も", "さ", "し", "べ", "ど", "ぱ", "ご", "ぜ", "び", "ふ",
+                "ほ", "じゃ",
+            ],
+            Locale::Ko => &[
+                "가", "네", "토", "리", "모", "사", "시", "베", "두", "파", "고", "제", "비", "후",
+                "호", "자",
+            ],
+            Locale::Zh => &[
+                "卡", "内", "托", "里", "莫", "萨", "李", "维", "杜", "帕", "戈", "泽", "比", "福",
+                "霍", "贾",
+            ],
+        }
+    }
+}
+
+impl fmt::Display for Locale {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for Locale {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let code = s.trim().to_ascii_lowercase();
+        match Locale::ALL.into_iter().find(|locale| locale.code() == code) {
+            Some(locale) => Ok(locale),
+            None => bail!(
+                "unknown locale '{}', expected one of {}",
+                s,
+                Locale::ALL.map(Locale::code).join(", ")
+            ),
+        }
+    }
+}
+
+/// `text` column parameters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TextConfig {
+    /// Fewest words per value
+    pub min_words: usize,
+    /// Most words per value
+    pub max_words: usize,
+    /// Distinct words
+    pub vocabulary: usize,
+    /// Add a `text_tokens` column with the token ids of each value
+    pub tokens: bool,
+    /// Locales picked uniformly per row, adding a `lang` column; English
+    /// without a `lang` column if empty
+    #[serde(default)]
+    pub locales: Vec<Locale>,
+}
+
+impl TextConfig {
+    /// Check the parameters
+    pub fn validate(&self) -> Result<()> {
+        if self.min_words == 0 || self.min_words > self.max_words {
+            bail!(
+                "text needs 1 <= min words <= max words, got {} and {}",
+                self.min_words,
+                self.max_words
+            );
+        }
+        if self.vocabulary == 0 {
+            bail!("text vocabulary must hold at least one word");
+        }
+        for (i, locale) in self.locales.iter().enumerate() {
+            if self.locales[..i].contains(locale) {
+                bail!("text locale '{}' is listed twice", locale);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Words of a vocabulary in every configured locale and their Zipf sampler
+#[derive(Debug, Clone)]
+pub(crate) struct Vocabulary {
+    config: TextConfig,
+    /// Words by locale, then frequency rank
+    words: Vec<Vec<String>>,
+    sampler: WeightedIndex<f64>,
+}
+
+impl Vocabulary {
+    /// The `config.vocabulary` most frequent words of each locale
+    pub(crate) fn new(config: &TextConfig) -> Result<Self> {
+        let locales = match config.locales.as_slice() {
+            [] => &[Locale::En][..],
+            locales => locales,
+        };
+        Ok(Self {
+            config: config.clone(),
+            words: locales
+                .iter()
+                .map(|&locale| {
+                    (0..config.vocabulary)
+                        .map(|rank| word(locale, rank))
+                        .collect()
+                })
+                .collect(),
+            sampler: Skew::Zipf(1.0).sampler(config.vocabulary)?,
+        })
+    }
+
+    /// Append one value to `out`, returning its locale
+    ///
+    /// Every column of the text stream draws values through this method, so
+    /// text, tokens and locales stay aligned row by row.
+    pub(crate) fn sample<R: Rng + ?Sized>(&self, rng: &mut R, out: &mut String) -> Locale {
+        let locale = match self.config.locales.len() {
+            0 => 0,
+            n => rng.gen_range(0..n),
+        };
+        let count = rng.gen_range(self.config.min_words..=self.config.max_words);
+        for i in 0..count {
+            if i > 0 {
+                out.push(' ');
+            }
+            out.push_str(&self.words[locale][self.sampler.sample(rng)]);
+        }
+        self.config.locales.get(locale).copied().unwrap_or_default()
+    }
+
+    /// Approximate bytes of one value, for size estimates
+    pub(crate) fn mean_value_len(&self) -> usize {
+        // Frequent words are the short ones; weigh by the first thousand
+        let common: Vec<&String> = self
+            .words
+            .iter()
+            .flat_map(|words| &words[..words.len().min(1000)])
+            .collect();
+        let word_len = common.iter().map(|w| w.len()).sum::<usize>() / common.len().max(1);
+        (self.config.min_words + self.config.max_words) / 2 * (word_len + 1)
+    }
+
+    /// Bytes of the longest possible value
+    pub(crate) fn max_value_len(&self) -> usize {
+        let word_len = self
+            .words
+            .iter()
+            .flatten()
+            .map(String::len)
+            .max()
+            .unwrap_or(0);
+        self.config.max_words * (word_len + 1)
+    }
+
+    /// Mean words per value
+    pub(crate) fn mean_words(&self) -> usize {
+        (self.config.min_words + self.config.max_words) / 2
+    }
+}
+
+/// Spelling in `locale` of the word of frequency rank `rank` (0-based)
+///
+/// Ranks map to the same syllable sequence in every locale.
+fn word(locale: Locale, rank: usize) -> String {
+    let syllables = locale.syllables();
+    let mut word = String::new();
+    let mut n = rank + 1;
+    while n > 0 {
+        word.push_str(syllables[n % syllables.len()]);
+        n /= syllables.len();
+    }
+    word
+}
+
+/// Token ids of `text` under the tokenizer seeded with `seed`
+pub fn tokenize(text: &str, seed: u64) -> Vec<i32> {
+    let state = fnv1a(&seed.to_le_bytes());
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let hash = fnv1a_extend(state, word.to_lowercase().as_bytes());
+            (hash % TOKEN_BUCKETS as u64) as i32
+        })
+        .collect()
+}
@@ -0,0 +1,92 @@
+//! Timestamp columns
+//!
+//! Time-partitioned ingestion and TTL expiry key off a timestamp per row. A
+//! timestamp column holds `Timestamp(Millisecond)` values that are either
+//! uniformly random within `[start_ms, end_ms)`, drawn from the column's own
+//! stream, or monotonic: `start_ms + row * step_ms` for the row's position
+//! in the dataset, so timestamps increase across batches and files.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Default name of the timestamp column
+pub const DEFAULT_COLUMN: &str = "created_at";
+
+/// Values of a timestamp column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimestampValues {
+    /// Uniformly random in `[start_ms, end_ms)`
+    Uniform { start_ms: i64, end_ms: i64 },
+    /// `start_ms + row * step_ms` for the row's position in the dataset
+    Monotonic { start_ms: i64, step_ms: i64 },
+}
+
+impl TimestampValues {
+    /// Timestamp of the row at global index `row` of monotonic values
+    pub fn monotonic_ms(start_ms: i64, step_ms: i64, row: u64) -> i64 {
+        start_ms.saturating_add((row as i64).saturating_mul(step_ms))
+    }
+}
+
+/// Timestamp column added to every row
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TimestampConfig {
+    /// Column name
+    pub column: String,
+    pub values: TimestampValues,
+}
+
+impl TimestampConfig {
+    /// Check the column name and time range
+    pub fn validate(&self) -> Result<()> {
+        if self.column.is_empty() {
+            bail!("timestamp column name must not be empty");
+        }
+        match self.values {
+            TimestampValues::Uniform { start_ms, end_ms } if start_ms >= end_ms => {
+                bail!("timestamp range {}..{} is empty", start_ms, end_ms)
+            }
+            TimestampValues::Monotonic { step_ms, .. } if step_ms <= 0 => {
+                bail!("monotonic timestamp step must be greater than zero")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A range of milliseconds since the epoch, written `START..END`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeRange {
+    pub start_ms: i64,
+    pub end_ms: i64,
+}
+
+impl fmt::Display for TimeRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start_ms, self.end_ms)
+    }
+}
+
+impl FromStr for TimeRange {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let Some((start, end)) = s.split_once("..") else {
+            bail!(
+                "invalid time range '{}', expected START..END in milliseconds",
+                s
+            );
+        };
+        let millis = |n: &str| -> Result<i64> {
+            n.trim()
+                .parse()
+                .with_context(|| format!("invalid timestamp '{}'", n.trim()))
+        };
+        Ok(Self {
+            start_ms: millis(start)?,
+            end_ms: millis(end)?,
+        })
+    }
+}
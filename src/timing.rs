@@ -0,0 +1,143 @@
+//! Per-stage timing of file generation
+//!
+//! Splits the time spent producing a file into value generation (sampling
+//! into Arrow builders), Arrow batch assembly, Parquet encoding (including
+//! compression) and file IO, so the bottleneck of a given schema and codec
+//! combination is visible.
+
+use std::fmt;
+#[cfg(feature = "parquet")]
+use std::io::{self, Write};
+use std::ops::{Add, AddAssign};
+#[cfg(feature = "parquet")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "parquet")]
+use std::sync::Arc;
+use std::time::Duration;
+#[cfg(feature = "parquet")]
+use std::time::Instant;
+
+/// Time spent in each stage of producing output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StageTimings {
+    /// Sampling values and appending them to Arrow builders (column tasks)
+    pub generate: Duration,
+    /// Assembling and validating record batches
+    pub arrow: Duration,
+    /// Parquet encoding and compression
+    pub encode: Duration,
+    /// Writing to and flushing the output file
+    pub io: Duration,
+}
+
+impl StageTimings {
+    /// Sum of all stages
+    pub fn total(&self) -> Duration {
+        self.generate + self.arrow + self.encode + self.io
+    }
+}
+
+impl Add for StageTimings {
+    type Output = StageTimings;
+
+    fn add(self, other: StageTimings) -> StageTimings {
+        StageTimings {
+            generate: self.generate + other.generate,
+            arrow: self.arrow + other.arrow,
+            encode: self.encode + other.encode,
+            io: self.io + other.io,
+        }
+    }
+}
+
+impl AddAssign for StageTimings {
+    fn add_assign(&mut self, other: StageTimings) {
+        *self = *self + other;
+    }
+}
+
+impl fmt::Display for StageTimings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total().as_secs_f64().max(f64::MIN_POSITIVE);
+        let stages = [
+            ("generate", self.generate),
+            ("arrow", self.arrow),
+            ("encode", self.encode),
+            ("io", self.io),
+        ];
+        for (i, (name, duration)) in stages.into_iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            write!(
+                f,
+                "{} {:.2?} ({:.0}%)",
+                name,
+                duration,
+                100.0 * duration.as_secs_f64() / total
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Writer recording the time spent in the inner writer's calls
+#[cfg(feature = "parquet")]
+#[derive(Debug)]
+pub(crate) struct TimedWrite<W> {
+    inner: W,
+    nanos: Arc<AtomicU64>,
+}
+
+#[cfg(feature = "parquet")]
+impl<W> TimedWrite<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        Self {
+            inner,
+            nanos: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Shared counter of nanoseconds spent writing
+    pub(crate) fn counter(&self) -> Arc<AtomicU64> {
+        Arc::clone(&self.nanos)
+    }
+
+    /// Mutable access to the inner writer, bypassing the timer
+    pub(crate) fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub(crate) fn into_inner(self) -> W {
+        self.inner
+    }
+
+    fn timed<T>(&mut self, f: impl FnOnce(&mut W) -> T) -> T {
+        let start = Instant::now();
+        let result = f(&mut self.inner);
+        self.nanos
+            .fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        result
+    }
+}
+
+#[cfg(feature = "parquet")]
+impl<W: Write> Write for TimedWrite<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.timed(|w| w.write(buf))
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.timed(|w| w.write_all(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.timed(|w| w.flush())
+    }
+}
+
+/// Nanoseconds recorded by a [`TimedWrite`] counter as a duration
+#[cfg(feature = "parquet")]
+pub(crate) fn counted(nanos: &AtomicU64) -> Duration {
+    Duration::from_nanos(nanos.load(Ordering::Relaxed))
+}
@@ -0,0 +1,134 @@
+//! Time-series vector trajectories
+//!
+//! In trajectory mode rows cycle through a fixed set of entities. Each time an
+//! entity reappears its vector has moved by a small Gaussian step and its
+//! timestamp has advanced, simulating items that are re-embedded over time.
+
+use anyhow::{bail, Result};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use rand_distr::{Distribution, Normal};
+use serde::{Deserialize, Serialize};
+
+use crate::core_gen::{stream_seed, VectorDistribution};
+
+/// Stream identifier for the initial entity positions
+const TRAJECTORY_STREAM: u64 = u64::MAX - 1;
+
+/// Trajectory mode parameters
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TrajectoryConfig {
+    /// Number of distinct entities; row `i` belongs to entity `i % entities`
+    pub entities: usize,
+    /// Standard deviation of each component's random-walk step
+    pub step_std: f64,
+    /// Timestamp of every entity's first row, in milliseconds since the epoch
+    pub start_ms: i64,
+    /// Time between consecutive rows of the same entity, in milliseconds
+    pub interval_ms: i64,
+}
+
+impl TrajectoryConfig {
+    /// Check the parameters
+    pub fn validate(&self) -> Result<()> {
+        if self.entities == 0 {
+            bail!("trajectory entity count must be greater than zero");
+        }
+        if !self.step_std.is_finite() || self.step_std < 0.0 {
+            bail!("trajectory step std must be a non-negative number");
+        }
+        if self.interval_ms <= 0 {
+            bail!("trajectory interval must be positive");
+        }
+        Ok(())
+    }
+}
+
+/// Current position of every entity, carried from batch to batch and file to file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrajectoryState {
+    dim: usize,
+    /// Positions laid out as `[entity][dim]`
+    positions: Vec<f32>,
+    /// Global index of the next row
+    next_row: u64,
+}
+
+/// Rows produced by one step of the trajectory state
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TrajectoryBlock {
+    /// Vector values laid out as `[row][dim]`
+    pub(crate) vectors: Vec<f64>,
+    pub(crate) entity_ids: Vec<i64>,
+    pub(crate) timestamps_ms: Vec<i64>,
+}
+
+impl TrajectoryState {
+    /// Place every entity at its seeded starting position, drawn from
+    /// `distribution`
+    pub fn new(
+        config: &TrajectoryConfig,
+        dim: usize,
+        distribution: VectorDistribution,
+        seed: u64,
+    ) -> Self {
+        let mut positions = Vec::with_capacity(config.entities * dim);
+        for entity in 0..config.entities {
+            let mut rng =
+                StdRng::seed_from_u64(stream_seed(seed, &[TRAJECTORY_STREAM, entity as u64]));
+            positions.extend(
+                distribution
+                    .sample(&mut rng, dim)
+                    .into_iter()
+                    .map(|v| v as f32),
+            );
+        }
+        Self {
+            dim,
+            positions,
+            next_row: 0,
+        }
+    }
+
+    /// Global index of the next row to be generated
+    pub fn next_row(&self) -> u64 {
+        self.next_row
+    }
+
+    /// Advance the walk by `rows` rows, drawing steps from `rng`
+    pub(crate) fn advance(
+        &mut self,
+        config: &TrajectoryConfig,
+        rng: &mut StdRng,
+        rows: usize,
+    ) -> Result<TrajectoryBlock> {
+        let step = Normal::new(0.0, config.step_std)?;
+        let entities = config.entities as u64;
+        let mut block = TrajectoryBlock {
+            vectors: Vec::with_capacity(rows * self.dim),
+            entity_ids: Vec::with_capacity(rows),
+            timestamps_ms: Vec::with_capacity(rows),
+        };
+
+        for _ in 0..rows {
+            let row = self.next_row;
+            self.next_row += 1;
+            let entity = (row % entities) as usize;
+            let visit = (row / entities) as i64;
+
+            let position = &mut self.positions[entity * self.dim..(entity + 1) * self.dim];
+            if visit > 0 {
+                for value in position.iter_mut() {
+                    *value += step.sample(rng) as f32;
+                }
+            }
+
+            block.vectors.extend(position.iter().map(|&v| v as f64));
+            block.entity_ids.push(entity as i64);
+            block
+                .timestamps_ms
+                .push(config.start_ms + visit * config.interval_ms);
+        }
+        Ok(block)
+    }
+}
@@ -0,0 +1,223 @@
+//! Generator algorithm versions
+//!
+//! Output for a seed is only reproducible as long as the algorithm consuming
+//! the RNG stays the same. Every algorithm change that alters output gets a new
+//! [`GeneratorVersion`]; older versions stay available so data generated by an
+//! earlier release can be reproduced exactly. The version is recorded in the
+//! key-value metadata of every Parquet file under [`GENERATOR_VERSION_KEY`].
+
+use anyhow::{bail, Result};
+use arrow::array::{ArrayRef, BinaryBuilder, StringBuilder};
+use arrow::datatypes::SchemaRef;
+use arrow::record_batch::RecordBatch;
+use rand::distributions::{Distribution, Uniform};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::core_gen::sample_scalar;
+use crate::{Config, VectorElementType};
+
+/// Parquet key-value metadata key holding the generator version
+pub const GENERATOR_VERSION_KEY: &str = "vdg.generator_version";
+
+/// Version of the generation algorithm
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+pub enum GeneratorVersion {
+    /// Original algorithm: one RNG per file seeded with `seed + file_index`,
+    /// drawing each row's f32 vector and then its scalar. Supports only the
+    /// `vector` and `scalar` columns.
+    V0,
+    /// Independent RNG streams per file, batch and column, vectors drawn at
+    /// double precision
+    #[default]
+    V1,
+}
+
+impl GeneratorVersion {
+    /// The version used unless configured otherwise
+    pub const LATEST: GeneratorVersion = GeneratorVersion::V1;
+
+    /// Every known version, oldest first
+    pub const ALL: [GeneratorVersion; 2] = [GeneratorVersion::V0, GeneratorVersion::V1];
+
+    /// Numeric version
+    pub fn number(self) -> u32 {
+        match self {
+            GeneratorVersion::V0 => 0,
+            GeneratorVersion::V1 => 1,
+        }
+    }
+
+    /// Check that `config` only uses features this version can generate
+    pub fn validate(self, config: &Config) -> Result<()> {
+        if self != GeneratorVersion::V0 {
+            return Ok(());
+        }
+        // Listing every field makes each new option decide whether v0
+        // supports it
+        let Config {
+            // What v0 generates
+            vector_dim: _,
+            scalar_len: _,
+            seed: _,
+            generator_version: _,
+            // Writer and run settings that leave the rows unchanged
+            target_file_size: _,
+            compression: _,
+            cancel_policy: _,
+            row_group_size: _,
+            parallel_batches: _,
+            holdout_rows: _,
+            value_samples: _,
+            index_column: _,
+            deletes: _,
+            // Everything added since
+            scalar_prefix,
+            id_column,
+            timestamp,
+            schema,
+            correlation,
+            vector_columns,
+            vector_type,
+            mixed_precision,
+            large_binary,
+            vector_layout,
+            vector_encoding,
+            vector_distribution,
+            mixture,
+            normalize,
+            quantization,
+            int8_scale,
+            pq,
+            tenants,
+            trajectory,
+            edges,
+            foreign_key,
+            sessions,
+            duplicates,
+            pruning,
+            page_index,
+            payload,
+            sparse,
+            wide,
+            text,
+            null_columns,
+            null_ratios,
+            constant_columns,
+            shuffle_buffer,
+            queries,
+            workload,
+            derived,
+            monotonic,
+            bucketing,
+            clustering,
+        } = config;
+        let unsupported = [
+            ("scalar_prefix", scalar_prefix.is_some()),
+            ("id_column", id_column.is_some()),
+            ("timestamp", timestamp.is_some()),
+            ("schema", schema.is_some()),
+            ("correlation", correlation.is_some()),
+            ("vector_columns", *vector_columns != 1),
+            ("vector_type", *vector_type != VectorElementType::Float32),
+            ("mixed_precision", mixed_precision.is_some()),
+            ("large_binary", *large_binary),
+            ("vector_layout", *vector_layout != Default::default()),
+            ("vector_encoding", *vector_encoding != Default::default()),
+            (
+                "vector_distribution",
+                *vector_distribution != Default::default(),
+            ),
+            ("mixture", mixture.is_some()),
+            ("normalize", *normalize),
+            ("quantization", quantization.is_some()),
+            ("int8_scale", *int8_scale),
+            ("pq", pq.is_some()),
+            ("tenants", tenants.is_some()),
+            ("trajectory", trajectory.is_some()),
+            ("edges", edges.is_some()),
+            ("foreign_key", foreign_key.is_some()),
+            ("sessions", sessions.is_some()),
+            ("duplicates", duplicates.is_some()),
+            ("pruning", pruning.is_some()),
+            ("page_index", page_index.is_some()),
+            ("payload", payload.is_some()),
+            ("sparse", sparse.is_some()),
+            ("wide", wide.is_some()),
+            ("text", text.is_some()),
+            ("null_columns", !null_columns.is_empty()),
+            ("null_ratios", !null_ratios.is_empty()),
+            ("constant_columns", !constant_columns.is_empty()),
+            ("shuffle_buffer", shuffle_buffer.is_some()),
+            ("queries", queries.is_some()),
+            ("workload", workload.is_some()),
+            ("derived", !derived.is_empty()),
+            ("monotonic", !monotonic.is_empty()),
+            ("bucketing", bucketing.is_some()),
+            ("clustering", clustering.is_some()),
+        ];
+        if let Some((name, _)) = unsupported.iter().find(|(_, used)| *used) {
+            bail!(
+                "`{}` is not supported by generator version v0, which only writes f32 \
+                 `vector` and `scalar` columns",
+                name
+            );
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for GeneratorVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}", self.number())
+    }
+}
+
+impl FromStr for GeneratorVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let number = s.trim().trim_start_matches(['v', 'V']);
+        GeneratorVersion::ALL
+            .into_iter()
+            .find(|v| v.number().to_string() == number)
+            .ok_or_else(|| anyhow::anyhow!("unknown generator version '{}'", s))
+    }
+}
+
+/// Seed of the single RNG of a v0 file
+pub(crate) fn v0_file_seed(seed: u64, file_index: u64) -> u64 {
+    seed.wrapping_add(file_index)
+}
+
+/// Generate a v0 batch of `vector` and `scalar` columns from the file RNG
+pub(crate) fn generate_v0_batch(
+    rng: &mut ChaCha12Rng,
+    schema: SchemaRef,
+    dim: usize,
+    scalar_len: usize,
+    rows: usize,
+) -> Result<RecordBatch> {
+    let uniform = Uniform::new(-1.0f32, 1.0);
+    let mut vectors = BinaryBuilder::with_capacity(rows, rows * dim * 4);
+    let mut scalars = StringBuilder::with_capacity(rows, rows * scalar_len);
+    let mut bytes = Vec::with_capacity(dim * 4);
+    for _ in 0..rows {
+        bytes.clear();
+        for _ in 0..dim {
+            bytes.extend_from_slice(&uniform.sample(rng).to_le_bytes());
+        }
+        vectors.append_value(&bytes);
+        scalars.append_value(sample_scalar(rng, scalar_len));
+    }
+    Ok(RecordBatch::try_new(
+        schema,
+        vec![
+            Arc::new(vectors.finish()) as ArrayRef,
+            Arc::new(scalars.finish()) as ArrayRef,
+        ],
+    )?)
+}
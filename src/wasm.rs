@@ -0,0 +1,81 @@
+//! WebAssembly bindings for the documentation playground
+//!
+//! Exposes batch generation to JavaScript as in-memory Parquet files or Arrow
+//! IPC streams, so a small sample can be generated and downloaded or loaded
+//! into Arrow JS in the browser. Configurations are passed as JSON in the
+//! same shape as [`crate::Config`] (see [`crate::config_format`]); omitted
+//! fields take their defaults. Zstd compression is unavailable on wasm32.
+
+use arrow::ipc::writer::StreamWriter;
+use wasm_bindgen::prelude::*;
+
+use crate::{config_format, DataGenerator, ParquetWriter};
+
+/// Generator handle held by JavaScript
+#[wasm_bindgen]
+pub struct WasmGenerator {
+    generator: DataGenerator,
+    writer: ParquetWriter,
+}
+
+#[wasm_bindgen]
+impl WasmGenerator {
+    /// Create a generator from a JSON configuration, e.g. `{"vector_dim": 128, "seed": 7}`
+    #[wasm_bindgen(constructor)]
+    pub fn new(config_json: &str) -> Result<WasmGenerator, JsError> {
+        let config = config_format::from_json(config_json).map_err(to_js)?;
+        let generator = DataGenerator::try_new(config.clone()).map_err(to_js)?;
+        Ok(Self {
+            generator,
+            writer: ParquetWriter::new(config),
+        })
+    }
+
+    /// Generate `rows` rows and return them as the bytes of a Parquet file
+    #[wasm_bindgen(js_name = parquetSample)]
+    pub fn parquet_sample(&mut self, rows: usize, batch_size: usize) -> Result<Vec<u8>, JsError> {
+        self.writer
+            .write_to_bytes(&mut self.generator, rows, batch_size)
+            .map_err(to_js)
+    }
+
+    /// Generate `rows` rows and return them as an Arrow IPC stream, as read by
+    /// `tableFromIPC` of Arrow JS
+    #[wasm_bindgen(js_name = ipcSample)]
+    pub fn ipc_sample(&mut self, rows: usize, batch_size: usize) -> Result<Vec<u8>, JsError> {
+        ipc_bytes(&mut self.generator, rows, batch_size).map_err(to_js)
+    }
+
+    /// Column names of the generated schema
+    #[wasm_bindgen(js_name = columnNames)]
+    pub fn column_names(&self) -> Vec<String> {
+        self.generator
+            .schema()
+            .fields()
+            .iter()
+            .map(|f| f.name().clone())
+            .collect()
+    }
+}
+
+/// Arrow IPC stream of `rows` rows from `generator`, in batches of at most
+/// `batch_size` rows
+pub(crate) fn ipc_bytes(
+    generator: &mut DataGenerator,
+    rows: usize,
+    batch_size: usize,
+) -> anyhow::Result<Vec<u8>> {
+    let mut writer = StreamWriter::try_new(Vec::new(), generator.schema())?;
+    let batch_size = batch_size.max(1).min(generator.max_batch_rows());
+    let mut remaining_rows = rows;
+    while remaining_rows > 0 {
+        let batch = generator.generate_batch(batch_size.min(remaining_rows))?;
+        writer.write(&batch)?;
+        remaining_rows -= batch.num_rows();
+    }
+    Ok(writer.into_inner()?)
+}
+
+fn to_js(e: anyhow::Error) -> JsError {
+    JsError::new(&format!("{:#}", e))
+}
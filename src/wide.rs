@@ -0,0 +1,119 @@
+//! Wide logical schemas written as per-file column subsets
+//!
+//! A table of `columns` extra columns (`wide_0000`, `wide_0001`, ...) is
+//! defined once, but every file writes only some of them, as happens after
+//! schema evolution or in sparse wide tables. Readers have to merge the file
+//! schemas into the logical one and fill the missing columns with nulls.
+//! Every column keeps its RNG stream, so its values in a file do not depend
+//! on which other columns the file holds.
+
+use anyhow::{bail, Result};
+use arrow::datatypes::DataType;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+use crate::core_gen::stream_seed;
+
+/// Length of the strings in `Utf8` wide columns
+pub const STRING_LEN: usize = 8;
+
+/// Stream identifier for the per-file column choice
+const SUBSET_STREAM: u64 = u64::MAX - 5;
+
+/// How the columns written to each file are chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ColumnSubset {
+    /// A random set of `per_file` columns per file
+    #[default]
+    Random,
+    /// File `i` holds the first `per_file + i` columns, as if one column was
+    /// added to the table per file
+    Evolving,
+}
+
+/// Wide schema parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WideConfig {
+    /// Columns of the logical schema
+    pub columns: usize,
+    /// Columns written to each file (to the first file with `Evolving`)
+    pub per_file: usize,
+    /// How each file's columns are chosen
+    pub subset: ColumnSubset,
+}
+
+impl WideConfig {
+    /// Check the parameters
+    pub fn validate(&self) -> Result<()> {
+        if self.columns == 0 {
+            bail!("wide schemas need at least one column");
+        }
+        if self.per_file == 0 || self.per_file > self.columns {
+            bail!(
+                "columns per file must be between 1 and {}, got {}",
+                self.columns,
+                self.per_file
+            );
+        }
+        Ok(())
+    }
+
+    /// Indices of the wide columns written to file `file_index`, ascending
+    pub fn file_columns(&self, seed: u64, file_index: u64) -> Vec<usize> {
+        match self.subset {
+            ColumnSubset::Random => {
+                let mut rng =
+                    StdRng::seed_from_u64(stream_seed(seed, &[SUBSET_STREAM, file_index]));
+                let mut picked =
+                    rand::seq::index::sample(&mut rng, self.columns, self.per_file).into_vec();
+                picked.sort_unstable();
+                picked
+            }
+            ColumnSubset::Evolving => {
+                let count = (self.per_file as u64).saturating_add(file_index);
+                (0..count.min(self.columns as u64) as usize).collect()
+            }
+        }
+    }
+}
+
+/// Name of wide column `index`
+pub fn column_name(index: usize) -> String {
+    format!("wide_{:04}", index)
+}
+
+/// Type of wide column `index`; types cycle through Int64, Float64 and Utf8
+pub fn column_type(index: usize) -> DataType {
+    match index % 3 {
+        0 => DataType::Int64,
+        1 => DataType::Float64,
+        _ => DataType::Utf8,
+    }
+}
+
+impl fmt::Display for ColumnSubset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ColumnSubset::Random => "random",
+            ColumnSubset::Evolving => "evolving",
+        })
+    }
+}
+
+impl FromStr for ColumnSubset {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "random" => Ok(ColumnSubset::Random),
+            "evolving" => Ok(ColumnSubset::Evolving),
+            other => bail!(
+                "unknown column subset '{}', expected random or evolving",
+                other
+            ),
+        }
+    }
+}
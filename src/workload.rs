@@ -0,0 +1,581 @@
+//! Query workloads
+//!
+//! A benchmark driver replaying a realistic workload needs more than query
+//! vectors: each query also asks for its own number of neighbors, and some
+//! carry a filter. With a workload configured, every query of the query set
+//! gets a `k` drawn from a [`KDistribution`] and, with probability
+//! `filtered_fraction`, a [`Predicate`] on one base column whose expected
+//! selectivity (fraction of base rows it keeps) is drawn log-uniformly from
+//! the configured range. The query file gains the columns `k` (Int32),
+//! `filter` (Utf8, e.g. `score < 0.0125`, null for unfiltered queries) and
+//! `selectivity` (Float64, the expected fraction of matching rows).
+//!
+//! Predicates are built from what the generator knows about the column:
+//!
+//! - Float32/Float64 columns of a schema spec: `column < q`, with `q` the
+//!   `s`-quantile of the column's [distribution](crate::numeric)
+//! - Int32/Int64 columns of a schema spec: `column < t`, with `t` the
+//!   `s`-quantile of the distribution or the type range
+//! - `tenant_id`: `tenant_id = t` for the tenant whose share of the rows is
+//!   closest to `s`
+//!
+//! Null values never match, so null ratios lower the selectivity. Workload
+//! values are drawn from their own stream: the query vectors are the same
+//! with or without a workload. [`Predicate::evaluate`] applies a filter to a
+//! batch of base rows, for filtered ground truth.
+
+use anyhow::{anyhow, bail, Context, Result};
+use arrow::array::{Array, AsArray};
+#[cfg(feature = "parquet")]
+use arrow::array::{ArrayRef, Float64Array, Int32Array, StringArray};
+use arrow::datatypes::{
+    DataType, Field, Float32Type, Float64Type, Int16Type, Int32Type, Int64Type, Int8Type,
+    UInt16Type, UInt32Type, UInt8Type,
+};
+use arrow::record_batch::RecordBatch;
+#[cfg(feature = "parquet")]
+use rand::distributions::{Distribution, WeightedIndex};
+#[cfg(feature = "parquet")]
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+#[cfg(feature = "parquet")]
+use std::sync::Arc;
+
+#[cfg(feature = "parquet")]
+use crate::columns::{columns_for, ColumnKind};
+#[cfg(feature = "parquet")]
+use crate::numeric::NumericDistribution;
+#[cfg(feature = "parquet")]
+use crate::Config;
+
+/// Distribution of the neighbors requested per query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum KDistribution {
+    /// Every query asks for `k` neighbors, written `100`
+    Fixed(usize),
+    /// Uniform over `min..=max`, written `10..100`
+    Uniform { min: usize, max: usize },
+    /// One of a few values with relative weights, written `10:0.8,100:0.2`
+    Weighted(Vec<(usize, f64)>),
+}
+
+impl Default for KDistribution {
+    fn default() -> Self {
+        KDistribution::Fixed(100)
+    }
+}
+
+impl KDistribution {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            KDistribution::Fixed(0) => bail!("k must be at least 1"),
+            KDistribution::Uniform { min, max } if *min == 0 || min > max => {
+                bail!(
+                    "k range {}..{} must be non-empty and start at 1 or more",
+                    min,
+                    max
+                )
+            }
+            KDistribution::Weighted(choices) => {
+                if choices.is_empty() || choices.iter().any(|&(k, _)| k == 0) {
+                    bail!("k choices must be at least 1");
+                }
+                if choices.iter().any(|&(_, w)| !w.is_finite() || w < 0.0)
+                    || choices.iter().all(|&(_, w)| w == 0.0)
+                {
+                    bail!("k weights must be non-negative and not all zero");
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Largest `k` any query can ask for
+    pub fn max(&self) -> usize {
+        match self {
+            KDistribution::Fixed(k) => *k,
+            KDistribution::Uniform { max, .. } => *max,
+            KDistribution::Weighted(choices) => choices.iter().map(|&(k, _)| k).max().unwrap_or(0),
+        }
+    }
+}
+
+impl fmt::Display for KDistribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KDistribution::Fixed(k) => write!(f, "{}", k),
+            KDistribution::Uniform { min, max } => write!(f, "{}..{}", min, max),
+            KDistribution::Weighted(choices) => {
+                for (i, (k, weight)) in choices.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(",")?;
+                    }
+                    write!(f, "{}:{}", k, weight)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl FromStr for KDistribution {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let number = |n: &str| -> Result<usize> {
+            n.trim()
+                .parse()
+                .with_context(|| format!("invalid k '{}'", n.trim()))
+        };
+        let distribution = if let Some((min, max)) = s.split_once("..") {
+            KDistribution::Uniform {
+                min: number(min)?,
+                max: number(max)?,
+            }
+        } else if s.contains(':') || s.contains(',') {
+            let choices = s
+                .split(',')
+                .map(|choice| {
+                    let (k, weight) = choice.split_once(':').unwrap_or((choice, "1"));
+                    let weight = weight
+                        .trim()
+                        .parse()
+                        .with_context(|| format!("invalid k weight '{}'", weight.trim()))?;
+                    Ok((number(k)?, weight))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            KDistribution::Weighted(choices)
+        } else {
+            KDistribution::Fixed(number(s)?)
+        };
+        distribution.validate()?;
+        Ok(distribution)
+    }
+}
+
+/// Comparison of a [`Predicate`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CompareOp {
+    Eq,
+    Lt,
+    LtEq,
+    Gt,
+    GtEq,
+}
+
+impl CompareOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            CompareOp::Eq => "=",
+            CompareOp::Lt => "<",
+            CompareOp::LtEq => "<=",
+            CompareOp::Gt => ">",
+            CompareOp::GtEq => ">=",
+        }
+    }
+
+    /// Whether a value ordered `ordering` against the literal passes
+    fn accepts(self, ordering: Ordering) -> bool {
+        match self {
+            CompareOp::Eq => ordering.is_eq(),
+            CompareOp::Lt => ordering.is_lt(),
+            CompareOp::LtEq => ordering.is_le(),
+            CompareOp::Gt => ordering.is_gt(),
+            CompareOp::GtEq => ordering.is_ge(),
+        }
+    }
+}
+
+/// Literal of a [`Predicate`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Literal {
+    Int(i64),
+    Float(f64),
+}
+
+impl Literal {
+    /// Order of `self` and `other`; integers compare exactly, anything else
+    /// as `f64`, with NaN unordered
+    fn compare(self, other: Literal) -> Option<Ordering> {
+        match (self, other) {
+            (Literal::Int(a), Literal::Int(b)) => Some(a.cmp(&b)),
+            (a, b) => a.as_f64().partial_cmp(&b.as_f64()),
+        }
+    }
+
+    fn as_f64(self) -> f64 {
+        match self {
+            Literal::Int(value) => value as f64,
+            Literal::Float(value) => value,
+        }
+    }
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Literal::Int(value) => write!(f, "{}", value),
+            Literal::Float(value) => write!(f, "{:?}", value),
+        }
+    }
+}
+
+/// Filter of one query: `column op literal`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Predicate {
+    pub column: String,
+    pub op: CompareOp,
+    pub value: Literal,
+}
+
+impl Predicate {
+    /// Whether each row of `batch` passes the filter; null values never do
+    pub fn evaluate(&self, batch: &RecordBatch) -> Result<Vec<bool>> {
+        let Some(array) = batch.column_by_name(&self.column) else {
+            bail!("filter column '{}' is missing", self.column);
+        };
+        let value: Box<dyn Fn(usize) -> Literal + '_> = match array.data_type() {
+            DataType::Int8 => int_values::<Int8Type>(array.as_ref()),
+            DataType::Int16 => int_values::<Int16Type>(array.as_ref()),
+            DataType::Int32 => int_values::<Int32Type>(array.as_ref()),
+            DataType::Int64 => int_values::<Int64Type>(array.as_ref()),
+            DataType::UInt8 => int_values::<UInt8Type>(array.as_ref()),
+            DataType::UInt16 => int_values::<UInt16Type>(array.as_ref()),
+            DataType::UInt32 => int_values::<UInt32Type>(array.as_ref()),
+            DataType::Float32 => {
+                let values = array.as_primitive::<Float32Type>();
+                Box::new(move |row| Literal::Float(values.value(row) as f64))
+            }
+            DataType::Float64 => {
+                let values = array.as_primitive::<Float64Type>();
+                Box::new(move |row| Literal::Float(values.value(row)))
+            }
+            other => bail!(
+                "cannot filter on column '{}' of type {}",
+                self.column,
+                other
+            ),
+        };
+        Ok((0..array.len())
+            .map(|row| {
+                array.is_valid(row)
+                    && value(row)
+                        .compare(self.value)
+                        .is_some_and(|ordering| self.op.accepts(ordering))
+            })
+            .collect())
+    }
+}
+
+/// Integer values of a primitive array as literals
+fn int_values<T>(array: &dyn Array) -> Box<dyn Fn(usize) -> Literal + '_>
+where
+    T: arrow::datatypes::ArrowPrimitiveType,
+    T::Native: Into<i64>,
+{
+    let values = array.as_primitive::<T>();
+    Box::new(move |row| Literal::Int(values.value(row).into()))
+}
+
+impl fmt::Display for Predicate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.column, self.op.symbol(), self.value)
+    }
+}
+
+impl FromStr for Predicate {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        // Two-character operators first, so `<=` is not read as `<`
+        let (position, op) = [
+            CompareOp::LtEq,
+            CompareOp::GtEq,
+            CompareOp::Lt,
+            CompareOp::Gt,
+            CompareOp::Eq,
+        ]
+        .into_iter()
+        .find_map(|op| s.find(op.symbol()).map(|position| (position, op)))
+        .ok_or_else(|| anyhow!("invalid filter '{}', expected COLUMN OP VALUE", s))?;
+        let column = s[..position].trim();
+        let value = s[position + op.symbol().len()..].trim();
+        if column.is_empty() {
+            bail!("invalid filter '{}', expected COLUMN OP VALUE", s);
+        }
+        let value = match value.parse::<i64>() {
+            Ok(value) => Literal::Int(value),
+            Err(_) => Literal::Float(
+                value
+                    .parse()
+                    .with_context(|| format!("invalid value '{}' in filter '{}'", value, s))?,
+            ),
+        };
+        Ok(Self {
+            column: column.to_string(),
+            op,
+            value,
+        })
+    }
+}
+
+/// Per-query `k` and filters added to a query set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkloadConfig {
+    pub k: KDistribution,
+    /// Column the filters apply to; unfiltered workload if `None`
+    pub filter_column: Option<String>,
+    /// Smallest and largest expected selectivity of a filter
+    pub selectivity: (f64, f64),
+    /// Fraction of queries with a filter
+    pub filtered_fraction: f64,
+}
+
+impl Default for WorkloadConfig {
+    fn default() -> Self {
+        Self {
+            k: KDistribution::default(),
+            filter_column: None,
+            selectivity: (0.01, 0.01),
+            filtered_fraction: 1.0,
+        }
+    }
+}
+
+impl WorkloadConfig {
+    pub fn validate(&self) -> Result<()> {
+        self.k.validate()?;
+        let (min, max) = self.selectivity;
+        if !(min > 0.0 && min <= max && max <= 1.0) {
+            bail!(
+                "selectivity range {}..{} must lie within (0, 1] and be non-empty",
+                min,
+                max
+            );
+        }
+        if !(0.0..=1.0).contains(&self.filtered_fraction) {
+            bail!("filtered fraction must be between 0 and 1");
+        }
+        Ok(())
+    }
+}
+
+/// Parse a selectivity range written `0.01` or `0.001..0.1`
+pub fn parse_selectivity(s: &str) -> Result<(f64, f64)> {
+    let number = |n: &str| -> Result<f64> {
+        n.trim()
+            .parse()
+            .with_context(|| format!("invalid selectivity '{}'", n.trim()))
+    };
+    match s.split_once("..") {
+        Some((min, max)) => Ok((number(min)?, number(max)?)),
+        None => {
+            let value = number(s)?;
+            Ok((value, value))
+        }
+    }
+}
+
+/// Fields added to the query file by a workload
+pub fn fields() -> Vec<Field> {
+    vec![
+        Field::new("k", DataType::Int32, false),
+        Field::new("filter", DataType::Utf8, true),
+        Field::new("selectivity", DataType::Float64, true),
+    ]
+}
+
+/// How filters on the configured column are made
+#[cfg(feature = "parquet")]
+#[derive(Debug, Clone)]
+enum FilterKind {
+    /// `column < q` over floats, `q` the `s`-quantile of their distribution
+    Float { distribution: NumericDistribution },
+    /// `column < t` over integers uniform in `[min, min + span)`
+    Int { min: f64, span: f64 },
+    /// `column < t` over integers of a distribution
+    IntDistribution { distribution: NumericDistribution },
+    /// `column = key` over keys with these shares of the rows
+    Key { shares: Vec<f64> },
+}
+
+/// Workload columns generator for one query set
+#[cfg(feature = "parquet")]
+pub(crate) struct WorkloadPlan {
+    config: WorkloadConfig,
+    k: Option<WeightedIndex<f64>>,
+    filter: Option<(String, FilterKind, f64)>,
+}
+
+#[cfg(feature = "parquet")]
+impl WorkloadPlan {
+    pub(crate) fn new(config: &Config, workload: &WorkloadConfig) -> Result<Self> {
+        workload.validate()?;
+        let k = match &workload.k {
+            KDistribution::Weighted(choices) => {
+                Some(WeightedIndex::new(choices.iter().map(|&(_, w)| w))?)
+            }
+            _ => None,
+        };
+        let filter = match &workload.filter_column {
+            Some(name) => Some(filter_kind(config, name)?),
+            None => None,
+        };
+        Ok(Self {
+            config: workload.clone(),
+            k,
+            filter,
+        })
+    }
+
+    /// Columns `k`, `filter` and `selectivity` of `rows` queries
+    pub(crate) fn generate<R: Rng>(&self, rng: &mut R, rows: usize) -> Vec<ArrayRef> {
+        let mut ks = Vec::with_capacity(rows);
+        let mut filters = Vec::with_capacity(rows);
+        let mut selectivities = Vec::with_capacity(rows);
+        for _ in 0..rows {
+            let k = match (&self.config.k, &self.k) {
+                (KDistribution::Fixed(k), _) => *k,
+                (KDistribution::Uniform { min, max }, _) => rng.gen_range(*min..=*max),
+                (KDistribution::Weighted(choices), Some(index)) => choices[index.sample(rng)].0,
+                (KDistribution::Weighted(choices), None) => choices[0].0,
+            };
+            ks.push(k as i32);
+            let filtered = rng.gen_bool(self.config.filtered_fraction);
+            match &self.filter {
+                Some((column, kind, valid)) if filtered => {
+                    let (min, max) = self.config.selectivity;
+                    let target = (min.ln() + rng.gen::<f64>() * (max.ln() - min.ln())).exp();
+                    let (predicate, selectivity) = predicate(column, kind, target);
+                    filters.push(Some(predicate.to_string()));
+                    selectivities.push(Some(selectivity * valid));
+                }
+                _ => {
+                    filters.push(None);
+                    selectivities.push(None);
+                }
+            }
+        }
+        vec![
+            Arc::new(Int32Array::from(ks)),
+            Arc::new(StringArray::from(filters)),
+            Arc::new(Float64Array::from(selectivities)),
+        ]
+    }
+}
+
+/// Filter kind of column `name` of `config`, and the fraction of its values
+/// that are not null
+#[cfg(feature = "parquet")]
+fn filter_kind(config: &Config, name: &str) -> Result<(String, FilterKind, f64)> {
+    let columns = columns_for(config)?;
+    let Some(column) = columns.iter().find(|c| c.field.name() == name) else {
+        bail!("unknown filter column '{}'", name);
+    };
+    if column.fill.is_some() {
+        bail!("filter column '{}' is null or constant", name);
+    }
+    let kind = match &column.kind {
+        ColumnKind::Numeric {
+            data_type: DataType::Float32 | DataType::Float64,
+            distribution,
+            ..
+        } => FilterKind::Float {
+            distribution: distribution
+                .clone()
+                .unwrap_or(NumericDistribution::Uniform { min: 0.0, max: 1.0 }),
+        },
+        ColumnKind::Numeric {
+            distribution: Some(distribution),
+            ..
+        } => FilterKind::IntDistribution {
+            distribution: distribution.clone(),
+        },
+        ColumnKind::Numeric {
+            data_type: DataType::Int32,
+            ..
+        } => FilterKind::Int {
+            min: i32::MIN as f64,
+            span: 2f64.powi(32),
+        },
+        ColumnKind::Numeric {
+            data_type: DataType::Int64,
+            ..
+        } => FilterKind::Int {
+            min: i64::MIN as f64,
+            span: 2f64.powi(64),
+        },
+        ColumnKind::Tenant { .. } => {
+            let tenants = config
+                .tenants
+                .as_ref()
+                .ok_or_else(|| anyhow!("tenant column without tenants"))?;
+            let weights = tenants.skew.weights(tenants.tenants as usize);
+            let sum: f64 = weights.iter().sum();
+            FilterKind::Key {
+                shares: weights.iter().map(|w| w / sum).collect(),
+            }
+        }
+        _ => bail!(
+            "cannot make filters of known selectivity on column '{}'",
+            name
+        ),
+    };
+    Ok((
+        name.to_string(),
+        kind,
+        1.0 - column.null_ratio.unwrap_or(0.0),
+    ))
+}
+
+/// Predicate on `column` keeping about `target` of the rows, and its
+/// expected selectivity among non-null values
+#[cfg(feature = "parquet")]
+fn predicate(column: &str, kind: &FilterKind, target: f64) -> (Predicate, f64) {
+    let (op, value, selectivity) = match kind {
+        FilterKind::Float { distribution } => (
+            CompareOp::Lt,
+            Literal::Float(distribution.quantile(target)),
+            target,
+        ),
+        FilterKind::IntDistribution { distribution } => {
+            let value = distribution.int_quantile(target);
+            (
+                CompareOp::Lt,
+                Literal::Int(value),
+                distribution.int_fraction_below(value),
+            )
+        }
+        FilterKind::Int { min, span } => {
+            let threshold = (min + target * span).round();
+            // Clamped to the type; i64 bounds round to just beyond it in f64
+            let value = threshold.clamp(i64::MIN as f64, i64::MAX as f64) as i64;
+            (
+                CompareOp::Lt,
+                Literal::Int(value),
+                ((value as f64 - min) / span).clamp(0.0, 1.0),
+            )
+        }
+        FilterKind::Key { shares } => {
+            let distance = |share: f64| (share.ln() - target.ln()).abs();
+            let (key, share) = shares
+                .iter()
+                .copied()
+                .enumerate()
+                .min_by(|a, b| distance(a.1).total_cmp(&distance(b.1)))
+                .unwrap_or((0, 0.0));
+            (CompareOp::Eq, Literal::Int(key as i64), share)
+        }
+    };
+    let predicate = Predicate {
+        column: column.to_string(),
+        op,
+        value,
+    };
+    (predicate, selectivity)
+}